@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use ant_mania::{
+    AntConfig, AntSimulation, CollisionPolicy, FightTrigger, MovementMode, ProceduralMapGenerator, SimOptions,
+    Snapshot, SnapshotFormat, Topology,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+/// An 82x82 grid, 6724 colonies: about the size and shape of the medium map
+const MEDIUM_GRID: Topology = Topology::Grid { width: 82, height: 82 };
+
+/// `num_ants` on `MEDIUM_GRID` with fights only at `collision_threshold`
+/// ants or more, so the crowd keeps moving instead of dying in the first iteration
+fn crowd(num_ants: usize, collision_threshold: u16) -> AntSimulation {
+    let graph = Arc::new(ProceduralMapGenerator::topology(MEDIUM_GRID, 0).unwrap());
+    let config = AntConfig {
+        collision_threshold,
+        collision_policy: CollisionPolicy::KillAll,
+        fight_trigger: FightTrigger::AtLeast,
+        ..AntConfig::new(num_ants)
+    };
+    AntSimulation::new(graph, config).unwrap()
+}
+
+/// ~15 ants per colony, so every move touches a crowded colony list
+fn run_iteration_dense(c: &mut Criterion) {
+    c.bench_function("run_iteration/grid_82x82/100k_ants", |b| {
+        b.iter_batched_ref(
+            || crowd(100_000, 30),
+            |sim| sim.run_iteration(),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+/// 1M ants, where the per-ant alive flags no longer fit in L2 as bytes
+fn run_iteration_1m(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run_iteration/grid_82x82/1M_ants");
+    group.sample_size(20);
+    group.bench_function("sequential", |b| {
+        b.iter_batched_ref(
+            || crowd(1_000_000, 250),
+            |sim| sim.run_iteration(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+/// 10M ants on a 1M colony grid, processed in ID order vs re-sorted by colony
+fn reorder_ants(c: &mut Criterion) {
+    let graph = Arc::new(ProceduralMapGenerator::topology(Topology::Grid { width: 1000, height: 1000 }, 0).unwrap());
+    let mut group = c.benchmark_group("reorder/grid_1000x1000/10M_ants");
+    group.sample_size(10);
+
+    for reorder_every in [None, Some(10)] {
+        let label = reorder_every.map_or("off".to_string(), |k| format!("every_{}", k));
+        let options = SimOptions {
+            movement: MovementMode::Simultaneous,
+            reorder_every,
+            ..SimOptions::default()
+        };
+        let mut sim = AntSimulation::new(Arc::clone(&graph), AntConfig::new(10_000_000)).unwrap().with_options(options);
+        // the colony-sorted order only kicks in after the first reorder
+        sim.run_iteration();
+
+        group.bench_function(BenchmarkId::from_parameter(label), |b| b.iter(|| sim.run_iteration()));
+    }
+    group.finish();
+}
+
+/// Writing and reading a snapshot of 100k ants on `MEDIUM_GRID` in each
+/// format, as bytes per second of that format's encoding
+fn snapshot_formats(c: &mut Criterion) {
+    let mut sim = crowd(100_000, 30);
+    for _ in 0..10 {
+        sim.run_iteration();
+    }
+    let snapshot = sim.snapshot();
+    let mut formats = vec![SnapshotFormat::Bincode];
+    if cfg!(feature = "msgpack") {
+        formats.push(SnapshotFormat::MessagePack);
+    }
+
+    let mut group = c.benchmark_group("snapshot/grid_82x82/100k_ants");
+    for format in formats {
+        let mut bytes = Vec::new();
+        snapshot.write_as(&mut bytes, format).unwrap();
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+
+        group.bench_function(BenchmarkId::new("write", format!("{:?}", format)), |b| {
+            b.iter(|| {
+                let mut out = Vec::with_capacity(bytes.len());
+                snapshot.write_as(&mut out, format).unwrap();
+                out
+            })
+        });
+        group.bench_function(BenchmarkId::new("read", format!("{:?}", format)), |b| {
+            b.iter(|| Snapshot::read_from(bytes.as_slice()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, run_iteration_dense, run_iteration_1m, reorder_ants, snapshot_formats);
+criterion_main!(benches);
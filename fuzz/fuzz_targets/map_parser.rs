@@ -0,0 +1,43 @@
+//! Arbitrary bytes as a map file. Parsing may fail, but never panic, and a
+//! map that loads has to hold together, survive being written out and read
+//! back, and run a few ants without its bookkeeping drifting.
+//!
+//! Seed inputs are in fuzz/corpus/map_parser, which tests/parser_corpus.rs
+//! also replays on every `cargo test`.
+
+#![no_main]
+
+use std::ops::ControlFlow;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(graph) = MapGraph::from_reader(data) else {
+        return;
+    };
+    graph.check_structure().unwrap();
+    let lines = data.split(|&byte| byte == b'\n').count();
+    assert!(graph.parse_warnings().iter().all(|warning| (1..=lines).contains(&warning.line)));
+
+    let mut written = Vec::new();
+    graph.write_map(&mut written, |_| true).unwrap();
+    let again = MapGraph::from_reader(&written[..]).expect("a written map reads back");
+    again.check_structure().unwrap();
+    assert_eq!(again.colony_count(), graph.colony_count());
+    assert_eq!(again.adjacency_slice(), graph.adjacency_slice());
+    assert_eq!(again.connection_counts(), graph.connection_counts());
+
+    if graph.colony_count() == 0 {
+        return;
+    }
+    let config = AntConfig { seed: Some(0), max_moves: 32, ..AntConfig::new(8) };
+    let mut sim = AntSimulation::new(Arc::new(graph), config).unwrap();
+    sim.check_invariants().unwrap();
+    sim.run_until(&AtomicBool::new(false), None, |sim, _| {
+        sim.check_invariants().unwrap();
+        ControlFlow::Continue(())
+    });
+});
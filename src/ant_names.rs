@@ -0,0 +1,83 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use ant_mania::AntId;
+
+/// `--ant-names`: a name per ant, by ID, read from a file with one name per
+/// line. The names are kept end to end in one string with where each ends,
+/// so a roster of millions costs little more than the file. Blank lines
+/// leave an ant unnamed; the default roster names nobody.
+#[derive(Default)]
+pub struct AntNames {
+    names: String,
+    ends: Vec<u32>,
+}
+
+impl AntNames {
+    pub fn read(path: &Path) -> Result<AntNames, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        if u32::try_from(text.len()).is_err() {
+            return Err(format!("{}: rosters over 4 GiB aren't supported", path.display()));
+        }
+        let mut names = String::with_capacity(text.len());
+        let mut ends = Vec::new();
+        for line in text.lines() {
+            names.push_str(line.trim());
+            ends.push(names.len() as u32);
+        }
+        Ok(AntNames { names, ends })
+    }
+
+    /// The name given to `ant`, none past the end of the roster or for a blank line
+    pub fn get(&self, ant: AntId) -> Option<&str> {
+        let end = *self.ends.get(ant)? as usize;
+        let start = if ant == 0 { 0 } else { self.ends[ant - 1] as usize };
+        Some(&self.names[start..end]).filter(|name| !name.is_empty())
+    }
+
+    /// `ant` by name, or by its ID if it has none
+    pub fn label(&self, ant: AntId) -> AntLabel<'_> {
+        AntLabel { names: self, ant }
+    }
+
+    /// Names given to more than one ant, in the order their second use comes
+    pub fn duplicates(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        let mut repeated = HashSet::new();
+        let mut duplicates = Vec::new();
+        for name in (0..self.ends.len()).filter_map(|ant| self.get(ant)) {
+            if !seen.insert(name) && repeated.insert(name) {
+                duplicates.push(name);
+            }
+        }
+        duplicates
+    }
+
+    /// `Foo has been destroyed by ant Alice and ant Bob!`, naming the ants
+    /// killed there, in the order they came
+    pub fn fight_message(&self, colony: &str, killed: &[AntId]) -> String {
+        let ants: Vec<String> = killed.iter().map(|&ant| format!("ant {}", self.label(ant))).collect();
+        let by = match ants.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+            None => "nobody".to_string(),
+        };
+        format!("{} has been destroyed by {}!", colony, by)
+    }
+}
+
+/// An ant's name, or its ID if it has none, see `AntNames::label`
+pub struct AntLabel<'a> {
+    names: &'a AntNames,
+    ant: AntId,
+}
+
+impl fmt::Display for AntLabel<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.names.get(self.ant) {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.ant),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::ops::ControlFlow;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use crate::config::AntConfig;
+use crate::error::SimError;
+use crate::graph::MapGraph;
+use crate::options::SimOptions;
+use crate::simulation::{AntSimulation, ColonyId};
+use crate::stats::SampleStats;
+
+/// How one run of a batch ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunSummary {
+    pub seed: u64,
+    pub iterations: u32,
+    pub alive_ants: usize,
+    pub dead_ants: usize,
+    pub active_colonies: usize,
+    pub colonies_destroyed: usize,
+    pub total_moves: u64,
+    pub oscillating_ants: usize,
+}
+
+/// Aggregates over a batch of runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchStats {
+    pub runs: usize,
+    pub alive_ants: SampleStats,
+    pub active_colonies: SampleStats,
+    pub iterations: SampleStats,
+    /// Seed of the run with the fewest survivors (the first one on ties)
+    pub fewest_survivors_seed: u64,
+    /// Seed of the run with the most survivors (the first one on ties)
+    pub most_survivors_seed: u64,
+}
+
+/// How many runs of a batch each colony came through, one counter per
+/// colony however many runs there are, see `run_batch_survival`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColonySurvival {
+    runs: u32,
+    survived: Vec<u32>,
+}
+
+/// Run `runs` simulations of one map, the i-th seeded with `base_seed + i`
+/// so any run can be repeated on its own. Runs are spread over `threads`
+/// worker threads; results come back in seed order either way.
+pub fn run_batch(
+    graph: &Arc<MapGraph>,
+    config: &AntConfig,
+    options: &SimOptions,
+    runs: usize,
+    base_seed: u64,
+    threads: usize,
+) -> Result<Vec<RunSummary>, SimError> {
+    run_batch_until(graph, config, options, runs, base_seed, threads, None)
+}
+
+/// `run_batch` with a wall-clock budget for the whole batch. Runs still going
+/// at `deadline` are abandoned and runs not started yet are skipped, so only
+/// the finished ones come back (in seed order, possibly with gaps).
+pub fn run_batch_until(
+    graph: &Arc<MapGraph>,
+    config: &AntConfig,
+    options: &SimOptions,
+    runs: usize,
+    base_seed: u64,
+    threads: usize,
+    deadline: Option<Instant>,
+) -> Result<Vec<RunSummary>, SimError> {
+    run_batch_survival(graph, config, options, runs, base_seed, threads, deadline).map(|(results, _)| results)
+}
+
+/// `run_batch_until`, also counting the runs each colony survived. Workers
+/// keep their own counters and add them up at the end, so the counts don't
+/// depend on `threads` either.
+pub fn run_batch_survival(
+    graph: &Arc<MapGraph>,
+    config: &AntConfig,
+    options: &SimOptions,
+    runs: usize,
+    base_seed: u64,
+    threads: usize,
+    deadline: Option<Instant>,
+) -> Result<(Vec<RunSummary>, ColonySurvival), SimError> {
+    let never_interrupted = AtomicBool::new(false);
+    let run_one = |i: usize, survival: &mut ColonySurvival| -> Result<Option<RunSummary>, SimError> {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Ok(None);
+        }
+        let seed = base_seed.wrapping_add(i as u64);
+        let config = AntConfig { seed: Some(seed), ..config.clone() };
+        let mut sim = AntSimulation::new(Arc::clone(graph), config)?.with_options(options.clone());
+        let status = sim.run_until(&never_interrupted, deadline, |_, _| ControlFlow::Continue(()));
+        if status.timed_out {
+            return Ok(None);
+        }
+        survival.record(&sim);
+        let (alive_ants, active_colonies, _) = sim.stats();
+        Ok(Some(RunSummary {
+            seed,
+            iterations: status.iterations,
+            alive_ants,
+            dead_ants: sim.total_ants() - alive_ants,
+            active_colonies,
+            colonies_destroyed: sim.colonies_destroyed(),
+            total_moves: sim.total_moves_made(),
+            oscillating_ants: sim.oscillating_ants(),
+        }))
+    };
+
+    let mut survival = ColonySurvival::new(graph.colony_count());
+    let threads = threads.clamp(1, runs.max(1));
+    if threads == 1 {
+        let results = (0..runs).map(|i| run_one(i, &mut survival)).filter_map(Result::transpose);
+        return Ok((results.collect::<Result<_, _>>()?, survival));
+    }
+
+    // thread t takes runs t, t + threads, t + 2 * threads, ...
+    let mut results: Vec<Option<Result<Option<RunSummary>, SimError>>> = (0..runs).map(|_| None).collect();
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|t| {
+                let run_one = &run_one;
+                scope.spawn(move || {
+                    let mut survival = ColonySurvival::new(graph.colony_count());
+                    let results: Vec<_> = (t..runs).step_by(threads).map(|i| (i, run_one(i, &mut survival))).collect();
+                    (results, survival)
+                })
+            })
+            .collect();
+        for worker in workers {
+            let (worker_results, worker_survival) = worker.join().expect("batch worker panicked");
+            for (i, result) in worker_results {
+                results[i] = Some(result);
+            }
+            survival.merge(&worker_survival);
+        }
+    });
+    let results = results
+        .into_iter()
+        .filter_map(|result| result.expect("every run is assigned a worker").transpose())
+        .collect::<Result<_, _>>()?;
+    Ok((results, survival))
+}
+
+impl BatchStats {
+    /// Aggregate a batch, `None` when it's empty
+    pub fn from_runs(runs: &[RunSummary]) -> Option<BatchStats> {
+        let fewest = runs.iter().min_by_key(|run| run.alive_ants)?;
+        let most = runs.iter().rev().max_by_key(|run| run.alive_ants)?;
+        let sample = |field: fn(&RunSummary) -> usize| {
+            let mut values: Vec<u32> = runs.iter().map(|run| field(run) as u32).collect();
+            SampleStats::from_samples(&mut values)
+        };
+
+        Some(BatchStats {
+            runs: runs.len(),
+            alive_ants: sample(|run| run.alive_ants),
+            active_colonies: sample(|run| run.active_colonies),
+            iterations: sample(|run| run.iterations as usize),
+            fewest_survivors_seed: fewest.seed,
+            most_survivors_seed: most.seed,
+        })
+    }
+}
+
+impl ColonySurvival {
+    /// No runs yet, for a map `colonies` long
+    pub fn new(colonies: usize) -> Self {
+        ColonySurvival { runs: 0, survived: vec![0; colonies] }
+    }
+
+    /// Count a finished run, one pass over its colonies
+    pub fn record(&mut self, sim: &AntSimulation) {
+        self.runs += 1;
+        for colony in sim {
+            self.survived[colony.id] += u32::from(!colony.destroyed);
+        }
+    }
+
+    /// Add in the counts of another part of the same batch
+    pub fn merge(&mut self, other: &ColonySurvival) {
+        self.runs += other.runs;
+        for (survived, other) in self.survived.iter_mut().zip(&other.survived) {
+            *survived += other;
+        }
+    }
+
+    pub fn runs(&self) -> u32 {
+        self.runs
+    }
+
+    /// Runs `colony` was still standing at the end of
+    pub fn survived(&self, colony: ColonyId) -> u32 {
+        self.survived[colony]
+    }
+
+    /// Fraction of the runs `colony` survived, 0 before any run
+    pub fn rate(&self, colony: ColonyId) -> f64 {
+        match self.runs {
+            0 => 0.0,
+            runs => self.survived[colony] as f64 / runs as f64,
+        }
+    }
+
+    /// `rate` of every colony, by ID
+    pub fn rates(&self) -> Vec<f64> {
+        (0..self.survived.len()).map(|colony| self.rate(colony)).collect()
+    }
+
+    /// Mean `rate` over the map's colonies
+    pub fn mean_rate(&self) -> f64 {
+        let rates = self.rates();
+        rates.iter().sum::<f64>() / rates.len().max(1) as f64
+    }
+
+    /// The `percentile`th (0 to 100) colony `rate`, nearest rank
+    pub fn percentile(&self, percentile: f64) -> f64 {
+        let mut rates = self.rates();
+        if rates.is_empty() {
+            return 0.0;
+        }
+        rates.sort_by(f64::total_cmp);
+        let rank = (percentile / 100.0 * rates.len() as f64).ceil() as usize;
+        rates[rank.clamp(1, rates.len()) - 1]
+    }
+}
@@ -0,0 +1,832 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use ant_mania::stop_condition::{self, Metric};
+use ant_mania::{CollisionPolicy, DeadEndBehavior, MapGraph, PlacementStrategy, StopCondition, DEFAULT_CLUSTER_RADIUS};
+
+use clap::error::{ContextKind, ContextValue};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Command, CommandFactory, FromArgMatches, Parser, ValueEnum};
+use serde::{Deserialize, Deserializer};
+
+/// Simulate an ant invasion of a map of colonies
+///
+/// Every option can also be set in a TOML file passed with `--config`, using
+/// the long flag names as keys (`max-moves = 500`), or in `ANT_MANIA_`
+/// environment variables (`ANT_MANIA_MAX_MOVES=500`). Flags given on the
+/// command line win over the environment, which wins over the file.
+///
+/// `ant-mania batch JOBS` runs a file of argument lines, one run per line,
+/// see `ant-mania batch --help`.
+#[derive(Parser, Deserialize, Debug, Default)]
+#[command(version, about)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Cli {
+    /// Map file, one colony per line: `Name north=Other south=...`
+    #[arg(required_unless_present_any = ["config", "resume"])]
+    pub map: Option<String>,
+
+    /// Number of ants to place on the map
+    #[arg(required_unless_present_any = ["ants_per_colony", "config", "resume"])]
+    pub num_ants: Option<usize>,
+
+    /// Place `ceil(DENSITY * colonies)` ants instead of a fixed count
+    #[arg(long, value_name = "DENSITY", conflicts_with = "num_ants")]
+    pub ants_per_colony: Option<f64>,
+
+    /// Read options from a TOML file, paths in it are relative to the file
+    #[arg(long, value_name = "PATH")]
+    #[serde(skip)]
+    pub config: Option<PathBuf>,
+
+    /// Initial placement: uniform, distinct, clustered:K[:RADIUS] or single:<colony>
+    #[arg(long, value_name = "SPEC", default_value = "uniform")]
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub distribution: Distribution,
+
+    /// Start each ant where this file says instead: a colony name per line in
+    /// ant ID order, or `ANT_ID COLONY` lines
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["distribution", "resume"])]
+    pub placement: Option<PathBuf>,
+
+    /// Place the ants --placement leaves out at random instead of refusing to start
+    #[arg(long, requires = "placement")]
+    pub placement_partial: bool,
+
+    /// Name the ants in fight messages, which this turns on: one name per line,
+    /// in ant ID order; ants past the end of the file go by their ID
+    #[arg(long, value_name = "PATH")]
+    pub ant_names: Option<PathBuf>,
+
+    /// Break the report down by region, read from `colony region` lines [default: the map's
+    /// `region` colony attributes, if it has any]
+    #[arg(long, value_name = "PATH")]
+    pub regions: Option<PathBuf>,
+
+    /// Who dies in a fight: all, first:N, random:N or oldest:N (most moves)
+    #[arg(long, value_name = "POLICY", default_value = "first:2", value_parser = parse_collision_policy)]
+    #[serde(deserialize_with = "deserialize_collision_policy")]
+    pub collision_policy: CollisionPolicy,
+
+    /// Which crowds fight: exactly two ants, or two or more (see --collision-policy).
+    /// Exactly unless --distribution single:<colony> drops every ant in one crowd
+    #[arg(long, value_enum)]
+    pub fight_at: Option<FightAt>,
+
+    /// RNG seed for placement and movement, random when omitted
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Moves each ant may make, the run stops once every ant has used them up [default: 10000]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_moves: Option<u32>,
+
+    /// How ants pick their moves within an iteration
+    #[arg(long, value_enum, default_value_t = Movement::Sequential)]
+    pub movement: Movement,
+
+    /// When fights destroy colonies: after every ant has moved, or on arrival (sequential only)
+    #[arg(long, value_enum, default_value_t = Destruction::Lazy)]
+    pub destruction: Destruction,
+
+    /// What ants with only destroyed neighbors do: stay, die:N (after N stuck moves) or teleport
+    #[arg(long, value_name = "BEHAVIOR", default_value = "stay", value_parser = parse_dead_end)]
+    #[serde(deserialize_with = "deserialize_dead_end")]
+    pub dead_end: DeadEndBehavior,
+
+    /// Re-sort ants by colony every N iterations for better memory locality
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub reorder_every: Option<u32>,
+
+    /// Split ants into this many factions that only fight each other
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub factions: u8,
+
+    /// Track connected components and stop once no fight is possible any more
+    #[arg(long)]
+    pub track_components: bool,
+
+    /// Play until one ant is left or no fight is possible any more (--track-components), then
+    /// announce the winner; exits with 3 unless exactly one ant survived
+    #[arg(long, conflicts_with_all = ["runs", "sweep_seeds"])]
+    pub battle_royale: bool,
+
+    /// Follow this ant and report the colonies it visited and how far it went (with coordinates
+    /// on the map); repeat it to follow more
+    #[arg(long, value_name = "ID", conflicts_with_all = ["runs", "sweep_seeds"])]
+    pub trace_ant: Vec<usize>,
+
+    /// When a fight destroys a colony, also destroy the neighbors it leaves with no way out (and
+    /// so on), killing the ants stuck there
+    #[arg(long)]
+    pub cascade_on_destroy: bool,
+
+    /// Count ants that go back and forth between the same two colonies for more than K moves
+    /// in a row, in the verbose report and as the oscillating_ants metric
+    #[arg(long, value_name = "K", value_parser = clap::value_parser!(u16).range(1..))]
+    pub detect_oscillation: Option<u16>,
+
+    /// Run this many simulations with seeds SEED, SEED+1, ... and report aggregates
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    #[serde(default = "one_run")]
+    pub runs: u32,
+
+    /// Run every seed in START..END (end excluded) on the same world and list
+    /// the top and bottom seeds by --rank-by
+    #[arg(
+        long,
+        value_name = "START..END",
+        value_parser = parse_seed_range,
+        conflicts_with_all = ["seed", "runs", "resume"]
+    )]
+    #[serde(deserialize_with = "deserialize_seed_range")]
+    pub sweep_seeds: Option<Range<u64>>,
+
+    /// What --sweep-seeds ranks by: iterations, alive_ants, dead_ants, active_colonies,
+    /// colonies_destroyed, total_moves or oscillating_ants [default: alive_ants]
+    #[arg(long, value_name = "METRIC", value_parser = parse_metric, requires = "sweep_seeds")]
+    #[serde(deserialize_with = "deserialize_metric")]
+    pub rank_by: Option<Metric>,
+
+    /// Also write every --sweep-seeds seed and its metrics here as CSV
+    #[arg(long, value_name = "PATH", requires = "sweep_seeds")]
+    pub sweep_csv: Option<PathBuf>,
+
+    /// Also write the fraction of --runs each colony survived here, as colony,survival_rate CSV
+    #[arg(long, value_name = "PATH")]
+    pub survival_csv: Option<PathBuf>,
+
+    /// Worker threads for --runs and --sweep-seeds, 1 runs everything on the main thread [default: all cores]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub threads: Option<u32>,
+
+    /// Stop at the next iteration once this much wall-clock time has passed
+    /// (e.g. 90s, 5m, 1h30m); with --runs it covers the whole batch
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, allow_hyphen_values = true)]
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub time_limit: Option<Duration>,
+
+    /// End the run once a condition holds, e.g. alive_ants<=2, colonies_destroyed>=100 or
+    /// oscillating_ants==alive_ants; repeat it to stop on whichever comes first
+    #[arg(long, value_name = "COND", value_parser = parse_stop_when)]
+    #[serde(deserialize_with = "deserialize_stop_when")]
+    pub stop_when: Vec<StopCondition>,
+
+    /// Format of the final report
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    /// Write the final report here instead of stdout
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Write progress, warnings and fight messages here (timestamped) instead of the terminal
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Add to the end of --log-file instead of replacing it
+    #[arg(long, requires = "log_file")]
+    pub log_append: bool,
+
+    /// Save the whole simulation to --snapshot-dir every N iterations
+    #[arg(long, value_name = "N", requires = "snapshot_dir", value_parser = clap::value_parser!(u32).range(1..))]
+    pub snapshot_every: Option<u32>,
+
+    /// Carry on the run saved in this snapshot; the map and ants come from it,
+    /// the limits and output options from this invocation
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["ants_per_colony", "dry_run"])]
+    pub resume: Option<PathBuf>,
+
+    /// Directory --snapshot-every writes snap_<iteration>.bin (or .msgpack) files to
+    #[arg(long, value_name = "PATH", requires = "snapshot_every")]
+    pub snapshot_dir: Option<PathBuf>,
+
+    /// Encoding of the snapshots --snapshot-every writes; --resume reads either
+    #[arg(long, value_enum, default_value_t = SnapshotEncoding::Bincode)]
+    pub snapshot_format: SnapshotEncoding,
+
+    /// Keep only the latest K snapshots, deleting older ones
+    #[arg(long, value_name = "K", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    #[serde(default = "three_snapshots")]
+    pub snapshot_keep: u32,
+
+    /// Write Prometheus text metrics (alive ants, surviving colonies, destructions,
+    /// iterations, duration) here when the run ends, for a textfile collector
+    #[arg(long, value_name = "PATH")]
+    pub metrics_file: Option<PathBuf>,
+
+    /// Also rewrite --metrics-file every N iterations
+    #[arg(long, value_name = "N", requires = "metrics_file", value_parser = clap::value_parser!(u32).range(1..))]
+    pub metrics_every: Option<u32>,
+
+    /// Write the surviving colonies and tunnels here as GeoJSON when the run
+    /// ends, with each colony's ants and, for a GeoJSON map, its coordinates
+    #[arg(long, value_name = "PATH")]
+    pub geojson_file: Option<PathBuf>,
+
+    /// Write every move, fight, death and destruction of the run here as a Parquet file, one row
+    /// per event (needs the parquet feature)
+    #[arg(long, value_name = "PATH")]
+    pub events_parquet: Option<PathBuf>,
+
+    /// Write each colony's results here when the run ends, one row per colony: as Feather for a
+    /// .feather or .arrow file, as Parquet for a .parquet one (needs the arrow feature, and parquet
+    /// for .parquet)
+    #[arg(long, value_name = "PATH")]
+    pub colony_stats: Option<PathBuf>,
+
+    /// Add the run to this SQLite database, creating it and its tables if they're missing: its
+    /// parameters and final stats, and the colonies it destroyed (needs the sqlite feature)
+    #[arg(long, value_name = "PATH")]
+    pub sqlite: Option<PathBuf>,
+
+    /// With --sqlite, also store the moves, destructions and deaths of every iteration
+    #[arg(long, requires = "sqlite")]
+    pub sqlite_timeline: bool,
+
+    /// Only print the final report
+    #[arg(short, long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Also print memory usage and every iteration that destroys a colony
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// With --verbose, also print the state hash every N iterations, to
+    /// compare long runs between machines
+    #[arg(long, value_name = "N", requires = "verbose", value_parser = clap::value_parser!(u32).range(1..))]
+    pub hash_every: Option<u32>,
+
+    /// Leave the run duration out of the report, so same-seed runs print identical output
+    #[arg(long)]
+    pub no_timing: bool,
+
+    /// Write the remaining world in a canonical order, colonies by name and tunnels by label,
+    /// so runs on maps listed in different orders diff cleanly
+    #[arg(long)]
+    pub sort_output: bool,
+
+    /// Print hot-loop perf counters at the end
+    #[arg(long)]
+    pub perf: bool,
+
+    /// Print the memory estimate and exit without simulating
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Print the map's shape (degrees, dead ends, articulation points, components, diameter,
+    /// clustering) and exit without simulating
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Add a tunnel back, north for south, east for west and the same label otherwise, for
+    /// every tunnel the map lists only one way
+    #[arg(long)]
+    pub symmetrize: bool,
+
+    /// Exit with code 65 if the map loads with parse warnings (dangling or one-way tunnels,
+    /// repeated labels or colonies, names differing only in case), after printing them
+    #[arg(long)]
+    pub fail_on_warning: bool,
+
+    /// Refuse to start if the estimated memory is above this (e.g. 512M, 4G)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    #[serde(deserialize_with = "deserialize_size")]
+    pub max_memory: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Movement {
+    #[default]
+    Sequential,
+    Simultaneous,
+    /// Simultaneous, with the moves picked on every core (needs the parallel feature)
+    Parallel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Destruction {
+    #[default]
+    Lazy,
+    Immediate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FightAt {
+    #[default]
+    Exactly,
+    AtLeast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotEncoding {
+    #[default]
+    Bincode,
+    /// MessagePack (needs the msgpack feature)
+    Msgpack,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Text,
+    #[cfg(feature = "json")]
+    Json,
+}
+
+/// `--distribution`, resolved against the map once it's loaded
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Distribution {
+    #[default]
+    Uniform,
+    Distinct,
+    Clustered { clusters: usize, radius: u32 },
+    Single(String),
+}
+
+impl FromStr for Distribution {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let (kind, arg) = match spec.split_once(':') {
+            Some((kind, arg)) => (kind, Some(arg)),
+            None => (spec, None),
+        };
+        match (kind, arg) {
+            ("uniform", None) => Ok(Distribution::Uniform),
+            ("distinct", None) => Ok(Distribution::Distinct),
+            ("clustered", Some(arg)) => {
+                let invalid = || format!("invalid cluster spec '{}', expected clustered:K or clustered:K:RADIUS", spec);
+                let (clusters, radius) = match arg.split_once(':') {
+                    Some((clusters, radius)) => (clusters, radius.parse().map_err(|_| invalid())?),
+                    None => (arg, DEFAULT_CLUSTER_RADIUS),
+                };
+                let clusters = clusters.parse().map_err(|_| invalid())?;
+                Ok(Distribution::Clustered { clusters, radius })
+            }
+            ("single", Some(colony)) if !colony.is_empty() => Ok(Distribution::Single(colony.to_string())),
+            _ => Err(format!(
+                "unknown distribution '{}', expected uniform, distinct, clustered:K or single:<colony>",
+                spec
+            )),
+        }
+    }
+}
+
+impl Distribution {
+    /// The library placement for this spec on `graph`
+    pub fn placement(&self, graph: &MapGraph) -> Result<PlacementStrategy, String> {
+        Ok(match self {
+            Distribution::Uniform => PlacementStrategy::Uniform,
+            Distribution::Distinct => PlacementStrategy::Distinct,
+            &Distribution::Clustered { clusters, radius } => PlacementStrategy::Clustered { clusters, radius },
+            Distribution::Single(name) => match graph.find_colony(name) {
+                Some(colony_id) => PlacementStrategy::Single(colony_id),
+                None => return Err(format!("--distribution single: no colony named '{}' on the map", name)),
+            },
+        })
+    }
+}
+
+/// Prefix of the environment variables that set options
+pub const ENV_PREFIX: &str = "ANT_MANIA_";
+
+/// The command line parser, with every option also read from its
+/// environment variable: `--max-moves` from `ANT_MANIA_MAX_MOVES` and so on
+pub fn command() -> Command {
+    Cli::command().mut_args(|arg| {
+        let var = format!("{}{}", ENV_PREFIX, arg.get_id().as_str().to_ascii_uppercase());
+        arg.env(var)
+    })
+}
+
+/// Reword a parse error caused by a bad environment variable so it names the
+/// variable, `None` if the bad value came from the command line. `command`
+/// must have been built.
+pub fn env_var_error(command: &Command, err: &clap::Error) -> Option<String> {
+    let Some(ContextValue::String(invalid_arg)) = err.get(ContextKind::InvalidArg) else {
+        return None;
+    };
+    let Some(ContextValue::String(invalid_value)) = err.get(ContextKind::InvalidValue) else {
+        return None;
+    };
+    let arg = command.get_arguments().find(|arg| arg.to_string() == *invalid_arg)?;
+    let var = arg.get_env()?;
+    if std::env::var_os(var)? != invalid_value.as_str() {
+        return None;
+    }
+    // the same bad value could also have been typed as the flag
+    if let Some(long) = arg.get_long() {
+        let flag = format!("--{}", long);
+        if std::env::args().any(|given| given == flag || given.starts_with(&format!("{}=", flag))) {
+            return None;
+        }
+    }
+
+    let reason = match (std::error::Error::source(err), err.get(ContextKind::ValidValue)) {
+        (Some(reason), _) => format!(": {}", reason),
+        (None, Some(ContextValue::Strings(valid))) => format!(", expected one of {}", valid.join(", ")),
+        (None, _) if !arg.get_action().takes_values() => ", expected true or false".to_string(),
+        (None, _) => String::new(),
+    };
+    Some(format!(
+        "invalid value '{}' in {} (for {}){}",
+        invalid_value,
+        var.to_string_lossy(),
+        invalid_arg,
+        reason
+    ))
+}
+
+impl Cli {
+    /// Options from the parsed command line, filled in from `--config` if given
+    pub fn from_matches(matches: &ArgMatches) -> Result<Cli, String> {
+        let mut cli = Cli::from_arg_matches(matches).map_err(|err| err.to_string())?;
+        if let Some(path) = cli.config.clone() {
+            let file = Cli::from_file(&path)?;
+            cli.fill_from(file, matches);
+        }
+        cli.validate()?;
+        Ok(cli)
+    }
+
+    /// Options from a TOML file, with its paths made relative to the file
+    fn from_file(path: &Path) -> Result<Cli, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let mut file: Cli = toml::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+        let base = path.parent().unwrap_or(Path::new(""));
+        file.map = file.map.map(|map| base.join(map).to_string_lossy().into_owned());
+        file.placement = file.placement.map(|placement| base.join(placement));
+        file.ant_names = file.ant_names.map(|ant_names| base.join(ant_names));
+        file.regions = file.regions.map(|regions| base.join(regions));
+        file.output = file.output.map(|output| base.join(output));
+        file.log_file = file.log_file.map(|log_file| base.join(log_file));
+        file.snapshot_dir = file.snapshot_dir.map(|snapshot_dir| base.join(snapshot_dir));
+        file.resume = file.resume.map(|resume| base.join(resume));
+        file.sweep_csv = file.sweep_csv.map(|sweep_csv| base.join(sweep_csv));
+        file.survival_csv = file.survival_csv.map(|survival_csv| base.join(survival_csv));
+        file.metrics_file = file.metrics_file.map(|metrics_file| base.join(metrics_file));
+        file.geojson_file = file.geojson_file.map(|geojson_file| base.join(geojson_file));
+        file.events_parquet = file.events_parquet.map(|events_parquet| base.join(events_parquet));
+        file.colony_stats = file.colony_stats.map(|colony_stats| base.join(colony_stats));
+        file.sqlite = file.sqlite.map(|sqlite| base.join(sqlite));
+        Ok(file)
+    }
+
+    /// Take every option neither the command line nor the environment set from `file`
+    fn fill_from(&mut self, file: Cli, matches: &ArgMatches) {
+        let on_command_line = |id: &str| {
+            matches!(matches.value_source(id), Some(ValueSource::CommandLine | ValueSource::EnvVariable))
+        };
+        macro_rules! fill {
+            ($($field:ident),*) => {
+                $(
+                    if !on_command_line(stringify!($field)) {
+                        self.$field = file.$field;
+                    }
+                )*
+            };
+        }
+
+        // these pairs exclude each other, so setting one on the command line
+        // overrides both from the file
+        if !on_command_line("num_ants") && !on_command_line("ants_per_colony") {
+            self.num_ants = file.num_ants;
+            self.ants_per_colony = file.ants_per_colony;
+        }
+        if !on_command_line("quiet") && !on_command_line("verbose") {
+            self.quiet = file.quiet;
+            self.verbose = file.verbose;
+        }
+        if !on_command_line("distribution") && !on_command_line("placement") {
+            self.distribution = file.distribution;
+            self.placement = file.placement;
+        }
+        fill!(
+            map, placement_partial, ant_names, regions, collision_policy, fight_at, seed, max_moves, movement,
+            destruction, dead_end, reorder_every, factions, track_components, battle_royale, trace_ant,
+            cascade_on_destroy, detect_oscillation, runs, sweep_seeds, rank_by, sweep_csv, survival_csv, threads,
+            time_limit, stop_when, format, output, log_file, log_append, snapshot_every, snapshot_dir, snapshot_format,
+            snapshot_keep, metrics_file, metrics_every, geojson_file, events_parquet, colony_stats, sqlite,
+            sqlite_timeline, resume, hash_every, no_timing, sort_output, perf, dry_run, stats, symmetrize,
+            fail_on_warning, max_memory
+        );
+    }
+
+    /// Checks clap does for flags, repeated for values that came from a file
+    fn validate(&self) -> Result<(), String> {
+        if self.resume.is_some() {
+            if self.ants_per_colony.is_some() || self.placement.is_some() || self.dry_run || self.runs > 1 {
+                return Err(
+                    "--resume continues a single run, without --ants-per-colony, --placement, --dry-run or --runs"
+                        .to_string(),
+                );
+            }
+        } else if self.map.is_none() {
+            return Err("no map given, pass it as the first argument or set `map` in the config".to_string());
+        }
+        match (self.num_ants, self.ants_per_colony) {
+            (None, None) if self.resume.is_none() => {
+                return Err("give either a number of ants or --ants-per-colony".to_string())
+            }
+            (Some(_), Some(_)) => return Err("a number of ants and --ants-per-colony can't be used together".to_string()),
+            _ => {}
+        }
+        if self.placement.is_some() && self.distribution != Distribution::Uniform {
+            return Err("--placement and --distribution can't be used together".to_string());
+        }
+        if self.placement_partial && self.placement.is_none() {
+            return Err("--placement-partial needs --placement".to_string());
+        }
+        if self.stats && (self.runs > 1 || self.sweep_seeds.is_some() || self.dry_run) {
+            return Err("--stats looks at a single map, without --runs, --sweep-seeds or --dry-run".to_string());
+        }
+        if self.quiet && self.verbose {
+            return Err("--quiet and --verbose can't be used together".to_string());
+        }
+        if self.max_moves == Some(0) {
+            return Err("max moves must be at least 1".to_string());
+        }
+        if self.runs == 0 {
+            return Err("runs must be at least 1".to_string());
+        }
+        if self.sweep_seeds.is_some() {
+            if self.seed.is_some() || self.runs > 1 || self.resume.is_some() || !self.stop_when.is_empty() {
+                return Err(
+                    "--sweep-seeds picks the seeds itself, without --seed, --runs, --resume or --stop-when".to_string()
+                );
+            }
+        } else if self.rank_by.is_some() || self.sweep_csv.is_some() {
+            return Err("--rank-by and --sweep-csv need --sweep-seeds".to_string());
+        }
+        if self.survival_csv.is_some() && (self.runs < 2 || self.sweep_seeds.is_some()) {
+            return Err("--survival-csv needs --runs".to_string());
+        }
+        if self.runs > 1 && !self.stop_when.is_empty() {
+            return Err("--stop-when works on a single run, not with --runs".to_string());
+        }
+        if self.threads == Some(0) {
+            return Err("threads must be at least 1".to_string());
+        }
+        if self.log_append && self.log_file.is_none() {
+            return Err("--log-append needs --log-file".to_string());
+        }
+        if self.snapshot_every == Some(0) {
+            return Err("snapshot every must be at least 1".to_string());
+        }
+        if self.snapshot_every.is_some() != self.snapshot_dir.is_some() {
+            return Err("--snapshot-every and --snapshot-dir go together".to_string());
+        }
+        if self.snapshot_keep == 0 {
+            return Err("snapshot keep must be at least 1".to_string());
+        }
+        if self.metrics_every == Some(0) {
+            return Err("metrics every must be at least 1".to_string());
+        }
+        if self.metrics_every.is_some() && self.metrics_file.is_none() {
+            return Err("--metrics-every needs --metrics-file".to_string());
+        }
+        if self.metrics_file.is_some() && (self.runs > 1 || self.sweep_seeds.is_some() || self.dry_run || self.stats) {
+            return Err(
+                "--metrics-file reports on one run, without --runs, --sweep-seeds, --dry-run or --stats".to_string(),
+            );
+        }
+        if self.ant_names.is_some() && (self.runs > 1 || self.sweep_seeds.is_some() || self.dry_run || self.stats) {
+            return Err(
+                "--ant-names is for a single run, without --runs, --sweep-seeds, --dry-run or --stats".to_string(),
+            );
+        }
+        if self.regions.is_some() && (self.runs > 1 || self.sweep_seeds.is_some() || self.dry_run || self.stats) {
+            return Err(
+                "--regions breaks down a single run, without --runs, --sweep-seeds, --dry-run or --stats".to_string(),
+            );
+        }
+        if self.geojson_file.is_some() && (self.runs > 1 || self.sweep_seeds.is_some() || self.dry_run || self.stats) {
+            return Err(
+                "--geojson-file maps one run, without --runs, --sweep-seeds, --dry-run or --stats".to_string(),
+            );
+        }
+        let batch = self.runs > 1 || self.sweep_seeds.is_some() || self.dry_run || self.stats;
+        if self.events_parquet.is_some() && batch {
+            return Err(
+                "--events-parquet logs one run, without --runs, --sweep-seeds, --dry-run or --stats".to_string(),
+            );
+        }
+        if self.colony_stats.is_some() && batch {
+            return Err(
+                "--colony-stats reports one run, without --runs, --sweep-seeds, --dry-run or --stats".to_string(),
+            );
+        }
+        if let Some(path) = &self.colony_stats {
+            if !matches!(path.extension().and_then(|ext| ext.to_str()), Some("feather" | "arrow" | "parquet")) {
+                return Err(format!("--colony-stats {}: expected a .feather, .arrow or .parquet file", path.display()));
+            }
+        }
+        if self.sqlite.is_some() && batch {
+            return Err("--sqlite stores one run, without --runs, --sweep-seeds, --dry-run or --stats".to_string());
+        }
+        if self.sqlite_timeline && self.sqlite.is_none() {
+            return Err("--sqlite-timeline goes with --sqlite".to_string());
+        }
+        if self.hash_every == Some(0) {
+            return Err("hash every must be at least 1".to_string());
+        }
+        if self.hash_every.is_some() && !self.verbose {
+            return Err("--hash-every needs --verbose".to_string());
+        }
+        if self.reorder_every == Some(0) {
+            return Err("reorder every must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Threads to run on: `--threads`, or every core the process may use
+    pub fn thread_count(&self) -> usize {
+        match self.threads {
+            Some(threads) => threads as usize,
+            None => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+        }
+    }
+}
+
+fn one_run() -> u32 {
+    1
+}
+
+fn three_snapshots() -> u32 {
+    3
+}
+
+/// `--collision-policy`, e.g. `all` or `oldest:2`
+pub fn parse_collision_policy(spec: &str) -> Result<CollisionPolicy, String> {
+    if spec == "all" {
+        return Ok(CollisionPolicy::KillAll);
+    }
+    let invalid = || format!("invalid collision policy '{}', expected all, first:N, random:N or oldest:N", spec);
+    let (kind, count) = spec.split_once(':').ok_or_else(invalid)?;
+    let count = match count.parse() {
+        Ok(count) if count > 0 => count,
+        _ => return Err(invalid()),
+    };
+    match kind {
+        "first" => Ok(CollisionPolicy::KillFirst { count }),
+        "random" => Ok(CollisionPolicy::KillRandom { count }),
+        "oldest" => Ok(CollisionPolicy::KillOldest { count }),
+        _ => Err(invalid()),
+    }
+}
+
+/// `--dead-end`, e.g. `stay` or `die:3`
+pub fn parse_dead_end(spec: &str) -> Result<DeadEndBehavior, String> {
+    let invalid = || format!("invalid dead-end behavior '{}', expected stay, die:N or teleport", spec);
+    match spec.split_once(':') {
+        None if spec == "stay" => Ok(DeadEndBehavior::StayForever),
+        None if spec == "teleport" => Ok(DeadEndBehavior::TeleportToRandom),
+        Some(("die", moves)) => match moves.parse() {
+            Ok(moves) if moves > 0 => Ok(DeadEndBehavior::DieAfterNMoves(moves)),
+            _ => Err(invalid()),
+        },
+        _ => Err(invalid()),
+    }
+}
+
+/// `--stop-when`, e.g. `alive_ants<=2`
+pub fn parse_stop_when(spec: &str) -> Result<StopCondition, String> {
+    stop_condition::parse(spec).map_err(|err| err.to_string())
+}
+
+/// `--sweep-seeds`, e.g. `0..1000`
+pub fn parse_seed_range(spec: &str) -> Result<Range<u64>, String> {
+    let invalid = || format!("invalid seed range '{}', expected START..END like 0..1000", spec);
+    let (start, end) = spec.split_once("..").ok_or_else(invalid)?;
+    let start: u64 = start.trim().parse().map_err(|_| invalid())?;
+    let end: u64 = end.trim().parse().map_err(|_| invalid())?;
+    if start >= end {
+        return Err(format!("seed range '{}' is empty", spec));
+    }
+    Ok(start..end)
+}
+
+/// `--rank-by`, any `--stop-when` metric
+pub fn parse_metric(name: &str) -> Result<Metric, String> {
+    Metric::from_name(name).map_err(|err| err.to_string())
+}
+
+/// Parse a byte size like `4096`, `512M` or `4GiB` (binary units)
+pub fn parse_size(value: &str) -> Result<usize, String> {
+    let invalid = || format!("invalid size '{}', expected e.g. 512M or 4G", value);
+    let trimmed = value.trim();
+    let digits_end = trimmed.find(|c: char| !c.is_ascii_digit()).unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(digits_end);
+    let number: usize = number.parse().map_err(|_| invalid())?;
+
+    let shift = match unit.trim().to_ascii_uppercase().trim_end_matches("IB").trim_end_matches('B') {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        "T" => 40,
+        _ => return Err(invalid()),
+    };
+    number.checked_mul(1 << shift).ok_or_else(invalid)
+}
+
+/// Parse a duration like `90s`, `5m`, `1.5h` or `1h30m` (units ms, s, m, h)
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration '{}', expected e.g. 90s, 5m or 1h30m", value);
+    let trimmed = value.trim();
+    if trimmed.starts_with('-') {
+        return Err(format!("duration '{}' is negative", value));
+    }
+    if trimmed.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = trimmed;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_end);
+        let unit_end = tail.find(|c: char| c.is_ascii_digit() || c == '.').unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_end);
+
+        let seconds_per_unit = match unit.trim() {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            // a bare `90` could be seconds or minutes, make the user say
+            "" => return Err(format!("duration '{}' needs a unit, e.g. {}s or {}m", value, number, number)),
+            _ => return Err(invalid()),
+        };
+        let amount: f64 = number.parse().map_err(|_| invalid())?;
+        let part = Duration::try_from_secs_f64(amount * seconds_per_unit).map_err(|_| invalid())?;
+        total = total.checked_add(part).ok_or_else(invalid)?;
+        rest = tail;
+    }
+
+    if total.is_zero() {
+        return Err(format!("duration '{}' must be above zero", value));
+    }
+    Ok(total)
+}
+
+/// Options that are strings in a config file, parsed like their flags
+fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(serde::de::Error::custom)
+}
+
+/// `collision-policy = "oldest:2"` in a config file
+fn deserialize_collision_policy<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CollisionPolicy, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_collision_policy(&value).map_err(serde::de::Error::custom)
+}
+
+/// `dead-end = "die:3"` in a config file
+fn deserialize_dead_end<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DeadEndBehavior, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_dead_end(&value).map_err(serde::de::Error::custom)
+}
+
+/// `stop-when = ["alive_ants<=2", "iterations>=5000"]` in a config file
+fn deserialize_stop_when<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<StopCondition>, D::Error> {
+    let specs = Vec::<String>::deserialize(deserializer)?;
+    specs.iter().map(|spec| parse_stop_when(spec).map_err(serde::de::Error::custom)).collect()
+}
+
+/// `sweep-seeds = "0..1000"` in a config file
+fn deserialize_seed_range<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Range<u64>>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_seed_range(&value).map(Some).map_err(serde::de::Error::custom)
+}
+
+/// `rank-by = "iterations"` in a config file
+fn deserialize_metric<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Metric>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_metric(&value).map(Some).map_err(serde::de::Error::custom)
+}
+
+/// `max-memory = "512M"` in a config file
+fn deserialize_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_size(&value).map(Some).map_err(serde::de::Error::custom)
+}
+
+/// `time-limit = "5m"` in a config file
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_duration(&value).map(Some).map_err(serde::de::Error::custom)
+}
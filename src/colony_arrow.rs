@@ -0,0 +1,62 @@
+//! Per-colony results as an Arrow `RecordBatch`, to join with other datasets
+//! after a run
+
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, BooleanArray, RecordBatch, StringArray, UInt32Array};
+use arrow_schema::{DataType, Field, Schema};
+
+use crate::simulation::AntSimulation;
+
+impl AntSimulation {
+    /// Every colony's results as it stands, one row per colony in ID order:
+    ///
+    /// | column                   | type    | null? | holds                                     |
+    /// |--------------------------|---------|-------|-------------------------------------------|
+    /// | `name`                   | utf8    | no    | the colony's name                         |
+    /// | `id`                     | uint32  | no    | its colony ID                             |
+    /// | `destroyed`              | boolean | no    | whether it's destroyed                    |
+    /// | `destroyed_at_iteration` | uint32  | yes   | 0-based iteration it was destroyed in     |
+    /// | `ant_count`              | uint32  | no    | ants there at the end, survivors included |
+    /// | `degree`                 | uint32  | no    | tunnels out of it                         |
+    /// | `live_degree`            | uint32  | no    | tunnels out of it into live colonies      |
+    /// | `visits`                 | uint32  | no    | ants that moved in, with visits kept      |
+    ///
+    /// `destroyed_at_iteration` comes from `record_destructions` and is null
+    /// for colonies destroyed while it was off; `visits` comes from
+    /// `record_visits` and is left out while that's off. Each column is
+    /// built straight from the simulation's per-colony state, with no row
+    /// structs in between.
+    pub fn colony_stats_to_arrow(&self) -> RecordBatch {
+        let graph = self.graph();
+        let colonies = graph.colony_count();
+
+        let mut destroyed_at = vec![None; colonies];
+        for &(iteration, colony) in self.destruction_log().unwrap_or_default() {
+            destroyed_at[colony] = Some(iteration);
+        }
+        let mut fields = vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("id", DataType::UInt32, false),
+            Field::new("destroyed", DataType::Boolean, false),
+            Field::new("destroyed_at_iteration", DataType::UInt32, true),
+            Field::new("ant_count", DataType::UInt32, false),
+            Field::new("degree", DataType::UInt32, false),
+            Field::new("live_degree", DataType::UInt32, false),
+        ];
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values((0..colonies).map(|colony| graph.colony_name(colony)))),
+            Arc::new(UInt32Array::from_iter_values(0..colonies as u32)),
+            Arc::new(BooleanArray::from(self.destroyed_flags().to_vec())),
+            Arc::new(UInt32Array::from(destroyed_at)),
+            Arc::new(UInt32Array::from_iter_values(self.ant_counts().iter().map(|&count| count as u32))),
+            Arc::new(UInt32Array::from_iter_values((0..colonies).map(|colony| self.colony_degree(colony) as u32))),
+            Arc::new(UInt32Array::from_iter_values((0..colonies).map(|colony| self.colony_out_degree(colony) as u32))),
+        ];
+        if let Some(visits) = self.colony_visits() {
+            fields.push(Field::new("visits", DataType::UInt32, false));
+            columns.push(Arc::new(UInt32Array::from(visits.to_vec())));
+        }
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).expect("columns match the schema")
+    }
+}
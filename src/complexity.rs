@@ -0,0 +1,111 @@
+use std::fmt;
+
+/// Iterations the termination model steps through before giving up and
+/// assuming the run goes the distance
+const MAX_MODEL_ITERATIONS: u32 = 1_000_000;
+
+/// Back-of-the-envelope bounds for a run, for picking `max_moves` before
+/// spending the time on it. The upper bounds are exact, the two expectations
+/// assume ants spread out like independent random walkers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComplexityEstimate {
+    /// Nothing runs past the move budget
+    pub max_iterations: u64,
+    /// Every alive ant moves at most once per iteration
+    pub max_moves_per_iteration: u64,
+    /// `max_moves_per_iteration * max_iterations`, the O(ants * max_moves) bound
+    pub total_ops_upper_bound: u64,
+    /// Birthday-paradox estimate of when the first fight breaks out, `None`
+    /// when there are too few ants for one
+    pub expected_first_collision_iteration: Option<f64>,
+    /// Mean-field (Markov chain) estimate of when the run ends, capped at
+    /// `max_iterations`
+    pub estimated_termination_iteration: f64,
+}
+
+impl ComplexityEstimate {
+    /// `live_degrees` holds the live tunnels out of each live colony, which
+    /// weights where a walker tends to be (the stationary distribution of a
+    /// random walk is proportional to degree)
+    pub(crate) fn new(alive_ants: usize, live_degrees: &[u32], max_moves: u32, threshold: u16) -> Self {
+        let ants = alive_ants as u64;
+        let max_iterations = max_moves as u64;
+        ComplexityEstimate {
+            max_iterations,
+            max_moves_per_iteration: ants,
+            total_ops_upper_bound: ants.saturating_mul(max_iterations),
+            expected_first_collision_iteration: first_collision(alive_ants, live_degrees, threshold),
+            estimated_termination_iteration: termination(alive_ants, live_degrees.len(), max_moves, threshold),
+        }
+    }
+}
+
+/// Any `threshold` ants landing together fights. With walkers spread by the
+/// stationary distribution `p`, the expected number of such groups per
+/// iteration is `C(ants, threshold) * sum(p_i ^ threshold)`; a Poisson count
+/// of them gives the chance of at least one, and the wait is geometric.
+fn first_collision(ants: usize, live_degrees: &[u32], threshold: u16) -> Option<f64> {
+    let total_degree: f64 = live_degrees.iter().map(|&degree| degree as f64).sum();
+    if ants < threshold as usize || total_degree == 0.0 {
+        return None;
+    }
+
+    let threshold_power = threshold as i32;
+    let concentration: f64 = live_degrees.iter().map(|&degree| (degree as f64 / total_degree).powi(threshold_power)).sum();
+    let ln_groups: f64 = (0..threshold as usize).map(|i| ((ants - i) as f64 / (i + 1) as f64).ln()).sum();
+    let fights_per_iteration = (ln_groups + concentration.ln()).exp();
+
+    let chance = -(-fights_per_iteration).exp_m1();
+    Some(1.0 / chance)
+}
+
+/// Step the expected ant and colony counts forward one iteration at a time,
+/// with ants Poisson-distributed over the live colonies: every colony that
+/// gets `threshold` or more ants is destroyed along with them. The run ends
+/// when the expected ants drop below one, or at the move budget if the
+/// survivors can't make a fight any more.
+fn termination(ants: usize, colonies: usize, max_moves: u32, threshold: u16) -> f64 {
+    let threshold = threshold as usize;
+    let (mut ants, mut colonies) = (ants as f64, colonies as f64);
+
+    for iteration in 1..=max_moves.min(MAX_MODEL_ITERATIONS) {
+        if ants < 1.0 {
+            return iteration as f64 - 1.0;
+        }
+        if ants < threshold as f64 || colonies < 1.0 {
+            break;
+        }
+
+        // P(j ants at a colony) and the ants in colonies below the threshold
+        let mean = ants / colonies;
+        let mut probability = (-mean).exp();
+        let mut below = probability;
+        let mut ants_below = 0.0;
+        for j in 1..threshold {
+            probability *= mean / j as f64;
+            below += probability;
+            ants_below += j as f64 * probability;
+        }
+
+        let killed = ants - colonies * ants_below;
+        if killed < 1e-9 {
+            break;
+        }
+        colonies -= colonies * (1.0 - below);
+        ants -= killed;
+    }
+    max_moves as f64
+}
+
+impl fmt::Display for ComplexityEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "iterations:       at most {} (max moves)", self.max_iterations)?;
+        writeln!(f, "moves/iteration:  at most {} (alive ants)", self.max_moves_per_iteration)?;
+        writeln!(f, "total moves:      at most {} (O(ants * max moves))", self.total_ops_upper_bound)?;
+        match self.expected_first_collision_iteration {
+            Some(iteration) => writeln!(f, "first fight:      around iteration {:.1}", iteration)?,
+            None => writeln!(f, "first fight:      never, too few ants")?,
+        }
+        write!(f, "run ends:         around iteration {:.0}", self.estimated_termination_iteration)
+    }
+}
@@ -0,0 +1,114 @@
+use crate::graph::{ColonyIndex, MapGraph};
+use crate::simulation::ColonyId;
+
+/// Connected components of the live map, with tunnels treated as two-way.
+///
+/// Built with union-find and then flattened so every colony points straight
+/// at its root, which makes lookups O(1) until the next rebuild. Destroying
+/// colonies can split a component, which union-find can't undo, so the
+/// simulation rebuilds after every iteration that destroyed something.
+#[derive(Debug, Clone)]
+pub(crate) struct Components {
+    root: Vec<ColonyIndex>,
+    /// Alive ants per component, indexed by root
+    alive: Vec<usize>,
+    /// Components holding at least two alive ants
+    crowded: usize,
+    /// Live colonies in the largest component
+    largest: usize,
+    /// Components with at least one live colony, and the live colonies in them
+    live_components: usize,
+    live_colonies: usize,
+}
+
+impl Components {
+    /// Find the components of the colonies still standing. A destroyed colony
+    /// that still holds survivors joins its live neighbors, since they can
+    /// walk out of it.
+    pub(crate) fn build(graph: &MapGraph, destroyed: &[bool], ant_count: &[u16]) -> Self {
+        let colonies = graph.colony_count();
+        let mut parent: Vec<ColonyIndex> = (0..colonies as ColonyIndex).collect();
+
+        for colony in 0..colonies {
+            if destroyed[colony] && ant_count[colony] == 0 {
+                continue;
+            }
+            for &neighbor in graph.neighbors(colony) {
+                if !destroyed[neighbor as usize] {
+                    union(&mut parent, colony as ColonyIndex, neighbor);
+                }
+            }
+        }
+        for colony in 0..colonies {
+            parent[colony] = find(&mut parent, colony as ColonyIndex);
+        }
+
+        let mut alive = vec![0; colonies];
+        for colony in 0..colonies {
+            alive[parent[colony] as usize] += ant_count[colony] as usize;
+        }
+        let crowded = alive.iter().filter(|&&ants| ants >= 2).count();
+
+        let mut size = vec![0; colonies];
+        for colony in (0..colonies).filter(|&colony| !destroyed[colony]) {
+            size[parent[colony] as usize] += 1;
+        }
+        let largest = size.iter().copied().max().unwrap_or(0);
+        let live_components = size.iter().filter(|&&colonies| colonies > 0).count();
+        let live_colonies = size.iter().sum();
+
+        Components { root: parent, alive, crowded, largest, live_components, live_colonies }
+    }
+
+    pub(crate) fn alive_ants_in(&self, colony: ColonyId) -> usize {
+        self.alive[self.root[colony] as usize]
+    }
+
+    /// Number of components, ruins left empty counting as their own
+    pub(crate) fn count(&self) -> usize {
+        self.root.iter().enumerate().filter(|&(colony, &root)| root as usize == colony).count()
+    }
+
+    /// Every colony in the same component as `colony`, in ID order
+    pub(crate) fn members(&self, colony: ColonyId) -> Vec<ColonyId> {
+        let root = self.root[colony];
+        (0..self.root.len()).filter(|&other| self.root[other] == root).collect()
+    }
+
+    pub(crate) fn crowded(&self) -> usize {
+        self.crowded
+    }
+
+    pub(crate) fn largest(&self) -> usize {
+        self.largest
+    }
+
+    /// Mean live colonies per component, over components with any, 0 for none
+    pub(crate) fn average_size(&self) -> f64 {
+        match self.live_components {
+            0 => 0.0,
+            components => self.live_colonies as f64 / components as f64,
+        }
+    }
+
+    pub(crate) fn heap_bytes(&self) -> usize {
+        self.root.capacity() * size_of::<ColonyIndex>() + self.alive.capacity() * size_of::<usize>()
+    }
+}
+
+fn find(parent: &mut [ColonyIndex], mut x: ColonyIndex) -> ColonyIndex {
+    while parent[x as usize] != x {
+        // path halving
+        parent[x as usize] = parent[parent[x as usize] as usize];
+        x = parent[x as usize];
+    }
+    x
+}
+
+fn union(parent: &mut [ColonyIndex], a: ColonyIndex, b: ColonyIndex) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    if a != b {
+        // keep the smaller index as root so rebuilds are deterministic
+        parent[a.max(b) as usize] = a.min(b);
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::SimError;
+use crate::simulation::{ColonyId, MAX_MOVES};
+
+/// Where ants start out
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PlacementStrategy {
+    /// Each ant picks a random live colony; several ants may share one
+    #[default]
+    Uniform,
+    /// Every ant gets a colony of its own, so there can't be more ants than colonies
+    Distinct,
+    /// Pick `clusters` random seed colonies and drop each ant on a random
+    /// colony at most `radius` tunnels away from one of them
+    Clustered { clusters: usize, radius: u32 },
+    /// Every ant starts on the same colony, and the crowd fights there (as
+    /// in a colony ants moved into) before the first move
+    Single(ColonyId),
+    /// The colony of each ant in ant ID order, one entry per ant; ants left
+    /// at `None` pick a random live colony as with `Uniform`
+    Explicit(Vec<Option<ColonyId>>),
+}
+
+/// Which ants die when a colony reaches the collision threshold. The colony
+/// is destroyed either way; survivors stay put and flee on their next move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CollisionPolicy {
+    /// Every ant in the colony dies
+    KillAll,
+    /// The first `count` ants to have arrived (in colony list order) die.
+    /// `KillFirst { count: 2 }` is the default, the original two-ant fight.
+    KillFirst { count: usize },
+    /// `count` ants picked at random die
+    KillRandom { count: usize },
+    /// The `count` ants with the most moves die, lowest ant ID first on ties
+    KillOldest { count: usize },
+}
+
+impl Default for CollisionPolicy {
+    fn default() -> Self {
+        CollisionPolicy::KillFirst { count: 2 }
+    }
+}
+
+/// How many ants, against `collision_threshold`, start a fight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FightTrigger {
+    /// Exactly `collision_threshold`; a bigger crowd, which lazy destruction
+    /// lets build up, doesn't fight. The original rule.
+    #[default]
+    Exactly,
+    /// `collision_threshold` or more, so crowds fight too and the collision
+    /// policy decides who of them dies
+    AtLeast,
+}
+
+/// Radius used for `clustered:K` on the command line
+pub const DEFAULT_CLUSTER_RADIUS: u32 = 2;
+
+/// Population and rules for one simulation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AntConfig {
+    pub num_ants: usize,
+    pub placement_strategy: PlacementStrategy,
+    /// RNG seed for placement and movement, random when `None`
+    pub seed: Option<u64>,
+    /// Moves each ant may make before it stops counting towards the run.
+    /// 0 places the ants without ever moving them.
+    pub max_moves: u32,
+    /// Ants that must meet in a colony to fight and destroy it
+    pub collision_threshold: u16,
+    /// Who dies in a fight
+    pub collision_policy: CollisionPolicy,
+    /// Whether crowds above `collision_threshold` fight
+    pub fight_trigger: FightTrigger,
+}
+
+impl AntConfig {
+    pub fn new(num_ants: usize) -> Self {
+        AntConfig { num_ants, ..AntConfig::default() }
+    }
+
+    /// `ceil(ants_per_colony * colonies)` ants, for sizing runs by map size
+    pub fn from_density(ants_per_colony: f64, colonies: usize) -> Result<Self, SimError> {
+        // colony ant counts are u16, a denser start can't even be placed
+        if !ants_per_colony.is_finite() || ants_per_colony <= 0.0 || ants_per_colony > u16::MAX as f64 {
+            return Err(SimError::InvalidConfig(format!(
+                "ants per colony must be above 0 and at most {}, got {}",
+                u16::MAX,
+                ants_per_colony
+            )));
+        }
+
+        let num_ants = (ants_per_colony * colonies as f64).ceil();
+        if num_ants > u32::MAX as f64 {
+            return Err(SimError::InvalidConfig(format!(
+                "{} ants per colony on {} colonies is {} ants, at most {} are supported",
+                ants_per_colony,
+                colonies,
+                num_ants,
+                u32::MAX
+            )));
+        }
+        Ok(AntConfig::new(num_ants as usize))
+    }
+}
+
+impl Default for AntConfig {
+    fn default() -> Self {
+        AntConfig {
+            num_ants: 0,
+            placement_strategy: PlacementStrategy::Uniform,
+            seed: None,
+            max_moves: MAX_MOVES,
+            collision_threshold: 2,
+            collision_policy: CollisionPolicy::default(),
+            fight_trigger: FightTrigger::Exactly,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+use std::io;
+
+use crate::simulation::ColonyId;
+
+/// Errors from building or running a simulation
+#[derive(Debug)]
+pub enum SimError {
+    /// Reading the map failed
+    Io(io::Error),
+    /// Edge refers to a colony index outside the map
+    InvalidEdge { from: ColonyId, to: ColonyId },
+    /// Map doesn't fit the u32 colony/connection indices
+    MapTooLarge { colonies: usize, connections: usize },
+    /// Colony has more connections than `connection_count` can hold
+    TooManyConnections { colony: String, max: usize },
+    /// Map file header names a format version this build can't read
+    UnsupportedMapVersion { found: u32, supported: u32 },
+    /// Alias can't be added: bad name, already taken, or no such colony
+    InvalidAlias { alias: String, reason: String },
+    /// Snapshot couldn't be written, read or restored
+    Snapshot(String),
+    /// Simulation settings that can't work
+    InvalidConfig(String),
+    /// Ants were to be placed at random but every colony is destroyed
+    NoLiveColoniesForPlacement,
+    /// A tunnel leads to a colony the map doesn't have
+    UnknownColony(String),
+    /// A tunnel that can't be built: back into its own colony, or one too many
+    /// to the same neighbor or in the same direction
+    InvalidTunnel { colony: String, reason: String },
+    /// A GeoJSON map that isn't a FeatureCollection of colonies
+    GeoJson(String),
+    /// Writing an export (Parquet, Arrow, SQLite) failed
+    Export(String),
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimError::Io(err) => write!(f, "failed to read map: {}", err),
+            SimError::InvalidEdge { from, to } => {
+                write!(f, "edge {} -> {} refers to a colony that doesn't exist", from, to)
+            }
+            SimError::MapTooLarge { colonies, connections } => write!(
+                f,
+                "map has {} colonies and {} connections, at most {} of each are supported",
+                colonies,
+                connections,
+                u32::MAX
+            ),
+            SimError::TooManyConnections { colony, max } => {
+                write!(f, "colony {} has more than {} connections", colony, max)
+            }
+            SimError::UnsupportedMapVersion { found, supported } => {
+                write!(f, "map format v{} isn't supported, this build reads v{}", found, supported)
+            }
+            SimError::InvalidAlias { alias, reason } => write!(f, "invalid alias '{}': {}", alias, reason),
+            SimError::Snapshot(reason) => write!(f, "snapshot: {}", reason),
+            SimError::InvalidConfig(reason) => write!(f, "invalid simulation config: {}", reason),
+            SimError::NoLiveColoniesForPlacement => {
+                write!(f, "every colony is destroyed, there's nowhere to place ants")
+            }
+            SimError::UnknownColony(name) => write!(f, "no colony called '{}'", name),
+            SimError::InvalidTunnel { colony, reason } => write!(f, "invalid tunnel out of {}: {}", colony, reason),
+            SimError::GeoJson(reason) => write!(f, "GeoJSON map: {}", reason),
+            SimError::Export(reason) => write!(f, "export: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SimError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SimError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SimError {
+    fn from(err: io::Error) -> Self {
+        SimError::Io(err)
+    }
+}
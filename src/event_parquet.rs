@@ -0,0 +1,150 @@
+//! Parquet files of `SimEvent`s, one row per event, for analysing big runs
+//! with columnar tools. The schema, which later versions keep:
+//!
+//! | column       | type   | null? | holds                                          |
+//! |--------------|--------|-------|------------------------------------------------|
+//! | `iteration`  | uint32 | no    | 0-based iteration of the event                 |
+//! | `event_type` | utf8   | no    | `move`, `fight`, `death` or `destroyed`        |
+//! | `ant_a`      | uint64 | yes   | the ant that moved or died, first of a fight   |
+//! | `ant_b`      | uint64 | yes   | the second ant of a fight                      |
+//! | `colony`     | uint32 | yes   | where a fight, death or destruction was        |
+//! | `from`       | uint32 | yes   | colony a move left                             |
+//! | `to`         | uint32 | yes   | colony a move went to                          |
+//!
+//! Columns an event type doesn't use are null. Events go out in row groups
+//! of a fixed size, so a run writes in bounded memory however long it is.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::builder::{StringBuilder, UInt32Builder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use crate::error::SimError;
+use crate::summary::SimEvent;
+
+/// Events per row group unless `EventParquetWriter::new` is told otherwise
+pub const EVENTS_PER_ROW_GROUP: usize = 1 << 20;
+
+/// Writes the `SimEvent`s of a run into a Parquet file. `finish` has to be
+/// called to write the footer, without it the file can't be read.
+pub struct EventParquetWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    schema: SchemaRef,
+    events_per_row_group: usize,
+    rows: usize,
+    written: u64,
+    iteration: UInt32Builder,
+    event_type: StringBuilder,
+    ant_a: UInt64Builder,
+    ant_b: UInt64Builder,
+    colony: UInt32Builder,
+    from: UInt32Builder,
+    to: UInt32Builder,
+}
+
+impl<W: Write + Send> EventParquetWriter<W> {
+    /// Start a file on `out`, flushing a row group every
+    /// `events_per_row_group` events
+    pub fn new(out: W, events_per_row_group: usize) -> Result<Self, SimError> {
+        if events_per_row_group == 0 {
+            return Err(SimError::Export("a row group has to hold at least one event".to_string()));
+        }
+        let schema = Self::schema();
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_row_count(Some(events_per_row_group))
+            .build();
+        let writer = ArrowWriter::try_new(out, Arc::clone(&schema), Some(properties)).map_err(export)?;
+        Ok(EventParquetWriter {
+            writer,
+            schema,
+            events_per_row_group,
+            rows: 0,
+            written: 0,
+            iteration: UInt32Builder::new(),
+            event_type: StringBuilder::new(),
+            ant_a: UInt64Builder::new(),
+            ant_b: UInt64Builder::new(),
+            colony: UInt32Builder::new(),
+            from: UInt32Builder::new(),
+            to: UInt32Builder::new(),
+        })
+    }
+
+    /// The columns of every file, see the module docs
+    pub fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("iteration", DataType::UInt32, false),
+            Field::new("event_type", DataType::Utf8, false),
+            Field::new("ant_a", DataType::UInt64, true),
+            Field::new("ant_b", DataType::UInt64, true),
+            Field::new("colony", DataType::UInt32, true),
+            Field::new("from", DataType::UInt32, true),
+            Field::new("to", DataType::UInt32, true),
+        ]))
+    }
+
+    /// Add the events of one iteration, e.g. `AntSimulation::last_events`
+    pub fn write(&mut self, iteration: u32, events: &[SimEvent]) -> Result<(), SimError> {
+        for &event in events {
+            let (ant_a, ant_b, colony, from, to) = match event {
+                SimEvent::Move { ant, from, to } => (Some(ant), None, None, Some(from), Some(to)),
+                SimEvent::Fight { colony, ant_a, ant_b } => (Some(ant_a), Some(ant_b), Some(colony), None, None),
+                SimEvent::Death { ant, colony, .. } => (Some(ant), None, Some(colony), None, None),
+                SimEvent::Destroyed { colony } => (None, None, Some(colony), None, None),
+            };
+            self.iteration.append_value(iteration);
+            self.event_type.append_value(event.name());
+            self.ant_a.append_option(ant_a.map(|ant| ant as u64));
+            self.ant_b.append_option(ant_b.map(|ant| ant as u64));
+            self.colony.append_option(colony.map(|colony| colony as u32));
+            self.from.append_option(from.map(|colony| colony as u32));
+            self.to.append_option(to.map(|colony| colony as u32));
+            self.rows += 1;
+            if self.rows == self.events_per_row_group {
+                self.flush_row_group()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Events written so far, the ones not flushed yet included
+    pub fn events_written(&self) -> u64 {
+        self.written + self.rows as u64
+    }
+
+    /// Flush what's left and write the footer, handing back `out`
+    pub fn finish(mut self) -> Result<W, SimError> {
+        if self.rows > 0 {
+            self.flush_row_group()?;
+        }
+        self.writer.into_inner().map_err(export)
+    }
+
+    fn flush_row_group(&mut self) -> Result<(), SimError> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.iteration.finish()),
+            Arc::new(self.event_type.finish()),
+            Arc::new(self.ant_a.finish()),
+            Arc::new(self.ant_b.finish()),
+            Arc::new(self.colony.finish()),
+            Arc::new(self.from.finish()),
+            Arc::new(self.to.finish()),
+        ];
+        let batch = RecordBatch::try_new(Arc::clone(&self.schema), columns).map_err(|err| export(err.into()))?;
+        self.writer.write(&batch).map_err(export)?;
+        self.writer.flush().map_err(export)?;
+        self.written += self.rows as u64;
+        self.rows = 0;
+        Ok(())
+    }
+}
+
+fn export(err: parquet::errors::ParquetError) -> SimError {
+    SimError::Export(format!("Parquet: {}", err))
+}
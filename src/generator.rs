@@ -0,0 +1,238 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::config::AntConfig;
+use crate::error::SimError;
+use crate::graph::MapGraph;
+use crate::names::ColonyNames;
+use crate::simulation::{AntSimulation, ColonyId};
+
+/// Attempts at a random regular map before giving up on the size asked for
+const REGULAR_ATTEMPTS: u32 = 1000;
+
+/// A map shape `ProceduralMapGenerator::topology` can build, written
+/// `grid:WxH`, `ring:N`, `torus:WxH` or `regular:N:D`. Every tunnel has a
+/// way back, labelled with the opposite direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// `width * height` lattice, colonies joined to their north, south, east
+    /// and west neighbors
+    Grid { width: usize, height: usize },
+    /// Cycle of `colonies`, each joined east to the next and west to the one before
+    Ring { colonies: usize },
+    /// A grid whose edges wrap around, so every colony has all four neighbors
+    Torus { width: usize, height: usize },
+    /// Random map where every colony has `degree` tunnels (at most 4), to
+    /// different colonies; picked by the seed
+    Regular { colonies: usize, degree: usize },
+}
+
+/// Builds simulations on maps that don't come in the ant_mania map format
+pub struct ProceduralMapGenerator;
+
+impl ProceduralMapGenerator {
+    /// Load a SNAP edge-list file, see `from_stanford_snap_format`
+    pub fn from_real_world_dataset(dataset: &str, num_ants: usize) -> Result<AntSimulation, SimError> {
+        let file = File::open(dataset)?;
+        Self::from_stanford_snap_format(BufReader::new(file), num_ants)
+    }
+
+    /// Parse a graph in the edge-list format of Stanford's SNAP datasets:
+    /// `#` comment lines, then one `from_id to_id` pair per line. Colonies
+    /// are named after the node IDs and every edge is a one-way `tunnel`, so
+    /// undirected datasets listing each edge once need both directions added.
+    pub fn from_stanford_snap_format<R: BufRead>(reader: R, num_ants: usize) -> Result<AntSimulation, SimError> {
+        let mut colony_names = ColonyNames::new();
+        let mut colonies = 0;
+        let mut edges = Vec::new();
+
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut ids = line.split_whitespace();
+            let (Some(from), Some(to)) = (ids.next(), ids.next()) else {
+                return Err(SimError::Io(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: expected 'from_id to_id', got '{}'", line_number + 1, line),
+                )));
+            };
+            let mut colony_id = |name: &str| {
+                let id = colony_names.get_or_insert(name);
+                colonies = colonies.max(id + 1);
+                id
+            };
+            let edge = (colony_id(from), colony_id(to));
+            edges.push(edge);
+        }
+
+        let graph = MapGraph::from_edges(colony_names, colonies, &edges)?;
+        AntSimulation::new(Arc::new(graph), AntConfig::new(num_ants))
+    }
+
+    /// A simulation of `num_ants` on a `topology` map, the seed both
+    /// picking the map and placing and moving the ants
+    pub fn from_topology(shape: Topology, num_ants: usize, seed: u64) -> Result<AntSimulation, SimError> {
+        let graph = Self::topology(shape, seed)?;
+        AntSimulation::new(Arc::new(graph), AntConfig { seed: Some(seed), ..AntConfig::new(num_ants) })
+    }
+
+    /// Build a map of the given shape in memory, without going through the
+    /// map format. Only `Topology::Regular` is random, the same seed gives it
+    /// the same map. Grid and torus colonies are named `Colony{x}_{y}`, with
+    /// y growing southwards; the others `Colony{i}`.
+    pub fn topology(shape: Topology, seed: u64) -> Result<MapGraph, SimError> {
+        let invalid = |reason: &str| Err(SimError::InvalidConfig(format!("{}: {}", shape, reason)));
+        match shape {
+            Topology::Grid { width, height } | Topology::Torus { width, height } if width == 0 || height == 0 => {
+                invalid("needs at least one colony each way")
+            }
+            // narrower and the wrapped tunnels would double up
+            Topology::Torus { width, height } if width < 3 || height < 3 => {
+                invalid("needs at least 3 colonies each way")
+            }
+            Topology::Ring { colonies } if colonies < 3 => invalid("needs at least 3 colonies"),
+            Topology::Regular { degree, .. } if degree > 4 => invalid("degree can be at most 4"),
+            Topology::Regular { colonies, degree } if colonies <= degree => {
+                invalid("needs more colonies than tunnels per colony")
+            }
+            Topology::Regular { colonies, degree } if degree % 2 == 1 && colonies % 2 == 1 => {
+                invalid("an odd degree needs an even number of colonies")
+            }
+            Topology::Grid { width, height } => lattice(width, height, false),
+            Topology::Torus { width, height } => lattice(width, height, true),
+            Topology::Ring { colonies } => {
+                let tunnels = (0..colonies)
+                    .map(|i| vec![("east", (i + 1) % colonies), ("west", (i + colonies - 1) % colonies)])
+                    .collect();
+                MapGraph::from_labelled_tunnels(&numbered_names(colonies), tunnels)
+            }
+            Topology::Regular { colonies, degree } => regular(colonies, degree, seed)
+                .map_or_else(|| invalid("no such map found, try another seed"), |tunnels| {
+                    MapGraph::from_labelled_tunnels(&numbered_names(colonies), tunnels)
+                }),
+        }
+    }
+
+    /// Write the map `topology` builds as a map file; reading it back gives
+    /// the same colonies and tunnels, in the same order
+    pub fn write_topology<W: Write + ?Sized>(shape: Topology, seed: u64, out: &mut W) -> Result<(), SimError> {
+        Self::topology(shape, seed)?.write_map(out, |_| true)?;
+        Ok(())
+    }
+}
+
+/// `Colony0` to `Colony{colonies - 1}`
+fn numbered_names(colonies: usize) -> Vec<String> {
+    (0..colonies).map(|i| format!("Colony{}", i)).collect()
+}
+
+/// Grid or torus tunnels, see `Topology`
+fn lattice(width: usize, height: usize, wrap: bool) -> Result<MapGraph, SimError> {
+    let id = |x: usize, y: usize| y * width + x;
+    let mut names = Vec::with_capacity(width * height);
+    let mut tunnels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            names.push(format!("Colony{}_{}", x, y));
+            let mut out = Vec::with_capacity(4);
+            if y > 0 || wrap {
+                out.push(("north", id(x, (y + height - 1) % height)));
+            }
+            if y + 1 < height || wrap {
+                out.push(("south", id(x, (y + 1) % height)));
+            }
+            if x + 1 < width || wrap {
+                out.push(("east", id((x + 1) % width, y)));
+            }
+            if x > 0 || wrap {
+                out.push(("west", id((x + width - 1) % width, y)));
+            }
+            tunnels.push(out);
+        }
+    }
+    MapGraph::from_labelled_tunnels(&names, tunnels)
+}
+
+/// Tunnels of a random `degree`-regular map: north/south along a random
+/// permutation for each pair of tunnels, plus east/west along a random
+/// perfect matching for an odd degree. Resampled until no colony has a
+/// tunnel to itself or two to the same colony; `None` if that keeps failing.
+fn regular(colonies: usize, degree: usize, seed: u64) -> Option<Vec<Vec<(&'static str, ColonyId)>>> {
+    let mut rng = fastrand::Rng::with_seed(seed);
+    let labels = [("north", "south"), ("east", "west")];
+    'attempt: for _ in 0..REGULAR_ATTEMPTS {
+        let mut tunnels: Vec<Vec<(&'static str, ColonyId)>> = vec![Vec::with_capacity(degree); colonies];
+        for &(forward, back) in &labels[..degree / 2] {
+            let mut order: Vec<ColonyId> = (0..colonies).collect();
+            rng.shuffle(&mut order);
+            for (i, &from) in order.iter().enumerate() {
+                let to = order[(i + 1) % colonies];
+                tunnels[from].push((forward, to));
+                tunnels[to].push((back, from));
+            }
+        }
+        if degree % 2 == 1 {
+            let (forward, back) = labels[degree / 2];
+            let mut order: Vec<ColonyId> = (0..colonies).collect();
+            rng.shuffle(&mut order);
+            for pair in order.chunks(2) {
+                tunnels[pair[0]].push((forward, pair[1]));
+                tunnels[pair[1]].push((back, pair[0]));
+            }
+        }
+        for (colony, out) in tunnels.iter().enumerate() {
+            for (i, &(_, to)) in out.iter().enumerate() {
+                if to == colony || out[..i].iter().any(|&(_, earlier)| earlier == to) {
+                    continue 'attempt;
+                }
+            }
+        }
+        return Some(tunnels);
+    }
+    None
+}
+
+impl FromStr for Topology {
+    type Err = SimError;
+
+    fn from_str(spec: &str) -> Result<Self, SimError> {
+        let invalid = || {
+            let expected = "expected grid:WxH, ring:N, torus:WxH or regular:N:D";
+            SimError::InvalidConfig(format!("map shape '{}': {}", spec, expected))
+        };
+        let number = |text: &str| text.parse::<usize>().map_err(|_| invalid());
+        let size = |text: &str| {
+            let (width, height) = text.split_once('x').ok_or_else(invalid)?;
+            Ok::<_, SimError>((number(width)?, number(height)?))
+        };
+        let (name, rest) = spec.split_once(':').ok_or_else(invalid)?;
+        match name {
+            "grid" => size(rest).map(|(width, height)| Topology::Grid { width, height }),
+            "torus" => size(rest).map(|(width, height)| Topology::Torus { width, height }),
+            "ring" => Ok(Topology::Ring { colonies: number(rest)? }),
+            "regular" => {
+                let (colonies, degree) = rest.split_once(':').ok_or_else(invalid)?;
+                Ok(Topology::Regular { colonies: number(colonies)?, degree: number(degree)? })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Topology::Grid { width, height } => write!(f, "grid:{}x{}", width, height),
+            Topology::Ring { colonies } => write!(f, "ring:{}", colonies),
+            Topology::Torus { width, height } => write!(f, "torus:{}x{}", width, height),
+            Topology::Regular { colonies, degree } => write!(f, "regular:{}:{}", colonies, degree),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! GeoJSON maps, for colonies with a place on the globe: a FeatureCollection
+//! of Point features, each with the colony's `name`, its `tunnels` written
+//! as in a map file and, optionally, other names for it in `aliases`:
+//!
+//! ```json
+//! {"type": "FeatureCollection", "features": [
+//!   {"type": "Feature", "geometry": {"type": "Point", "coordinates": [2.35, 48.86]},
+//!    "properties": {"name": "Paris", "tunnels": ["south=Lyon"], "aliases": ["Lutetia"]}}
+//! ]}
+//! ```
+//!
+//! Coordinates are only carried along for output and `distance_km`; ants
+//! move through a GeoJSON map exactly as through the same map file.
+
+use std::io::{self, Write};
+
+use serde_json::{json, Map, Value};
+
+use crate::error::SimError;
+use crate::graph::{MapGraph, ALIASES_SECTION};
+use crate::simulation::{AntSimulation, ColonyId};
+
+impl MapGraph {
+    /// Parse a GeoJSON map. Each feature is read as one line of a map file,
+    /// so `parse_warnings` count features (from 1) for lines. Features with a
+    /// `null` geometry have no coordinates; tunnels can't go by an alias.
+    pub fn from_geojson(text: &str) -> Result<Self, SimError> {
+        let collection: Value = serde_json::from_str(text).map_err(|err| invalid(err.to_string()))?;
+        if collection["type"] != "FeatureCollection" {
+            return Err(invalid("expected a FeatureCollection".to_string()));
+        }
+        let features = collection["features"].as_array().ok_or_else(|| invalid("no features".to_string()))?;
+        
+        let mut lines = String::new();
+        let mut colonies = Vec::with_capacity(features.len());
+        for (i, feature) in features.iter().enumerate() {
+            let at = |reason: &str| invalid(format!("feature {}: {}", i + 1, reason));
+            let properties = &feature["properties"];
+            let name = properties["name"]
+                .as_str()
+                .filter(|&name| is_word(name) && !name.contains('=') && name != ALIASES_SECTION)
+                .ok_or_else(|| at("`name` has to be a single word without '='"))?;
+            let tunnels = words(&properties["tunnels"]).ok_or_else(|| at("`tunnels` has to be a list of words"))?;
+            let aliases = words(&properties["aliases"]).ok_or_else(|| at("`aliases` has to be a list of words"))?;
+            let coordinates = match &feature["geometry"] {
+                Value::Null => None,
+                geometry => Some(point(geometry).ok_or_else(|| at("geometry has to be a Point or null"))?),
+            };
+            lines.push_str(name);
+            for tunnel in tunnels {
+                lines.push(' ');
+                lines.push_str(tunnel);
+            }
+            lines.push('\n');
+            colonies.push((name, aliases, coordinates));
+        }
+        
+        let mut graph = MapGraph::from_reader(lines.as_bytes())?;
+        graph.legacy_format = false;
+        graph.coordinates = vec![None; graph.colony_count()];
+        for (name, aliases, coordinates) in colonies {
+            let colony_id = graph.find_colony(name).expect("every feature is a colony");
+            // a repeated colony keeps its first feature's coordinates
+            graph.coordinates[colony_id] = graph.coordinates[colony_id].or(coordinates);
+            for alias in aliases {
+                graph.add_alias(colony_id, alias)?;
+            }
+        }
+        Ok(graph)
+    }
+    
+    /// Write the map as a GeoJSON FeatureCollection `from_geojson` reads
+    /// back, skipping colonies (and tunnels into and aliases of colonies)
+    /// for which `keep` returns false
+    pub fn write_geojson<W: Write + ?Sized>(&self, out: &mut W, keep: impl Fn(ColonyId) -> bool) -> io::Result<()> {
+        self.write_features(out, keep, |_| Map::new())
+    }
+    
+    /// `write_geojson` with `properties` added to each colony's own
+    fn write_features<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        keep: impl Fn(ColonyId) -> bool,
+        properties: impl Fn(ColonyId) -> Map<String, Value>,
+    ) -> io::Result<()> {
+        let features: Vec<Value> = (0..self.colony_count())
+            .filter(|&colony_id| keep(colony_id))
+            .map(|colony_id| {
+                let tunnels: Vec<String> = self
+                    .neighbors(colony_id)
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &neighbor_id)| keep(neighbor_id as ColonyId))
+                    .map(|(i, &neighbor_id)| {
+                        format!("{}={}", self.edge_label(colony_id, i), self.colony_name(neighbor_id as ColonyId))
+                    })
+                    .collect();
+                let mut colony = Map::new();
+                colony.insert("name".to_string(), json!(self.colony_name(colony_id)));
+                colony.insert("tunnels".to_string(), json!(tunnels));
+                let aliases: Vec<&str> = self
+                    .aliases
+                    .iter()
+                    .filter(|&&(_, aliased)| aliased == colony_id)
+                    .map(|(alias, _)| alias.as_str())
+                    .collect();
+                if !aliases.is_empty() {
+                    colony.insert("aliases".to_string(), json!(aliases));
+                }
+                colony.extend(properties(colony_id));
+                let geometry = match self.coordinates(colony_id) {
+                    Some(coordinates) => json!({"type": "Point", "coordinates": coordinates}),
+                    None => Value::Null,
+                };
+                json!({"type": "Feature", "geometry": geometry, "properties": colony})
+            })
+            .collect();
+        serde_json::to_writer(&mut *out, &json!({"type": "FeatureCollection", "features": features}))?;
+        writeln!(out)
+    }
+}
+
+impl AntSimulation {
+    /// Write the surviving colonies and tunnels as GeoJSON (see
+    /// `MapGraph::write_geojson`), each colony with its `ants`
+    pub fn write_geojson<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        let colonies: Vec<_> = self.iter().collect();
+        self.graph().write_features(
+            out,
+            |colony_id| !colonies[colony_id].destroyed,
+            |colony_id| Map::from_iter([("ants".to_string(), json!(colonies[colony_id].ant_count))]),
+        )
+    }
+}
+
+/// The words of a string list, none for a missing list
+fn words(value: &Value) -> Option<Vec<&str>> {
+    match value {
+        Value::Null => Some(Vec::new()),
+        Value::Array(list) => list.iter().map(|word| word.as_str().filter(|&word| is_word(word))).collect(),
+        _ => None,
+    }
+}
+
+fn is_word(text: &str) -> bool {
+    !text.is_empty() && !text.contains(char::is_whitespace)
+}
+
+/// `[longitude, latitude]` of a Point geometry
+fn point(geometry: &Value) -> Option<[f64; 2]> {
+    if geometry["type"] != "Point" {
+        return None;
+    }
+    match geometry["coordinates"].as_array()?.as_slice() {
+        [longitude, latitude, ..] => Some([longitude.as_f64()?, latitude.as_f64()?]),
+        _ => None,
+    }
+}
+
+fn invalid(reason: String) -> SimError {
+    SimError::GeoJson(reason)
+}
@@ -0,0 +1,1132 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+use crate::components::Components;
+use crate::error::SimError;
+use crate::names::ColonyNames;
+use crate::options::MapOptions;
+use crate::simulation::ColonyId;
+use crate::stats::SampleStats;
+use crate::summary::{Preview, DEBUG_PREVIEW};
+
+/// Compact colony index for the hot per-colony/per-ant arrays
+pub(crate) type ColonyIndex = u32;
+
+/// First line of a versioned map file, followed by ` v<N>`
+pub const MAP_HEADER: &str = "ant_mania_map";
+/// Map format version this build reads and writes
+pub const MAP_FORMAT_VERSION: u32 = 1;
+
+/// Label given to edges built without a direction
+pub const DEFAULT_EDGE_LABEL: &str = "tunnel";
+
+/// Line starting the section of `alias=colony` lines in a map file
+pub(crate) const ALIASES_SECTION: &str = "[aliases]";
+
+/// `key=value` attributes of one colony, in the order given
+pub(crate) type Attributes = Vec<(String, String)>;
+
+/// Colony attributes some feature reads, see `MapGraph::colony_attr`;
+/// others are kept but warned about
+pub const KNOWN_ATTRIBUTES: &[&str] = &["region"];
+
+/// Mean radius of the Earth, for `distance_km`
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Order `MapGraph::write_map_ordered` writes a map in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MapOrder {
+    /// Colonies and tunnels as the map declared them
+    #[default]
+    Declared,
+    /// A canonical form for diffing maps from different sources: colonies
+    /// and aliases by name, each colony's tunnels by label then target,
+    /// comparing bytes
+    Sorted,
+}
+
+/// Direction a tunnel leads in, its label in map files
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    /// Any other label (`ferry`, `up`, ...), a single word without `=`
+    Other(String),
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Direction::North => "north",
+            Direction::South => "south",
+            Direction::East => "east",
+            Direction::West => "west",
+            Direction::Other(label) => label,
+        }
+    }
+    
+    /// Label of the tunnel back: north and south swap, as do east and west.
+    /// Any other label is its own opposite, so the way back from a `ferry`
+    /// is a `ferry` too.
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+            Direction::Other(label) => Direction::Other(label.clone()),
+        }
+    }
+}
+
+impl From<&str> for Direction {
+    fn from(label: &str) -> Self {
+        match label {
+            "north" => Direction::North,
+            "south" => Direction::South,
+            "east" => Direction::East,
+            "west" => Direction::West,
+            other => Direction::Other(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parsed map: colony names plus the compressed adjacency list. It never
+/// changes once built, so one graph can back many simulations through an `Arc`.
+pub struct MapGraph {
+    /// Colony names for final output (only used at start/end)
+    pub(crate) colony_names: ColonyNames,
+    
+    /// Adjacency List (compressed)
+    pub(crate) adjacency_list: Vec<ColonyIndex>,
+    
+    /// Starting index in adjacency_list for each colony's connections
+    pub(crate) start_index: Vec<u32>,
+    
+    /// Number of connections for each colony
+    pub(crate) connection_count: Vec<u8>,  // u8 since max is 4 connections, more is rejected while parsing
+    
+    /// Direction label of each connection (`north`, `ferry`, ...), parallel to adjacency_list
+    pub(crate) edge_labels: Vec<String>,
+    
+    /// Other names for colonies, in the order they were added
+    pub(crate) aliases: Vec<(String, ColonyId)>,
+    
+    /// Parsed from a file without the version header
+    pub(crate) legacy_format: bool,
+    
+    /// What `from_reader` let through, in line order
+    parse_warnings: Vec<ParseWarning>,
+    
+    /// `[longitude, latitude]` of each colony, empty unless read from GeoJSON
+    pub(crate) coordinates: Vec<Option<[f64; 2]>>,
+    
+    /// Tunnels `symmetrize` added
+    reverse_tunnels_added: usize,
+    
+    /// Attributes of each colony, empty unless some colony has one
+    pub(crate) attributes: Vec<Attributes>,
+}
+
+/// Sizes and the first few colonies, cheap however big the map
+impl fmt::Debug for MapGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let first: Vec<&str> = (0..self.colony_count().min(DEBUG_PREVIEW)).map(|id| self.colony_name(id)).collect();
+        f.debug_struct("MapGraph")
+            .field("colonies", &self.colony_count())
+            .field("tunnels", &self.edge_count())
+            .field("first_colonies", &first)
+            .field("aliases", &Preview(&self.aliases))
+            .field("legacy_format", &self.legacy_format)
+            .field("parse_warnings", &self.parse_warnings.len())
+            .field("coordinates", &!self.coordinates.is_empty())
+            .field("reverse_tunnels_added", &self.reverse_tunnels_added)
+            .field("attributes", &!self.attributes.is_empty())
+            .finish()
+    }
+}
+
+/// Something odd in a map file that didn't stop it loading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// 1-based line of the map file
+    pub line: usize,
+    pub kind: ParseWarningKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarningKind {
+    /// A tunnel to a colony the map doesn't have, left out
+    DanglingTunnel { colony: String, label: String, target: String },
+    /// A word after the colony name that isn't `label=colony`, ignored
+    MalformedTunnel { colony: String, text: String },
+    /// A tunnel with no tunnel back the other way
+    OneWayTunnel { colony: String, label: String, target: String },
+    /// Two tunnels out of a colony under one label, both kept
+    DuplicateLabel { colony: String, label: String },
+    /// A colony on more than one line, its tunnels merged
+    RepeatedColony { colony: String, first_line: usize },
+    /// A colony name that only differs in case from an earlier one
+    CaseCollision { colony: String, other: String },
+    /// A region file line for a colony the map doesn't have, skipped
+    UnknownColony { colony: String },
+    /// A colony given a region on more than one line of a region file
+    RepeatedRegion { colony: String, first_line: usize },
+    /// A colony attribute no feature reads (not in `KNOWN_ATTRIBUTES`), kept
+    UnknownAttribute { colony: String, key: String },
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ParseWarningKind::DanglingTunnel { colony, label, target } => {
+                write!(f, "{} {}={} leads to no colony on the map, dropped", colony, label, target)
+            }
+            ParseWarningKind::MalformedTunnel { colony, text } => {
+                write!(f, "'{}' after {} isn't a label=colony tunnel, ignored", text, colony)
+            }
+            ParseWarningKind::OneWayTunnel { colony, label, target } => {
+                write!(f, "{} {}={} has no tunnel back from {}", colony, label, target, target)
+            }
+            ParseWarningKind::DuplicateLabel { colony, label } => {
+                write!(f, "{} has more than one '{}' tunnel, all are kept", colony, label)
+            }
+            ParseWarningKind::RepeatedColony { colony, first_line } => {
+                write!(f, "{} was already listed on line {}, its tunnels are merged", colony, first_line)
+            }
+            ParseWarningKind::CaseCollision { colony, other } => {
+                write!(f, "{} differs from {} only in case", colony, other)
+            }
+            ParseWarningKind::UnknownColony { colony } => write!(f, "no colony called {} on the map, skipped", colony),
+            ParseWarningKind::RepeatedRegion { colony, first_line } => {
+                write!(f, "{} was already given a region on line {}, that one is kept", colony, first_line)
+            }
+            ParseWarningKind::UnknownAttribute { colony, key } => {
+                write!(f, "{} has attribute '{}', which nothing reads", colony, key)
+            }
+        }
+    }
+}
+
+/// Shape of a map, for reports before anything is simulated
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapStats {
+    pub colonies: usize,
+    pub tunnels: usize,
+    /// Tunnels out of each colony
+    pub degree: SampleStats,
+    /// Number of colonies with each number of tunnels out, indexed by degree
+    pub degree_histogram: Vec<usize>,
+    /// Connected components, with tunnels counted as two-way
+    pub components: usize,
+}
+
+impl MapGraph {
+    /// Load a map file, or a GeoJSON one (see `from_geojson`) when the
+    /// name ends in `.geojson`
+    pub fn from_file(filename: &str) -> Result<Self, SimError> {
+        #[cfg(feature = "geojson")]
+        if filename.ends_with(".geojson") {
+            return Self::from_geojson(&std::fs::read_to_string(filename)?);
+        }
+        let file = File::open(filename)?;
+        Self::from_reader(BufReader::new(file))
+    }
+    
+    /// Load a map file as `from_file` does, then apply `options` to it
+    pub fn from_file_with(filename: &str, options: &MapOptions) -> Result<Self, SimError> {
+        let mut graph = Self::from_file(filename)?;
+        if options.symmetrize {
+            graph.symmetrize()?;
+        }
+        if options.drop_name_index {
+            graph.drop_name_index();
+        }
+        Ok(graph)
+    }
+    
+    /// Parse a map. A first line of `ant_mania_map v1` marks the versioned
+    /// format; without it the map is read the legacy way (same syntax, see
+    /// `is_legacy_format`). Assumes no colony is called `ant_mania_map`.
+    ///
+    /// An optional `[aliases]` line ends the colonies; each line after it,
+    /// `NYC=New_York_City`, gives a colony another name tunnels can use.
+    ///
+    /// A colony name can be followed by attributes in brackets,
+    /// `Foo[cap=2,protected,note="a, b"] north=Bar`, read with `colony_attr`.
+    /// Values with commas, brackets or quotes in them go in double quotes,
+    /// with `\"` and `\\` inside; a bare key has an empty value.
+    ///
+    /// Oddities that don't stop the map loading are collected in
+    /// `parse_warnings` rather than reported.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, SimError> {
+        // First pass: collect all colony names and build name->ID mapping
+        let mut colony_names = ColonyNames::new();
+        // (label, target, line) of every tunnel, and the line each colony is on
+        let mut raw_connections: Vec<Vec<(String, String, usize)>> = Vec::new();
+        let mut colony_lines: Vec<usize> = Vec::new();
+        let mut attributes: Vec<Attributes> = Vec::new();
+        let mut warnings = Vec::new();
+        let mut raw_aliases: Vec<(String, String)> = Vec::new();
+        let mut in_aliases = false;
+        let mut legacy_format = true;
+        let mut first_line = true;
+        
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line.map_err(|err| match err.kind() {
+                io::ErrorKind::InvalidData => invalid_line(line_number + 1, "isn't valid UTF-8".to_string()),
+                _ => err,
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if std::mem::take(&mut first_line) {
+                if let Some(version) = line.trim().strip_prefix(MAP_HEADER) {
+                    check_map_version(version)?;
+                    legacy_format = false;
+                    continue;
+                }
+            }
+            if in_aliases {
+                let Some((alias, colony)) = line.trim().split_once('=') else {
+                    let reason = format!("expected 'alias=colony' in {}, got '{}'", ALIASES_SECTION, line);
+                    return Err(SimError::Io(invalid_line(line_number + 1, reason)));
+                };
+                raw_aliases.push((alias.trim().to_string(), colony.trim().to_string()));
+                continue;
+            }
+            if line.trim() == ALIASES_SECTION {
+                in_aliases = true;
+                continue;
+            }
+            
+            // First comes the colony name, assign ID if new colony. A name
+            // with `=` could never be a tunnel's target, so it's a missing name.
+            let line_number = line_number + 1;
+            let (name, colony_attributes, tunnels) =
+                split_attributes(&line).map_err(|reason| SimError::Io(invalid_line(line_number, reason)))?;
+            if name.is_empty() || name.contains('=') {
+                let reason = format!("expected a colony name before the tunnels, got '{}'", line.trim());
+                return Err(SimError::Io(invalid_line(line_number, reason)));
+            }
+            let parts: Vec<&str> = tunnels.split_whitespace().collect();
+            let colony_id = colony_names.get_or_insert(name);
+            if colony_id == raw_connections.len() {
+                raw_connections.push(Vec::new());
+                colony_lines.push(line_number);
+                attributes.push(Vec::new());
+            } else {
+                warnings.push(ParseWarning {
+                    line: line_number,
+                    kind: ParseWarningKind::RepeatedColony {
+                        colony: name.to_string(),
+                        first_line: colony_lines[colony_id],
+                    },
+                });
+            }
+            for (key, value) in colony_attributes {
+                if !KNOWN_ATTRIBUTES.contains(&key.as_str()) {
+                    let kind = ParseWarningKind::UnknownAttribute { colony: name.to_string(), key: key.clone() };
+                    warnings.push(ParseWarning { line: line_number, kind });
+                }
+                // a repeated colony's attributes are merged, the later line winning
+                let colony_attributes = &mut attributes[colony_id];
+                match colony_attributes.iter_mut().find(|(other, _)| *other == key) {
+                    Some((_, old)) => *old = value,
+                    None => colony_attributes.push((key, value)),
+                }
+            }
+            
+            // Parse connections
+            for part in &parts {
+                let connection_parts: Vec<&str> = part.split('=').collect();
+                if connection_parts.len() == 2 {
+                    let label = connection_parts[0].to_string();
+                    let target_name = connection_parts[1].to_string();
+                    raw_connections[colony_id].push((label, target_name, line_number));
+                } else {
+                    let (colony, text) = (name.to_string(), part.to_string());
+                    let kind = ParseWarningKind::MalformedTunnel { colony, text };
+                    warnings.push(ParseWarning { line: line_number, kind });
+                }
+            }
+        }
+        
+        let total_colonies = raw_connections.len();
+        let total_connections: usize = raw_connections.iter().map(Vec::len).sum();
+        check_map_size(total_colonies, total_connections)?;
+        
+        let mut alias_ids = HashMap::with_capacity(raw_aliases.len());
+        for (alias, colony) in &raw_aliases {
+            check_alias_name(alias)?;
+            let Some(colony_id) = colony_names.get(colony) else {
+                return Err(invalid_alias(alias, format!("no colony named '{}'", colony)));
+            };
+            if colony_names.get(alias).is_some() || alias_ids.insert(alias.as_str(), colony_id).is_some() {
+                return Err(invalid_alias(alias, "the name is already taken".to_string()));
+            }
+        }
+        
+        // Build adjacency list
+        let mut adjacency_list = Vec::with_capacity(total_connections);
+        let mut edge_labels = Vec::with_capacity(total_connections);
+        let mut start_index = vec![0; total_colonies];
+        let mut connection_count = vec![0u8; total_colonies];
+        
+        let mut edge_lines = Vec::with_capacity(total_connections);
+        
+        for (colony_id, connections) in raw_connections.into_iter().enumerate() {
+            start_index[colony_id] = adjacency_list.len() as u32;
+            
+            for (label, target_name, line) in connections {
+                let target_id = colony_names.get(&target_name).or_else(|| alias_ids.get(target_name.as_str()).copied());
+                if let Some(target_id) = target_id {
+                    if connection_count[colony_id] == u8::MAX {
+                        return Err(too_many_connections(colony_names.name(colony_id)));
+                    }
+                    adjacency_list.push(target_id as ColonyIndex);
+                    edge_labels.push(label);
+                    edge_lines.push(line);
+                    connection_count[colony_id] += 1;
+                } else {
+                    let colony = colony_names.name(colony_id).to_string();
+                    let kind = ParseWarningKind::DanglingTunnel { colony, label, target: target_name };
+                    warnings.push(ParseWarning { line, kind });
+                }
+            }
+        }
+        
+        let aliases = raw_aliases.iter().map(|(alias, _)| (alias.clone(), alias_ids[alias.as_str()])).collect();
+        colony_names.finish();
+        
+        let mut graph = MapGraph {
+            colony_names,
+            adjacency_list,
+            start_index,
+            connection_count,
+            edge_labels,
+            aliases,
+            legacy_format,
+            parse_warnings: Vec::new(),
+            coordinates: Vec::new(),
+            reverse_tunnels_added: 0,
+            attributes: if attributes.iter().all(Vec::is_empty) { Vec::new() } else { attributes },
+        };
+        warnings.extend(graph.tunnel_warnings(&edge_lines));
+        warnings.extend(graph.case_collisions(&colony_lines));
+        warnings.sort_by_key(|warning| warning.line);
+        graph.parse_warnings = warnings;
+        Ok(graph)
+    }
+    
+    /// One-way tunnels and labels used twice, `edge_lines` giving the line
+    /// each tunnel was read from
+    fn tunnel_warnings(&self, edge_lines: &[usize]) -> Vec<ParseWarning> {
+        let mut warnings = Vec::new();
+        for colony_id in 0..self.colony_count() {
+            let start = self.start_index[colony_id] as usize;
+            let colony = self.colony_name(colony_id);
+            for (i, &target) in self.neighbors(colony_id).iter().enumerate() {
+                let edge = start + i;
+                let label = &self.edge_labels[edge];
+                if !self.neighbors(target as ColonyId).contains(&(colony_id as ColonyIndex)) {
+                    warnings.push(ParseWarning {
+                        line: edge_lines[edge],
+                        kind: ParseWarningKind::OneWayTunnel {
+                            colony: colony.to_string(),
+                            label: label.clone(),
+                            target: self.colony_name(target as ColonyId).to_string(),
+                        },
+                    });
+                }
+                // reported once, at the label's second use
+                if self.edge_labels[start..edge].iter().filter(|&other| other == label).count() == 1 {
+                    warnings.push(ParseWarning {
+                        line: edge_lines[edge],
+                        kind: ParseWarningKind::DuplicateLabel { colony: colony.to_string(), label: label.clone() },
+                    });
+                }
+            }
+        }
+        warnings
+    }
+    
+    /// Colony names equal to an earlier one but for case
+    fn case_collisions(&self, colony_lines: &[usize]) -> Vec<ParseWarning> {
+        let mut folded: HashMap<String, ColonyId> = HashMap::new();
+        let mut warnings = Vec::new();
+        for (colony_id, &line) in colony_lines.iter().enumerate() {
+            let name = self.colony_name(colony_id);
+            let first = *folded.entry(name.to_lowercase()).or_insert(colony_id);
+            if first != colony_id {
+                warnings.push(ParseWarning {
+                    line,
+                    kind: ParseWarningKind::CaseCollision {
+                        colony: name.to_string(),
+                        other: self.colony_name(first).to_string(),
+                    },
+                });
+            }
+        }
+        warnings
+    }
+    
+    /// Build a map from `(source, target)` edges between colonies `0..n`,
+    /// named `Colony0` to `Colony{n-1}`, with every edge labelled `tunnel`
+    pub fn from_adjacency_list_raw(edges: &[(usize, usize)], n: usize) -> Result<Self, SimError> {
+        check_map_size(n, edges.len())?;
+        
+        let mut colony_names = ColonyNames::new();
+        for colony_id in 0..n {
+            colony_names.get_or_insert(&format!("Colony{}", colony_id));
+        }
+        Self::from_edges(colony_names, n, edges)
+    }
+    
+    /// Build a map from `(source, target)` edges between the `n` colonies in
+    /// `colony_names`, with every edge labelled `tunnel`
+    pub(crate) fn from_edges(mut colony_names: ColonyNames, n: usize, edges: &[(usize, usize)]) -> Result<Self, SimError> {
+        check_map_size(n, edges.len())?;
+        
+        // Count connections per colony, then bucket edges by source (keeping their order)
+        let mut connection_count = vec![0u8; n];
+        for &(from, to) in edges {
+            if from >= n || to >= n {
+                return Err(SimError::InvalidEdge { from, to });
+            }
+            if connection_count[from] == u8::MAX {
+                return Err(too_many_connections(colony_names.name(from)));
+            }
+            connection_count[from] += 1;
+        }
+        
+        let mut start_index = vec![0u32; n];
+        let mut next_free = 0;
+        for (colony_id, &count) in connection_count.iter().enumerate() {
+            start_index[colony_id] = next_free;
+            next_free += count as u32;
+        }
+        
+        let mut adjacency_list = vec![0; edges.len()];
+        let mut fill = start_index.clone();
+        for &(from, to) in edges {
+            adjacency_list[fill[from] as usize] = to as ColonyIndex;
+            fill[from] += 1;
+        }
+        
+        colony_names.finish();
+        
+        let edge_labels = vec![DEFAULT_EDGE_LABEL.to_string(); edges.len()];
+        Ok(MapGraph {
+            colony_names,
+            adjacency_list,
+            start_index,
+            connection_count,
+            edge_labels,
+            aliases: Vec::new(),
+            legacy_format: false,
+            parse_warnings: Vec::new(),
+            coordinates: Vec::new(),
+            reverse_tunnels_added: 0,
+            attributes: Vec::new(),
+        })
+    }
+    
+    /// Build a map from each colony's name and its `(neighbor, direction)`
+    /// tunnels, in order; colonies without tunnels still need an entry.
+    /// Colony IDs go by name, sorted, so the same map always numbers its
+    /// colonies the same way. A neighbor that has no entry, a tunnel back to
+    /// its own colony, and two tunnels out of one colony to the same neighbor
+    /// or in the same direction are errors.
+    pub fn from_dict(adjacency: HashMap<String, Vec<(String, Direction)>>) -> Result<Self, SimError> {
+        let mut names: Vec<&str> = adjacency.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        let ids: HashMap<&str, ColonyId> = names.iter().enumerate().map(|(id, &name)| (name, id)).collect();
+        
+        let mut tunnels = Vec::with_capacity(names.len());
+        for (colony, &name) in names.iter().enumerate() {
+            check_colony_name(name)?;
+            let mut out: Vec<(&str, ColonyId)> = Vec::with_capacity(adjacency[name].len());
+            for (neighbor, direction) in &adjacency[name] {
+                let &to = ids.get(neighbor.as_str()).ok_or_else(|| SimError::UnknownColony(neighbor.clone()))?;
+                push_tunnel(&mut out, (colony, name), direction.as_str(), (to, neighbor))?;
+            }
+            tunnels.push(out);
+        }
+        Self::from_labelled_tunnels(&names, tunnels)
+    }
+    
+    /// Build a map from colony names and each colony's `(label, target)` tunnels
+    pub(crate) fn from_labelled_tunnels<S: AsRef<str>>(
+        names: &[S],
+        tunnels: Vec<Vec<(&str, ColonyId)>>,
+    ) -> Result<Self, SimError> {
+        let mut colony_names = ColonyNames::new();
+        for name in names {
+            colony_names.get_or_insert(name.as_ref());
+        }
+        
+        let mut adjacency_list = Vec::new();
+        let mut edge_labels = Vec::new();
+        let mut start_index = Vec::with_capacity(names.len());
+        let mut connection_count = Vec::with_capacity(names.len());
+        for (colony, out) in tunnels.into_iter().enumerate() {
+            let count = u8::try_from(out.len()).map_err(|_| too_many_connections(names[colony].as_ref()))?;
+            start_index.push(adjacency_list.len() as u32);
+            connection_count.push(count);
+            for (label, target) in out {
+                if target >= names.len() {
+                    return Err(SimError::InvalidEdge { from: colony, to: target });
+                }
+                adjacency_list.push(target as ColonyIndex);
+                edge_labels.push(label.to_string());
+            }
+        }
+        check_map_size(names.len(), adjacency_list.len())?;
+        colony_names.finish();
+        Ok(Self::from_parts(colony_names, adjacency_list, start_index, connection_count, edge_labels, Vec::new()))
+    }
+    
+    /// Reassemble a map stored in a snapshot, which checked the parts fit together
+    pub(crate) fn from_parts(
+        colony_names: ColonyNames,
+        adjacency_list: Vec<ColonyIndex>,
+        start_index: Vec<u32>,
+        connection_count: Vec<u8>,
+        edge_labels: Vec<String>,
+        aliases: Vec<(String, ColonyId)>,
+    ) -> Self {
+        MapGraph {
+            colony_names,
+            adjacency_list,
+            start_index,
+            connection_count,
+            edge_labels,
+            aliases,
+            legacy_format: false,
+            parse_warnings: Vec::new(),
+            coordinates: Vec::new(),
+            reverse_tunnels_added: 0,
+            attributes: Vec::new(),
+        }
+    }
+    
+    /// Add a tunnel back for every one-way tunnel, labelled with the
+    /// `Direction::opposite` of the tunnel there and put after the colony's
+    /// own tunnels, returning how many were added. The one-way tunnel
+    /// warnings go, the others stay. Fails, leaving the map as it was, if a
+    /// colony would end up with more than `max_connections_per_colony`
+    /// tunnels.
+    pub fn symmetrize(&mut self) -> Result<usize, SimError> {
+        let colonies = self.colony_count();
+        let mut added: Vec<Vec<(String, ColonyIndex)>> = vec![Vec::new(); colonies];
+        for colony_id in 0..colonies {
+            let start = self.start_index[colony_id] as usize;
+            for (i, &target) in self.neighbors(colony_id).iter().enumerate() {
+                if !self.neighbors(target as ColonyId).contains(&(colony_id as ColonyIndex)) {
+                    let label = Direction::from(self.edge_labels[start + i].as_str()).opposite();
+                    added[target as usize].push((label.as_str().to_string(), colony_id as ColonyIndex));
+                }
+            }
+        }
+        let count: usize = added.iter().map(Vec::len).sum();
+        if count == 0 {
+            return Ok(0);
+        }
+        for (colony_id, back) in added.iter().enumerate() {
+            if self.connection_count[colony_id] as usize + back.len() > Self::max_connections_per_colony() {
+                return Err(too_many_connections(self.colony_name(colony_id)));
+            }
+        }
+        check_map_size(colonies, self.edge_count() + count)?;
+        
+        let mut adjacency_list = Vec::with_capacity(self.edge_count() + count);
+        let mut edge_labels = Vec::with_capacity(self.edge_count() + count);
+        for (colony_id, back) in added.into_iter().enumerate() {
+            let start = self.start_index[colony_id] as usize;
+            let end = start + self.connection_count[colony_id] as usize;
+            self.start_index[colony_id] = adjacency_list.len() as u32;
+            self.connection_count[colony_id] += back.len() as u8;
+            adjacency_list.extend_from_slice(&self.adjacency_list[start..end]);
+            edge_labels.extend(self.edge_labels[start..end].iter_mut().map(std::mem::take));
+            for (label, target) in back {
+                adjacency_list.push(target);
+                edge_labels.push(label);
+            }
+        }
+        self.adjacency_list = adjacency_list;
+        self.edge_labels = edge_labels;
+        self.parse_warnings.retain(|warning| !matches!(warning.kind, ParseWarningKind::OneWayTunnel { .. }));
+        self.reverse_tunnels_added += count;
+        Ok(count)
+    }
+    
+    /// Tunnels `symmetrize` added to the map, 0 if it never ran
+    pub fn reverse_tunnels_added(&self) -> usize {
+        self.reverse_tunnels_added
+    }
+    
+    /// Value of a colony's `key` attribute, none if it wasn't given; a bare
+    /// key in the map file gives an empty value
+    pub fn colony_attr(&self, colony: ColonyId, key: &str) -> Option<&str> {
+        let attributes = self.attributes.get(colony)?;
+        attributes.iter().find(|(other, _)| other == key).map(|(_, value)| value.as_str())
+    }
+    
+    /// Every attribute of a colony, in the order the map gave them
+    pub fn colony_attrs(&self, colony: ColonyId) -> impl Iterator<Item = (&str, &str)> + '_ {
+        let attributes = self.attributes.get(colony).map_or(&[][..], Vec::as_slice);
+        attributes.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+    
+    /// Number of colonies in the map
+    pub fn colony_count(&self) -> usize {
+        self.start_index.len()
+    }
+    
+    /// Number of connections in the map
+    pub fn edge_count(&self) -> usize {
+        self.adjacency_list.len()
+    }
+    
+    pub fn colony_name(&self, colony: ColonyId) -> &str {
+        self.colony_names.name(colony)
+    }
+    
+    /// Look a colony up by name or alias. Names match exactly, case
+    /// included; a hash lookup while the name index is kept (see
+    /// `drop_name_index`), a scan after. Aliases are always scanned.
+    pub fn find_colony(&self, name: &str) -> Option<ColonyId> {
+        self.colony_names.find(name).or_else(|| {
+            self.aliases.iter().find(|(alias, _)| alias == name).map(|&(_, colony_id)| colony_id)
+        })
+    }
+    
+    /// Drop the name -> ID index kept for `find_colony`, for runs that never
+    /// look a colony up by name and would rather have the memory. Lookups
+    /// still work, as a scan over every name. No-op with the `intern`
+    /// feature, whose pool is the index.
+    pub fn drop_name_index(&mut self) {
+        self.colony_names.drop_index();
+    }
+    
+    /// Whether `find_colony` has the name index to go by
+    pub fn has_name_index(&self) -> bool {
+        self.colony_names.has_index()
+    }
+    
+    /// `[longitude, latitude]` of a colony, in degrees, if the map gave one
+    pub fn coordinates(&self, colony: ColonyId) -> Option<[f64; 2]> {
+        self.coordinates.get(colony).copied().flatten()
+    }
+    
+    /// Great-circle distance in kilometres between two colonies (haversine,
+    /// on a sphere of the Earth's mean radius), if both have coordinates.
+    /// Only for reporting: ants move along tunnels however far apart.
+    pub fn distance_km(&self, from: ColonyId, to: ColonyId) -> Option<f64> {
+        let [from_lon, from_lat] = self.coordinates(from)?.map(f64::to_radians);
+        let [to_lon, to_lat] = self.coordinates(to)?.map(f64::to_radians);
+        let a = ((to_lat - from_lat) / 2.0).sin().powi(2)
+            + from_lat.cos() * to_lat.cos() * ((to_lon - from_lon) / 2.0).sin().powi(2);
+        Some(2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin())
+    }
+    
+    /// Total `distance_km` along a path of colonies, e.g. the ones an ant
+    /// visited, if every colony on it has coordinates
+    pub fn path_km(&self, path: &[ColonyId]) -> Option<f64> {
+        path.windows(2).map(|step| self.distance_km(step[0], step[1])).sum()
+    }
+    
+    /// Let `alias` stand for `colony_id` in `find_colony` from now on. The
+    /// alias has to be free (no colony or alias by that name) and a single
+    /// word without `=`, so it survives `write_map`.
+    pub fn add_alias(&mut self, colony_id: ColonyId, alias: &str) -> Result<(), SimError> {
+        check_alias_name(alias)?;
+        if colony_id >= self.colony_count() {
+            return Err(invalid_alias(alias, format!("no colony {}", colony_id)));
+        }
+        if self.find_colony(alias).is_some() {
+            return Err(invalid_alias(alias, "the name is already taken".to_string()));
+        }
+        self.aliases.push((alias.to_string(), colony_id));
+        Ok(())
+    }
+    
+    /// Raw CSR (compressed sparse row) adjacency: the tunnels out of colony
+    /// `c` are `adjacency_slice()[start_offsets()[c]..][..connection_counts()[c]]`,
+    /// each entry a colony ID, in the order `edge_label` numbers them.
+    /// Colonies' runs are laid out back to back in colony order, so the
+    /// offsets are sorted, `start_offsets()[c + 1] == start_offsets()[c] +
+    /// connection_counts()[c]` and the counts add up to `edge_count()`.
+    pub fn adjacency_slice(&self) -> &[u32] {
+        &self.adjacency_list
+    }
+    
+    /// Where each colony's tunnels start in `adjacency_slice`
+    pub fn start_offsets(&self) -> &[u32] {
+        &self.start_index
+    }
+    
+    /// Tunnels out of each colony, see `adjacency_slice`
+    pub fn connection_counts(&self) -> &[u8] {
+        &self.connection_count
+    }
+    
+    /// Every alias with the colony it stands for, in the order they were added
+    pub fn aliases(&self) -> impl Iterator<Item = (&str, ColonyId)> + '_ {
+        self.aliases.iter().map(|(alias, colony_id)| (alias.as_str(), *colony_id))
+    }
+    
+    /// Most connections a single colony can have
+    pub fn max_connections_per_colony() -> usize {
+        u8::MAX as usize
+    }
+    
+    /// Direction label of a colony's `neighbor_index`-th connection
+    pub fn edge_label(&self, from: ColonyId, neighbor_index: usize) -> &str {
+        assert!(neighbor_index < self.connection_count[from] as usize, "colony {} has no connection {}", from, neighbor_index);
+        &self.edge_labels[self.start_index[from] as usize + neighbor_index]
+    }
+    
+    /// Sizes, degree distribution and components, without building a simulation
+    pub fn map_stats(&self) -> MapStats {
+        let mut degrees: Vec<u32> = self.connection_count.iter().map(|&count| count as u32).collect();
+        let mut degree_histogram = vec![0; degrees.iter().max().map_or(0, |&max| max as usize + 1)];
+        for &degree in &degrees {
+            degree_histogram[degree as usize] += 1;
+        }
+        let colonies = self.colony_count();
+        let components = Components::build(self, &vec![false; colonies], &vec![0; colonies]).count();
+        
+        MapStats {
+            colonies,
+            tunnels: self.edge_count(),
+            degree: SampleStats::from_samples(&mut degrees),
+            degree_histogram,
+            components,
+        }
+    }
+    
+    /// Whether the map came from a file without the version header. Those
+    /// still load, but the headerless format is deprecated.
+    pub fn is_legacy_format(&self) -> bool {
+        self.legacy_format
+    }
+    
+    /// Check that the map holds together: the tunnels laid out as
+    /// `adjacency_slice` describes, every tunnel and alias leading to a
+    /// colony on the map, and no name, colony or alias, used twice. Every map
+    /// this crate builds passes; meant for tests and fuzzing.
+    pub fn check_structure(&self) -> Result<(), String> {
+        let colonies = self.colony_count();
+        if self.connection_count.len() != colonies || self.colony_names.len() != colonies {
+            return Err(format!(
+                "{} colonies but {} connection counts and {} names",
+                colonies,
+                self.connection_count.len(),
+                self.colony_names.len()
+            ));
+        }
+        if self.edge_labels.len() != self.edge_count() {
+            return Err(format!("{} tunnels but {} labels", self.edge_count(), self.edge_labels.len()));
+        }
+        let mut next_start = 0;
+        for (colony, (&start, &count)) in self.start_index.iter().zip(&self.connection_count).enumerate() {
+            if start as usize != next_start {
+                return Err(format!("tunnels of colony {} start at {}, expected {}", colony, start, next_start));
+            }
+            next_start += count as usize;
+        }
+        if next_start != self.edge_count() {
+            return Err(format!("connection counts add up to {}, there are {} tunnels", next_start, self.edge_count()));
+        }
+        if let Some(&target) = self.adjacency_list.iter().find(|&&target| target as usize >= colonies) {
+            return Err(format!("a tunnel leads to colony {}, there are {}", target, colonies));
+        }
+        if !self.attributes.is_empty() && self.attributes.len() != colonies {
+            return Err(format!("{} colonies but attributes for {}", colonies, self.attributes.len()));
+        }
+        
+        let mut names = HashMap::with_capacity(colonies + self.aliases.len());
+        for colony in 0..colonies {
+            if names.insert(self.colony_name(colony), colony).is_some() {
+                return Err(format!("colony name '{}' is used twice", self.colony_name(colony)));
+            }
+        }
+        for (alias, colony) in self.aliases() {
+            if colony >= colonies {
+                return Err(format!("alias '{}' stands for colony {}, there are {}", alias, colony, colonies));
+            }
+            if names.insert(alias, colony).is_some() {
+                return Err(format!("alias '{}' is already a name", alias));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Dangling, malformed or one-way tunnels, repeated labels and colonies,
+    /// and names differing only in case, found while parsing, in line order.
+    /// Empty for maps not read from a file.
+    pub fn parse_warnings(&self) -> &[ParseWarning] {
+        &self.parse_warnings
+    }
+    
+    /// Write the map in the versioned file format, skipping colonies (and
+    /// tunnels into and aliases of colonies) for which `keep` returns false
+    pub fn write_map<W: Write + ?Sized>(&self, out: &mut W, keep: impl Fn(ColonyId) -> bool) -> io::Result<()> {
+        self.write_map_ordered(out, keep, MapOrder::Declared)
+    }
+    
+    /// `write_map` with the colonies, tunnels and aliases in `order`. Only
+    /// the output is sorted, the map keeps its IDs and tunnel order.
+    pub fn write_map_ordered<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        keep: impl Fn(ColonyId) -> bool,
+        order: MapOrder,
+    ) -> io::Result<()> {
+        writeln!(out, "{} v{}", MAP_HEADER, MAP_FORMAT_VERSION)?;
+        let mut colonies: Vec<ColonyId> = (0..self.colony_count()).filter(|&colony_id| keep(colony_id)).collect();
+        let mut aliases: Vec<_> = self.aliases.iter().filter(|&&(_, colony_id)| keep(colony_id)).collect();
+        let mut tunnels: Vec<(&str, &str)> = Vec::new();
+        if order == MapOrder::Sorted {
+            colonies.sort_unstable_by_key(|&colony_id| self.colony_name(colony_id));
+            aliases.sort_unstable();
+        }
+        for colony_id in colonies {
+            write!(out, "{}", self.colony_name(colony_id))?;
+            write_attributes(out, self.colony_attrs(colony_id))?;
+            tunnels.clear();
+            for (i, &neighbor_id) in self.neighbors(colony_id).iter().enumerate() {
+                let neighbor_id = neighbor_id as ColonyId;
+                if keep(neighbor_id) {
+                    tunnels.push((self.edge_label(colony_id, i), self.colony_name(neighbor_id)));
+                }
+            }
+            if order == MapOrder::Sorted {
+                tunnels.sort_unstable();
+            }
+            for (label, target) in &tunnels {
+                write!(out, " {}={}", label, target)?;
+            }
+            writeln!(out)?;
+        }
+        
+        if !aliases.is_empty() {
+            writeln!(out, "{}", ALIASES_SECTION)?;
+        }
+        for (alias, colony_id) in aliases {
+            writeln!(out, "{}={}", alias, self.colony_name(*colony_id))?;
+        }
+        Ok(())
+    }
+    
+    /// Every connection out of a colony, destroyed or not
+    #[inline]
+    pub(crate) fn neighbors(&self, colony: ColonyId) -> &[ColonyIndex] {
+        let start = self.start_index[colony] as usize;
+        let count = self.connection_count[colony] as usize;
+        &self.adjacency_list[start..start + count]
+    }
+}
+
+impl fmt::Display for MapStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "colonies:   {}", self.colonies)?;
+        writeln!(f, "tunnels:    {}", self.tunnels)?;
+        writeln!(f, "degree:     {}", self.degree)?;
+        let histogram: Vec<String> = self
+            .degree_histogram
+            .iter()
+            .enumerate()
+            .filter(|&(_, &colonies)| colonies > 0)
+            .map(|(degree, colonies)| format!("{}: {}", degree, colonies))
+            .collect();
+        writeln!(f, "histogram:  {}", histogram.join(", "))?;
+        write!(f, "components: {}", self.components)
+    }
+}
+
+/// Split a colony line, `Foo[cap=2,note="a b"] north=Bar`, into the colony
+/// name, its attributes and the tunnels after them
+fn split_attributes(line: &str) -> Result<(&str, Attributes, &str), String> {
+    let line = line.trim_start();
+    let (name, rest) = line.split_at(line.find(|c: char| c.is_whitespace() || c == '[').unwrap_or(line.len()));
+    let Some(mut block) = rest.strip_prefix('[') else {
+        return Ok((name, Vec::new(), rest));
+    };
+    let unclosed = || format!("no ']' closing the attributes of {}", name);
+    let mut attributes = Attributes::new();
+    loop {
+        let key_end = block.find(['=', ',', ']']).ok_or_else(unclosed)?;
+        let key = block[..key_end].trim();
+        block = &block[key_end..];
+        let mut value = String::new();
+        if let Some(after) = block.strip_prefix('=') {
+            (value, block) = split_value(after, name)?;
+        }
+        if key.is_empty() {
+            // only `[]` has nothing in it
+            if !(attributes.is_empty() && value.is_empty() && block.starts_with(']')) {
+                return Err(format!("an attribute of {} has no name", name));
+            }
+        } else if key.contains(|c: char| c.is_whitespace() || c == '"' || c == '[') {
+            return Err(format!("'{}' isn't an attribute name", key));
+        } else if attributes.iter().any(|(other, _)| other == key) {
+            return Err(format!("{} is given attribute '{}' twice", name, key));
+        } else {
+            attributes.push((key.to_string(), value));
+        }
+        match block.as_bytes().first() {
+            Some(b',') => block = &block[1..],
+            Some(b']') => break,
+            _ => return Err(unclosed()),
+        }
+    }
+    let rest = &block[1..];
+    if rest.starts_with(|c: char| !c.is_whitespace()) {
+        return Err(format!("expected a space after the attributes of {}, got '{}'", name, rest));
+    }
+    Ok((name, attributes, rest))
+}
+
+/// An attribute value, quoted or up to the next `,` or `]`, and what's after it
+fn split_value<'a>(text: &'a str, colony: &str) -> Result<(String, &'a str), String> {
+    let text = text.trim_start();
+    let Some(quoted) = text.strip_prefix('"') else {
+        let end = text.find([',', ']']).unwrap_or(text.len());
+        let value = text[..end].trim();
+        if value.contains('"') {
+            return Err(format!("attribute value '{}' of {} has a quote but isn't quoted", value, colony));
+        }
+        return Ok((value.to_string(), &text[end..]));
+    };
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, quoted[i + 1..].trim_start())),
+            '\\' => match chars.next() {
+                Some((_, escaped @ ('"' | '\\'))) => value.push(escaped),
+                _ => return Err(format!("attribute value of {} has a '\\' not before '\"' or '\\'", colony)),
+            },
+            c => value.push(c),
+        }
+    }
+    Err(format!("attribute value of {} has no closing quote", colony))
+}
+
+/// `[key=value,flag]` as `split_attributes` reads it back, nothing for no
+/// attributes
+fn write_attributes<'a, W: Write + ?Sized>(
+    out: &mut W,
+    attributes: impl Iterator<Item = (&'a str, &'a str)>,
+) -> io::Result<()> {
+    let mut separator = '[';
+    for (key, value) in attributes {
+        write!(out, "{}{}", separator, key)?;
+        separator = ',';
+        if value.is_empty() {
+            continue;
+        }
+        if value.contains(|c: char| c.is_whitespace() || "\",[]=\\".contains(c)) {
+            write!(out, "=\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        } else {
+            write!(out, "={}", value)?;
+        }
+    }
+    if separator == ',' {
+        write!(out, "]")?;
+    }
+    Ok(())
+}
+
+/// `version` is what follows the header keyword, e.g. ` v1`
+fn check_map_version(version: &str) -> Result<(), SimError> {
+    let found = version.trim().strip_prefix('v').and_then(|number| number.parse::<u32>().ok());
+    match found {
+        Some(MAP_FORMAT_VERSION) => Ok(()),
+        Some(found) => Err(SimError::UnsupportedMapVersion { found, supported: MAP_FORMAT_VERSION }),
+        None => Err(SimError::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("malformed map header '{}{}', expected '{} v{}'", MAP_HEADER, version, MAP_HEADER, MAP_FORMAT_VERSION),
+        ))),
+    }
+}
+
+/// Colonies and connections are stored as u32 indices
+fn check_map_size(colonies: usize, connections: usize) -> Result<(), SimError> {
+    if u32::try_from(colonies).is_err() || u32::try_from(connections).is_err() {
+        return Err(SimError::MapTooLarge { colonies, connections });
+    }
+    Ok(())
+}
+
+/// Aliases are written as `alias=colony` lines, so they can't hold spaces or `=`
+fn check_alias_name(alias: &str) -> Result<(), SimError> {
+    if alias.is_empty() || alias.contains(|c: char| c.is_whitespace() || c == '=') {
+        return Err(invalid_alias(alias, "aliases are a single word without '='".to_string()));
+    }
+    Ok(())
+}
+
+/// A map line that can't be read, as the I/O error `from_reader` returns
+fn invalid_line(line: usize, reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line, reason))
+}
+
+fn invalid_alias(alias: &str, reason: String) -> SimError {
+    SimError::InvalidAlias { alias: alias.to_string(), reason }
+}
+
+/// A colony name `from_dict` and the like take: a single word without `=`
+pub(crate) fn check_colony_name(name: &str) -> Result<(), SimError> {
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '=') {
+        return Err(SimError::InvalidConfig(format!(
+            "colony name '{}': colony names are a single word without '='",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Add the tunnel `label` out of `colony` to `out`, its tunnels so far: a
+/// single word, not back into `colony`, and to another neighbor in another
+/// direction than the ones before
+pub(crate) fn push_tunnel<'a>(
+    out: &mut Vec<(&'a str, ColonyId)>,
+    (colony, name): (ColonyId, &str),
+    label: &'a str,
+    (to, neighbor): (ColonyId, &str),
+) -> Result<(), SimError> {
+    let invalid = |reason: String| SimError::InvalidTunnel { colony: name.to_string(), reason };
+    if label.is_empty() || label.contains(|c: char| c.is_whitespace() || c == '=') {
+        return Err(invalid(format!("direction '{}' isn't a single word without '='", label)));
+    }
+    if to == colony {
+        return Err(invalid(format!("the tunnel {} leads back to it", label)));
+    }
+    if out.iter().any(|&(_, earlier)| earlier == to) {
+        return Err(invalid(format!("two tunnels lead to {}", neighbor)));
+    }
+    if out.iter().any(|&(earlier, _)| earlier == label) {
+        return Err(invalid(format!("two tunnels lead {}", label)));
+    }
+    out.push((label, to));
+    Ok(())
+}
+
+fn too_many_connections(colony: &str) -> SimError {
+    SimError::TooManyConnections { colony: colony.to_string(), max: MapGraph::max_connections_per_colony() }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+
+use crate::graph::MapGraph;
+use crate::stats::SampleStats;
+
+/// Largest `colonies * (colonies + tunnels)` the diameter is worked out
+/// for, a breadth-first search from every colony
+const DIAMETER_BUDGET: usize = 50_000_000;
+
+/// Shape of the live part of a map: what's left once the destroyed colonies
+/// and the tunnels into them are gone. Tunnels count as two-way for the
+/// components, articulation points, diameter and clustering.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStats {
+    pub live_colonies: usize,
+    pub total_colonies: usize,
+    /// Tunnels between two live colonies
+    pub tunnels: usize,
+    /// Live tunnels out of each live colony, the moves an ant there has
+    pub degree: SampleStats,
+    /// Live colonies with no live tunnel out
+    pub dead_ends: usize,
+    /// Live colonies whose loss would split their component
+    pub articulation_points: usize,
+    pub components: usize,
+    /// Most tunnels on the shortest path between two colonies of one
+    /// component, `None` when the map is too big to work it out quickly
+    pub diameter: Option<u32>,
+    /// Mean over live colonies of how many pairs of a colony's neighbors are
+    /// neighbors too, 0 for colonies with under two neighbors
+    pub clustering_coefficient: f64,
+}
+
+impl GraphStats {
+    /// Scans the map a few times, plus a search from every colony for the
+    /// diameter when that stays under `DIAMETER_BUDGET`
+    pub(crate) fn of(graph: &MapGraph, destroyed: &[bool]) -> Self {
+        // live colonies renumbered 0.., with sorted two-way neighbor lists
+        let mut live_index = vec![usize::MAX; graph.colony_count()];
+        let live: Vec<usize> = (0..graph.colony_count()).filter(|&colony| !destroyed[colony]).collect();
+        for (index, &colony) in live.iter().enumerate() {
+            live_index[colony] = index;
+        }
+        let mut neighbors = vec![Vec::new(); live.len()];
+        let mut degrees = Vec::with_capacity(live.len());
+        let mut tunnels = 0;
+        for (index, &colony) in live.iter().enumerate() {
+            let mut degree = 0;
+            for &neighbor in graph.neighbors(colony) {
+                let other = live_index[neighbor as usize];
+                if other == usize::MAX {
+                    continue;
+                }
+                degree += 1;
+                if other != index {
+                    neighbors[index].push(other);
+                    neighbors[other].push(index);
+                }
+            }
+            tunnels += degree as usize;
+            degrees.push(degree);
+        }
+        for list in &mut neighbors {
+            list.sort_unstable();
+            list.dedup();
+        }
+
+        let dead_ends = degrees.iter().filter(|&&degree| degree == 0).count();
+        let affordable = live.len().saturating_mul(live.len() + tunnels) <= DIAMETER_BUDGET;
+        GraphStats {
+            live_colonies: live.len(),
+            total_colonies: graph.colony_count(),
+            tunnels,
+            degree: SampleStats::from_samples(&mut degrees),
+            dead_ends,
+            articulation_points: articulation_points(&neighbors),
+            components: components(&neighbors),
+            diameter: affordable.then(|| diameter(&neighbors)),
+            clustering_coefficient: clustering_coefficient(&neighbors),
+        }
+    }
+}
+
+fn components(neighbors: &[Vec<usize>]) -> usize {
+    let mut seen = vec![false; neighbors.len()];
+    let mut stack = Vec::new();
+    let mut count = 0;
+    for start in 0..neighbors.len() {
+        if seen[start] {
+            continue;
+        }
+        count += 1;
+        seen[start] = true;
+        stack.push(start);
+        while let Some(vertex) = stack.pop() {
+            for &next in &neighbors[vertex] {
+                if !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+    }
+    count
+}
+
+/// Tarjan's low-link search, iterative so long paths can't overflow the stack
+fn articulation_points(neighbors: &[Vec<usize>]) -> usize {
+    let vertices = neighbors.len();
+    let mut order = vec![usize::MAX; vertices];
+    let mut low = vec![0; vertices];
+    let mut is_cut = vec![false; vertices];
+    let mut next_order = 0;
+    // (vertex, parent, next neighbor to look at)
+    let mut stack: Vec<(usize, usize, usize)> = Vec::new();
+    for root in 0..vertices {
+        if order[root] != usize::MAX {
+            continue;
+        }
+        order[root] = next_order;
+        low[root] = next_order;
+        next_order += 1;
+        let mut root_children = 0;
+        stack.push((root, usize::MAX, 0));
+        while let Some(&mut (vertex, parent, ref mut next)) = stack.last_mut() {
+            if let Some(&child) = neighbors[vertex].get(*next) {
+                *next += 1;
+                if order[child] == usize::MAX {
+                    order[child] = next_order;
+                    low[child] = next_order;
+                    next_order += 1;
+                    if vertex == root {
+                        root_children += 1;
+                    }
+                    stack.push((child, vertex, 0));
+                } else if child != parent {
+                    low[vertex] = low[vertex].min(order[child]);
+                }
+                continue;
+            }
+            stack.pop();
+            if parent != usize::MAX {
+                low[parent] = low[parent].min(low[vertex]);
+                if parent != root && low[vertex] >= order[parent] {
+                    is_cut[parent] = true;
+                }
+            }
+        }
+        is_cut[root] = root_children > 1;
+    }
+    is_cut.iter().filter(|&&cut| cut).count()
+}
+
+/// Longest shortest path within a component, a search from every vertex
+fn diameter(neighbors: &[Vec<usize>]) -> u32 {
+    let mut distance = vec![u32::MAX; neighbors.len()];
+    let mut queue = Vec::with_capacity(neighbors.len());
+    let mut longest = 0;
+    for start in 0..neighbors.len() {
+        queue.clear();
+        queue.push(start);
+        distance[start] = 0;
+        let mut next = 0;
+        while let Some(&vertex) = queue.get(next) {
+            next += 1;
+            longest = longest.max(distance[vertex]);
+            for &neighbor in &neighbors[vertex] {
+                if distance[neighbor] == u32::MAX {
+                    distance[neighbor] = distance[vertex] + 1;
+                    queue.push(neighbor);
+                }
+            }
+        }
+        for &vertex in &queue {
+            distance[vertex] = u32::MAX;
+        }
+    }
+    longest
+}
+
+fn clustering_coefficient(neighbors: &[Vec<usize>]) -> f64 {
+    if neighbors.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = (neighbors.iter())
+        .map(|list| {
+            let degree = list.len();
+            if degree < 2 {
+                return 0.0;
+            }
+            let mut linked = 0;
+            for (i, &a) in list.iter().enumerate() {
+                linked += list[i + 1..].iter().filter(|b| neighbors[a].binary_search(b).is_ok()).count();
+            }
+            linked as f64 / (degree * (degree - 1) / 2) as f64
+        })
+        .sum();
+    total / neighbors.len() as f64
+}
+
+impl fmt::Display for GraphStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "colonies:            {}/{} live", self.live_colonies, self.total_colonies)?;
+        writeln!(f, "tunnels:             {}", self.tunnels)?;
+        writeln!(f, "degree:              {}", self.degree)?;
+        writeln!(f, "dead ends:           {}", self.dead_ends)?;
+        writeln!(f, "articulation points: {}", self.articulation_points)?;
+        writeln!(f, "components:          {}", self.components)?;
+        match self.diameter {
+            Some(diameter) => writeln!(f, "diameter:            {}", diameter)?,
+            None => writeln!(f, "diameter:            skipped, map too large")?,
+        }
+        write!(f, "clustering:          {:.3}", self.clustering_coefficient)
+    }
+}
@@ -0,0 +1,217 @@
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use clap::Parser;
+
+use crate::{install_interrupt_handler, EXIT_INTERRUPTED, EXIT_MAP_REJECTED, EXIT_TIME_LIMIT};
+
+/// First argument that switches to batch mode
+pub const SUBCOMMAND: &str = "batch";
+
+/// Run every line of a jobs file as its own ant-mania invocation
+///
+/// Each line that isn't blank or a `#` comment holds the arguments of one
+/// run, quoted as in a shell (`"my map.txt" 100 --seed 7`). Paths in it are
+/// relative to the current directory. A line's stdout goes to
+/// `line_<N>.out` in the output directory and its stderr to `line_<N>.err`,
+/// and a summary of how every line ended is printed at the end.
+#[derive(Parser, Debug)]
+#[command(name = "ant-mania batch", bin_name = "ant-mania batch", version)]
+pub struct BatchCli {
+    /// Jobs file, one set of ant-mania arguments per line
+    pub jobs: PathBuf,
+
+    /// Directory for the per-line output files [default: JOBS with a .out extension]
+    #[arg(long, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+
+    /// Lines to run at once
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub threads: u32,
+
+    /// Start no more lines once one has failed
+    #[arg(long)]
+    pub fail_fast: bool,
+}
+
+/// One line of the jobs file
+struct Job {
+    line: usize,
+    /// The arguments, or why the line couldn't be split into them
+    args: Result<Vec<String>, String>,
+}
+
+/// How a line ended
+struct JobResult {
+    line: usize,
+    /// Exit code, `None` if the line never ran or was killed by a signal
+    exit: Option<i32>,
+    status: &'static str,
+    /// Why it failed, when it did
+    error: Option<String>,
+}
+
+impl JobResult {
+    fn succeeded(&self) -> bool {
+        self.exit == Some(0)
+    }
+}
+
+/// `ant-mania batch ...`, with `args` starting at `batch`
+pub fn main(args: &[OsString]) -> ExitCode {
+    let cli = match BatchCli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            return if err.use_stderr() { ExitCode::FAILURE } else { ExitCode::SUCCESS };
+        }
+    };
+    match run(&cli) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run the jobs and print the summary, true if every line succeeded
+fn run(cli: &BatchCli) -> Result<bool, String> {
+    let text = fs::read_to_string(&cli.jobs).map_err(|err| format!("{}: {}", cli.jobs.display(), err))?;
+    let jobs: Vec<Job> = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| Job { line: index + 1, args: split_args(line) })
+        .collect();
+    let out_dir = cli.out_dir.clone().unwrap_or_else(|| cli.jobs.with_extension("out"));
+    fs::create_dir_all(&out_dir).map_err(|err| format!("can't create {}: {}", out_dir.display(), err))?;
+    let exe = std::env::current_exe().map_err(|err| format!("can't find the ant-mania binary: {}", err))?;
+
+    // every line runs in a process of its own, so nothing one run sets up
+    // (log file, Ctrl-C handler, stdout) leaks into another; Ctrl-C reaches
+    // the running lines directly and stops new ones from starting
+    let interrupted = install_interrupt_handler();
+    let failed = AtomicBool::new(false);
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<JobResult>>> = Mutex::new((0..jobs.len()).map(|_| None).collect());
+    let threads = (cli.threads as usize).min(jobs.len()).max(1);
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                if interrupted.load(Ordering::Relaxed) || (cli.fail_fast && failed.load(Ordering::Relaxed)) {
+                    return;
+                }
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(job) = jobs.get(index) else {
+                    return;
+                };
+                let result = run_job(&exe, job, &out_dir);
+                if !result.succeeded() {
+                    failed.store(true, Ordering::Relaxed);
+                }
+                results.lock().unwrap_or_else(|poisoned| poisoned.into_inner())[index] = Some(result);
+            });
+        }
+    });
+
+    let results: Vec<JobResult> = (results.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner()))
+        .into_iter()
+        .zip(&jobs)
+        .map(|(result, job)| {
+            result.unwrap_or(JobResult { line: job.line, exit: None, status: "skipped", error: None })
+        })
+        .collect();
+    write_summary(&mut io::stdout().lock(), &cli.jobs, &out_dir, &results)
+        .map_err(|err| format!("failed to write summary: {}", err))?;
+    Ok(results.iter().all(JobResult::succeeded))
+}
+
+/// Run one line as a child ant-mania, its stdout and stderr going to files
+fn run_job(exe: &Path, job: &Job, out_dir: &Path) -> JobResult {
+    let failed = |error: String| JobResult { line: job.line, exit: None, status: "failed", error: Some(error) };
+    let args = match &job.args {
+        Ok(args) if args.first().is_some_and(|arg| arg == SUBCOMMAND) => {
+            return failed("a jobs file can't run another batch".to_string())
+        }
+        Ok(args) => args,
+        Err(reason) => return failed(reason.clone()),
+    };
+
+    let out_path = out_dir.join(format!("line_{}.out", job.line));
+    let err_path = out_dir.join(format!("line_{}.err", job.line));
+    let status = File::create(&out_path).and_then(|stdout| {
+        let stderr = File::create(&err_path)?;
+        Command::new(exe).args(args).stdin(Stdio::null()).stdout(stdout).stderr(stderr).status()
+    });
+    let status = match status {
+        Ok(status) => status,
+        Err(err) => return failed(format!("can't start: {}", err)),
+    };
+
+    let exit = status.code();
+    let status = match exit {
+        Some(0) => "finished",
+        Some(code) if code == EXIT_TIME_LIMIT as i32 => "timed out",
+        Some(code) if code == EXIT_INTERRUPTED as i32 => "interrupted",
+        Some(code) if code == EXIT_MAP_REJECTED as i32 => "map rejected",
+        Some(_) => "failed",
+        None => "killed",
+    };
+    // a failed run says why on the last `error:` line of its stderr
+    let error = (exit != Some(0)).then(|| fs::read_to_string(&err_path).unwrap_or_default()).and_then(|stderr| {
+        stderr.lines().rev().find_map(|line| line.strip_prefix("error: ")).map(str::to_string)
+    });
+    JobResult { line: job.line, exit, status, error }
+}
+
+/// Split a jobs line into arguments: whitespace separates them, and single
+/// or double quotes keep a piece with spaces together
+fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut arg: Option<String> = None;
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => arg.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                arg.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => args.extend(arg.take()),
+            (None, c) => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(open) = quote {
+        return Err(format!("unterminated {} quote", open));
+    }
+    args.extend(arg);
+    Ok(args)
+}
+
+/// How each line ended, in file order, then the failures' reasons
+fn write_summary(out: &mut dyn Write, jobs: &Path, out_dir: &Path, results: &[JobResult]) -> io::Result<()> {
+    let succeeded = results.iter().filter(|result| result.succeeded()).count();
+    writeln!(out, "=== Batch: {} ===", jobs.display())?;
+    writeln!(out, "{:>6}  {:>4}  status", "line", "exit")?;
+    for result in results {
+        let exit = result.exit.map_or("-".to_string(), |code| code.to_string());
+        writeln!(out, "{:>6}  {:>4}  {}", result.line, exit, result.status)?;
+    }
+
+    let failures: Vec<_> = results.iter().filter_map(|result| Some((result.line, result.error.as_ref()?))).collect();
+    if !failures.is_empty() {
+        writeln!(out, "\nFailures:")?;
+        for (line, error) in failures {
+            writeln!(out, "  line {}: {}", line, error)?;
+        }
+    }
+    writeln!(out, "\n{} of {} lines succeeded, output in {}", succeeded, results.len(), out_dir.display())
+}
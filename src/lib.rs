@@ -0,0 +1,62 @@
+mod batch;
+#[cfg(feature = "arrow")]
+mod colony_arrow;
+mod complexity;
+mod components;
+mod config;
+mod error;
+#[cfg(feature = "parquet")]
+mod event_parquet;
+mod generator;
+#[cfg(feature = "geojson")]
+mod geojson;
+mod graph;
+mod graph_stats;
+mod memory;
+mod metrics;
+mod min_cut;
+#[cfg(feature = "nalgebra")]
+mod nalgebra_map;
+mod names;
+mod options;
+mod perf;
+#[cfg(feature = "sqlite")]
+pub mod persist;
+#[cfg(feature = "petgraph")]
+mod petgraph_map;
+#[cfg(feature = "python")]
+pub mod python;
+mod regions;
+mod simulation;
+mod snapshot;
+pub mod stop_condition;
+mod stats;
+mod summary;
+
+pub use batch::{run_batch, run_batch_survival, run_batch_until, BatchStats, ColonySurvival, RunSummary};
+pub use complexity::ComplexityEstimate;
+pub use config::{AntConfig, CollisionPolicy, FightTrigger, PlacementStrategy, DEFAULT_CLUSTER_RADIUS};
+pub use error::SimError;
+#[cfg(feature = "parquet")]
+pub use event_parquet::{EventParquetWriter, EVENTS_PER_ROW_GROUP};
+pub use generator::{ProceduralMapGenerator, Topology};
+pub use graph::{
+    Direction, MapGraph, MapOrder, MapStats, ParseWarning, ParseWarningKind, DEFAULT_EDGE_LABEL, KNOWN_ATTRIBUTES,
+    MAP_FORMAT_VERSION, MAP_HEADER,
+};
+pub use graph_stats::GraphStats;
+pub use memory::{estimate_memory, format_bytes, MemoryBreakdown, MemoryEstimate};
+pub use metrics::write_prometheus;
+pub use options::{DeadEndBehavior, DestructionMode, MapOptions, MovementMode, SimOptions};
+pub use perf::PerfCounters;
+pub use regions::{RegionId, RegionStats, Regions, UNASSIGNED_REGION};
+pub use simulation::{
+    AntId, AntSimulation, ColonyId, ColonyState, IterationResult, RunStatus, SimColonyIter, SimulationStats, MAX_MOVES,
+};
+pub use snapshot::{Snapshot, SnapshotFormat, SNAPSHOT_VERSION};
+pub use stats::{MoveStats, SampleStats};
+pub use stop_condition::StopCondition;
+pub use summary::{
+    AntRecord, AntView, ColonySummary, DeathCause, Deaths, Destruction, MarathonAnts, SimEvent, Summary, Termination,
+    Winner, BUSIEST_COLONIES, RECENT_DESTRUCTIONS,
+};
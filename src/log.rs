@@ -0,0 +1,62 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `--log-file`, shared with the Ctrl-C handler thread
+static LOG_FILE: Mutex<Option<LogFile>> = Mutex::new(None);
+
+struct LogFile {
+    path: PathBuf,
+    out: BufWriter<File>,
+}
+
+/// Send diagnostics to `path` from now on, added to the end of it with
+/// `append` and replacing it otherwise
+pub fn open(path: &Path, append: bool) -> io::Result<()> {
+    let file = OpenOptions::new().create(true).write(true).append(append).truncate(!append).open(path)?;
+    *lock() = Some(LogFile { path: path.to_path_buf(), out: BufWriter::new(file) });
+    Ok(())
+}
+
+/// Write each line of `message` to the log file, timestamped. Returns false
+/// when there's no log file, so the caller prints the message itself. The
+/// first failed write is reported on stderr and turns the log file off.
+pub fn write(message: &str) -> bool {
+    let mut log = lock();
+    let Some(file) = log.as_mut() else {
+        return false;
+    };
+
+    let timestamp = timestamp();
+    let written = message.lines().try_for_each(|line| writeln!(file.out, "[{}] {}", timestamp, line));
+    if let Err(err) = written {
+        eprintln!("warning: can't write to log file {}: {}, logging to the terminal instead", file.path.display(), err);
+        *log = None;
+        return false;
+    }
+    true
+}
+
+/// Flush the log file, to be called on every way out of the program
+pub fn flush() {
+    let mut log = lock();
+    if let Some(file) = log.as_mut() {
+        if let Err(err) = file.out.flush() {
+            eprintln!("warning: can't write to log file {}: {}", file.path.display(), err);
+            *log = None;
+        }
+    }
+}
+
+/// A panic while logging shouldn't take the rest of the logging down with it
+fn lock() -> MutexGuard<'static, Option<LogFile>> {
+    LOG_FILE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Seconds since the Unix epoch, to the millisecond
+fn timestamp() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    format!("{}.{:03}", now.as_secs(), now.subsec_millis())
+}
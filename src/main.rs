@@ -1,329 +1,1240 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cmp::Reverse;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use fastrand;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::ops::{ControlFlow, Range};
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-const MAX_MOVES: u32 = 10000;
+use ant_mania::{
+    estimate_memory, format_bytes, run_batch_survival, run_batch_until, write_prometheus, AntConfig, AntSimulation,
+    BatchStats, ColonySurvival, DeathCause, DestructionMode, FightTrigger, GraphStats, MapGraph, MapOrder,
+    MarathonAnts, MemoryEstimate, MovementMode, PlacementStrategy, Regions, RunStatus, RunSummary, SimOptions,
+    Snapshot, SnapshotFormat, StopCondition, MAP_FORMAT_VERSION, MAP_HEADER,
+};
+#[cfg(feature = "json")]
+use ant_mania::{AntRecord, SampleStats};
+#[cfg(feature = "parquet")]
+use ant_mania::{EventParquetWriter, EVENTS_PER_ROW_GROUP};
+#[cfg(feature = "sqlite")]
+use ant_mania::persist::{self, RunReport};
+use ant_mania::stop_condition::Metric;
 
-/// Represents a colony ID (0-based index)
-type ColonyId = usize;
-type AntId = usize;
+use crate::ant_names::AntNames;
+use crate::checkpoint::Checkpointer;
+use crate::cli::{Cli, Destruction, FightAt, Format, Movement, SnapshotEncoding};
 
-/// Main simulation state 
-pub struct AntSimulation {
-    /// Number of ants currently at each colony
-    ant_count: Vec<u16>,  
+mod ant_names;
+mod checkpoint;
+mod cli;
+mod jobs;
+mod log;
+mod placement;
+#[cfg(feature = "serve")]
+mod serve;
+#[cfg(feature = "serve")]
+mod websocket;
+
+fn main() -> ExitCode {
+    let args: Vec<_> = std::env::args_os().collect();
+    if args.get(1).is_some_and(|arg| arg == jobs::SUBCOMMAND) {
+        return jobs::main(&args[1..]);
+    }
+    #[cfg(feature = "serve")]
+    if args.get(1).is_some_and(|arg| arg == serve::SUBCOMMAND) {
+        return serve::main(&args[1..]);
+    }
     
-    /// Is a colony destroyed
-    destroyed: Vec<bool>,
+    let mut command = cli::command();
+    command.build();
+    let matches = match command.clone().try_get_matches() {
+        Ok(matches) => matches,
+        Err(err) if !err.use_stderr() => {
+            // --help / --version
+            let _ = err.print();
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            match cli::env_var_error(&command, &err) {
+                Some(message) => eprintln!("error: {}", message),
+                None => drop(err.print()),
+            }
+            return ExitCode::FAILURE;
+        }
+    };
     
-    /// Colony names for final output (only used at start/end)
-    colony_names: Vec<String>,
+    let outcome = Cli::from_matches(&matches).and_then(run);
+    if let Err(message) = &outcome {
+        log::write(&format!("error: {}", message));
+    }
+    log::flush();
     
-    /// Adjacency List (compressed)
-    adjacency_list: Vec<ColonyId>,
+    match outcome {
+        Ok(Outcome::Finished) => ExitCode::SUCCESS,
+        Ok(Outcome::Interrupted) => ExitCode::from(EXIT_INTERRUPTED),
+        Ok(Outcome::TimedOut) => ExitCode::from(EXIT_TIME_LIMIT),
+        Ok(Outcome::MapRejected) => ExitCode::from(EXIT_MAP_REJECTED),
+        Ok(Outcome::NoWinner) => ExitCode::from(EXIT_NO_WINNER),
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// How a successful invocation ended
+enum Outcome {
+    Finished,
+    Interrupted,
+    /// `--time-limit` ran out first
+    TimedOut,
+    /// `--fail-on-warning` found something wrong with the map
+    MapRejected,
+    /// `--battle-royale` ended with no ant, or more than one, left
+    NoWinner,
+}
+
+/// Exit code after Ctrl-C, the usual 128 + SIGINT
+const EXIT_INTERRUPTED: u8 = 130;
+/// Exit code when `--time-limit` stops the run, as with timeout(1)
+const EXIT_TIME_LIMIT: u8 = 124;
+/// Exit code when `--fail-on-warning` rejects the map, sysexits' EX_DATAERR
+const EXIT_MAP_REJECTED: u8 = 65;
+/// Exit code when `--battle-royale` has no single winner
+const EXIT_NO_WINNER: u8 = 3;
+
+fn run(cli: Cli) -> Result<Outcome, String> {
+    if let Some(path) = &cli.log_file {
+        log::open(path, cli.log_append).map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    if let Some(path) = &cli.resume {
+        let sim = resume(&cli, path)?;
+        return run_single(&cli, sim);
+    }
     
-    /// Starting index in adjacency_list for each colony's connections
-    start_index: Vec<usize>,
+    #[cfg(not(feature = "parallel"))]
+    if cli.movement == Movement::Parallel {
+        return Err("the parallel engine is compiled out, rebuild with --features parallel".to_string());
+    }
+    let options = SimOptions {
+        movement: match cli.movement {
+            Movement::Sequential => MovementMode::Sequential,
+            Movement::Simultaneous => MovementMode::Simultaneous,
+            Movement::Parallel => MovementMode::Parallel,
+        },
+        destruction: match cli.destruction {
+            Destruction::Lazy => DestructionMode::Lazy,
+            Destruction::Immediate => DestructionMode::Immediate,
+        },
+        dead_end_behavior: cli.dead_end,
+        reorder_every: cli.reorder_every,
+        factions: cli.factions,
+        track_components: cli.track_components || cli.battle_royale,
+        cascade_on_destroy: cli.cascade_on_destroy,
+        oscillation_after: cli.detect_oscillation,
+        ..SimOptions::default()
+    };
     
-    /// Number of connections for each colony
-    connection_count: Vec<u8>,  // u8 since max is 4 connections
+    // load the map alone first so nothing per-ant is allocated yet, and a
+    // density can be turned into an ant count
+    let map = cli.map.as_deref().expect("checked in Cli::validate");
+    let graph = load_map(&cli, map)?;
+    if graph.reverse_tunnels_added() > 0 && !cli.quiet {
+        status(&cli, format!("{}: added {} reverse tunnels", map, graph.reverse_tunnels_added()));
+    }
+    if graph.is_legacy_format() && !cli.quiet {
+        warn(format!(
+            "{} has no '{} v{}' header, the headerless format is deprecated",
+            map, MAP_HEADER, MAP_FORMAT_VERSION
+        ));
+    }
+    if cli.fail_on_warning || !cli.quiet {
+        for warning in graph.parse_warnings() {
+            warn(format!("{} {}", map, warning));
+        }
+    }
+    if cli.fail_on_warning && !graph.parse_warnings().is_empty() {
+        let message = format!("error: {}: {} parse warnings with --fail-on-warning", map, graph.parse_warnings().len());
+        log::write(&message);
+        eprintln!("{}", message);
+        return Ok(Outcome::MapRejected);
+    }
     
-    /// Ant Tracking
-    ant_position: Vec<ColonyId>,
-    move_count: Vec<u32>,
-    ant_alive: Vec<bool>,
-    ants_at_colony: Vec<Vec<AntId>>,
+    let mut config = match (cli.num_ants, cli.ants_per_colony) {
+        (_, Some(density)) => AntConfig::from_density(density, graph.colony_count()).map_err(|err| err.to_string())?,
+        (num_ants, None) => AntConfig::new(num_ants.unwrap_or_default()),
+    };
+    config.placement_strategy = match &cli.placement {
+        Some(path) => placement::read(path, &graph, config.num_ants, cli.placement_partial)?,
+        None => cli.distribution.placement(&graph)?,
+    };
+    config.collision_policy = cli.collision_policy;
+    config.fight_trigger = match cli.fight_at {
+        Some(FightAt::Exactly) => FightTrigger::Exactly,
+        Some(FightAt::AtLeast) => FightTrigger::AtLeast,
+        // the whole crowd fights, not only a pair of it
+        None if matches!(config.placement_strategy, PlacementStrategy::Single(_)) => FightTrigger::AtLeast,
+        None => FightTrigger::Exactly,
+    };
+    config.seed = cli.seed;
+    if let Some(max_moves) = cli.max_moves {
+        config.max_moves = max_moves;
+    }
+    let num_ants = config.num_ants;
     
-    // Metadata
-    total_colonies: usize,
-    total_ants: usize,
-    alive_ants: usize,
-    active_ants_under_max_moves: usize, // counter to avoid O(n) scan
-}
-
-impl AntSimulation {
-    /// Create a new simulation from a map file
-    pub fn from_file(filename: &str, num_ants: usize) -> Result<Self, Box<dyn std::error::Error>> {
-        let file = File::open(filename)?;
-        let reader = BufReader::new(file);
-        
-        // First pass: collect all colony names and build name->ID mapping
-        let mut name_to_id: HashMap<String, ColonyId> = HashMap::new();
-        let mut raw_connections: Vec<Vec<(String, ColonyId)>> = Vec::new();
-        let mut colony_names: Vec<String> = Vec::new();
+    if cli.dry_run || cli.max_memory.is_some() {
+        let estimate = estimate_memory(graph.colony_count(), graph.edge_count(), num_ants, &options);
         
-        for line in reader.lines() {
-            let line = line?;
-            if line.trim().is_empty() {
-                continue;
-            }
-            
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.is_empty() {
-                continue;
-            }
-            
-            // First part is colony name
-            let colony_name = parts[0].to_string();
-            
-            // Assign ID if new colony
-            if !name_to_id.contains_key(&colony_name) {
-                let id = colony_names.len();
-                name_to_id.insert(colony_name.clone(), id);
-                colony_names.push(colony_name.clone());
-                raw_connections.push(Vec::new());
-            }
-            
-            let colony_id = name_to_id[&colony_name];
-            
-            // Parse connections
-            for i in 1..parts.len() {
-                let connection_parts: Vec<&str> = parts[i].split('=').collect();
-                if connection_parts.len() == 2 {
-                    let target_name = connection_parts[1].to_string();
-                    raw_connections[colony_id].push((target_name, colony_id));
-                }
+        if let Some(limit) = cli.max_memory {
+            if estimate.total() > limit {
+                return Err(format!(
+                    "estimated memory {} exceeds --max-memory {}",
+                    format_bytes(estimate.total()),
+                    format_bytes(limit)
+                ));
             }
         }
-        
-        let total_colonies = colony_names.len();
-        
-        // Build adjacency list
-        let mut adjacency_list = Vec::new();
-        let mut start_index = vec![0; total_colonies];
-        let mut connection_count = vec![0u8; total_colonies];
-        
-        for (colony_id, connections) in raw_connections.iter().enumerate() {
-            start_index[colony_id] = adjacency_list.len();
-            
-            for (target_name, _) in connections {
-                if let Some(&target_id) = name_to_id.get(target_name) {
-                    adjacency_list.push(target_id);
-                    connection_count[colony_id] += 1;
-                }
-            }
+        if cli.dry_run {
+            // everything AntSimulation::new would reject, without its per-ant allocations
+            AntSimulation::validate(&graph, &config).map_err(|err| err.to_string())?;
+            let config = AntConfig { seed: Some(config.seed.unwrap_or_else(|| fastrand::u64(..))), ..config };
+            write_output(&cli, |out| write_dry_run(out, &cli, &graph, &config, &options, &estimate))?;
+            return Ok(Outcome::Finished);
         }
-        
-        // Initialize simulation state
-        let mut sim = AntSimulation {
-            ant_count: vec![0; total_colonies],
-            destroyed: vec![false; total_colonies],
-            colony_names,
-            
-            adjacency_list,
-            start_index,
-            connection_count,
-            
-            ant_position: vec![0; num_ants],
-            move_count: vec![0; num_ants],
-            ant_alive: vec![true; num_ants],
-            ants_at_colony: vec![Vec::new(); total_colonies],
-            
-            total_colonies,
-            total_ants: num_ants,
-            alive_ants: num_ants,
-            active_ants_under_max_moves: num_ants,
-        };
-        
-        // Place ants at random colonies
-        sim.initialize_ants();
-        
-        Ok(sim)
     }
     
-    /// Place ants randomly across colonies
-    fn initialize_ants(&mut self) {
-        for ant_id in 0..self.total_ants {
-            let mut colony_id;
-            loop {
-                colony_id = fastrand::usize(..self.total_colonies);
-                if !self.destroyed[colony_id] {
-                    break;
-                }
-            }
-            
-            self.ant_position[ant_id] = colony_id;
-            self.ant_count[colony_id] += 1;
-            self.ants_at_colony[colony_id].push(ant_id);
+    let graph = Arc::new(graph);
+    if let Some(seeds) = cli.sweep_seeds.clone() {
+        return sweep_seeds(&cli, &graph, config, &options, seeds);
+    }
+    if cli.runs > 1 {
+        return run_many(&cli, &graph, config, &options);
+    }
+    
+    let sim = AntSimulation::new(graph, config)
+        .map_err(|err| err.to_string())?
+        .with_options(options);
+    run_single(&cli, sim)
+}
+
+/// The map file, with the tunnels back `--symmetrize` adds
+fn load_map(cli: &Cli, map: &str) -> Result<MapGraph, String> {
+    let mut graph = MapGraph::from_file(map).map_err(|err| format!("{}: {}", map, err))?;
+    if cli.symmetrize {
+        graph.symmetrize().map_err(|err| format!("{}: can't symmetrize: {}", map, err))?;
+    }
+    Ok(graph)
+}
+
+/// `--resume`: the run saved in a snapshot, checked against the map and ant
+/// count if they were given too
+fn resume(cli: &Cli, path: &Path) -> Result<AntSimulation, String> {
+    let file = File::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let snapshot = Snapshot::read_from(BufReader::new(file)).map_err(|err| format!("{}: {}", path.display(), err))?;
+    
+    if let Some(map) = &cli.map {
+        let graph = load_map(cli, map)?;
+        if !snapshot.matches_map(&graph) {
+            return Err(format!("{} wasn't taken on {}", path.display(), map));
         }
     }
+    if let Some(num_ants) = cli.num_ants.filter(|&num_ants| num_ants != snapshot.total_ants()) {
+        return Err(format!("{} has {} ants, not {}", path.display(), snapshot.total_ants(), num_ants));
+    }
     
-    /// Get valid moves from a colony
-    #[inline]
-    pub fn get_valid_moves(&self, colony_id: ColonyId, buffer: &mut Vec<ColonyId>) {
-        buffer.clear();
-        let start = self.start_index[colony_id];
-        let count = self.connection_count[colony_id] as usize;
-        
-        for i in start..start + count {
-            let neighbor = self.adjacency_list[i];
-            if !self.destroyed[neighbor] {
-                buffer.push(neighbor);
+    let sim = AntSimulation::from_snapshot(snapshot).map_err(|err| format!("{}: {}", path.display(), err))?;
+    Ok(match cli.max_moves {
+        Some(max_moves) => sim.with_max_moves(max_moves),
+        None => sim,
+    })
+}
+
+/// Run one simulation, fresh or resumed, and report on it
+fn run_single(cli: &Cli, mut sim: AntSimulation) -> Result<Outcome, String> {
+    #[cfg(not(feature = "msgpack"))]
+    if cli.snapshot_format == SnapshotEncoding::Msgpack {
+        return Err("MessagePack snapshots are compiled out, rebuild with --features msgpack".to_string());
+    }
+    if cli.stats {
+        write_output(cli, |out| write_graph_stats(out, cli, &sim.graph_stats()))?;
+        return Ok(Outcome::Finished);
+    }
+    
+    // fights are told with --verbose, and with --ant-names to tell them by name
+    let ant_names = match &cli.ant_names {
+        Some(path) => AntNames::read(path)?,
+        None => AntNames::default(),
+    };
+    let duplicates = ant_names.duplicates();
+    if let Some(first) = duplicates.first() {
+        let others = match duplicates.len() - 1 {
+            0 => String::new(),
+            others => format!(", as are {} other names", others),
+        };
+        warn(format!("--ant-names gives '{}' to more than one ant{}", first, others));
+    }
+    if let Some(path) = &cli.regions {
+        let regions = Regions::from_file(&path.to_string_lossy(), sim.graph())
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+        if !cli.quiet {
+            for warning in regions.warnings() {
+                warn(format!("{} {}", path.display(), warning));
             }
         }
+        sim = sim.with_regions(regions);
+    } else if let Some(regions) = Regions::from_attributes(sim.graph()) {
+        sim = sim.with_regions(regions);
     }
-    
-    /// Remove ant from colony 
-    #[inline]
-    fn remove_ant_from_colony(&mut self, colony: ColonyId, ant: AntId) {
-        if let Some(pos) = self.ants_at_colony[colony].iter().position(|&x| x == ant) {
-            self.ants_at_colony[colony].swap_remove(pos);
+    let resumed_from = cli.resume.is_some().then(|| sim.simulation_stats().iterations);
+    if !cli.quiet {
+        let (ants, colonies, total) = sim.stats();
+        match resumed_from {
+            Some(iteration) => status(cli, format!(
+                "Resuming simulation at iteration {}: {} ants, {}/{} active colonies",
+                iteration, ants, colonies, total
+            )),
+            None => status(cli, format!("Starting simulation: {} ants, {}/{} active colonies", ants, colonies, total)),
         }
+        status(cli, format!("seed: {}", sim.seed()));
+    }
+    if cli.verbose {
+        status(cli, format!("Memory usage:\n{}", sim.memory_usage()));
+        status(cli, format!("Complexity estimate:\n{}", sim.simulation_complexity_estimate()));
     }
     
-    /// Move an ant once
-    #[inline]
-    pub fn move_ant(&mut self, ant_id: AntId, buffer: &mut Vec<ColonyId>) -> Option<(ColonyId, ColonyId)> {
-        if !self.ant_alive[ant_id] {
-            return None;
+    let interrupted = install_interrupt_handler();
+    let start = Instant::now();
+    let deadline = cli.time_limit.map(|limit| start + limit);
+    let mut checkpointer = match (&cli.snapshot_dir, cli.snapshot_every) {
+        (Some(dir), Some(every)) => {
+            let format = match cli.snapshot_format {
+                SnapshotEncoding::Bincode => SnapshotFormat::Bincode,
+                SnapshotEncoding::Msgpack => SnapshotFormat::MessagePack,
+            };
+            Some(Checkpointer::new(dir, every, cli.snapshot_keep, format)?)
         }
-        
-        let current_colony = self.ant_position[ant_id];
-        self.get_valid_moves(current_colony, buffer);
-        
-        if buffer.is_empty() {
-            return None;
+        _ => None,
+    };
+    
+    let map_label = match (&cli.map, &cli.resume) {
+        (Some(map), _) => map.clone(),
+        (None, resume) => resume.as_deref().map_or(String::new(), |path| path.display().to_string()),
+    };
+    
+    sim.record_fights(!cli.quiet && (cli.verbose || cli.ant_names.is_some()));
+    sim.record_deaths(cli.verbose || cli.format != Format::Text);
+    sim.record_fight_counts(cli.battle_royale);
+    if let Some(&ant) = cli.trace_ant.iter().find(|&&ant| ant >= sim.total_ants()) {
+        return Err(format!("--trace-ant {}: there are only {} ants", ant, sim.total_ants()));
+    }
+    sim.trace_ants(&cli.trace_ant);
+    #[cfg(feature = "parquet")]
+    let mut events = cli.events_parquet.as_deref().map(open_events_parquet).transpose()?;
+    #[cfg(not(feature = "parquet"))]
+    if cli.events_parquet.is_some() {
+        return Err("Parquet output is compiled out, rebuild with --features parquet".to_string());
+    }
+    sim.record_events(cli.events_parquet.is_some());
+    #[cfg(not(feature = "sqlite"))]
+    if cli.sqlite.is_some() {
+        return Err("SQLite output is compiled out, rebuild with --features sqlite".to_string());
+    }
+    #[cfg(not(feature = "arrow"))]
+    if cli.colony_stats.is_some() {
+        return Err("Arrow output is compiled out, rebuild with --features arrow".to_string());
+    }
+    #[cfg(all(feature = "arrow", not(feature = "parquet")))]
+    if cli.colony_stats.as_deref().is_some_and(is_parquet) {
+        return Err("Parquet output is compiled out, rebuild with --features parquet".to_string());
+    }
+    sim.record_destructions(cli.sqlite.is_some() || cli.colony_stats.is_some());
+    sim.record_visits(cli.colony_stats.is_some());
+    #[cfg(feature = "sqlite")]
+    let mut timeline = Vec::new();
+    let mut stopped_by = None;
+    let run = sim.run_until(&interrupted, deadline, |sim, result| {
+        if cli.verbose && result.colonies_destroyed > 0 {
+            status(cli, format!(
+                "iteration {}: {} colonies destroyed, {} ants killed",
+                result.iteration, result.colonies_destroyed, result.ants_killed
+            ));
         }
-        
-        let next_colony = buffer[fastrand::usize(..buffer.len())];
-        
-        self.ant_position[ant_id] = next_colony;
-        self.move_count[ant_id] += 1;
-        
-        if self.move_count[ant_id] == MAX_MOVES {
-            self.active_ants_under_max_moves -= 1; // stop scanning in should_continue
+        for (colony, killed) in sim.last_fights() {
+            status(cli, ant_names.fight_message(sim.graph().colony_name(*colony), killed));
         }
-        
-        self.ant_count[current_colony] -= 1;
-        self.ant_count[next_colony] += 1;
-        
-        self.remove_ant_from_colony(current_colony, ant_id);
-        self.ants_at_colony[next_colony].push(ant_id);
-        
-        Some((current_colony, next_colony))
-    }
-    
-    #[inline]
-    pub fn check_collision(&mut self, colony_id: ColonyId) -> Option<(AntId, AntId)> {
-        if self.ant_count[colony_id] == 2 {
-            let ant1 = self.ants_at_colony[colony_id][0];
-            let ant2 = self.ants_at_colony[colony_id][1];
-            
-            self.destroy_colony(colony_id);
-            self.kill_ant(ant1);
-            self.kill_ant(ant2);
-            
-            return Some((ant1, ant2));
+        if cli.hash_every.is_some_and(|every| (result.iteration + 1).is_multiple_of(every)) {
+            status(cli, format!("state hash after {} iterations: {:016x}", result.iteration + 1, sim.state_hash()));
+        }
+        #[cfg(feature = "sqlite")]
+        if cli.sqlite_timeline {
+            timeline.push(*result);
+        }
+        #[cfg(feature = "parquet")]
+        if let Some(Err(err)) = events.as_mut().map(|events| events.write(result.iteration, sim.last_events())) {
+            warn(format!("stopped writing events: {}", err));
+            events = None;
+        }
+        if let Some(checkpointer) = &mut checkpointer {
+            if let Err(err) = checkpointer.after_iteration(sim, result.iteration + 1) {
+                warn(err);
+            }
+        }
+        if let (Some(path), Some(every)) = (&cli.metrics_file, cli.metrics_every) {
+            if (result.iteration + 1).is_multiple_of(every) {
+                if let Err(err) = write_metrics_file(path, sim, &map_label, start.elapsed()) {
+                    warn(err);
+                }
+            }
         }
-        None
+        stopped_by = cli.stop_when.iter().find(|condition| condition.is_met(sim)).copied();
+        match stopped_by {
+            Some(_) => ControlFlow::Break(()),
+            None => ControlFlow::Continue(()),
+        }
+    });
+    let duration = start.elapsed();
+    if let Some(Err(err)) = checkpointer.as_mut().map(Checkpointer::wait) {
+        warn(err);
     }
     
-    #[inline]
-    fn destroy_colony(&mut self, colony_id: ColonyId) {
-        self.destroyed[colony_id] = true;
-        self.ant_count[colony_id] = 0;
-        self.ants_at_colony[colony_id].clear();
+    let end = RunEnd { run, stopped_by, resumed_from, duration };
+    write_output(cli, |out| write_report(out, &sim, cli, &ant_names, end))?;
+    if let Some(path) = &cli.metrics_file {
+        write_metrics_file(path, &sim, &map_label, duration)?;
+    }
+    if let Some(path) = &cli.geojson_file {
+        write_geojson_file(path, &sim)?;
+    }
+    #[cfg(feature = "arrow")]
+    if let Some(path) = &cli.colony_stats {
+        write_colony_stats(path, &sim)?;
+    }
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = &cli.sqlite {
+        let mut report = RunReport::new(&sim, &map_label);
+        report.status = RunStatus { iterations: report.status.iterations, ..run };
+        report.time_limit = cli.time_limit;
+        report.timeline = timeline;
+        let written = persist::open(path).and_then(|mut conn| persist::write_run(&mut conn, &report));
+        written.map_err(|err| format!("can't add the run to {}: {}", path.display(), err))?;
+    }
+    #[cfg(feature = "parquet")]
+    if let (Some(events), Some(path)) = (events, &cli.events_parquet) {
+        let finished = events.finish().and_then(|mut out| Ok(out.flush()?));
+        finished.map_err(|err| format!("can't write events to {}: {}", path.display(), err))?;
     }
     
-    #[inline]
-    fn kill_ant(&mut self, ant_id: AntId) {
-        if self.ant_alive[ant_id] {
-            self.ant_alive[ant_id] = false;
-            self.alive_ants -= 1;
-            if self.move_count[ant_id] < MAX_MOVES {
-                self.active_ants_under_max_moves -= 1;
+    if cli.perf {
+        match sim.perf_counters() {
+            Some(counters) => status(cli, format!("\n=== Perf Counters ===\n{}", counters)),
+            None => warn("perf counters are compiled out, rebuild with --features perf-counters".to_string()),
+        }
+    }
+    Ok(if run.interrupted {
+        Outcome::Interrupted
+    } else if run.timed_out {
+        Outcome::TimedOut
+    } else if cli.battle_royale && sim.winner().is_none() {
+        Outcome::NoWinner
+    } else {
+        Outcome::Finished
+    })
+}
+
+/// `--metrics-file`, written under a temporary name and renamed into place
+/// so a scraper never reads half of it
+fn write_metrics_file(path: &Path, sim: &AntSimulation, map: &str, elapsed: Duration) -> Result<(), String> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let written = (|| {
+        let mut out = BufWriter::new(File::create(&partial)?);
+        write_prometheus(&mut out, sim, map, elapsed)?;
+        out.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+        std::fs::rename(&partial, path)
+    })();
+    written.map_err(|err| {
+        let _ = std::fs::remove_file(&partial);
+        format!("can't write metrics to {}: {}", path.display(), err)
+    })
+}
+
+/// `--geojson-file`, the surviving world for a web map
+#[cfg(feature = "geojson")]
+fn write_geojson_file(path: &Path, sim: &AntSimulation) -> Result<(), String> {
+    let written = File::create(path).and_then(|file| {
+        let mut out = BufWriter::new(file);
+        sim.write_geojson(&mut out)?;
+        out.flush()
+    });
+    written.map_err(|err| format!("can't write GeoJSON to {}: {}", path.display(), err))
+}
+
+#[cfg(not(feature = "geojson"))]
+fn write_geojson_file(_path: &Path, _sim: &AntSimulation) -> Result<(), String> {
+    Err("GeoJSON output is compiled out, rebuild with --features geojson".to_string())
+}
+
+/// `--events-parquet`, filled in iteration by iteration
+#[cfg(feature = "parquet")]
+fn open_events_parquet(path: &Path) -> Result<EventParquetWriter<BufWriter<File>>, String> {
+    let file = File::create(path).map_err(|err| format!("can't create {}: {}", path.display(), err))?;
+    EventParquetWriter::new(BufWriter::new(file), EVENTS_PER_ROW_GROUP).map_err(|err| err.to_string())
+}
+
+/// `--colony-stats`, as Feather or Parquet going by the extension
+#[cfg(feature = "arrow")]
+fn write_colony_stats(path: &Path, sim: &AntSimulation) -> Result<(), String> {
+    let batch = sim.colony_stats_to_arrow();
+    let written = (|| -> Result<(), Box<dyn std::error::Error>> {
+        let mut out = BufWriter::new(File::create(path)?);
+        if is_parquet(path) {
+            #[cfg(feature = "parquet")]
+            {
+                let mut writer = parquet::arrow::ArrowWriter::try_new(&mut out, batch.schema(), None)?;
+                writer.write(&batch)?;
+                writer.close()?;
             }
+        } else {
+            let mut writer = arrow_ipc::writer::FileWriter::try_new(&mut out, &batch.schema())?;
+            writer.write(&batch)?;
+            writer.finish()?;
         }
+        Ok(out.flush()?)
+    })();
+    written.map_err(|err| format!("can't write colony stats to {}: {}", path.display(), err))
+}
+
+/// Whether `--colony-stats` is a Parquet file rather than Feather
+#[cfg(feature = "arrow")]
+fn is_parquet(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "parquet")
+}
+
+/// Ctrl-C sets the returned flag so the run stops at the next iteration
+/// boundary and still reports; a second Ctrl-C quits on the spot
+fn install_interrupt_handler() -> Arc<AtomicBool> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&interrupted);
+    let installed = ctrlc::set_handler(move || {
+        if flag.swap(true, Ordering::Relaxed) {
+            log::write("interrupted again, quitting");
+            log::flush();
+            std::process::exit(EXIT_INTERRUPTED.into());
+        }
+        let message = "Interrupted, finishing the current iteration (Ctrl-C again to quit now)";
+        if !log::write(message) {
+            eprintln!("\n{}", message);
+        }
+    });
+    if let Err(err) = installed {
+        warn(format!("can't catch Ctrl-C, an interrupted run won't report: {}", err));
+    }
+    interrupted
+}
+
+/// Progress output: to `--log-file` if given, and kept off stdout when
+/// stdout carries the JSON report
+fn status(cli: &Cli, message: String) {
+    if log::write(&message) {
+        return;
+    }
+    if cli.format != Format::Text && cli.output.is_none() {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+/// Something worth knowing that doesn't stop the run, to `--log-file` if given
+fn warn(message: String) {
+    let message = format!("warning: {}", message);
+    if !log::write(&message) {
+        eprintln!("{}", message);
     }
+}
+
+/// `--runs N`: independent runs with consecutive seeds, reported as aggregates
+fn run_many(cli: &Cli, graph: &Arc<MapGraph>, config: AntConfig, options: &SimOptions) -> Result<Outcome, String> {
+    let runs = cli.runs as usize;
+    let base_seed = config.seed.unwrap_or_else(|| fastrand::u64(..));
+    let threads = cli.thread_count();
     
-    /// check if simulation should continue
-    #[inline]
-    pub fn should_continue(&self) -> bool {
-        self.alive_ants > 0 && self.active_ants_under_max_moves > 0
+    if !cli.quiet {
+        status(cli, format!(
+            "Starting {} simulations: {} ants, {} colonies",
+            runs, config.num_ants, graph.colony_count()
+        ));
+        status(cli, format!("seed: {}", base_seed));
+    }
+    if cli.verbose {
+        status(cli, format!("threads: {}", threads.min(runs)));
     }
     
-    /// Run one iteration of the simulation
-    pub fn run_iteration(&mut self) {
-        let mut buffer = Vec::with_capacity(4);
-        let mut colonies_to_check = Vec::new();
-        
-        for ant_id in 0..self.total_ants {
-            if let Some((_, next_colony)) = self.move_ant(ant_id, &mut buffer) {
-                if self.ant_count[next_colony] == 2 {
-                    // avoid pushing duplicates
-                    if colonies_to_check.last() != Some(&next_colony) {
-                        colonies_to_check.push(next_colony);
-                    }
-                }
+    let start = Instant::now();
+    let deadline = cli.time_limit.map(|limit| start + limit);
+    let (results, survival) = run_batch_survival(graph, &config, options, runs, base_seed, threads, deadline)
+        .map_err(|err| err.to_string())?;
+    let duration = start.elapsed();
+    
+    let Some(stats) = BatchStats::from_runs(&results) else {
+        status(cli, format!("Time limit reached before any of the {} runs finished", runs));
+        return Ok(Outcome::TimedOut);
+    };
+    if let Some(path) = &cli.survival_csv {
+        File::create(path)
+            .and_then(|file| write_survival_csv(&mut BufWriter::new(file), graph, &survival))
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    write_output(cli, |out| write_batch_report(out, cli, base_seed, &results, &stats, &survival, duration))?;
+    Ok(if results.len() < runs { Outcome::TimedOut } else { Outcome::Finished })
+}
+
+/// Seeds shown at each end of the `--sweep-seeds` ranking
+const SWEEP_SHOWN: usize = 10;
+
+/// `--sweep-seeds`: every seed in the range on the same world, ranked by
+/// `--rank-by` to pick out the outliers worth a closer look
+fn sweep_seeds(
+    cli: &Cli,
+    graph: &Arc<MapGraph>,
+    config: AntConfig,
+    options: &SimOptions,
+    seeds: Range<u64>,
+) -> Result<Outcome, String> {
+    let requested = (seeds.end - seeds.start) as usize;
+    let threads = cli.thread_count();
+    if !cli.quiet {
+        status(cli, format!(
+            "Sweeping seeds {}..{}: {} ants, {} colonies",
+            seeds.start, seeds.end, config.num_ants, graph.colony_count()
+        ));
+    }
+    if cli.verbose {
+        status(cli, format!("threads: {}", threads.min(requested)));
+    }
+    
+    let start = Instant::now();
+    let deadline = cli.time_limit.map(|limit| start + limit);
+    let results = run_batch_until(graph, &config, options, requested, seeds.start, threads, deadline)
+        .map_err(|err| err.to_string())?;
+    let duration = start.elapsed();
+    if results.is_empty() {
+        status(cli, format!("Time limit reached before any of the {} seeds finished", requested));
+        return Ok(Outcome::TimedOut);
+    }
+    
+    if let Some(path) = &cli.sweep_csv {
+        File::create(path)
+            .and_then(|file| write_sweep_csv(&mut BufWriter::new(file), &results))
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    write_output(cli, |out| write_sweep_report(out, cli, &seeds, &results, duration))?;
+    Ok(if results.len() < requested { Outcome::TimedOut } else { Outcome::Finished })
+}
+
+/// `--survival-csv`: the fraction of runs each colony survived, in colony ID order
+fn write_survival_csv(out: &mut impl Write, graph: &MapGraph, survival: &ColonySurvival) -> io::Result<()> {
+    writeln!(out, "colony,survival_rate")?;
+    for (colony, rate) in survival.rates().into_iter().enumerate() {
+        writeln!(out, "{},{}", csv_field(graph.colony_name(colony)), rate)?;
+    }
+    out.flush()
+}
+
+/// A CSV field, quoted if it holds a comma, quote or line break
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// `--sweep-csv`: a row of metrics per seed, in seed order
+fn write_sweep_csv(out: &mut impl Write, results: &[RunSummary]) -> io::Result<()> {
+    let names: Vec<_> = Metric::ALL.iter().map(|metric| metric.name()).collect();
+    writeln!(out, "seed,{}", names.join(","))?;
+    for run in results {
+        let values: Vec<_> = Metric::ALL.iter().map(|metric| metric.of_run(run).to_string()).collect();
+        writeln!(out, "{},{}", run.seed, values.join(","))?;
+    }
+    out.flush()
+}
+
+/// Top and bottom `SWEEP_SHOWN` seeds by the `--rank-by` metric, ties going
+/// to the lower seed
+fn write_sweep_report(
+    out: &mut dyn Write,
+    cli: &Cli,
+    seeds: &Range<u64>,
+    results: &[RunSummary],
+    duration: Duration,
+) -> io::Result<()> {
+    let metric = cli.rank_by.unwrap_or(Metric::AliveAnts);
+    let mut ranked = results.to_vec();
+    ranked.sort_by_key(|run| (Reverse(metric.of_run(run)), run.seed));
+    let top: Vec<_> = ranked.iter().take(SWEEP_SHOWN).copied().collect();
+    ranked.sort_by_key(|run| (metric.of_run(run), run.seed));
+    let bottom: Vec<_> = ranked.iter().take(SWEEP_SHOWN).copied().collect();
+    let requested = (seeds.end - seeds.start) as usize;
+    
+    match cli.format {
+        Format::Text => {
+            writeln!(out, "\n=== Seed Sweep: seeds {}..{}, ranked by {} ===", seeds.start, seeds.end, metric.name())?;
+            if results.len() < requested {
+                writeln!(out, "time limit hit: {} of {} seeds finished", results.len(), requested)?;
             }
+            writeln!(out, "\nTop {}:", top.len())?;
+            write_sweep_table(out, &top)?;
+            writeln!(out, "\nBottom {}:", bottom.len())?;
+            write_sweep_table(out, &bottom)?;
+            if !cli.no_timing {
+                writeln!(out, "\nSweep completed in {:?}", duration)?;
+            }
+            Ok(())
         }
-        
-        for colony_id in colonies_to_check {
-            self.check_collision(colony_id);
+        #[cfg(feature = "json")]
+        Format::Json => {
+            let rows = |runs: &[RunSummary]| -> Vec<serde_json::Value> {
+                runs.iter()
+                    .map(|run| {
+                        let mut row = serde_json::json!({ "seed": run.seed });
+                        for metric in Metric::ALL {
+                            row[metric.name()] = metric.of_run(run).into();
+                        }
+                        row
+                    })
+                    .collect()
+            };
+            let mut report = serde_json::json!({
+                "map": cli.map,
+                "seeds": { "start": seeds.start, "end": seeds.end },
+                "rank_by": metric.name(),
+                "finished": results.len(),
+                "requested": requested,
+                "time_limit_hit": results.len() < requested,
+                "top": rows(&top),
+                "bottom": rows(&bottom),
+            });
+            if !cli.no_timing {
+                report["duration_ms"] = (duration.as_secs_f64() * 1000.0).into();
+            }
+            serde_json::to_writer_pretty(&mut *out, &report)?;
+            writeln!(out)
         }
     }
+}
+
+/// Seed and every metric, right-aligned under their names
+fn write_sweep_table(out: &mut dyn Write, runs: &[RunSummary]) -> io::Result<()> {
+    let mut rows: Vec<Vec<String>> = vec![
+        std::iter::once("seed").chain(Metric::ALL.iter().map(|metric| metric.name())).map(str::to_string).collect(),
+    ];
+    for run in runs {
+        let values = Metric::ALL.iter().map(|metric| metric.of_run(run).to_string());
+        rows.push(std::iter::once(run.seed.to_string()).chain(values).collect());
+    }
     
-    /// Print the remaining map
-    pub fn print_remaining_world(&self) {
-        println!("\n=== Remaining World ===");
-        
-        for colony_id in 0..self.total_colonies {
-            if self.destroyed[colony_id] {
-                continue;
+    let widths: Vec<usize> =
+        (0..rows[0].len()).map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0)).collect();
+    for row in &rows {
+        let cells: Vec<_> = row.iter().zip(&widths).map(|(cell, width)| format!("{:>1$}", cell, width)).collect();
+        writeln!(out, "  {}", cells.join("  "))?;
+    }
+    Ok(())
+}
+
+/// Write to `--output` if given, stdout otherwise
+fn write_output(cli: &Cli, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> Result<(), String> {
+    let written = match &cli.output {
+        Some(path) => {
+            let file = File::create(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+            let mut out = BufWriter::new(file);
+            write(&mut out).and_then(|()| out.flush())
+        }
+        None => write(&mut io::stdout().lock()),
+    };
+    written.map_err(|err| format!("failed to write report: {}", err))
+}
+
+/// How a run ended, for `write_report`
+#[derive(Clone, Copy)]
+struct RunEnd {
+    run: RunStatus,
+    /// The `--stop-when` condition that ended the run, if one did
+    stopped_by: Option<StopCondition>,
+    /// Iteration a `--resume` run picked up at; iteration counts in the
+    /// report are totals since the original start
+    resumed_from: Option<u32>,
+    duration: Duration,
+}
+
+/// Final report in the chosen format, naming ants from `ant_names`
+fn write_report(
+    out: &mut dyn Write,
+    sim: &AntSimulation,
+    cli: &Cli,
+    ant_names: &AntNames,
+    end: RunEnd,
+) -> io::Result<()> {
+    let RunEnd { run, stopped_by, resumed_from, duration } = end;
+    let iterations = sim.simulation_stats().iterations;
+    match cli.format {
+        Format::Text => {
+            if run.interrupted {
+                writeln!(out, "\nSimulation interrupted at iteration {}", iterations)?;
+            } else if run.timed_out {
+                let limit = cli.time_limit.expect("only a time limit times out");
+                writeln!(out, "\nSimulation hit the {:?} time limit at iteration {}", limit, iterations)?;
+            } else if let Some(condition) = stopped_by {
+                writeln!(out, "\nSimulation stopped at iteration {}: {} holds", iterations, condition)?;
+            } else {
+                writeln!(out, "\nSimulation ended after {} iterations", iterations)?;
             }
-            
-            print!("{}", self.colony_names[colony_id]);
-            
-            let start = self.start_index[colony_id];
-            let count = self.connection_count[colony_id] as usize;
-            
-            for i in start..start + count {
-                let neighbor_id = self.adjacency_list[i];
-                if !self.destroyed[neighbor_id] {
-                    print!(" north={}", self.colony_names[neighbor_id]);
+            if let Some(iteration) = resumed_from {
+                writeln!(out, "Resumed from iteration {}, ran {} more", iteration, run.iterations)?;
+            }
+            writeln!(out, "Move budget: {} moves per ant", sim.max_moves())?;
+            if !cli.no_timing {
+                writeln!(out, "\nSimulation completed in {:?}", duration)?;
+            }
+            sim.write_remaining_world_ordered(out, map_order(cli))?;
+            if sim.regions().is_some() {
+                writeln!(out, "\n=== Regions ===")?;
+                for region in sim.region_stats() {
+                    let standing = format!("{}/{} colonies standing", region.surviving, region.colonies);
+                    write!(out, "{}: {}, {} ants, {} destroyed", region.name, standing, region.ants, region.destroyed)?;
+                    match region.destructions.last() {
+                        Some(last) => writeln!(out, ", the last in iteration {}", last)?,
+                        None => writeln!(out)?,
+                    }
                 }
             }
-            
-            println!();
+            if cli.verbose {
+                let stats = sim.simulation_stats();
+                writeln!(out, "\nTotal moves: {} ({:.1} per iteration)", stats.total_moves_made, stats.moves_per_iteration)?;
+                if sim.options().oscillation_after.is_some() {
+                    writeln!(out, "Oscillating ants: {}", sim.oscillating_ants())?;
+                }
+                writeln!(out, "\n=== Moves Per Ant ===")?;
+                writeln!(out, "all:   {}", sim.ant_move_statistics())?;
+                writeln!(out, "alive: {}", sim.alive_ant_move_statistics())?;
+                writeln!(out, "dead:  {}", sim.dead_ant_move_statistics())?;
+                write_deaths(out, sim)?;
+                write_marathon_ants(out, &sim.marathon_ants(), ant_names)?;
+            }
+            if cli.battle_royale {
+                write_battle_royale(out, sim, ant_names)?;
+            }
+            if !cli.trace_ant.is_empty() {
+                write_traced_ants(out, sim, ant_names)?;
+            }
+            Ok(())
+        }
+        #[cfg(feature = "json")]
+        Format::Json => {
+            let stats = sim.simulation_stats();
+            let mut world = Vec::new();
+            sim.write_map_ordered(&mut world, map_order(cli))?;
+            let mut report = serde_json::json!({
+                "map": cli.map,
+                "seed": sim.seed(),
+                "iterations": iterations,
+                "resumed_from": resumed_from,
+                "interrupted": run.interrupted,
+                "time_limit_hit": run.timed_out,
+                "stopped_by": stopped_by.map(|condition| condition.to_string()),
+                "max_moves": sim.max_moves(),
+                "total_ants": sim.total_ants(),
+                "alive_ants": stats.alive_ants,
+                "active_colonies": stats.active_colonies,
+                "total_colonies": stats.total_colonies,
+                "total_moves": stats.total_moves_made,
+                "moves_per_iteration": stats.moves_per_iteration,
+                "oscillating_ants": sim.options().oscillation_after.map(|_| sim.oscillating_ants()),
+                "move_stats": {
+                    "all": sample_stats_json(sim.ant_move_statistics()),
+                    "alive": sample_stats_json(sim.alive_ant_move_statistics()),
+                    "dead": sample_stats_json(sim.dead_ant_move_statistics()),
+                },
+                "remaining_world": String::from_utf8_lossy(&world).lines().collect::<Vec<_>>(),
+                "regions": sim.regions().map(|_| sim.region_stats()),
+                "deaths": sim.deaths_timeline(),
+                "marathon_ants": marathon_ants_json(&sim.marathon_ants(), ant_names),
+            });
+            if !cli.trace_ant.is_empty() {
+                report["traced_ants"] = traced_ants_json(sim, ant_names);
+            }
+            if !cli.no_timing {
+                report["duration_ms"] = (duration.as_secs_f64() * 1000.0).into();
+            }
+            if cli.battle_royale {
+                let winner = sim.winner().map(|winner| {
+                    serde_json::json!({
+                        "ant": winner.ant,
+                        "name": ant_names.get(winner.ant),
+                        "colony": sim.graph().colony_name(winner.colony),
+                        "moves": winner.moves,
+                        "colonies_destroyed": winner.colonies_destroyed,
+                    })
+                });
+                report["battle_royale"] = serde_json::json!({ "winner": winner, "survivors": stats.alive_ants });
+            }
+            serde_json::to_writer_pretty(&mut *out, &report)?;
+            writeln!(out)
         }
-        
-        println!("\nAlive ants: {}/{}", self.alive_ants, self.total_ants);
     }
-    
-    /// Get statistics
-    pub fn stats(&self) -> (usize, usize, usize) {
-        let active_colonies = self.destroyed.iter().filter(|&&d| !d).count();
-        (self.alive_ants, active_colonies, self.total_colonies)
+}
+
+/// Verbose `=== Deaths ===` section: deaths by cause, the worst iteration
+/// and a sparkline of the whole run
+fn write_deaths(out: &mut dyn Write, sim: &AntSimulation) -> io::Result<()> {
+    let per_iteration = sim.deaths_per_iteration();
+    let Some((worst, &most)) = per_iteration.iter().enumerate().rev().max_by_key(|&(_, deaths)| deaths) else {
+        return Ok(());
+    };
+    if most == 0 {
+        return Ok(());
+    }
+    writeln!(out, "\n=== Deaths ===")?;
+    let mut by_cause: Vec<(DeathCause, u32)> = Vec::new();
+    for deaths in sim.deaths_timeline() {
+        match by_cause.iter_mut().find(|(cause, _)| *cause == deaths.cause) {
+            Some((_, ants)) => *ants += deaths.ants,
+            None => by_cause.push((deaths.cause, deaths.ants)),
+        }
     }
+    let by_cause: Vec<String> = by_cause.iter().map(|(cause, ants)| format!("{}: {}", cause.name(), ants)).collect();
+    writeln!(out, "{}", by_cause.join(", "))?;
+    writeln!(out, "Most in one iteration: {}, in iteration {}", most, worst)?;
+    writeln!(out, "|{}|", sparkline(&per_iteration))
 }
 
-fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() != 3 {
-        eprintln!("Usage: {} <map_file> <num_ants>", args[0]);
-        std::process::exit(1);
+/// Verbose `=== Marathon Ants ===` section, left out if no ant moved
+fn write_marathon_ants(out: &mut dyn Write, marathon: &MarathonAnts, ant_names: &AntNames) -> io::Result<()> {
+    let Some(most_moves) = marathon.most_moves else {
+        return Ok(());
+    };
+    writeln!(out, "\n=== Marathon Ants ===")?;
+    writeln!(out, "Most moves: ant {}, {} moves", ant_names.label(most_moves.ant), most_moves.value)?;
+    if let Some(most_distinct) = marathon.most_distinct {
+        let ant = ant_names.label(most_distinct.ant);
+        writeln!(out, "Most distinct colonies: ant {}, {} colonies", ant, most_distinct.value)?;
     }
-    
-    let filename = &args[1];
-    let num_ants: usize = args[2].parse().expect("Number of ants must be a valid number");
-    
-    let mut sim = AntSimulation::from_file(filename, num_ants)
-        .expect("Failed to load map file");
-    
-    let (ants, colonies, total) = sim.stats();
-    println!("Starting simulation: {} ants, {}/{} active colonies", ants, colonies, total);
-    
-    let mut iterations = 0;
-    let start = std::time::Instant::now();
-    
-    while sim.should_continue() && iterations < MAX_MOVES {
-        sim.run_iteration();
-        iterations += 1;
+    if let Some(longest_lived) = marathon.longest_lived {
+        let ant = ant_names.label(longest_lived.ant);
+        writeln!(out, "Longest lived: ant {}, died in iteration {}", ant, longest_lived.value)?;
     }
-    
-    println!("\nSimulation ended after {} iterations", iterations);
-    let duration = start.elapsed();
-    println!("\nSimulation completed in {:?}", duration);
-    sim.print_remaining_world();
+    Ok(())
+}
+
+/// `marathon_ants` in the JSON report, each record with the ant's name
+#[cfg(feature = "json")]
+fn marathon_ants_json(marathon: &MarathonAnts, ant_names: &AntNames) -> serde_json::Value {
+    let record = |record: Option<AntRecord>| {
+        record.map(|record| {
+            serde_json::json!({ "ant": record.ant, "name": ant_names.get(record.ant), "value": record.value })
+        })
+    };
+    serde_json::json!({
+        "most_moves": record(marathon.most_moves),
+        "most_distinct": record(marathon.most_distinct),
+        "longest_lived": record(marathon.longest_lived),
+    })
+}
+
+/// `=== Battle Royale ===`: the winner, or why there is none
+fn write_battle_royale(out: &mut dyn Write, sim: &AntSimulation, ant_names: &AntNames) -> io::Result<()> {
+    writeln!(out, "\n=== Battle Royale ===")?;
+    if let Some(winner) = sim.winner() {
+        let colony = sim.graph().colony_name(winner.colony);
+        writeln!(out, "Winner: ant {}, at {} after {} moves", ant_names.label(winner.ant), colony, winner.moves)?;
+        return writeln!(out, "Colonies destroyed in its fights: {}", winner.colonies_destroyed);
+    }
+    match sim.simulation_stats().alive_ants {
+        0 => writeln!(out, "No winner: every ant died"),
+        alive if sim.components_with_at_least_two_ants() == 0 => {
+            writeln!(out, "No winner: {} ants left, none of them can meet", alive)
+        }
+        alive => writeln!(out, "No winner: {} ants left", alive),
+    }
+}
+
+/// Moves, distance and whereabouts of each ant `trace_ants` follows
+fn write_traced_ants(out: &mut dyn Write, sim: &AntSimulation, ant_names: &AntNames) -> io::Result<()> {
+    writeln!(out, "\n=== Traced Ants ===")?;
+    for ant in 0..sim.total_ants() {
+        let (Some(path), Some(view)) = (sim.ant_path(ant), sim.ant_view(ant)) else {
+            continue;
+        };
+        write!(out, "ant {}: {} moves", ant_names.label(ant), path.len() - 1)?;
+        if let Some(km) = sim.distance_traveled_km(ant) {
+            write!(out, ", {:.1} km", km)?;
+        }
+        writeln!(out, ", {} {}", if view.alive { "now at" } else { "died at" }, view.colony_name)?;
+    }
+    Ok(())
+}
+
+/// `traced_ants` in the JSON report, each ant's path by colony name
+#[cfg(feature = "json")]
+fn traced_ants_json(sim: &AntSimulation, ant_names: &AntNames) -> serde_json::Value {
+    let traced = (0..sim.total_ants()).filter_map(|ant| {
+        let (path, view) = (sim.ant_path(ant)?, sim.ant_view(ant)?);
+        Some(serde_json::json!({
+            "ant": ant,
+            "name": ant_names.get(ant),
+            "alive": view.alive,
+            "moves": path.len() - 1,
+            "distance_km": sim.distance_traveled_km(ant),
+            "path": path.iter().map(|&colony| sim.graph().colony_name(colony)).collect::<Vec<_>>(),
+        }))
+    });
+    traced.collect()
+}
+
+/// Widest `sparkline` drawn, longer runs are summed into buckets
+const SPARKLINE_WIDTH: usize = 60;
+
+/// One bar per iteration (or bucket of them), blank for none
+fn sparkline(values: &[u32]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let buckets: Vec<u64> = values
+        .chunks(values.len().div_ceil(SPARKLINE_WIDTH).max(1))
+        .map(|bucket| bucket.iter().map(|&value| value as u64).sum())
+        .collect();
+    let max = buckets.iter().copied().max().unwrap_or(0).max(1);
+    buckets
+        .iter()
+        .map(|&value| match value {
+            0 => ' ',
+            value => BARS[((value * BARS.len() as u64).div_ceil(max) as usize - 1).min(BARS.len() - 1)],
+        })
+        .collect()
+}
+
+/// Order the remaining world is written in, `--sort-output` or not
+fn map_order(cli: &Cli) -> MapOrder {
+    if cli.sort_output {
+        MapOrder::Sorted
+    } else {
+        MapOrder::Declared
+    }
+}
+
+/// The placement as the dry run shows it; an explicit one is summed up
+/// rather than listing a colony per ant
+fn placement_label(strategy: &PlacementStrategy) -> String {
+    match strategy {
+        PlacementStrategy::Explicit(positions) => {
+            format!("Explicit {{ placed: {} }}", positions.iter().flatten().count())
+        }
+        strategy => format!("{:?}", strategy),
+    }
+}
+
+/// `--stats` report: the shape of what's live of the map
+fn write_graph_stats(out: &mut dyn Write, cli: &Cli, stats: &GraphStats) -> io::Result<()> {
+    match cli.format {
+        Format::Text => {
+            writeln!(out, "=== Map Stats ===")?;
+            writeln!(out, "{}", stats)
+        }
+        #[cfg(feature = "json")]
+        Format::Json => {
+            let report = serde_json::json!({
+                "live_colonies": stats.live_colonies,
+                "total_colonies": stats.total_colonies,
+                "tunnels": stats.tunnels,
+                "degree": sample_stats_json(stats.degree),
+                "dead_ends": stats.dead_ends,
+                "articulation_points": stats.articulation_points,
+                "components": stats.components,
+                "diameter": stats.diameter,
+                "clustering_coefficient": stats.clustering_coefficient,
+            });
+            serde_json::to_writer_pretty(&mut *out, &report)?;
+            writeln!(out)
+        }
+    }
+}
+
+/// `--dry-run` report: the map, the resolved options and the memory estimate
+fn write_dry_run(
+    out: &mut dyn Write,
+    cli: &Cli,
+    graph: &MapGraph,
+    config: &AntConfig,
+    options: &SimOptions,
+    estimate: &MemoryEstimate,
+) -> io::Result<()> {
+    let map = graph.map_stats();
+    let seed = config.seed.expect("resolved before the dry run");
+    let threads = cli.thread_count().min(cli.runs as usize);
+    match cli.format {
+        Format::Text => {
+            writeln!(out, "Dry run, nothing was simulated")?;
+            writeln!(out, "\n=== Map ===")?;
+            writeln!(out, "map:        {}", cli.map.as_deref().unwrap_or_default())?;
+            writeln!(out, "{}", map)?;
+            
+            writeln!(out, "\n=== Options ===")?;
+            let placement = placement_label(&config.placement_strategy);
+            writeln!(out, "ants:        {} ({} placement)", config.num_ants, placement)?;
+            writeln!(out, "seed:        {}", seed)?;
+            writeln!(out, "max moves:   {}", config.max_moves)?;
+            let crowd = match config.fight_trigger {
+                FightTrigger::Exactly => "exactly",
+                FightTrigger::AtLeast => "at least",
+            };
+            let (threshold, policy) = (config.collision_threshold, config.collision_policy);
+            writeln!(out, "collisions:  {} {} ants fight, {:?}", crowd, threshold, policy)?;
+            writeln!(out, "movement:    {:?}, {:?} destruction", options.movement, options.destruction)?;
+            writeln!(out, "dead ends:   {:?}", options.dead_end_behavior)?;
+            match options.reorder_every {
+                Some(every) => writeln!(out, "reorder:     every {} iterations", every)?,
+                None => writeln!(out, "reorder:     off")?,
+            }
+            writeln!(out, "factions:    {}", options.factions)?;
+            writeln!(out, "components:  {}", if options.track_components { "tracked" } else { "not tracked" })?;
+            writeln!(out, "cascade:     {}", if options.cascade_on_destroy { "on" } else { "off" })?;
+            writeln!(out, "runs:        {} on {} thread(s)", cli.runs, threads)?;
+            match cli.time_limit {
+                Some(limit) => writeln!(out, "time limit:  {:?}", limit)?,
+                None => writeln!(out, "time limit:  none")?,
+            }
+            
+            writeln!(out, "\n=== Memory Estimate ===")?;
+            writeln!(out, "{}", estimate)
+        }
+        #[cfg(feature = "json")]
+        Format::Json => {
+            let report = serde_json::json!({
+                "dry_run": true,
+                "map": {
+                    "path": cli.map,
+                    "colonies": map.colonies,
+                    "tunnels": map.tunnels,
+                    "degree": sample_stats_json(map.degree),
+                    "degree_histogram": map.degree_histogram,
+                    "components": map.components,
+                },
+                "options": {
+                    "ants": config.num_ants,
+                    "placement": placement_label(&config.placement_strategy),
+                    "seed": seed,
+                    "max_moves": config.max_moves,
+                    "collision_threshold": config.collision_threshold,
+                    "collision_policy": format!("{:?}", config.collision_policy),
+                    "fight_trigger": format!("{:?}", config.fight_trigger),
+                    "movement": format!("{:?}", options.movement),
+                    "destruction": format!("{:?}", options.destruction),
+                    "dead_end": format!("{:?}", options.dead_end_behavior),
+                    "reorder_every": options.reorder_every,
+                    "factions": options.factions,
+                    "track_components": options.track_components,
+                    "cascade_on_destroy": options.cascade_on_destroy,
+                    "runs": cli.runs,
+                    "threads": threads,
+                    "time_limit_ms": cli.time_limit.map(|limit| limit.as_millis() as u64),
+                },
+                "memory_estimate_bytes": estimate.total(),
+            });
+            serde_json::to_writer_pretty(&mut *out, &report)?;
+            writeln!(out)
+        }
+    }
+}
+
+/// Aggregate report for `--runs`
+#[cfg_attr(not(feature = "json"), allow(unused_variables))]
+fn write_batch_report(
+    out: &mut dyn Write,
+    cli: &Cli,
+    base_seed: u64,
+    results: &[RunSummary],
+    stats: &BatchStats,
+    survival: &ColonySurvival,
+    duration: Duration,
+) -> io::Result<()> {
+    let requested_runs = cli.runs as usize;
+    let (p10, p90) = (survival.percentile(10.0), survival.percentile(90.0));
+    match cli.format {
+        Format::Text => {
+            let (first, last) = (results[0].seed, results[results.len() - 1].seed);
+            writeln!(out, "\n=== {} Runs, seeds {} to {} ===", stats.runs, first, last)?;
+            if stats.runs < requested_runs {
+                writeln!(out, "time limit hit: {} of {} runs finished", stats.runs, requested_runs)?;
+            }
+            writeln!(out, "alive ants:      {}", stats.alive_ants)?;
+            writeln!(out, "active colonies: {}", stats.active_colonies)?;
+            writeln!(out, "iterations:      {}", stats.iterations)?;
+            writeln!(out, "fewest survivors: {} (seed {})", stats.alive_ants.min, stats.fewest_survivors_seed)?;
+            writeln!(out, "most survivors:   {} (seed {})", stats.alive_ants.max, stats.most_survivors_seed)?;
+            writeln!(
+                out,
+                "colony survival:  mean {:.2}, p10 {:.2}, median {:.2}, p90 {:.2}",
+                survival.mean_rate(), p10, survival.percentile(50.0), p90
+            )?;
+            if !cli.no_timing {
+                writeln!(out, "\nRuns completed in {:?}", duration)?;
+            }
+            Ok(())
+        }
+        #[cfg(feature = "json")]
+        Format::Json => {
+            let runs: Vec<_> = results
+                .iter()
+                .map(|run| {
+                    serde_json::json!({
+                        "seed": run.seed,
+                        "iterations": run.iterations,
+                        "alive_ants": run.alive_ants,
+                        "active_colonies": run.active_colonies,
+                    })
+                })
+                .collect();
+            let mut report = serde_json::json!({
+                "map": cli.map,
+                "base_seed": base_seed,
+                "runs": stats.runs,
+                "requested_runs": requested_runs,
+                "time_limit_hit": stats.runs < requested_runs,
+                "aggregate": {
+                    "alive_ants": sample_stats_json(stats.alive_ants),
+                    "active_colonies": sample_stats_json(stats.active_colonies),
+                    "iterations": sample_stats_json(stats.iterations),
+                    "fewest_survivors_seed": stats.fewest_survivors_seed,
+                    "most_survivors_seed": stats.most_survivors_seed,
+                    "colony_survival": {
+                        "mean": survival.mean_rate(),
+                        "p10": p10,
+                        "median": survival.percentile(50.0),
+                        "p90": p90,
+                    },
+                },
+                "results": runs,
+            });
+            if !cli.no_timing {
+                report["duration_ms"] = (duration.as_secs_f64() * 1000.0).into();
+            }
+            serde_json::to_writer_pretty(&mut *out, &report)?;
+            writeln!(out)
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+fn sample_stats_json(stats: SampleStats) -> serde_json::Value {
+    serde_json::json!({
+        "count": stats.count,
+        "min": stats.min,
+        "max": stats.max,
+        "mean": stats.mean,
+        "stddev": stats.stddev,
+        "median": stats.median,
+    })
 }
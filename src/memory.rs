@@ -0,0 +1,107 @@
+use std::fmt;
+use std::mem::size_of;
+
+use crate::options::{DeadEndBehavior, SimOptions};
+use crate::simulation::{AntId, ColonyId, COLONY_LIST_CAPACITY};
+
+/// Rough average colony name length used by estimates, before the map is known
+const ESTIMATED_NAME_LEN: usize = 12;
+/// Same for direction labels (`north`, `south`, ...)
+const ESTIMATED_LABEL_LEN: usize = 5;
+
+/// Heap bytes used by each part of a simulation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryBreakdown {
+    /// Compressed adjacency list, offsets and connection counts
+    pub adjacency: usize,
+    /// Direction label of every connection
+    pub edge_labels: usize,
+    /// Colony names (and the name lookup while it's kept)
+    pub names: usize,
+    /// Per-colony ant counts, peak counts, destroyed flags and live in-degrees
+    pub colony_state: usize,
+    /// Per-colony ant lists
+    pub colony_lists: usize,
+    /// Per-ant position, move count, alive flag, slot, processing orders and,
+    /// when dead-end ants die, failed move attempts
+    pub ant_state: usize,
+    /// Per-ant factions and per-faction counts, when faction mode is on
+    pub factions: usize,
+    /// Reused per-iteration buffers
+    pub scratch: usize,
+}
+
+/// Estimates share the breakdown's shape
+pub type MemoryEstimate = MemoryBreakdown;
+
+impl MemoryBreakdown {
+    pub fn total(&self) -> usize {
+        self.adjacency
+            + self.edge_labels
+            + self.names
+            + self.colony_state
+            + self.colony_lists
+            + self.ant_state
+            + self.factions
+            + self.scratch
+    }
+}
+
+/// Heap bytes a simulation will need, without building it
+pub fn estimate_memory(colonies: usize, edges: usize, ants: usize, opts: &SimOptions) -> MemoryEstimate {
+    let factions = opts.factions as usize;
+    MemoryBreakdown {
+        adjacency: edges * size_of::<u32>() + colonies * (size_of::<u32>() + size_of::<u8>()),
+        edge_labels: edges * (size_of::<String>() + ESTIMATED_LABEL_LEN),
+        // each name is held twice, in the list and as a key of the lookup table
+        names: colonies * (2 * (size_of::<String>() + ESTIMATED_NAME_LEN) + size_of::<ColonyId>()),
+        colony_state: colonies * (size_of::<u16>() + size_of::<u32>() + size_of::<bool>() + size_of::<u32>())
+            + if opts.track_components { colonies * (size_of::<u32>() + size_of::<usize>()) } else { 0 },
+        // every ant sits in exactly one list, on top of the room reserved up front
+        colony_lists: colonies * size_of::<Vec<AntId>>()
+            + (colonies * COLONY_LIST_CAPACITY).max(ants) * size_of::<AntId>(),
+        ant_state: ants * ANT_STATE_BYTES
+            + ants.div_ceil(64) * size_of::<u64>()
+            + match opts.dead_end_behavior {
+                DeadEndBehavior::DieAfterNMoves(_) => ants * size_of::<u32>(),
+                _ => 0,
+            }
+            + if opts.oscillation_after.is_some() { ants * (size_of::<u32>() + size_of::<u16>()) } else { 0 },
+        factions: if factions > 1 { ants * size_of::<u8>() + factions * size_of::<usize>() } else { 0 },
+        scratch: 0,
+    }
+}
+
+/// Bytes of per-ant state: position, move count, slot, order and the order
+/// of the live ants (the alive flags are a bitset on top)
+const ANT_STATE_BYTES: usize =
+    size_of::<u32>() + size_of::<u32>() + size_of::<u32>() + size_of::<AntId>() + size_of::<AntId>();
+
+impl fmt::Display for MemoryBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "adjacency:    {}", format_bytes(self.adjacency))?;
+        writeln!(f, "edge labels:  {}", format_bytes(self.edge_labels))?;
+        writeln!(f, "names:        {}", format_bytes(self.names))?;
+        writeln!(f, "colony state: {}", format_bytes(self.colony_state))?;
+        writeln!(f, "colony lists: {}", format_bytes(self.colony_lists))?;
+        writeln!(f, "ant state:    {}", format_bytes(self.ant_state))?;
+        writeln!(f, "factions:     {}", format_bytes(self.factions))?;
+        writeln!(f, "scratch:      {}", format_bytes(self.scratch))?;
+        write!(f, "total:        {}", format_bytes(self.total()))
+    }
+}
+
+/// Human readable byte count
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
@@ -0,0 +1,42 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::simulation::AntSimulation;
+
+/// Write where `sim` stands in Prometheus' text exposition format, every
+/// sample labelled with the run's seed and `map`:
+///
+/// ```text
+/// ant_mania_alive_ants              gauge    ants still alive
+/// ant_mania_surviving_colonies      gauge    colonies not destroyed
+/// ant_mania_destructions_total      counter  colonies destroyed so far
+/// ant_mania_iterations_total        counter  iterations run so far
+/// ant_mania_run_duration_seconds    gauge    `elapsed`, wall-clock time of the run
+/// ```
+pub fn write_prometheus<W: Write + ?Sized>(
+    out: &mut W,
+    sim: &AntSimulation,
+    map: &str,
+    elapsed: Duration,
+) -> io::Result<()> {
+    let labels = format!("seed=\"{}\",map=\"{}\"", sim.seed(), escape_label(map));
+    let (alive_ants, surviving_colonies, _) = sim.stats();
+    let samples: [(&str, &str, &str, String); 5] = [
+        ("alive_ants", "gauge", "Ants still alive", alive_ants.to_string()),
+        ("surviving_colonies", "gauge", "Colonies not destroyed", surviving_colonies.to_string()),
+        ("destructions_total", "counter", "Colonies destroyed so far", sim.colonies_destroyed().to_string()),
+        ("iterations_total", "counter", "Iterations run so far", sim.iteration().to_string()),
+        ("run_duration_seconds", "gauge", "Wall-clock time of the run", elapsed.as_secs_f64().to_string()),
+    ];
+    for (name, kind, help, value) in samples {
+        writeln!(out, "# HELP ant_mania_{} {}", name, help)?;
+        writeln!(out, "# TYPE ant_mania_{} {}", name, kind)?;
+        writeln!(out, "ant_mania_{}{{{}}} {}", name, labels, value)?;
+    }
+    Ok(())
+}
+
+/// Label values escape backslashes, double quotes and newlines
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,61 @@
+use std::collections::{BinaryHeap, HashMap};
+
+/// Stoer-Wagner global minimum cut of an undirected graph given as
+/// `adjacency[v]`: neighbor -> weight of the edges between them, both
+/// directions listed. Each phase orders the vertices by how tightly they hang
+/// on to the ones already taken (a binary heap with stale entries skipped),
+/// the last two are merged, and the weight tying the last one on is a cut.
+/// O(V * (E + V) * log V). 0 for a disconnected graph, and for fewer than
+/// two vertices, which have no cut to make.
+pub(crate) fn stoer_wagner(mut adjacency: Vec<HashMap<usize, usize>>) -> usize {
+    let vertices = adjacency.len();
+    let mut active: Vec<usize> = (0..vertices).collect();
+    let mut tie = vec![0; vertices];
+    let mut taken = vec![false; vertices];
+    let mut best = usize::MAX;
+
+    while active.len() > 1 {
+        for &v in &active {
+            tie[v] = 0;
+            taken[v] = false;
+        }
+        let mut heap = BinaryHeap::from([(0, active[0])]);
+        let (mut before_last, mut last, mut last_tie) = (active[0], active[0], 0);
+        let mut taken_count = 0;
+        while let Some((weight, v)) = heap.pop() {
+            if taken[v] || weight != tie[v] {
+                continue;
+            }
+            taken[v] = true;
+            taken_count += 1;
+            (before_last, last, last_tie) = (last, v, weight);
+            for (&u, &edges) in &adjacency[v] {
+                if !taken[u] {
+                    tie[u] += edges;
+                    heap.push((tie[u], u));
+                }
+            }
+        }
+        if taken_count < active.len() {
+            // the first vertex can't reach them all
+            return 0;
+        }
+        best = best.min(last_tie);
+
+        // merge `last` into `before_last`
+        let merged = std::mem::take(&mut adjacency[last]);
+        for (u, edges) in merged {
+            adjacency[u].remove(&last);
+            if u != before_last {
+                *adjacency[u].entry(before_last).or_insert(0) += edges;
+                *adjacency[before_last].entry(u).or_insert(0) += edges;
+            }
+        }
+        active.retain(|&v| v != last);
+    }
+    if best == usize::MAX {
+        0
+    } else {
+        best
+    }
+}
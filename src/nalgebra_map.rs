@@ -0,0 +1,64 @@
+//! Maps from `nalgebra` adjacency matrices, for simulations set up from
+//! scientific code that already has the graph as a matrix
+
+use std::sync::Arc;
+
+use nalgebra::DMatrix;
+
+use crate::config::AntConfig;
+use crate::error::SimError;
+use crate::graph::{check_colony_name, MapGraph};
+use crate::names::ColonyNames;
+use crate::simulation::AntSimulation;
+
+impl MapGraph {
+    /// Build a map from a square adjacency matrix, `m[(i, j)]` for a tunnel
+    /// from colony `i` to colony `j`, the colonies named by `names` in
+    /// order. Tunnels are labelled `tunnel` and come in column order; a
+    /// tunnel from a colony to itself is an error.
+    pub fn from_nalgebra_matrix(m: &DMatrix<bool>, names: Vec<String>) -> Result<Self, SimError> {
+        if !m.is_square() {
+            return Err(SimError::InvalidConfig(format!(
+                "adjacency matrix is {}x{}, not square",
+                m.nrows(),
+                m.ncols()
+            )));
+        }
+        if m.nrows() != names.len() {
+            return Err(SimError::InvalidConfig(format!(
+                "adjacency matrix is {}x{} but {} colony names were given",
+                m.nrows(),
+                m.ncols(),
+                names.len()
+            )));
+        }
+
+        let mut colony_names = ColonyNames::new();
+        for (colony, name) in names.iter().enumerate() {
+            check_colony_name(name)?;
+            if colony_names.get_or_insert(name) != colony {
+                return Err(SimError::InvalidConfig(format!("two colonies are called '{}'", name)));
+            }
+        }
+        let mut edges = Vec::new();
+        for from in 0..m.nrows() {
+            for to in (0..m.ncols()).filter(|&to| m[(from, to)]) {
+                if to == from {
+                    let reason = "the tunnel in the matrix's diagonal leads back to it".to_string();
+                    return Err(SimError::InvalidTunnel { colony: names[from].clone(), reason });
+                }
+                edges.push((from, to));
+            }
+        }
+        Self::from_edges(colony_names, names.len(), &edges)
+    }
+}
+
+impl AntSimulation {
+    /// Create a new simulation from an adjacency matrix and colony names,
+    /// see `MapGraph::from_nalgebra_matrix`
+    pub fn from_nalgebra_matrix(m: &DMatrix<bool>, names: Vec<String>, num_ants: usize) -> Result<Self, SimError> {
+        let graph = MapGraph::from_nalgebra_matrix(m, names)?;
+        Self::new(Arc::new(graph), AntConfig::new(num_ants))
+    }
+}
@@ -0,0 +1,143 @@
+#[cfg(not(feature = "intern"))]
+use std::collections::HashMap;
+
+#[cfg(feature = "intern")]
+use string_interner::{DefaultStringInterner, DefaultSymbol, Symbol};
+
+use std::mem::size_of;
+
+use crate::simulation::ColonyId;
+
+/// Handle to a colony name in the intern pool
+#[cfg(feature = "intern")]
+type SymbolId = DefaultSymbol;
+
+/// Colony names, indexed by colony ID
+#[cfg(not(feature = "intern"))]
+pub(crate) struct ColonyNames {
+    names: Vec<String>,
+    /// name -> ID mapping, empty once `drop_index` is called
+    name_to_id: HashMap<String, ColonyId>,
+}
+
+#[cfg(not(feature = "intern"))]
+impl ColonyNames {
+    pub(crate) fn new() -> Self {
+        ColonyNames { names: Vec::new(), name_to_id: HashMap::new() }
+    }
+
+    /// Get the ID of a colony, assigning the next one if it's new
+    pub(crate) fn get_or_insert(&mut self, name: &str) -> ColonyId {
+        if let Some(&id) = self.name_to_id.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.name_to_id.insert(name.to_string(), id);
+        self.names.push(name.to_string());
+        id
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<ColonyId> {
+        self.name_to_id.get(name).copied()
+    }
+
+    /// `get`, or a scan of the names once the lookup table is dropped
+    pub(crate) fn find(&self, name: &str) -> Option<ColonyId> {
+        if self.has_index() {
+            self.get(name)
+        } else {
+            self.names.iter().position(|other| other == name)
+        }
+    }
+
+    /// Done adding names, the lookup table is kept for `find`
+    pub(crate) fn finish(&mut self) {
+        self.name_to_id.shrink_to_fit();
+    }
+
+    /// Drop the lookup table to save its memory, `find` scans from then on
+    pub(crate) fn drop_index(&mut self) {
+        self.name_to_id = HashMap::new();
+    }
+
+    pub(crate) fn has_index(&self) -> bool {
+        self.name_to_id.len() == self.names.len()
+    }
+
+    pub(crate) fn name(&self, id: ColonyId) -> &str {
+        &self.names[id]
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Heap bytes held by the names and, while it's kept, the lookup table
+    pub(crate) fn heap_bytes(&self) -> usize {
+        let names: usize = self.names.iter().map(String::capacity).sum();
+        let lookup: usize = self.name_to_id.keys().map(String::capacity).sum::<usize>()
+            + self.name_to_id.capacity() * (size_of::<String>() + size_of::<ColonyId>());
+        self.names.capacity() * size_of::<String>() + names + lookup
+    }
+}
+
+/// Colony names, stored once in an intern pool
+#[cfg(feature = "intern")]
+pub(crate) struct ColonyNames {
+    interner: DefaultStringInterner,
+    colony_names: Vec<SymbolId>,
+}
+
+#[cfg(feature = "intern")]
+impl ColonyNames {
+    pub(crate) fn new() -> Self {
+        ColonyNames { interner: DefaultStringInterner::new(), colony_names: Vec::new() }
+    }
+
+    /// Get the ID of a colony, assigning the next one if it's new
+    pub(crate) fn get_or_insert(&mut self, name: &str) -> ColonyId {
+        if let Some(id) = self.get(name) {
+            return id;
+        }
+        let symbol = self.interner.get_or_intern(name);
+        // only colony names are interned, so symbols come out in colony ID order
+        debug_assert_eq!(symbol.to_usize(), self.colony_names.len());
+        self.colony_names.push(symbol);
+        self.colony_names.len() - 1
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<ColonyId> {
+        self.interner.get(name).map(|symbol| symbol.to_usize())
+    }
+
+    pub(crate) fn find(&self, name: &str) -> Option<ColonyId> {
+        self.get(name)
+    }
+
+    pub(crate) fn finish(&mut self) {
+        self.interner.shrink_to_fit();
+    }
+
+    /// The interner doubles as the lookup table, nothing to drop
+    pub(crate) fn drop_index(&mut self) {}
+
+    pub(crate) fn has_index(&self) -> bool {
+        true
+    }
+
+    pub(crate) fn name(&self, id: ColonyId) -> &str {
+        self.interner.resolve(self.colony_names[id]).expect("colony symbol is interned")
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.colony_names.len()
+    }
+
+    /// Approximate heap bytes: the interner doesn't expose its capacities, so
+    /// count the string bytes plus an end offset and a dedup slot per name
+    pub(crate) fn heap_bytes(&self) -> usize {
+        let strings: usize = self.interner.iter().map(|(_, name)| name.len()).sum();
+        let per_name = size_of::<usize>() + 2 * size_of::<SymbolId>();
+        self.colony_names.capacity() * size_of::<SymbolId>() + strings + self.interner.len() * per_name
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+/// How ants pick their moves within an iteration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MovementMode {
+    /// Ants move one after another, drawing from the shared RNG in processing
+    /// order. Results depend on the order ants are processed in.
+    #[default]
+    Sequential,
+    /// Every ant draws from its own stream keyed by (seed, iteration, ant), so
+    /// the outcome of an iteration doesn't depend on processing order.
+    Simultaneous,
+    /// `Simultaneous`, with every ant's move picked on rayon's thread pool
+    /// before any ant moves; the same run as `Simultaneous` for the same
+    /// seed. Needs the `parallel` feature, without it the moves are picked
+    /// on the calling thread.
+    Parallel,
+}
+
+/// When colonies where a fight broke out are destroyed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DestructionMode {
+    /// Collisions are queued while ants move and settled once every ant has
+    /// moved, so the whole iteration sees the same set of live colonies.
+    #[default]
+    Lazy,
+    /// A colony is destroyed the moment an arriving ant makes the fight, so
+    /// ants processed later in the iteration can no longer move into it.
+    /// Sequential mode only, simultaneous movement always settles lazily.
+    Immediate,
+}
+
+/// What an ant does when every tunnel out of its colony leads to a destroyed one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DeadEndBehavior {
+    /// Stay put for good (it still counts as active until its moves run out)
+    #[default]
+    StayForever,
+    /// Die on the N-th move attempt that finds no way out (0 acts like 1)
+    DieAfterNMoves(u32),
+    /// Jump to a random live colony, which counts as a move. Picking the
+    /// target scans the map, so this is slow if many ants get stuck.
+    TeleportToRandom,
+}
+
+/// Fixes applied to a map once it's parsed, see `MapGraph::from_file_with`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MapOptions {
+    /// Give every one-way tunnel a tunnel back, see `MapGraph::symmetrize`
+    pub symmetrize: bool,
+    /// Don't keep the name index, see `MapGraph::drop_name_index`
+    pub drop_name_index: bool,
+}
+
+/// Tuning knobs for the simulation engine
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimOptions {
+    pub movement: MovementMode,
+
+    pub destruction: DestructionMode,
+
+    pub dead_end_behavior: DeadEndBehavior,
+
+    /// Shuffle the ant processing order every iteration (sequential mode only)
+    pub shuffle_order: bool,
+
+    /// Every K iterations, re-sort the processing order by current colony so
+    /// consecutive ants touch nearby memory. `None` disables it.
+    ///
+    /// Results are unchanged in simultaneous mode. In sequential mode the
+    /// reorder changes which ant gets which random draw, so it changes the
+    /// outcome unless `shuffle_order` is also on (the order is random then
+    /// anyway).
+    pub reorder_every: Option<u32>,
+
+    /// Keep the map's connected components up to date, so component queries
+    /// are O(1) and the run also ends once no component holds two ants (no
+    /// fight can happen any more). Costs a rebuild after every iteration that
+    /// destroys a colony.
+    pub track_components: bool,
+
+    /// Split ants round-robin into this many factions that only fight each
+    /// other. 0 or 1 turns faction mode off.
+    pub factions: u8,
+
+    /// After a fight destroys a colony, also destroy the neighbors it leaves
+    /// with no live tunnel out, then theirs and so on, killing the ants in
+    /// them (see `AntSimulation::destroy_cascade`). Sparse maps end much
+    /// sooner, since stuck ants no longer wait out their moves.
+    pub cascade_on_destroy: bool,
+
+    /// Flag ants that have gone back and forth between the same two colonies
+    /// for more than this many moves in a row, see
+    /// `AntSimulation::oscillating_ants`. `None` turns detection off, and
+    /// with it the six bytes per ant it costs.
+    pub oscillation_after: Option<u16>,
+}
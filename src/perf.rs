@@ -0,0 +1,27 @@
+use std::fmt;
+
+/// Work done by the engine's hot paths, counted when built with the
+/// `perf-counters` feature (or in debug builds)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerfCounters {
+    /// Neighbor scans done while moving ants
+    pub valid_move_queries: u64,
+    /// Moves actually executed
+    pub moves: u64,
+    /// Colonies checked for a fight
+    pub collision_checks: u64,
+    /// Removals from a colony's ant list
+    pub list_removals: u64,
+    /// Random draws for placement and moves
+    pub rng_calls: u64,
+}
+
+impl fmt::Display for PerfCounters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "valid move queries: {}", self.valid_move_queries)?;
+        writeln!(f, "moves executed:     {}", self.moves)?;
+        writeln!(f, "collision checks:   {}", self.collision_checks)?;
+        writeln!(f, "list removals:      {}", self.list_removals)?;
+        write!(f, "rng calls:          {}", self.rng_calls)
+    }
+}
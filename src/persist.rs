@@ -0,0 +1,194 @@
+//! Finished runs in an SQLite database, to query thousands of them at once.
+//! `open` creates the tables if they're missing:
+//!
+//! - `runs`: one row per run, its parameters and final stats
+//! - `destructions`: the colonies each run destroyed, by `run_id`
+//! - `per_iteration_stats`: each run's iterations, if its report kept them
+//!
+//! SQLite integers are signed, so the 64-bit `seed` and `map_hash` are
+//! stored as the `i64` with the same bits. Every run goes in in one
+//! transaction, taken up front so that processes writing to the same
+//! database wait for each other (up to `BUSY_TIMEOUT`) instead of failing.
+
+use std::path::Path;
+use std::time::Duration;
+
+use rusqlite::{params, Connection, TransactionBehavior};
+
+use crate::config::{CollisionPolicy, FightTrigger};
+use crate::error::SimError;
+use crate::options::{DestructionMode, MovementMode};
+use crate::simulation::{AntSimulation, IterationResult, RunStatus, SimulationStats};
+use crate::summary::Destruction;
+
+/// How long a write waits for another one to the same database to finish
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY,
+        map TEXT NOT NULL,
+        map_hash INTEGER NOT NULL,
+        engine TEXT NOT NULL,
+        ants INTEGER NOT NULL,
+        seed INTEGER NOT NULL,
+        max_moves INTEGER NOT NULL,
+        time_limit_ms INTEGER,
+        movement TEXT NOT NULL,
+        destruction TEXT NOT NULL,
+        collision_policy TEXT NOT NULL,
+        fight_trigger TEXT NOT NULL,
+        iterations INTEGER NOT NULL,
+        interrupted INTEGER NOT NULL,
+        timed_out INTEGER NOT NULL,
+        alive_ants INTEGER NOT NULL,
+        active_colonies INTEGER NOT NULL,
+        total_colonies INTEGER NOT NULL,
+        total_moves INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS destructions (
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        iteration INTEGER NOT NULL,
+        colony INTEGER NOT NULL,
+        name TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS per_iteration_stats (
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        iteration INTEGER NOT NULL,
+        moves INTEGER NOT NULL,
+        colonies_destroyed INTEGER NOT NULL,
+        ants_killed INTEGER NOT NULL
+    );
+";
+
+/// Everything `write_run` stores about one run
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    /// How the map was given, e.g. its path
+    pub map: String,
+    /// FNV-1a of the map as `MapGraph::write_map` writes it, the same for
+    /// the same map however it was loaded
+    pub map_hash: u64,
+    /// Crate name and version of the engine that ran it
+    pub engine: String,
+    pub ants: usize,
+    pub seed: u64,
+    pub max_moves: u32,
+    pub time_limit: Option<Duration>,
+    pub movement: MovementMode,
+    pub destruction: DestructionMode,
+    pub collision_policy: CollisionPolicy,
+    pub fight_trigger: FightTrigger,
+    pub status: RunStatus,
+    pub stats: SimulationStats,
+    /// See `AntSimulation::destructions`
+    pub destructions: Vec<Destruction>,
+    /// One entry per iteration for `per_iteration_stats`, none to leave it
+    /// out
+    pub timeline: Vec<IterationResult>,
+}
+
+impl RunReport {
+    /// The report of `sim` as it stands, with its destructions if it kept
+    /// them; how the run ended, `time_limit` and `timeline` are left for the
+    /// caller
+    pub fn new(sim: &AntSimulation, map: &str) -> Self {
+        let mut text = Vec::new();
+        sim.graph().write_map(&mut text, |_| true).expect("writing to a Vec can't fail");
+        RunReport {
+            map: map.to_string(),
+            map_hash: fnv1a(&text),
+            engine: concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION")).to_string(),
+            ants: sim.total_ants(),
+            seed: sim.seed(),
+            max_moves: sim.max_moves(),
+            time_limit: None,
+            movement: sim.options().movement,
+            destruction: sim.options().destruction,
+            collision_policy: sim.collision_policy(),
+            fight_trigger: sim.fight_trigger(),
+            status: RunStatus { iterations: sim.iteration(), ..RunStatus::default() },
+            stats: sim.simulation_stats(),
+            destructions: sim.destructions(),
+            timeline: Vec::new(),
+        }
+    }
+}
+
+/// Open (or create) the database at `path` with the tables in place
+pub fn open(path: &Path) -> Result<Connection, SimError> {
+    let conn = Connection::open(path).map_err(sqlite)?;
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(sqlite)?;
+    create_tables(&conn)?;
+    Ok(conn)
+}
+
+/// Create the tables that are missing, leaving the others as they are
+pub fn create_tables(conn: &Connection) -> Result<(), SimError> {
+    conn.execute_batch(SCHEMA).map_err(sqlite)
+}
+
+/// Insert one run with its destructions and timeline, all or nothing,
+/// returning its `runs.id`
+pub fn write_run(conn: &mut Connection, report: &RunReport) -> Result<i64, SimError> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate).map_err(sqlite)?;
+    tx.execute(
+        "INSERT INTO runs (map, map_hash, engine, ants, seed, max_moves, time_limit_ms, movement, destruction,
+            collision_policy, fight_trigger, iterations, interrupted, timed_out, alive_ants, active_colonies,
+            total_colonies, total_moves)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+        params![
+            report.map,
+            report.map_hash as i64,
+            report.engine,
+            report.ants as i64,
+            report.seed as i64,
+            report.max_moves,
+            report.time_limit.map(|limit| limit.as_millis() as i64),
+            format!("{:?}", report.movement),
+            format!("{:?}", report.destruction),
+            format!("{:?}", report.collision_policy),
+            format!("{:?}", report.fight_trigger),
+            report.status.iterations,
+            report.status.interrupted,
+            report.status.timed_out,
+            report.stats.alive_ants as i64,
+            report.stats.active_colonies as i64,
+            report.stats.total_colonies as i64,
+            report.stats.total_moves_made as i64,
+        ],
+    )
+    .map_err(sqlite)?;
+    let run_id = tx.last_insert_rowid();
+    {
+        let mut insert = tx
+            .prepare("INSERT INTO destructions (run_id, iteration, colony, name) VALUES (?1, ?2, ?3, ?4)")
+            .map_err(sqlite)?;
+        for destruction in &report.destructions {
+            insert
+                .execute(params![run_id, destruction.iteration, destruction.colony as i64, destruction.name])
+                .map_err(sqlite)?;
+        }
+        let mut insert = tx
+            .prepare(
+                "INSERT INTO per_iteration_stats (run_id, iteration, moves, colonies_destroyed, ants_killed)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )
+            .map_err(sqlite)?;
+        for result in &report.timeline {
+            let [moves, destroyed, killed] =
+                [result.moves, result.colonies_destroyed, result.ants_killed].map(|count| count as i64);
+            insert.execute(params![run_id, result.iteration, moves, destroyed, killed]).map_err(sqlite)?;
+        }
+    }
+    tx.commit().map_err(sqlite)?;
+    Ok(run_id)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3))
+}
+
+fn sqlite(err: rusqlite::Error) -> SimError {
+    SimError::Export(format!("SQLite: {}", err))
+}
@@ -0,0 +1,53 @@
+//! Conversions to and from `petgraph`, to run its algorithms (shortest
+//! paths, PageRank, ...) on a map, or simulate ants on a graph built with it.
+//! Nodes are colonies, weighted by name, and edges tunnels, weighted by
+//! direction; node indices are colony IDs both ways.
+
+use std::collections::HashSet;
+
+use petgraph::graph::{Graph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Directed;
+
+use crate::error::SimError;
+use crate::graph::{check_colony_name, push_tunnel, Direction, MapGraph};
+use crate::simulation::ColonyId;
+
+impl MapGraph {
+    /// Build a map from a `petgraph` graph, colony `i` from node `i` and each
+    /// colony's tunnels in the order their edges were added. Checked as
+    /// `from_dict` checks its colonies, with names that have to be unique.
+    pub fn from_petgraph(graph: &Graph<String, Direction, Directed>) -> Result<Self, SimError> {
+        let names: Vec<&str> = graph.node_weights().map(String::as_str).collect();
+        let mut seen = HashSet::with_capacity(names.len());
+        for &name in &names {
+            check_colony_name(name)?;
+            if !seen.insert(name) {
+                return Err(SimError::InvalidConfig(format!("two colonies are called '{}'", name)));
+            }
+        }
+
+        let mut tunnels: Vec<Vec<(&str, ColonyId)>> = vec![Vec::new(); names.len()];
+        for edge in graph.edge_references() {
+            let (from, to) = (edge.source().index(), edge.target().index());
+            push_tunnel(&mut tunnels[from], (from, names[from]), edge.weight().as_str(), (to, names[to]))?;
+        }
+        Self::from_labelled_tunnels(&names, tunnels)
+    }
+
+    /// The map as a `petgraph` graph that `from_petgraph` turns back into
+    /// it; aliases, coordinates and attributes are left behind
+    pub fn to_petgraph(&self) -> Graph<String, Direction, Directed> {
+        let mut graph = Graph::with_capacity(self.colony_count(), self.edge_count());
+        for colony in 0..self.colony_count() {
+            graph.add_node(self.colony_name(colony).to_string());
+        }
+        for colony in 0..self.colony_count() {
+            for (i, &neighbor) in self.neighbors(colony).iter().enumerate() {
+                let direction = Direction::from(self.edge_label(colony, i));
+                graph.add_edge(NodeIndex::new(colony), NodeIndex::new(neighbor as usize), direction);
+            }
+        }
+        graph
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use ant_mania::{ColonyId, MapGraph, PlacementStrategy};
+
+/// `--placement`: where each ant starts, read from a file and checked against
+/// `graph` before anything per-ant is allocated. Each line is either a colony
+/// name, for the ant after the previous line's (ant 0 on the first line), or
+/// `ANT_ID COLONY`; blank lines are skipped. Every bad line is reported at
+/// once. Ants the file leaves out are an error unless `partial`, which
+/// places them at random.
+pub fn read(path: &Path, graph: &MapGraph, num_ants: usize, partial: bool) -> Result<PlacementStrategy, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    let mut positions: Vec<Option<ColonyId>> = vec![None; num_ants];
+    // line each ant was placed on, for reporting a second placement
+    let mut placed_on = vec![0; num_ants];
+    let mut problems = Vec::new();
+    let mut next_ant = 0;
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (ant, colony) = match words[..] {
+            [] => continue,
+            [colony] => (Ok(next_ant), colony),
+            [ant, colony] => (ant.parse().map_err(|_| format!("'{}' isn't an ant ID", ant)), colony),
+            _ => {
+                problems.push(format!("line {}: expected COLONY or ANT_ID COLONY, got '{}'", line_number, line.trim()));
+                continue;
+            }
+        };
+        let ant = ant.and_then(|ant: usize| match placed_on.get(ant) {
+            None => Err(format!("ant {} doesn't exist, there are {} ants", ant, num_ants)),
+            Some(&0) => Ok(ant),
+            Some(&line) => Err(format!("ant {} is already placed on line {}", ant, line)),
+        });
+        let colony = graph.find_colony(colony).ok_or_else(|| format!("no colony named '{}' on the map", colony));
+
+        match (ant, colony) {
+            (Ok(ant), Ok(colony)) => {
+                positions[ant] = Some(colony);
+                placed_on[ant] = line_number;
+                next_ant = ant + 1;
+            }
+            (ant, colony) => {
+                if let Ok(ant) = ant {
+                    next_ant = ant + 1;
+                }
+                for reason in [ant.err(), colony.err()].into_iter().flatten() {
+                    problems.push(format!("line {}: {}", line_number, reason));
+                }
+            }
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(format!("{}:\n  {}", path.display(), problems.join("\n  ")));
+    }
+    let placed = positions.iter().flatten().count();
+    if placed < num_ants && !partial {
+        return Err(format!(
+            "{} places {} of {} ants, give every ant a colony or pass --placement-partial",
+            path.display(),
+            placed,
+            num_ants
+        ));
+    }
+    Ok(PlacementStrategy::Explicit(positions))
+}
@@ -0,0 +1,153 @@
+//! Python bindings, the `ant_mania` extension module `maturin` builds from
+//! `pyproject.toml`. `SimError`s come out as the exceptions below, or
+//! `OSError` for a map that can't be read.
+
+use std::sync::Arc;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyIOError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::config::AntConfig;
+use crate::error::SimError;
+use crate::graph::MapGraph;
+use crate::simulation::AntSimulation;
+
+create_exception!(ant_mania, MapError, PyValueError, "A map that can't be built");
+create_exception!(ant_mania, ConfigError, PyValueError, "Simulation settings that can't work");
+create_exception!(ant_mania, SnapshotError, PyRuntimeError, "A snapshot that can't be written, read or restored");
+create_exception!(ant_mania, ExportError, PyRuntimeError, "An export that failed");
+
+impl From<SimError> for PyErr {
+    fn from(err: SimError) -> PyErr {
+        let message = err.to_string();
+        match err {
+            SimError::Io(_) => PyIOError::new_err(message),
+            SimError::InvalidConfig(_) | SimError::NoLiveColoniesForPlacement => ConfigError::new_err(message),
+            SimError::Snapshot(_) => SnapshotError::new_err(message),
+            SimError::Export(_) => ExportError::new_err(message),
+            SimError::InvalidEdge { .. }
+            | SimError::MapTooLarge { .. }
+            | SimError::TooManyConnections { .. }
+            | SimError::UnsupportedMapVersion { .. }
+            | SimError::InvalidAlias { .. }
+            | SimError::UnknownColony(_)
+            | SimError::InvalidTunnel { .. }
+            | SimError::GeoJson(_) => MapError::new_err(message),
+        }
+    }
+}
+
+/// One simulation, stepped or run from Python
+#[pyclass(module = "ant_mania")]
+pub struct AntMania {
+    sim: AntSimulation,
+    /// Destructions `step` has already handed out
+    destructions_seen: usize,
+}
+
+#[pymethods]
+impl AntMania {
+    /// `map` is the text of a map if it has a line break in it, otherwise
+    /// the path of a map file; `seed` picks a random one if left out
+    #[new]
+    #[pyo3(signature = (map, ants, seed = None))]
+    fn new(map: &str, ants: usize, seed: Option<u64>) -> PyResult<Self> {
+        let graph = if map.contains('\n') { MapGraph::from_reader(map.as_bytes())? } else { MapGraph::from_file(map)? };
+        let mut sim = AntSimulation::new(Arc::new(graph), AntConfig { seed, ..AntConfig::new(ants) })?;
+        sim.record_destructions(true);
+        Ok(AntMania { sim, destructions_seen: 0 })
+    }
+
+    /// Run one iteration and return what it did: `iteration`, `moves`,
+    /// `ants_killed` and the names of the colonies it `destroyed`. None once
+    /// every ant is dead or out of moves.
+    fn step<'py>(&mut self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        if !self.running() {
+            return Ok(None);
+        }
+        let result = self.sim.run_iteration();
+        let tick = PyDict::new(py);
+        tick.set_item("iteration", result.iteration)?;
+        tick.set_item("moves", result.moves)?;
+        tick.set_item("ants_killed", result.ants_killed)?;
+        let destroyed = self.sim.destruction_log().unwrap_or_default()[self.destructions_seen..]
+            .iter()
+            .map(|&(_, colony)| self.sim.graph().colony_name(colony))
+            .collect::<Vec<_>>();
+        self.destructions_seen += destroyed.len();
+        tick.set_item("destroyed", destroyed)?;
+        Ok(Some(tick))
+    }
+
+    /// Run to the end and return the final report: the counts of
+    /// `SimulationStats` and every destruction as `(iteration, name)`
+    fn run<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        while self.running() {
+            self.sim.run_iteration();
+        }
+        self.destructions_seen = self.sim.destruction_log().unwrap_or_default().len();
+        let stats = self.sim.simulation_stats();
+        let report = PyDict::new(py);
+        report.set_item("iterations", stats.iterations)?;
+        report.set_item("alive_ants", stats.alive_ants)?;
+        report.set_item("active_colonies", stats.active_colonies)?;
+        report.set_item("total_colonies", stats.total_colonies)?;
+        report.set_item("total_moves", stats.total_moves_made)?;
+        let destructions: Vec<(u32, String)> =
+            self.sim.destructions().into_iter().map(|destruction| (destruction.iteration, destruction.name)).collect();
+        report.set_item("destructions", destructions)?;
+        Ok(report)
+    }
+
+    /// The colony ID each ant is at by ant ID, -1 for dead ones, ready for
+    /// `numpy.asarray`
+    fn positions(&self) -> Vec<i64> {
+        self.sim.ant_positions().map(|colony| colony.map_or(-1, |colony| colony as i64)).collect()
+    }
+
+    /// Everything about one ant as a dict with the fields of `AntView`,
+    /// None past the last ant
+    fn ant_view<'py>(&self, py: Python<'py>, ant: usize) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(view) = self.sim.ant_view(ant) else {
+            return Ok(None);
+        };
+        let dict = PyDict::new(py);
+        dict.set_item("ant", view.ant)?;
+        dict.set_item("alive", view.alive)?;
+        dict.set_item("trapped", view.trapped)?;
+        dict.set_item("colony", view.colony)?;
+        dict.set_item("colony_name", view.colony_name)?;
+        dict.set_item("moves", view.moves)?;
+        dict.set_item("moves_left", view.moves_left)?;
+        dict.set_item("faction", view.faction)?;
+        dict.set_item("died_in", view.died_in)?;
+        dict.set_item("path", view.path)?;
+        Ok(Some(dict))
+    }
+    
+    /// What's left of the map, in the map file format
+    fn remaining_world(&self) -> String {
+        let mut out = Vec::new();
+        self.sim.write_remaining_world(&mut out).expect("writing to a Vec can't fail");
+        String::from_utf8(out).expect("colony names are UTF-8")
+    }
+}
+
+impl AntMania {
+    fn running(&self) -> bool {
+        self.sim.should_continue() && self.sim.iteration() < self.sim.max_moves()
+    }
+}
+
+/// The `ant_mania` module: `AntMania` and the exceptions
+#[pymodule]
+pub fn ant_mania(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<AntMania>()?;
+    m.add("MapError", m.py().get_type::<MapError>())?;
+    m.add("ConfigError", m.py().get_type::<ConfigError>())?;
+    m.add("SnapshotError", m.py().get_type::<SnapshotError>())?;
+    m.add("ExportError", m.py().get_type::<ExportError>())?;
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use serde::Serialize;
+
+use crate::error::SimError;
+use crate::graph::{MapGraph, ParseWarning, ParseWarningKind};
+use crate::simulation::ColonyId;
+
+/// Index of a region in `Regions::names`
+pub type RegionId = usize;
+
+/// Region of every colony no region was given for, always region 0
+pub const UNASSIGNED_REGION: &str = "unassigned";
+
+/// Colonies grouped into named regions (boroughs, continents, ...), for
+/// reports broken down by region. Built against one map; colonies left out
+/// are in `UNASSIGNED_REGION`.
+#[derive(Debug, Clone)]
+pub struct Regions {
+    /// Region names, `UNASSIGNED_REGION` first, then in the order first seen
+    names: Vec<String>,
+    /// Region of each colony
+    colony_region: Vec<u32>,
+    warnings: Vec<ParseWarning>,
+}
+
+/// How the colonies of one region stand, see `AntSimulation::region_stats`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RegionStats {
+    pub name: String,
+    pub colonies: usize,
+    pub destroyed: usize,
+    pub surviving: usize,
+    /// Ants in the region now, survivors of a fight in a destroyed colony included
+    pub ants: usize,
+    /// 0-based iteration each colony of the region fell in, in order, for
+    /// colonies destroyed since the regions were given to the simulation
+    pub destructions: Vec<u32>,
+}
+
+impl Regions {
+    /// Every colony of a map `colonies` long unassigned
+    pub fn unassigned(colonies: usize) -> Self {
+        Regions { names: vec![UNASSIGNED_REGION.to_string()], colony_region: vec![0; colonies], warnings: Vec::new() }
+    }
+
+    /// Load a region file, see `from_reader`
+    pub fn from_file(filename: &str, graph: &MapGraph) -> Result<Self, SimError> {
+        let file = File::open(filename)?;
+        Self::from_reader(BufReader::new(file), graph)
+    }
+
+    /// Read `colony region` lines for `graph`, the region name being the
+    /// rest of the line after the colony (`Harlem Upper Manhattan`). Blank
+    /// lines are skipped. Colonies the map doesn't have, and a colony given
+    /// a second region (the first is kept), are left to `warnings`.
+    pub fn from_reader<R: BufRead>(reader: R, graph: &MapGraph) -> Result<Self, SimError> {
+        let mut regions = Regions::unassigned(graph.colony_count());
+        // line each colony got its region on
+        let mut assigned_on = vec![0; graph.colony_count()];
+        let mut region_ids = HashMap::from([(UNASSIGNED_REGION.to_string(), 0)]);
+        // find_colony is a scan, too slow for a line per colony
+        let colony_ids: HashMap<&str, ColonyId> = (0..graph.colony_count())
+            .map(|colony_id| (graph.colony_name(colony_id), colony_id))
+            .chain(graph.aliases.iter().map(|(alias, colony_id)| (alias.as_str(), *colony_id)))
+            .collect();
+        for (line_number, line) in reader.lines().enumerate() {
+            let line_number = line_number + 1;
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((colony, region)) = line.split_once(char::is_whitespace) else {
+                let reason = format!("line {}: expected 'colony region', got '{}'", line_number, line);
+                return Err(SimError::Io(io::Error::new(io::ErrorKind::InvalidData, reason)));
+            };
+            let Some(&colony_id) = colony_ids.get(colony) else {
+                let kind = ParseWarningKind::UnknownColony { colony: colony.to_string() };
+                regions.warnings.push(ParseWarning { line: line_number, kind });
+                continue;
+            };
+            if assigned_on[colony_id] != 0 {
+                let kind = ParseWarningKind::RepeatedRegion {
+                    colony: colony.to_string(),
+                    first_line: assigned_on[colony_id],
+                };
+                regions.warnings.push(ParseWarning { line: line_number, kind });
+                continue;
+            }
+            assigned_on[colony_id] = line_number;
+            let names = &mut regions.names;
+            let region_id = *region_ids.entry(region.trim().to_string()).or_insert_with_key(|region| {
+                names.push(region.clone());
+                names.len() - 1
+            });
+            regions.colony_region[colony_id] = region_id as u32;
+        }
+        Ok(regions)
+    }
+
+    /// Regions the map's `region` attributes give, none if no colony has one
+    pub fn from_attributes(graph: &MapGraph) -> Option<Self> {
+        let mut regions = Regions::unassigned(graph.colony_count());
+        let mut region_ids = HashMap::from([(UNASSIGNED_REGION, 0)]);
+        let mut any = false;
+        for colony_id in 0..graph.colony_count() {
+            let Some(region) = graph.colony_attr(colony_id, "region") else {
+                continue;
+            };
+            any = true;
+            let names = &mut regions.names;
+            let region_id = *region_ids.entry(region).or_insert_with(|| {
+                names.push(region.to_string());
+                names.len() - 1
+            });
+            regions.colony_region[colony_id] = region_id as u32;
+        }
+        any.then_some(regions)
+    }
+
+    /// Region names by `RegionId`, `UNASSIGNED_REGION` first
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn region_of(&self, colony: ColonyId) -> RegionId {
+        self.colony_region[colony] as RegionId
+    }
+
+    /// Number of colonies the regions were built for
+    pub fn colony_count(&self) -> usize {
+        self.colony_region.len()
+    }
+
+    /// Unknown and repeated colonies `from_reader` skipped, in line order
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+}
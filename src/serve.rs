@@ -0,0 +1,541 @@
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use serde_json::json;
+
+use ant_mania::{write_prometheus, AntConfig, AntId, AntSimulation, AntView, MapGraph, SimulationStats};
+
+use crate::{install_interrupt_handler, websocket};
+
+/// First argument that switches to server mode
+pub const SUBCOMMAND: &str = "serve";
+
+/// Destruction events kept for `/events`, older ones are dropped
+const EVENT_LIMIT: usize = 1000;
+
+/// Longest the published stats lag behind the simulation
+const PUBLISH_EVERY: Duration = Duration::from_millis(50);
+
+/// How often a paused or finished simulation, and an idle server, look for work
+const IDLE_POLL: Duration = Duration::from_millis(20);
+
+/// Longest a client gets to send its request, or to take a frame off `/ws`
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Frames waiting for a `/ws` client; one that falls this far behind is
+/// dropped rather than slowing the simulation down
+const CLIENT_QUEUE: usize = 256;
+
+/// Most colonies, and live ants, the `/ws` snapshot lists; past that the
+/// colony list is cut short and every Nth ant is taken
+const SNAPSHOT_LIMIT: usize = 10_000;
+
+/// Run a simulation and serve its state over HTTP
+///
+/// The simulation runs on a thread of its own. `GET /stats` gives its
+/// counters, `GET /events?since=N` the colonies destroyed from event N on,
+/// `GET /world` the map that's left, `GET /ant/ID` everything about one ant
+/// and `GET /metrics` the same Prometheus text `--metrics-file` writes. `/ws`
+/// is a WebSocket pushing a snapshot of the colonies and ants, then every
+/// destruction as it happens. `POST /control/pause`, `/control/resume` and
+/// `/control/stop` steer it; stop also shuts the server down. Everything
+/// answers with JSON.
+#[derive(Parser, Debug)]
+#[command(name = "ant-mania serve", bin_name = "ant-mania serve", version)]
+pub struct ServeCli {
+    /// Path to the map file
+    pub map: String,
+
+    /// Number of ants to place
+    pub num_ants: usize,
+
+    /// Random seed (random if not set)
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Maximum moves per ant
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_moves: Option<u32>,
+
+    /// Address to listen on, port 0 picks a free one
+    #[arg(long, value_name = "HOST:PORT", default_value = "127.0.0.1:8080")]
+    pub addr: String,
+
+    /// Wait for POST /control/resume before the first iteration
+    #[arg(long)]
+    pub paused: bool,
+
+    /// Wait this long between iterations, so a run lasts long enough to watch
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    pub delay_ms: u64,
+
+    /// Longest map `/world` sends, in bytes; it stops at the last whole line before
+    #[arg(long, value_name = "BYTES", default_value_t = 1 << 20)]
+    pub world_limit: usize,
+}
+
+/// Colonies destroyed in one iteration
+#[derive(Debug, Clone)]
+struct Event {
+    id: u64,
+    iteration: u32,
+    colonies: Vec<String>,
+    ants_killed: usize,
+}
+
+impl Event {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "id": self.id,
+            "iteration": self.iteration,
+            "colonies": self.colonies,
+            "ants_killed": self.ants_killed,
+        })
+    }
+}
+
+/// What the simulation thread last published, the only state readers lock
+#[derive(Default)]
+struct Published {
+    stats: SimulationStats,
+    finished: bool,
+    events: VecDeque<Event>,
+    /// ID the next event will get
+    next_event: u64,
+}
+
+/// What the server and the simulation thread share
+struct Shared {
+    published: Mutex<Published>,
+    paused: AtomicBool,
+    stop: AtomicBool,
+    world_limit: usize,
+    /// The map's path, for the metrics' `map` label
+    map: String,
+}
+
+impl Shared {
+    fn published(&self) -> std::sync::MutexGuard<'_, Published> {
+        self.published.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Questions only the simulation thread can answer, as it owns the simulation
+enum Request {
+    World(Sender<serde_json::Value>),
+    Ant(AntId, Sender<Option<AntView>>),
+    Metrics(Sender<String>),
+    /// Send a snapshot, then every frame from here on, down the queue
+    Subscribe(SyncSender<String>),
+}
+
+/// `ant-mania serve ...`, with `args` starting at `serve`
+pub fn main(args: &[OsString]) -> ExitCode {
+    let cli = match ServeCli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            return if err.use_stderr() { ExitCode::FAILURE } else { ExitCode::SUCCESS };
+        }
+    };
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: &ServeCli) -> Result<(), String> {
+    let graph = MapGraph::from_file(&cli.map).map_err(|err| format!("{}: {}", cli.map, err))?;
+    let mut config = AntConfig { seed: cli.seed, ..AntConfig::new(cli.num_ants) };
+    if let Some(max_moves) = cli.max_moves {
+        config.max_moves = max_moves;
+    }
+    let sim = AntSimulation::new(Arc::new(graph), config).map_err(|err| err.to_string())?;
+
+    let listener = TcpListener::bind(&cli.addr).map_err(|err| format!("can't listen on {}: {}", cli.addr, err))?;
+    let addr = listener.local_addr().map_err(|err| err.to_string())?;
+    // polled, so Ctrl-C and /control/stop are seen between connections
+    listener.set_nonblocking(true).map_err(|err| err.to_string())?;
+    println!("listening on http://{}", addr);
+    let _ = io::stdout().flush();
+
+    let shared = Shared {
+        published: Mutex::new(Published { stats: sim.simulation_stats(), ..Published::default() }),
+        paused: AtomicBool::new(cli.paused),
+        stop: AtomicBool::new(false),
+        world_limit: cli.world_limit,
+        map: cli.map.clone(),
+    };
+    let (requests, incoming) = mpsc::channel();
+    let interrupted = install_interrupt_handler();
+    let delay = Duration::from_millis(cli.delay_ms);
+    std::thread::scope(|scope| {
+        scope.spawn(|| simulate(sim, &shared, incoming, delay));
+        while !shared.stop.load(Ordering::Relaxed) && !interrupted.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let (shared, requests) = (&shared, requests.clone());
+                    scope.spawn(move || {
+                        // a client that hangs up early is its own problem
+                        let _ = handle(stream, shared, &requests);
+                    });
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => std::thread::sleep(IDLE_POLL),
+                Err(err) => crate::warn(format!("accepting a connection failed: {}", err)),
+            }
+        }
+        shared.stop.store(true, Ordering::Relaxed);
+    });
+    Ok(())
+}
+
+/// The simulation thread: iterate unless paused or done, publishing as it goes
+fn simulate(mut sim: AntSimulation, shared: &Shared, requests: Receiver<Request>, delay: Duration) {
+    let mut destroyed: Vec<bool> = sim.iter().map(|colony| colony.destroyed).collect();
+    let mut last_published = Instant::now();
+    let mut unpublished = false;
+    let mut published_finished = false;
+    let mut subscribers = Vec::new();
+    // time spent iterating, pauses and --delay-ms left out
+    let mut running_time = Duration::ZERO;
+    loop {
+        while let Ok(request) = requests.try_recv() {
+            answer(&sim, shared, request, &mut subscribers, running_time);
+        }
+        if shared.stop.load(Ordering::Relaxed) {
+            return;
+        }
+        let running = sim.should_continue() && sim.iteration() < sim.max_moves();
+        if !running || shared.paused.load(Ordering::Relaxed) {
+            if unpublished || (!running && !published_finished) {
+                publish(&sim, shared, !running);
+                unpublished = false;
+                if !running && !published_finished {
+                    let (alive_ants, active_colonies, _) = sim.stats();
+                    let frame = json!({
+                        "type": "finished",
+                        "iteration": sim.iteration(),
+                        "alive_ants": alive_ants,
+                        "active_colonies": active_colonies,
+                    });
+                    broadcast(&mut subscribers, frame);
+                }
+                published_finished = !running;
+            }
+            match requests.recv_timeout(IDLE_POLL) {
+                Ok(request) => answer(&sim, shared, request, &mut subscribers, running_time),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+            continue;
+        }
+
+        let started = Instant::now();
+        let result = sim.run_iteration();
+        running_time += started.elapsed();
+        unpublished = true;
+        if result.colonies_destroyed > 0 {
+            let mut colonies = Vec::with_capacity(result.colonies_destroyed);
+            for colony in sim.iter() {
+                if colony.destroyed && !destroyed[colony.id] {
+                    destroyed[colony.id] = true;
+                    colonies.push(colony.name.to_string());
+                }
+            }
+            let mut published = shared.published();
+            let (id, iteration, ants_killed) = (published.next_event, result.iteration, result.ants_killed);
+            let event = Event { id, iteration, colonies, ants_killed };
+            if !subscribers.is_empty() {
+                let mut frame = event.to_json();
+                frame["type"] = "destroyed".into();
+                broadcast(&mut subscribers, frame);
+            }
+            published.next_event += 1;
+            if published.events.len() == EVENT_LIMIT {
+                published.events.pop_front();
+            }
+            published.events.push_back(event);
+        }
+        if last_published.elapsed() >= PUBLISH_EVERY {
+            publish(&sim, shared, false);
+            last_published = Instant::now();
+            unpublished = false;
+        }
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+    }
+}
+
+fn publish(sim: &AntSimulation, shared: &Shared, finished: bool) {
+    let stats = sim.simulation_stats();
+    let mut published = shared.published();
+    published.stats = stats;
+    published.finished = finished;
+}
+
+/// Queue `frame` for every `/ws` client, dropping those whose queue is full
+/// or who have gone
+fn broadcast(subscribers: &mut Vec<SyncSender<String>>, frame: serde_json::Value) {
+    let frame = frame.to_string();
+    subscribers.retain(|client| client.try_send(frame.clone()).is_ok());
+}
+
+fn answer(
+    sim: &AntSimulation,
+    shared: &Shared,
+    request: Request,
+    subscribers: &mut Vec<SyncSender<String>>,
+    running_time: Duration,
+) {
+    match request {
+        Request::Metrics(reply) => {
+            let mut text = Vec::new();
+            write_prometheus(&mut text, sim, &shared.map, running_time).expect("writing to a Vec can't fail");
+            let _ = reply.send(String::from_utf8_lossy(&text).into_owned());
+        }
+        Request::World(reply) => {
+            let mut map = Vec::new();
+            sim.write_map(&mut map).expect("writing to a Vec can't fail");
+            let truncated = map.len() > shared.world_limit;
+            if truncated {
+                let end = map[..shared.world_limit].iter().rposition(|&byte| byte == b'\n').map_or(0, |i| i + 1);
+                map.truncate(end);
+            }
+            let (alive_ants, active_colonies, total_colonies) = sim.stats();
+            let _ = reply.send(json!({
+                "iteration": sim.iteration(),
+                "alive_ants": alive_ants,
+                "active_colonies": active_colonies,
+                "total_colonies": total_colonies,
+                "truncated": truncated,
+                "map": String::from_utf8_lossy(&map),
+            }));
+        }
+        Request::Ant(ant, reply) => {
+            let _ = reply.send(sim.ant_view(ant));
+        }
+        Request::Subscribe(client) => {
+            // the queue is empty, so the snapshot always fits
+            if client.try_send(snapshot(sim).to_string()).is_ok() {
+                subscribers.push(client);
+            }
+        }
+    }
+}
+
+/// The first frame a `/ws` client gets: the colonies and where the live ants
+/// are, both within `SNAPSHOT_LIMIT`
+fn snapshot(sim: &AntSimulation) -> serde_json::Value {
+    let (alive_ants, active_colonies, total_colonies) = sim.stats();
+    let colonies: Vec<_> = (sim.iter().take(SNAPSHOT_LIMIT))
+        .map(|colony| json!({ "id": colony.id, "name": colony.name, "destroyed": colony.destroyed }))
+        .collect();
+    let every = alive_ants.div_ceil(SNAPSHOT_LIMIT).max(1);
+    let mut ants = Vec::with_capacity(alive_ants.min(SNAPSHOT_LIMIT));
+    let mut seen = 0;
+    for colony in sim.iter() {
+        for &ant in sim.ants_at_slice(colony.id) {
+            if seen % every == 0 {
+                ants.push([ant, colony.id]);
+            }
+            seen += 1;
+        }
+    }
+    json!({
+        "type": "snapshot",
+        "iteration": sim.iteration(),
+        "alive_ants": alive_ants,
+        "active_colonies": active_colonies,
+        "total_colonies": total_colonies,
+        "colonies": colonies,
+        "colonies_truncated": total_colonies > SNAPSHOT_LIMIT,
+        "ants": ants,
+        "ants_sampled": every > 1,
+    })
+}
+
+/// Read one request off `stream` and answer it, then hang up
+fn handle(stream: TcpStream, shared: &Shared, requests: &Sender<Request>) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    let (mut upgrade, mut websocket_key) = (false, None);
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        let (name, value) = (name.trim().to_ascii_lowercase(), value.trim());
+        match name.as_str() {
+            "content-length" => content_length = value.parse().unwrap_or(0),
+            "upgrade" => upgrade = value.eq_ignore_ascii_case("websocket"),
+            "sec-websocket-key" => websocket_key = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    // nothing takes a body, but leaving one unread can turn the close into a reset
+    io::copy(&mut reader.take(content_length), &mut io::sink())?;
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (status, body) = match (path, upgrade, websocket_key) {
+        ("/ws", true, Some(key)) if method == "GET" => return stream_events(&stream, &key, shared, requests),
+        ("/ws", ..) => ("400 Bad Request", json!({ "error": "/ws takes a WebSocket handshake" })),
+        ("/metrics", ..) if method == "GET" => {
+            let (reply, metrics) = mpsc::channel();
+            let metrics = requests.send(Request::Metrics(reply)).ok().and_then(|()| metrics.recv().ok());
+            return match metrics {
+                Some(text) => respond(&stream, "200 OK", "text/plain; version=0.0.4", &text),
+                None => respond(&stream, "503 Service Unavailable", "text/plain", "the simulation has stopped\n"),
+            };
+        }
+        _ => route(method, path, query, shared, requests),
+    };
+    respond(&stream, status, "application/json", &body.to_string())
+}
+
+fn respond(mut stream: &TcpStream, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Answer a `/ws` handshake, then pass the simulation's frames on until the
+/// server stops or the client falls behind
+fn stream_events(mut stream: &TcpStream, key: &str, shared: &Shared, requests: &Sender<Request>) -> io::Result<()> {
+    let (client, frames) = mpsc::sync_channel(CLIENT_QUEUE);
+    websocket::write_handshake(&mut stream, key)?;
+    if requests.send(Request::Subscribe(client)).is_err() {
+        return websocket::write_close(&mut stream, websocket::GOING_AWAY, "the simulation has stopped");
+    }
+    loop {
+        match frames.recv_timeout(IDLE_POLL) {
+            Ok(frame) => websocket::write_text(&mut stream, &frame)?,
+            Err(RecvTimeoutError::Timeout) if !shared.stop.load(Ordering::Relaxed) => {}
+            Err(RecvTimeoutError::Timeout) => {
+                return websocket::write_close(&mut stream, websocket::GOING_AWAY, "server stopping");
+            }
+            // the simulation thread lets go of clients only when it ends or they lag
+            Err(RecvTimeoutError::Disconnected) if shared.stop.load(Ordering::Relaxed) => {
+                return websocket::write_close(&mut stream, websocket::GOING_AWAY, "server stopping");
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return websocket::write_close(&mut stream, websocket::POLICY_VIOLATION, "fell too far behind");
+            }
+        }
+    }
+}
+
+fn route(
+    method: &str,
+    path: &str,
+    query: &str,
+    shared: &Shared,
+    requests: &Sender<Request>,
+) -> (&'static str, serde_json::Value) {
+    let expected = match path {
+        "/stats" | "/events" | "/world" | "/metrics" => "GET",
+        _ if path.starts_with("/ant/") => "GET",
+        "/control/pause" | "/control/resume" | "/control/stop" => "POST",
+        _ => return ("404 Not Found", json!({ "error": format!("no such endpoint {}", path) })),
+    };
+    if method != expected {
+        return ("405 Method Not Allowed", json!({ "error": format!("{} takes {}", path, expected) }));
+    }
+
+    match path {
+        "/stats" => {
+            let published = shared.published();
+            let stats = &published.stats;
+            let body = json!({
+                "iteration": stats.iterations,
+                "alive_ants": stats.alive_ants,
+                "active_colonies": stats.active_colonies,
+                "total_colonies": stats.total_colonies,
+                "total_moves_made": stats.total_moves_made,
+                "moves_per_iteration": stats.moves_per_iteration,
+                "paused": shared.paused.load(Ordering::Relaxed),
+                "finished": published.finished,
+            });
+            ("200 OK", body)
+        }
+        "/events" => {
+            let since = query.split('&').find_map(|pair| pair.strip_prefix("since="));
+            let since = match since.map(str::parse::<u64>) {
+                None => 0,
+                Some(Ok(since)) => since,
+                Some(Err(_)) => return ("400 Bad Request", json!({ "error": "since must be an event number" })),
+            };
+            let published = shared.published();
+            let events: Vec<_> = (published.events.iter())
+                .filter(|event| event.id >= since)
+                .map(Event::to_json)
+                .collect();
+            // `oldest` past `since` means events were dropped in between
+            let oldest = published.events.front().map_or(published.next_event, |event| event.id);
+            ("200 OK", json!({ "events": events, "next": published.next_event, "oldest": oldest }))
+        }
+        "/world" => {
+            let (reply, world) = mpsc::channel();
+            if requests.send(Request::World(reply)).is_err() {
+                return ("503 Service Unavailable", json!({ "error": "the simulation has stopped" }));
+            }
+            match world.recv() {
+                Ok(world) => ("200 OK", world),
+                Err(_) => ("503 Service Unavailable", json!({ "error": "the simulation has stopped" })),
+            }
+        }
+        _ if path.starts_with("/ant/") => {
+            let Ok(ant) = path["/ant/".len()..].parse::<AntId>() else {
+                return ("400 Bad Request", json!({ "error": "expected /ant/ID with a numeric ant ID" }));
+            };
+            let (reply, view) = mpsc::channel();
+            if requests.send(Request::Ant(ant, reply)).is_err() {
+                return ("503 Service Unavailable", json!({ "error": "the simulation has stopped" }));
+            }
+            match view.recv() {
+                Ok(Some(view)) => ("200 OK", json!(view)),
+                Ok(None) => ("404 Not Found", json!({ "error": format!("no ant {}", ant) })),
+                Err(_) => ("503 Service Unavailable", json!({ "error": "the simulation has stopped" })),
+            }
+        }
+        _ => {
+            match path {
+                "/control/pause" => shared.paused.store(true, Ordering::Relaxed),
+                "/control/resume" => shared.paused.store(false, Ordering::Relaxed),
+                _ => shared.stop.store(true, Ordering::Relaxed),
+            }
+            let body = json!({
+                "paused": shared.paused.load(Ordering::Relaxed),
+                "stopped": shared.stop.load(Ordering::Relaxed),
+            });
+            ("200 OK", body)
+        }
+    }
+}
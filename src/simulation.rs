@@ -0,0 +1,2577 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::mem::size_of;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::complexity::ComplexityEstimate;
+use crate::components::Components;
+use crate::config::{AntConfig, CollisionPolicy, FightTrigger, PlacementStrategy};
+use crate::error::SimError;
+use crate::graph::{ColonyIndex, Direction, MapGraph, MapOrder};
+use crate::graph_stats::GraphStats;
+use crate::memory::MemoryBreakdown;
+use crate::min_cut;
+use crate::names::ColonyNames;
+use crate::options::{DeadEndBehavior, DestructionMode, MovementMode, SimOptions};
+use crate::perf::PerfCounters;
+use crate::regions::{RegionId, RegionStats, Regions, UNASSIGNED_REGION};
+use crate::snapshot::{MapSnapshot, Snapshot};
+use crate::stats::MoveStats;
+use crate::summary::{
+    AntRecord, AntView, ColonySummary, DeathCause, Deaths, Destruction, MarathonAnts, Preview, SimEvent, Summary,
+    Termination, Winner, BUSIEST_COLONIES, RECENT_DESTRUCTIONS,
+};
+
+pub const MAX_MOVES: u32 = 10000;
+
+/// Bump a hot-loop counter; compiles to nothing without perf counters
+macro_rules! perf_count {
+    ($sim:expr, $counter:ident) => {
+        perf_count!($sim, $counter, 1)
+    };
+    ($sim:expr, $counter:ident, $amount:expr) => {
+        #[cfg(any(feature = "perf-counters", debug_assertions))]
+        {
+            $sim.perf.$counter += $amount as u64;
+        }
+    };
+}
+
+/// Represents a colony ID (0-based index)
+pub type ColonyId = usize;
+pub type AntId = usize;
+
+/// Slot value for ants that aren't in any colony list
+const NO_SLOT: u32 = u32::MAX;
+
+/// Planned move of an ant with nowhere to go, see `plan_moves`
+#[cfg(feature = "parallel")]
+const NO_MOVE: u32 = u32::MAX;
+
+/// `died_in` of an ant that hasn't died
+const STILL_ALIVE: u32 = u32::MAX;
+
+/// Room reserved up front in each colony's ant list. Two ants meeting destroy
+/// the colony, so lists rarely grow past this once the ants start moving.
+pub(crate) const COLONY_LIST_CAPACITY: usize = 4;
+
+/// What happened during one iteration
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IterationResult {
+    /// 0-based index of the iteration
+    pub iteration: u32,
+    /// Ants that actually moved
+    pub moves: usize,
+    /// Colonies destroyed by fights, cascades included
+    pub colonies_destroyed: usize,
+    pub ants_killed: usize,
+}
+
+/// How a call to `run_interruptible` ended
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunStatus {
+    pub iterations: u32,
+    /// Stopped by the interrupt flag rather than running out of ants or moves
+    pub interrupted: bool,
+    /// Stopped because the deadline passed
+    pub timed_out: bool,
+    /// Stopped because `on_iteration` said so, e.g. on a stop condition
+    pub stopped: bool,
+}
+
+/// Snapshot of a simulation's counters
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulationStats {
+    pub alive_ants: usize,
+    pub active_colonies: usize,
+    pub total_colonies: usize,
+    pub iterations: u32,
+    pub total_moves_made: u64,
+    /// Average moves per iteration run so far, 0 before the first one
+    pub moves_per_iteration: f64,
+    /// Most ants ever at each colony at once, by colony ID
+    pub peak_ant_counts: Vec<u32>,
+}
+
+/// One colony as the simulation stands, yielded by iterating `&AntSimulation`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColonyState<'a> {
+    pub id: ColonyId,
+    pub name: &'a str,
+    pub destroyed: bool,
+    /// Ants there now, survivors of a fight included for destroyed colonies
+    pub ant_count: usize,
+    /// Most ants that were ever there at once
+    pub peak_ant_count: u32,
+}
+
+/// Iterator over every colony of a simulation, destroyed ones included
+#[derive(Clone)]
+pub struct SimColonyIter<'a> {
+    colony: ColonyId,
+    sim: &'a AntSimulation,
+}
+
+impl<'a> Iterator for SimColonyIter<'a> {
+    type Item = ColonyState<'a>;
+    
+    fn next(&mut self) -> Option<ColonyState<'a>> {
+        let sim = self.sim;
+        let id = self.colony;
+        if id >= sim.total_colonies {
+            return None;
+        }
+        self.colony += 1;
+        Some(ColonyState {
+            id,
+            name: sim.graph.colony_name(id),
+            destroyed: sim.destroyed[id],
+            ant_count: sim.ant_count[id] as usize,
+            peak_ant_count: sim.colony_peak_ant_count[id],
+        })
+    }
+    
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.sim.total_colonies - self.colony;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for SimColonyIter<'_> {}
+
+impl<'a> IntoIterator for &'a AntSimulation {
+    type Item = ColonyState<'a>;
+    type IntoIter = SimColonyIter<'a>;
+    
+    fn into_iter(self) -> SimColonyIter<'a> {
+        SimColonyIter { colony: 0, sim: self }
+    }
+}
+
+/// Main simulation state 
+pub struct AntSimulation {
+    /// Number of ants currently at each colony
+    ant_count: Vec<u16>,  
+    /// Most ants ever at each colony at once
+    colony_peak_ant_count: Vec<u32>,
+    
+    /// Is a colony destroyed
+    destroyed: Vec<bool>,
+    /// Tunnels into each colony from live colonies, kept up to date as
+    /// colonies fall. u32 as a hub in a big imported graph can have more
+    /// than u16::MAX tunnels in.
+    colony_in_degree: Vec<u32>,
+    /// Live colonies, in no particular order once any has fallen
+    alive_colony_ids: Vec<ColonyId>,
+    /// Index of each live colony inside alive_colony_ids, NO_SLOT once destroyed
+    alive_colony_index: Vec<u32>,
+    
+    /// Colony names and connections, shared between runs on the same map
+    graph: Arc<MapGraph>,
+    
+    /// Ant Tracking
+    ant_position: Vec<ColonyIndex>,
+    move_count: Vec<u32>,
+    ant_alive_bits: Vec<u64>,  // one bit per ant, 64 ants per word
+    ants_at_colony: Vec<Vec<AntId>>,
+    /// Index of each ant inside its colony's ants_at_colony list
+    ant_slot: Vec<u32>,
+    /// Order ants are processed in each iteration
+    ant_order: Vec<AntId>,
+    /// `ant_order` without the dead, what an iteration walks; ants killed
+    /// since the last iteration started are still in it while
+    /// `dead_in_alive_order` is set
+    alive_ant_order: Vec<AntId>,
+    dead_in_alive_order: bool,
+    components: Option<Components>,
+    /// Set when ants changed component outside a fight, so the next
+    /// iteration rebuilds the components
+    components_stale: bool,
+    /// Faction of each ant, empty unless faction mode is on
+    ant_faction: Vec<u8>,
+    faction_alive_counts: Vec<usize>,
+    /// Failed move attempts per ant, empty unless dead-end ants die
+    failed_moves: Vec<u32>,
+    /// The colony `PlacementStrategy::Single` put every ant on, until the
+    /// first iteration has them fight there
+    start_crowd: Option<ColonyId>,
+    /// Colony each ant was at before its last move, NO_SLOT before the
+    /// first one; empty unless oscillations are detected
+    previous_position: Vec<ColonyIndex>,
+    /// Moves in a row each ant made back to the colony before the last
+    oscillation_streak: Vec<u16>,
+    /// Ants whose streak is over `oscillation_after`
+    oscillating_ants: usize,
+    
+    /// Scratch buffers reused by every run_iteration, sized up front by
+    /// `scratch_buffers`
+    move_buffer: Vec<ColonyId>,
+    collision_check_buffer: Vec<ColonyId>,
+    /// Where each ant of `alive_ant_order` moves this iteration, for
+    /// `MovementMode::Parallel`
+    #[cfg(feature = "parallel")]
+    planned_moves: Vec<u32>,
+    /// Colonies destroyed by the last cascade, empty until one happens
+    cascade_buffer: Vec<ColonyId>,
+    /// Ants killed by the last fight
+    killed_ants: Vec<AntId>,
+    /// First fight since `watch_first_fight` was set, and the ants it killed
+    first_fight: Option<(ColonyId, Vec<AntId>)>,
+    watch_first_fight: bool,
+    /// Every fight of the last iteration, kept while `record_fights` is on
+    fights: Option<Vec<(ColonyId, Vec<AntId>)>>,
+    /// Everything that happened in the last iteration, kept while
+    /// `record_events` is on
+    events: Option<Vec<SimEvent>>,
+    /// (iteration, colony) of the last `RECENT_DESTRUCTIONS` colonies destroyed
+    recent_destructions: VecDeque<(u32, ColonyId)>,
+    /// (iteration, colony) of every destruction, kept while
+    /// `record_destructions` is on
+    destruction_log: Option<Vec<(u32, ColonyId)>>,
+    /// Ants that moved into each colony, kept while `record_visits` is on
+    visits: Option<Vec<u32>>,
+    /// Deaths by iteration and cause, only for iterations someone died in,
+    /// kept while `record_deaths` is on
+    deaths: Option<Vec<Deaths>>,
+    /// Iteration each ant died in, `STILL_ALIVE` for ants that haven't since
+    /// `record_deaths`, empty while it's off
+    died_in: Vec<u32>,
+    /// Fights each ant was in, empty while `record_fight_counts` is off
+    fights_by_ant: Vec<u32>,
+    /// Colonies each ant picked by `trace_ants` has been at, in order
+    traces: Vec<Vec<ColonyId>>,
+    /// Index into `traces` of each ant, NO_SLOT for untraced ones; empty
+    /// while nothing is traced
+    trace_slot: Vec<u32>,
+    regions: Option<Regions>,
+    /// Iteration of every destruction in each region since `with_regions`
+    region_destructions: Vec<Vec<u32>>,
+    
+    options: SimOptions,
+    max_moves: u32,
+    collision_threshold: u16,
+    collision_policy: CollisionPolicy,
+    fight_trigger: FightTrigger,
+    
+    /// Seed everything random in the run derives from
+    seed: u64,
+    rng: fastrand::Rng,
+    /// Seed for the per-ant move streams in simultaneous mode
+    stream_seed: u64,
+    iteration: u32,
+    /// Moves made by all ants so far, kept up to date by move_ant
+    total_moves_made: u64,
+    
+    #[cfg(any(feature = "perf-counters", debug_assertions))]
+    perf: PerfCounters,
+    
+    // Metadata
+    total_colonies: usize,
+    colonies_destroyed: usize,
+    total_ants: usize,
+    alive_ants: usize,
+    active_ants_under_max_moves: usize, // counter to avoid O(n) scan
+}
+
+impl AntSimulation {
+    /// Create a new simulation from a map file
+    pub fn from_file(filename: &str, num_ants: usize) -> Result<Self, SimError> {
+        let graph = MapGraph::from_file(filename)?;
+        Self::new(Arc::new(graph), AntConfig::new(num_ants))
+    }
+    
+    /// Create a new simulation from `(source, target)` edges between colonies `0..n`,
+    /// named `Colony0` to `Colony{n-1}`
+    pub fn from_adjacency_list_raw(edges: &[(usize, usize)], n: usize, num_ants: usize) -> Result<Self, SimError> {
+        let graph = MapGraph::from_adjacency_list_raw(edges, n)?;
+        Self::new(Arc::new(graph), AntConfig::new(num_ants))
+    }
+    
+    /// Create a new simulation from colony names and their `(neighbor,
+    /// direction)` tunnels, see `MapGraph::from_dict`
+    pub fn from_dict(adjacency: HashMap<String, Vec<(String, Direction)>>, num_ants: usize) -> Result<Self, SimError> {
+        let graph = MapGraph::from_dict(adjacency)?;
+        Self::new(Arc::new(graph), AntConfig::new(num_ants))
+    }
+    
+    /// Check that `config` can run on `graph`, without allocating any of
+    /// the simulation. `new` runs the same checks.
+    pub fn validate(graph: &MapGraph, config: &AntConfig) -> Result<(), SimError> {
+        if config.collision_threshold < 2 {
+            return Err(SimError::InvalidConfig("collision_threshold must be at least 2".to_string()));
+        }
+        let total_colonies = graph.colony_count();
+        let num_ants = config.num_ants;
+        if num_ants > 0 && total_colonies == 0 {
+            return Err(SimError::InvalidConfig("can't place ants on a map with no colonies".to_string()));
+        }
+        check_placement(&config.placement_strategy, num_ants, total_colonies)?;
+        match config.collision_policy {
+            CollisionPolicy::KillFirst { count: 0 }
+            | CollisionPolicy::KillRandom { count: 0 }
+            | CollisionPolicy::KillOldest { count: 0 } => {
+                Err(SimError::InvalidConfig("a fight has to kill at least one ant".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// Set up a simulation on a (possibly shared) map
+    pub fn new(graph: Arc<MapGraph>, config: AntConfig) -> Result<Self, SimError> {
+        Self::validate(&graph, &config)?;
+        let total_colonies = graph.colony_count();
+        let num_ants = config.num_ants;
+        
+        let seed = config.seed.unwrap_or_else(|| fastrand::u64(..));
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let stream_seed = rng.u64(..);
+        let (move_buffer, collision_check_buffer) = scratch_buffers(&graph, num_ants, config.collision_threshold);
+        let (alive_colony_ids, alive_colony_index) = alive_colony_list(&vec![false; total_colonies]);
+        
+        // Initialize simulation state
+        let mut sim = AntSimulation {
+            ant_count: vec![0; total_colonies],
+            colony_peak_ant_count: vec![0; total_colonies],
+            destroyed: vec![false; total_colonies],
+            colony_in_degree: live_in_degrees(&graph, &vec![false; total_colonies]),
+            alive_colony_ids,
+            alive_colony_index,
+            graph,
+            
+            ant_position: vec![0; num_ants],
+            move_count: vec![0; num_ants],
+            ant_alive_bits: alive_bits(num_ants),
+            ants_at_colony: (0..total_colonies).map(|_| Vec::with_capacity(COLONY_LIST_CAPACITY)).collect(),
+            ant_slot: vec![NO_SLOT; num_ants],
+            ant_order: (0..num_ants).collect(),
+            alive_ant_order: (0..num_ants).collect(),
+            dead_in_alive_order: true,
+            components: None,
+            components_stale: false,
+            ant_faction: Vec::new(),
+            faction_alive_counts: Vec::new(),
+            failed_moves: Vec::new(),
+            start_crowd: None,
+            previous_position: Vec::new(),
+            oscillation_streak: Vec::new(),
+            oscillating_ants: 0,
+            
+            move_buffer,
+            collision_check_buffer,
+            #[cfg(feature = "parallel")]
+            planned_moves: Vec::new(),
+            cascade_buffer: Vec::new(),
+            killed_ants: Vec::with_capacity(config.collision_threshold as usize),
+            first_fight: None,
+            watch_first_fight: false,
+            fights: None,
+            events: None,
+            recent_destructions: VecDeque::with_capacity(RECENT_DESTRUCTIONS),
+            destruction_log: None,
+            visits: None,
+            deaths: None,
+            died_in: Vec::new(),
+            fights_by_ant: Vec::new(),
+            traces: Vec::new(),
+            trace_slot: Vec::new(),
+            regions: None,
+            region_destructions: Vec::new(),
+            
+            options: SimOptions::default(),
+            max_moves: config.max_moves,
+            collision_threshold: config.collision_threshold,
+            collision_policy: config.collision_policy,
+            fight_trigger: config.fight_trigger,
+            
+            seed,
+            rng,
+            stream_seed,
+            iteration: 0,
+            total_moves_made: 0,
+            
+            #[cfg(any(feature = "perf-counters", debug_assertions))]
+            perf: PerfCounters::default(),
+            
+            total_colonies,
+            colonies_destroyed: 0,
+            total_ants: num_ants,
+            alive_ants: num_ants,
+            active_ants_under_max_moves: if config.max_moves > 0 { num_ants } else { 0 },
+        };
+        
+        // Place ants at random colonies
+        sim.initialize_ants(config.placement_strategy)?;
+        for (peak, &count) in sim.colony_peak_ant_count.iter_mut().zip(&sim.ant_count) {
+            *peak = count as u32;
+        }
+        
+        Ok(sim)
+    }
+    
+    /// Copy out everything the simulation needs to carry on from here, see
+    /// `Snapshot`. Only meaningful between iterations.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            map: MapSnapshot::of(&self.graph),
+            
+            ant_count: self.ant_count.clone(),
+            colony_peak_ant_count: self.colony_peak_ant_count.clone(),
+            destroyed: self.destroyed.clone(),
+            
+            ant_position: self.ant_position.clone(),
+            move_count: self.move_count.clone(),
+            ant_alive_bits: self.ant_alive_bits.clone(),
+            ants_at_colony: self.ants_at_colony.clone(),
+            ant_slot: self.ant_slot.clone(),
+            ant_order: self.ant_order.clone(),
+            ant_faction: self.ant_faction.clone(),
+            faction_alive_counts: self.faction_alive_counts.clone(),
+            failed_moves: self.failed_moves.clone(),
+            start_crowd: self.start_crowd,
+            
+            options: self.options.clone(),
+            max_moves: self.max_moves,
+            collision_threshold: self.collision_threshold,
+            collision_policy: self.collision_policy,
+            fight_trigger: self.fight_trigger,
+            
+            seed: self.seed,
+            rng_state: self.rng.get_seed(),
+            stream_seed: self.stream_seed,
+            iteration: self.iteration,
+            total_moves_made: self.total_moves_made,
+            
+            total_ants: self.total_ants,
+            alive_ants: self.alive_ants,
+            active_ants_under_max_moves: self.active_ants_under_max_moves,
+        }
+    }
+    
+    /// Pick a simulation back up from a snapshot; running it on gives the
+    /// same world as the run the snapshot was taken from would have
+    pub fn from_snapshot(snapshot: Snapshot) -> Result<Self, SimError> {
+        let graph = snapshot.map.into_graph()?;
+        let total_colonies = graph.colony_count();
+        let total_ants = snapshot.total_ants;
+        
+        let colony_lengths_match = snapshot.ant_count.len() == total_colonies
+            && snapshot.colony_peak_ant_count.len() == total_colonies
+            && snapshot.destroyed.len() == total_colonies
+            && snapshot.ants_at_colony.len() == total_colonies;
+        let ant_lengths_match = snapshot.ant_position.len() == total_ants
+            && snapshot.move_count.len() == total_ants
+            && snapshot.ant_alive_bits.len() == alive_bits(total_ants).len()
+            && snapshot.ant_slot.len() == total_ants
+            && snapshot.ant_order.len() == total_ants
+            && (snapshot.ant_faction.is_empty() || snapshot.ant_faction.len() == total_ants)
+            && (snapshot.failed_moves.is_empty() || snapshot.failed_moves.len() == total_ants);
+        let ants_in_range = snapshot.ant_position.iter().all(|&colony| (colony as usize) < total_colonies)
+            && snapshot.ant_order.iter().all(|&ant| ant < total_ants)
+            && snapshot.ants_at_colony.iter().flatten().all(|&ant| ant < total_ants)
+            && (snapshot.ant_faction.iter()).all(|&faction| (faction as usize) < snapshot.faction_alive_counts.len())
+            && snapshot.start_crowd.is_none_or(|colony| colony < total_colonies);
+        if !colony_lengths_match || !ant_lengths_match || !ants_in_range || snapshot.alive_ants > total_ants {
+            return Err(SimError::Snapshot("simulation state in snapshot is inconsistent".to_string()));
+        }
+        let (move_buffer, collision_check_buffer) = scratch_buffers(&graph, total_ants, snapshot.collision_threshold);
+        let (alive_colony_ids, alive_colony_index) = alive_colony_list(&snapshot.destroyed);
+        
+        let mut sim = AntSimulation {
+            ant_count: snapshot.ant_count,
+            colony_peak_ant_count: snapshot.colony_peak_ant_count,
+            colony_in_degree: live_in_degrees(&graph, &snapshot.destroyed),
+            alive_colony_ids,
+            alive_colony_index,
+            destroyed: snapshot.destroyed,
+            graph: Arc::new(graph),
+            
+            ant_position: snapshot.ant_position,
+            move_count: snapshot.move_count,
+            ant_alive_bits: snapshot.ant_alive_bits,
+            ants_at_colony: snapshot.ants_at_colony,
+            ant_slot: snapshot.ant_slot,
+            alive_ant_order: snapshot.ant_order.clone(),
+            dead_in_alive_order: true,
+            ant_order: snapshot.ant_order,
+            components: None,
+            components_stale: false,
+            ant_faction: snapshot.ant_faction,
+            faction_alive_counts: snapshot.faction_alive_counts,
+            failed_moves: snapshot.failed_moves,
+            start_crowd: snapshot.start_crowd,
+            previous_position: Vec::new(),
+            oscillation_streak: Vec::new(),
+            oscillating_ants: 0,
+            
+            move_buffer,
+            collision_check_buffer,
+            #[cfg(feature = "parallel")]
+            planned_moves: Vec::new(),
+            cascade_buffer: Vec::new(),
+            killed_ants: Vec::with_capacity(snapshot.collision_threshold as usize),
+            first_fight: None,
+            watch_first_fight: false,
+            fights: None,
+            events: None,
+            recent_destructions: VecDeque::with_capacity(RECENT_DESTRUCTIONS),
+            destruction_log: None,
+            visits: None,
+            deaths: None,
+            died_in: Vec::new(),
+            fights_by_ant: Vec::new(),
+            traces: Vec::new(),
+            trace_slot: Vec::new(),
+            regions: None,
+            region_destructions: Vec::new(),
+            
+            options: snapshot.options,
+            max_moves: snapshot.max_moves,
+            collision_threshold: snapshot.collision_threshold,
+            collision_policy: snapshot.collision_policy,
+            fight_trigger: snapshot.fight_trigger,
+            
+            seed: snapshot.seed,
+            rng: fastrand::Rng::with_seed(snapshot.rng_state),
+            stream_seed: snapshot.stream_seed,
+            iteration: snapshot.iteration,
+            total_moves_made: snapshot.total_moves_made,
+            
+            #[cfg(any(feature = "perf-counters", debug_assertions))]
+            perf: PerfCounters::default(),
+            
+            total_colonies,
+            colonies_destroyed: 0,
+            total_ants,
+            alive_ants: snapshot.alive_ants,
+            active_ants_under_max_moves: snapshot.active_ants_under_max_moves,
+        };
+        sim.colonies_destroyed = sim.destroyed.iter().filter(|&&destroyed| destroyed).count();
+        sim.components = sim.options.track_components.then(|| sim.build_components());
+        sim.reset_oscillations();
+        // the checks above keep indexing safe, this catches state that doesn't add up
+        sim.check_invariants()
+            .map_err(|problem| SimError::Snapshot(format!("inconsistent simulation state: {}", problem)))?;
+        Ok(sim)
+    }
+    
+    /// Every colony's state, in colony ID order; same as `&sim` in a `for` loop
+    pub fn iter(&self) -> SimColonyIter<'_> {
+        self.into_iter()
+    }
+    
+    /// Ants placed at the start, dead or alive
+    pub fn total_ants(&self) -> usize {
+        self.total_ants
+    }
+    
+    /// Live ants that have gone back and forth between the same two colonies
+    /// for more than `SimOptions::oscillation_after` moves in a row, 0 with
+    /// detection off. Streaks start over on `with_options` and on resuming
+    /// from a snapshot.
+    pub fn oscillating_ants(&self) -> usize {
+        self.oscillating_ants
+    }
+    
+    /// The map this simulation runs on
+    pub fn graph(&self) -> &Arc<MapGraph> {
+        &self.graph
+    }
+    
+    /// Engine options the run is using, see `with_options`
+    pub fn options(&self) -> &SimOptions {
+        &self.options
+    }
+    
+    /// Seed the run's randomness derives from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    
+    /// Moves each ant may make
+    pub fn max_moves(&self) -> u32 {
+        self.max_moves
+    }
+    
+    /// Who dies when ants fight
+    pub fn collision_policy(&self) -> CollisionPolicy {
+        self.collision_policy
+    }
+    
+    /// Whether exactly `collision_threshold` ants fight, or that many or more
+    pub fn fight_trigger(&self) -> FightTrigger {
+        self.fight_trigger
+    }
+    
+    /// Give every ant `max_moves` moves in all, e.g. more for a resumed run.
+    /// Ants that had run out of moves carry on if the new budget allows.
+    pub fn with_max_moves(mut self, max_moves: u32) -> Self {
+        self.max_moves = max_moves;
+        self.active_ants_under_max_moves = (0..self.total_ants)
+            .filter(|&ant| self.ant_is_alive_bit(ant) && self.move_count[ant] < max_moves)
+            .count();
+        self
+    }
+    
+    /// Use the given engine options for the rest of the run
+    pub fn with_options(mut self, options: SimOptions) -> Self {
+        self.options = options;
+        self.assign_factions();
+        self.failed_moves = match self.options.dead_end_behavior {
+            DeadEndBehavior::DieAfterNMoves(_) => vec![0; self.total_ants],
+            _ => Vec::new(),
+        };
+        self.components = self.options.track_components.then(|| self.build_components());
+        self.reset_oscillations();
+        self
+    }
+    
+    /// Start every ant's oscillation streak over, allocating the streaks
+    /// only if `oscillation_after` is set
+    fn reset_oscillations(&mut self) {
+        let ants = if self.options.oscillation_after.is_some() { self.total_ants } else { 0 };
+        self.previous_position = vec![NO_SLOT; ants];
+        self.oscillation_streak = vec![0; ants];
+        self.oscillating_ants = 0;
+    }
+    
+    /// Break `region_stats` down by `regions`, read for this simulation's map.
+    /// Destructions before this are counted but not dated.
+    pub fn with_regions(mut self, regions: Regions) -> Self {
+        assert_eq!(regions.colony_count(), self.total_colonies, "regions are for a map of another size");
+        self.region_destructions = vec![Vec::new(); regions.names().len()];
+        self.regions = Some(regions);
+        self
+    }
+    
+    pub fn regions(&self) -> Option<&Regions> {
+        self.regions.as_ref()
+    }
+    
+    /// Keep `deaths_timeline` and the iteration each ant dies in from now
+    /// on, or stop keeping (and drop) them. Off by default, as it allocates
+    /// as ants die.
+    pub fn record_deaths(&mut self, record: bool) {
+        self.deaths = record.then(Vec::new);
+        self.died_in = if record { vec![STILL_ALIVE; self.total_ants()] } else { Vec::new() };
+    }
+    
+    /// Keep every destruction from now on for `destructions`, or stop keeping
+    /// (and drop) them. Off by default, `Summary::recent_destructions` only
+    /// has the last few.
+    pub fn record_destructions(&mut self, record: bool) {
+        self.destruction_log = record.then(Vec::new);
+    }
+    
+    /// Every colony destroyed since `record_destructions` was turned on,
+    /// oldest first
+    pub fn destructions(&self) -> Vec<Destruction> {
+        let log = self.destruction_log.as_deref().unwrap_or_default();
+        log.iter()
+            .map(|&(iteration, colony)| Destruction {
+                iteration,
+                colony,
+                name: self.graph.colony_name(colony).to_string(),
+            })
+            .collect()
+    }
+    
+    /// (iteration, colony) of every destruction `destructions` has, without
+    /// the names
+    #[cfg(any(feature = "arrow", feature = "python"))]
+    pub(crate) fn destruction_log(&self) -> Option<&[(u32, ColonyId)]> {
+        self.destruction_log.as_deref()
+    }
+    
+    /// Count the ants that move into each colony from now on, for
+    /// `colony_visits`, or stop counting (and drop the counts). Off by default.
+    pub fn record_visits(&mut self, record: bool) {
+        self.visits = record.then(|| vec![0; self.total_colonies]);
+    }
+    
+    /// Ants that moved into each colony since `record_visits` was turned on,
+    /// by colony ID; none while it's off
+    pub fn colony_visits(&self) -> Option<&[u32]> {
+        self.visits.as_deref()
+    }
+    
+    /// Ants killed in each iteration by each cause, in iteration order, since
+    /// `record_deaths` was turned on. Iterations nobody died in are left out;
+    /// an iteration has an entry per cause that killed in it.
+    pub fn deaths_timeline(&self) -> &[Deaths] {
+        self.deaths.as_deref().unwrap_or_default()
+    }
+    
+    /// Ants killed in each iteration run so far, whatever the cause, from
+    /// `deaths_timeline`; 0 for iterations before it was kept. A cascade set
+    /// off between iterations counts towards the next one.
+    pub fn deaths_per_iteration(&self) -> Vec<u32> {
+        let timeline = self.deaths_timeline();
+        let last = timeline.last().map_or(0, |deaths| deaths.iteration as usize + 1);
+        let mut per_iteration = vec![0; last.max(self.iteration as usize)];
+        for deaths in timeline {
+            per_iteration[deaths.iteration as usize] += deaths.ants;
+        }
+        per_iteration
+    }
+    
+    /// The ant with the most moves, the traced ant that saw the most
+    /// colonies and the dead ant that lasted longest, ties going to the
+    /// lowest ID. The longest lived is only known with `record_deaths`, for
+    /// ants that died since it was turned on.
+    pub fn marathon_ants(&self) -> MarathonAnts {
+        let most_moves = (0..self.total_ants())
+            .map(|ant| AntRecord { ant, value: self.move_count[ant] })
+            .filter(|record| record.value > 0)
+            .min_by_key(|record| (std::cmp::Reverse(record.value), record.ant));
+        let most_distinct = (0..self.trace_slot.len())
+            .filter_map(|ant| {
+                let mut path = self.ant_path(ant)?.to_vec();
+                path.sort_unstable();
+                path.dedup();
+                Some(AntRecord { ant, value: path.len() as u32 })
+            })
+            .min_by_key(|record| (std::cmp::Reverse(record.value), record.ant));
+        let longest_lived = (self.died_in.iter().enumerate())
+            .filter(|&(_, &iteration)| iteration != STILL_ALIVE)
+            .map(|(ant, &iteration)| AntRecord { ant, value: iteration })
+            .min_by_key(|record| (std::cmp::Reverse(record.value), record.ant));
+        MarathonAnts { most_moves, most_distinct, longest_lived }
+    }
+    
+    /// Count the fights each ant is in from now on, for
+    /// `colonies_destroyed_by`, or stop counting (and drop the counts)
+    pub fn record_fight_counts(&mut self, record: bool) {
+        self.fights_by_ant = if record { vec![0; self.total_ants()] } else { Vec::new() };
+    }
+    
+    /// Colonies destroyed in fights `ant` was in, dead or alive, since
+    /// `record_fight_counts` was turned on; 0 while it's off
+    pub fn colonies_destroyed_by(&self, ant: AntId) -> u32 {
+        self.fights_by_ant.get(ant).copied().unwrap_or(0)
+    }
+    
+    /// Follow `ants` from now on, for `ant_path` and
+    /// `distance_traveled_km`, each from the colony it's at now. Replaces
+    /// the ants traced before; an empty list stops tracing. Panics on an ID
+    /// past `total_ants`.
+    pub fn trace_ants(&mut self, ants: &[AntId]) {
+        self.traces.clear();
+        self.trace_slot = if ants.is_empty() { Vec::new() } else { vec![NO_SLOT; self.total_ants()] };
+        for &ant in ants {
+            if self.trace_slot[ant] == NO_SLOT {
+                self.trace_slot[ant] = self.traces.len() as u32;
+                self.traces.push(vec![self.ant_position[ant] as ColonyId]);
+            }
+        }
+    }
+    
+    /// Colonies `ant` has been at since `trace_ants` picked it, where it
+    /// started first; none for an ant that isn't traced
+    pub fn ant_path(&self, ant: AntId) -> Option<&[ColonyId]> {
+        let slot = *self.trace_slot.get(ant)?;
+        self.traces.get(slot as usize).map(Vec::as_slice)
+    }
+    
+    /// Great-circle distance along `ant_path` in kilometres, see
+    /// `MapGraph::path_km`; none for an untraced ant or if a colony on the
+    /// path has no coordinates
+    pub fn distance_traveled_km(&self, ant: AntId) -> Option<f64> {
+        self.graph.path_km(self.ant_path(ant)?)
+    }
+    
+    /// The one ant left alive, none while two or more are (or nobody is)
+    pub fn winner(&self) -> Option<Winner> {
+        if self.alive_ants != 1 {
+            return None;
+        }
+        let ant = (0..self.total_ants()).find(|&ant| self.ant_is_alive_bit(ant))?;
+        Some(Winner {
+            ant,
+            colony: self.ant_position[ant] as ColonyId,
+            moves: self.move_count[ant],
+            colonies_destroyed: self.colonies_destroyed_by(ant),
+        })
+    }
+    
+    /// Region of a colony, 0 (unassigned) without `with_regions`
+    pub fn region_of(&self, colony: ColonyId) -> RegionId {
+        self.regions.as_ref().map_or(0, |regions| regions.region_of(colony))
+    }
+    
+    /// Colonies, destructions and ants of each region by `RegionId`, one
+    /// unassigned region holding everything without `with_regions`.
+    /// O(colonies).
+    pub fn region_stats(&self) -> Vec<RegionStats> {
+        let names = match &self.regions {
+            Some(regions) => regions.names().to_vec(),
+            None => vec![UNASSIGNED_REGION.to_string()],
+        };
+        let mut stats: Vec<RegionStats> = names
+            .into_iter()
+            .enumerate()
+            .map(|(region, name)| RegionStats {
+                name,
+                colonies: 0,
+                destroyed: 0,
+                surviving: 0,
+                ants: 0,
+                destructions: self.region_destructions.get(region).cloned().unwrap_or_default(),
+            })
+            .collect();
+        for colony in 0..self.total_colonies {
+            let region = &mut stats[self.region_of(colony)];
+            region.colonies += 1;
+            region.ants += self.ant_count[colony] as usize;
+            if self.destroyed[colony] {
+                region.destroyed += 1;
+            } else {
+                region.surviving += 1;
+            }
+        }
+        stats
+    }
+    
+    fn build_components(&self) -> Components {
+        Components::build(&self.graph, &self.destroyed, &self.ant_count)
+    }
+    
+    /// Alive ants that could still reach `colony_id` or be reached from it
+    /// (tunnels counted as two-way). O(1) with `track_components`, a full
+    /// scan of the map otherwise.
+    pub fn alive_ants_in_component(&self, colony_id: ColonyId) -> usize {
+        match &self.components {
+            Some(components) => components.alive_ants_in(colony_id),
+            None => self.build_components().alive_ants_in(colony_id),
+        }
+    }
+    
+    /// `alive_ants_in_component` under the name the other component queries
+    /// use; any colony of the component gives the same count
+    pub fn component_ant_count(&self, representative_colony: ColonyId) -> usize {
+        self.alive_ants_in_component(representative_colony)
+    }
+    
+    /// A simulation of just the component `colony_id` is in (tunnels counted
+    /// as two-way, ruins with survivors included), to run on its own. Colonies
+    /// are renumbered 0.. in ID order and keep their names and destroyed
+    /// flags; tunnels out of the component are gone. Ants keep their IDs,
+    /// moves and factions, and every ant outside the component is dead in
+    /// the copy. It carries on from this iteration with the same random stream.
+    pub fn isolate_component(&self, colony_id: ColonyId) -> AntSimulation {
+        let colonies = match &self.components {
+            Some(components) => components.members(colony_id),
+            None => self.build_components().members(colony_id),
+        };
+        self.subgraph(&colonies)
+    }
+    
+    /// `isolate_component` for any set of colonies, given in ID order
+    fn subgraph(&self, colonies: &[ColonyId]) -> AntSimulation {
+        const OUTSIDE: ColonyIndex = ColonyIndex::MAX;
+        let mut new_id = vec![OUTSIDE; self.total_colonies];
+        for (index, &colony) in colonies.iter().enumerate() {
+            new_id[colony] = index as ColonyIndex;
+        }
+        let names: Vec<&str> = colonies.iter().map(|&colony| self.graph.colony_name(colony)).collect();
+        let tunnels = (colonies.iter())
+            .map(|&colony| {
+                (self.graph.neighbors(colony).iter().enumerate())
+                    .filter(|&(_, &to)| new_id[to as usize] != OUTSIDE)
+                    .map(|(i, &to)| (self.graph.edge_label(colony, i), new_id[to as usize] as ColonyId))
+                    .collect()
+            })
+            .collect();
+        let graph = MapGraph::from_labelled_tunnels(&names, tunnels).expect("a part of a valid map is valid");
+        
+        let mut snapshot = self.snapshot();
+        snapshot.map = MapSnapshot::of(&graph);
+        snapshot.ant_count = colonies.iter().map(|&colony| self.ant_count[colony]).collect();
+        snapshot.colony_peak_ant_count = colonies.iter().map(|&colony| self.colony_peak_ant_count[colony]).collect();
+        snapshot.destroyed = colonies.iter().map(|&colony| self.destroyed[colony]).collect();
+        snapshot.ants_at_colony = colonies.iter().map(|&colony| self.ants_at_colony[colony].clone()).collect();
+        for ant in 0..self.total_ants {
+            let colony = new_id[self.ant_position[ant] as usize];
+            if colony != OUTSIDE {
+                snapshot.ant_position[ant] = colony;
+                continue;
+            }
+            // dead ants keep no colony of their own, any in range will do
+            snapshot.ant_position[ant] = 0;
+            snapshot.ant_slot[ant] = NO_SLOT;
+            if self.ant_is_alive_bit(ant) {
+                snapshot.ant_alive_bits[ant / 64] &= !(1 << (ant % 64));
+                snapshot.alive_ants -= 1;
+                if self.move_count[ant] < self.max_moves {
+                    snapshot.active_ants_under_max_moves -= 1;
+                }
+                if self.faction_mode() {
+                    snapshot.faction_alive_counts[self.ant_faction[ant] as usize] -= 1;
+                }
+            }
+        }
+        AntSimulation::from_snapshot(snapshot).expect("a part of a consistent simulation is consistent")
+    }
+    
+    /// Connected components holding at least two alive ants, the only places a
+    /// fight can still happen. O(1) with `track_components`.
+    pub fn components_with_at_least_two_ants(&self) -> usize {
+        match &self.components {
+            Some(components) => components.crowded(),
+            None => self.build_components().crowded(),
+        }
+    }
+    
+    /// Live colonies in the largest connected component, 0 once every colony
+    /// is destroyed. O(1) with `track_components`.
+    pub fn max_component_size(&self) -> usize {
+        match &self.components {
+            Some(components) => components.largest(),
+            None => self.build_components().largest(),
+        }
+    }
+    
+    /// Mean live colonies per connected component, leaving out components
+    /// that are nothing but ruins. O(1) with `track_components`.
+    pub fn average_component_size(&self) -> f64 {
+        match &self.components {
+            Some(components) => components.average_size(),
+            None => self.build_components().average_size(),
+        }
+    }
+    
+    /// Fewest tunnels whose loss would split the live map in two, with
+    /// tunnels treated as two-way and colonies joined by several counted as
+    /// joined by one. 1 means the map hangs together by a bridge. 0 if the
+    /// live map is already in pieces or has under two colonies. Stoer-Wagner,
+    /// O(V * (E + V) * log V) over the live colonies and tunnels.
+    pub fn global_min_cut(&self) -> usize {
+        let mut adjacency = vec![HashMap::new(); self.alive_colony_ids.len()];
+        for (index, &colony) in self.alive_colony_ids.iter().enumerate() {
+            for &neighbor in self.graph.neighbors(colony) {
+                let neighbor = neighbor as ColonyId;
+                if neighbor != colony && !self.destroyed[neighbor] {
+                    let other = self.alive_colony_index[neighbor] as usize;
+                    adjacency[index].insert(other, 1);
+                    adjacency[other].insert(index, 1);
+                }
+            }
+        }
+        min_cut::stoer_wagner(adjacency)
+    }
+    
+    /// Deal ants out to factions round-robin
+    fn assign_factions(&mut self) {
+        let factions = self.options.factions as usize;
+        if factions < 2 {
+            self.ant_faction = Vec::new();
+            self.faction_alive_counts = Vec::new();
+            return;
+        }
+        
+        self.ant_faction = (0..self.total_ants).map(|ant| (ant % factions) as u8).collect();
+        self.faction_alive_counts = vec![0; factions];
+        // walk only the set bits, whole words of dead ants are skipped at once
+        for (word_index, &word) in self.ant_alive_bits.iter().enumerate() {
+            let mut bits = word;
+            while bits != 0 {
+                let ant = word_index * 64 + bits.trailing_zeros() as usize;
+                self.faction_alive_counts[self.ant_faction[ant] as usize] += 1;
+                bits &= bits - 1;
+            }
+        }
+    }
+    
+    #[inline]
+    fn faction_mode(&self) -> bool {
+        !self.faction_alive_counts.is_empty()
+    }
+    
+    /// Number of alive ants in a faction (all ants are faction 0 without faction mode)
+    pub fn alive_count_for_faction(&self, faction: u8) -> usize {
+        if !self.faction_mode() {
+            return if faction == 0 { self.alive_ants } else { 0 };
+        }
+        self.faction_alive_counts.get(faction as usize).copied().unwrap_or(0)
+    }
+    
+    /// Has a faction lost all its ants
+    pub fn is_faction_eliminated(&self, faction: u8) -> bool {
+        self.alive_count_for_faction(faction) == 0
+    }
+    
+    /// Factions that still have alive ants, i.e. the winners once every other faction is extinct
+    pub fn winning_factions(&self) -> Vec<u8> {
+        if !self.faction_mode() {
+            return if self.alive_ants > 0 { vec![0] } else { Vec::new() };
+        }
+        (0..self.faction_alive_counts.len())
+            .filter(|&faction| self.faction_alive_counts[faction] > 0)
+            .map(|faction| faction as u8)
+            .collect()
+    }
+    
+    /// Place ants according to the placement strategy
+    fn initialize_ants(&mut self, strategy: PlacementStrategy) -> Result<(), SimError> {
+        debug_assert!(
+            self.total_ants == 0 || self.destroyed.iter().any(|&destroyed| !destroyed),
+            "placing ants with every colony destroyed"
+        );
+        match strategy {
+            PlacementStrategy::Uniform => self.place_uniform()?,
+            PlacementStrategy::Distinct => self.place_distinct(),
+            PlacementStrategy::Clustered { clusters, radius } => self.place_clustered(clusters, radius),
+            PlacementStrategy::Single(colony_id) => {
+                for ant_id in 0..self.total_ants {
+                    self.place_ant(ant_id, colony_id);
+                }
+                self.start_crowd = Some(colony_id);
+            }
+            PlacementStrategy::Explicit(positions) => {
+                for (ant_id, position) in positions.into_iter().enumerate() {
+                    let colony_id = match position {
+                        Some(colony_id) => colony_id,
+                        None => self.random_live_colony()?,
+                    };
+                    self.place_ant(ant_id, colony_id);
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    #[inline]
+    fn place_ant(&mut self, ant_id: AntId, colony_id: ColonyId) {
+        self.ant_position[ant_id] = colony_id as ColonyIndex;
+        self.ant_count[colony_id] += 1;
+        self.add_ant_to_colony(colony_id, ant_id);
+    }
+    
+    /// `count` different colonies in random order (partial Fisher-Yates)
+    fn sample_colonies(&mut self, count: usize) -> Vec<ColonyIndex> {
+        let mut colonies: Vec<ColonyIndex> = (0..self.total_colonies as ColonyIndex).collect();
+        for i in 0..count {
+            let j = self.rng.usize(i..colonies.len());
+            perf_count!(self, rng_calls);
+            colonies.swap(i, j);
+        }
+        colonies.truncate(count);
+        colonies
+    }
+    
+    fn place_distinct(&mut self) {
+        let colonies = self.sample_colonies(self.total_ants);
+        for (ant_id, &colony_id) in colonies.iter().enumerate() {
+            self.place_ant(ant_id, colony_id as ColonyId);
+        }
+    }
+    
+    fn place_clustered(&mut self, clusters: usize, radius: u32) {
+        let seeds = self.sample_colonies(clusters.min(self.total_ants));
+        if seeds.is_empty() {
+            return;
+        }
+        
+        // colonies within `radius` of each seed, found ring by ring
+        let mut distance = vec![u32::MAX; self.total_colonies];
+        let mut neighborhoods = Vec::with_capacity(seeds.len());
+        for &seed in &seeds {
+            let mut ball = vec![seed];
+            distance[seed as usize] = 0;
+            let mut next = 0;
+            while next < ball.len() {
+                let colony = ball[next] as ColonyId;
+                next += 1;
+                if distance[colony] == radius {
+                    continue;
+                }
+                for &neighbor in self.graph.neighbors(colony) {
+                    if distance[neighbor as usize] == u32::MAX {
+                        distance[neighbor as usize] = distance[colony] + 1;
+                        ball.push(neighbor);
+                    }
+                }
+            }
+            for &colony in &ball {
+                distance[colony as usize] = u32::MAX;
+            }
+            neighborhoods.push(ball);
+        }
+        
+        // deal ants out to the clusters round-robin so none is left empty
+        for ant_id in 0..self.total_ants {
+            let ball = &neighborhoods[ant_id % neighborhoods.len()];
+            let colony_id = ball[self.rng.usize(..ball.len())] as ColonyId;
+            perf_count!(self, rng_calls);
+            self.place_ant(ant_id, colony_id);
+        }
+    }
+    
+    /// Place ants randomly across colonies
+    fn place_uniform(&mut self) -> Result<(), SimError> {
+        for ant_id in 0..self.total_ants {
+            let colony_id = self.random_live_colony()?;
+            self.place_ant(ant_id, colony_id);
+        }
+        Ok(())
+    }
+    
+    /// A colony drawn uniformly from the live ones, as used to place ants.
+    /// Draws over the whole map until one is live, which takes one draw
+    /// while nothing is destroyed. After `2 * total_colonies` misses it
+    /// doesn't give up but picks from the live colony list, so a mostly
+    /// destroyed map still gets an answer; only with every colony destroyed
+    /// is it `Err(SimError::NoLiveColoniesForPlacement)`. Advances the RNG.
+    pub fn random_live_colony(&mut self) -> Result<ColonyId, SimError> {
+        for _ in 0..2 * self.total_colonies {
+            let colony_id = self.rng.usize(..self.total_colonies);
+            perf_count!(self, rng_calls);
+            if !self.destroyed[colony_id] {
+                return Ok(colony_id);
+            }
+        }
+        if self.alive_colony_ids.is_empty() {
+            return Err(SimError::NoLiveColoniesForPlacement);
+        }
+        let index = self.rng.usize(..self.alive_colony_ids.len());
+        perf_count!(self, rng_calls);
+        Ok(self.alive_colony_ids[index])
+    }
+    
+    /// Get valid moves from a colony
+    #[inline]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self, buffer)))]
+    pub fn get_valid_moves(&self, colony_id: ColonyId, buffer: &mut Vec<ColonyId>) {
+        buffer.clear();
+        for &neighbor in self.graph.neighbors(colony_id) {
+            let neighbor = neighbor as ColonyId;
+            if !self.destroyed[neighbor] {
+                buffer.push(neighbor);
+            }
+        }
+    }
+    
+    /// IDs of the colonies not destroyed, O(1). The order is arbitrary and
+    /// changes as colonies fall.
+    pub fn alive_colony_list(&self) -> &[ColonyId] {
+        &self.alive_colony_ids
+    }
+    
+    /// Live colonies whose tunnels all lead to destroyed colonies (or that
+    /// have none), ants there can't move on
+    pub fn dead_end_colonies(&self) -> Vec<ColonyId> {
+        (0..self.total_colonies)
+            .filter(|&colony| {
+                !self.destroyed[colony]
+                    && self.graph.neighbors(colony).iter().all(|&neighbor| self.destroyed[neighbor as ColonyId])
+            })
+            .collect()
+    }
+    
+    /// Ants currently at a colony
+    #[inline]
+    pub fn ants_at_slice(&self, colony: ColonyId) -> &[AntId] {
+        &self.ants_at_colony[colony]
+    }
+    
+    /// Iterate over the ants currently at a colony
+    pub fn iter_ants_at(&self, colony: ColonyId) -> impl Iterator<Item = AntId> + '_ {
+        self.ants_at_colony[colony].iter().copied()
+    }
+    
+    /// Is any ant at a colony
+    #[inline]
+    pub fn has_ants_at(&self, colony: ColonyId) -> bool {
+        self.ant_count[colony] > 0
+    }
+    
+    /// The colony each ant is at by ant ID, none for dead ones
+    #[cfg(feature = "python")]
+    pub(crate) fn ant_positions(&self) -> impl Iterator<Item = Option<ColonyId>> + '_ {
+        (0..self.total_ants()).map(|ant| self.ant_is_alive_bit(ant).then(|| self.ant_position[ant] as ColonyId))
+    }
+    
+    /// Everything about one ant, dead or alive, for debugging; none for an
+    /// ID past `total_ants`
+    pub fn ant_view(&self, ant: AntId) -> Option<AntView> {
+        if ant >= self.total_ants() {
+            return None;
+        }
+        let alive = self.ant_is_alive_bit(ant);
+        let colony = self.ant_position[ant] as ColonyId;
+        let trapped = alive && self.graph.neighbors(colony).iter().all(|&neighbor| self.destroyed[neighbor as usize]);
+        Some(AntView {
+            ant,
+            alive,
+            trapped,
+            colony,
+            colony_name: self.graph.colony_name(colony).to_string(),
+            moves: self.move_count[ant],
+            moves_left: self.max_moves.saturating_sub(self.move_count[ant]),
+            faction: self.ant_faction.get(ant).copied(),
+            died_in: self.died_in.get(ant).copied().filter(|&iteration| iteration != STILL_ALIVE),
+            path: self.ant_path(ant).map(<[ColonyId]>::to_vec),
+        })
+    }
+    
+    /// A colony by name or alias, see `MapGraph::find_colony`
+    pub fn colony_by_name(&self, name: &str) -> Option<ColonyId> {
+        self.graph.find_colony(name)
+    }
+    
+    /// `ants_at_slice` for a colony given by name
+    pub fn ants_at_named(&self, name: &str) -> Result<&[AntId], SimError> {
+        let colony = self.colony_by_name(name).ok_or_else(|| SimError::UnknownColony(name.to_string()))?;
+        Ok(self.ants_at_slice(colony))
+    }
+    
+    /// Whether a colony given by name is destroyed
+    pub fn is_destroyed_named(&self, name: &str) -> Result<bool, SimError> {
+        let colony = self.colony_by_name(name).ok_or_else(|| SimError::UnknownColony(name.to_string()))?;
+        Ok(self.destroyed[colony])
+    }
+    
+    /// Add ant to colony, remembering its slot in the list
+    #[inline]
+    fn add_ant_to_colony(&mut self, colony: ColonyId, ant: AntId) {
+        self.ant_slot[ant] = self.ants_at_colony[colony].len() as u32;
+        self.ants_at_colony[colony].push(ant);
+    }
+    
+    /// Remove ant from colony in O(1) using its slot
+    #[inline]
+    fn remove_ant_from_colony(&mut self, colony: ColonyId, ant: AntId) {
+        let slot = self.ant_slot[ant] as usize;
+        let ants = &mut self.ants_at_colony[colony];
+        debug_assert_eq!(ants.get(slot), Some(&ant), "ant slot out of sync with colony list");
+        
+        ants.swap_remove(slot);
+        // the last ant was moved into the freed slot
+        if let Some(&moved) = ants.get(slot) {
+            self.ant_slot[moved] = slot as u32;
+        }
+        self.ant_slot[ant] = NO_SLOT;
+    }
+    
+    /// Pick one of `count` valid moves for an ant
+    #[inline]
+    fn pick_move(&mut self, ant_id: AntId, count: usize) -> usize {
+        match self.options.movement {
+            MovementMode::Sequential => self.rng.usize(..count),
+            MovementMode::Simultaneous | MovementMode::Parallel => self.stream_pick(ant_id, count),
+        }
+    }
+    
+    /// The ant's own draw for this iteration, a number below `count`
+    #[inline]
+    fn stream_pick(&self, ant_id: AntId, count: usize) -> usize {
+        let key = ((self.iteration as u64) << 32) ^ ant_id as u64;
+        let random = splitmix64(self.stream_seed ^ splitmix64(key));
+        (((random >> 32) * count as u64) >> 32) as usize
+    }
+    
+    /// Pick the move of every ant in `alive_ant_order` on rayon's thread
+    /// pool, `NO_MOVE` for the ones at a dead end. Nothing moves while
+    /// they're picked, so each pick sees what `move_ant` would.
+    #[cfg(feature = "parallel")]
+    fn plan_moves(&mut self) {
+        use rayon::prelude::*;
+        
+        let mut planned = std::mem::take(&mut self.planned_moves);
+        self.alive_ant_order
+            .par_iter()
+            .map_init(Vec::new, |buffer, &ant_id| {
+                self.get_valid_moves(self.ant_position[ant_id] as ColonyId, buffer);
+                match buffer.len() {
+                    0 => NO_MOVE,
+                    count => buffer[self.stream_pick(ant_id, count)] as u32,
+                }
+            })
+            .collect_into_vec(&mut planned);
+        self.planned_moves = planned;
+    }
+    
+    /// `move_ant` to the colony `plan_moves` picked
+    #[cfg(feature = "parallel")]
+    fn move_ant_as_planned(&mut self, ant_id: AntId, planned: u32) -> Option<(ColonyId, ColonyId)> {
+        if !self.ant_is_alive_bit(ant_id) {
+            return None;
+        }
+        perf_count!(self, valid_move_queries);
+        let current_colony = self.ant_position[ant_id] as ColonyId;
+        if planned == NO_MOVE {
+            return self.handle_dead_end(ant_id, current_colony);
+        }
+        perf_count!(self, rng_calls);
+        Some(self.relocate_ant(ant_id, current_colony, planned as ColonyId))
+    }
+    
+    /// Move an ant once
+    #[inline]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self, buffer)))]
+    pub fn move_ant(&mut self, ant_id: AntId, buffer: &mut Vec<ColonyId>) -> Option<(ColonyId, ColonyId)> {
+        if !self.ant_is_alive_bit(ant_id) {
+            return None;
+        }
+        
+        let current_colony = self.ant_position[ant_id] as ColonyId;
+        self.get_valid_moves(current_colony, buffer);
+        perf_count!(self, valid_move_queries);
+        
+        if buffer.is_empty() {
+            return self.handle_dead_end(ant_id, current_colony);
+        }
+        
+        let next_colony = buffer[self.pick_move(ant_id, buffer.len())];
+        perf_count!(self, rng_calls);
+        Some(self.relocate_ant(ant_id, current_colony, next_colony))
+    }
+    
+    /// An ant with no live colony next door, see `DeadEndBehavior`
+    #[cold]
+    fn handle_dead_end(&mut self, ant_id: AntId, current_colony: ColonyId) -> Option<(ColonyId, ColonyId)> {
+        if !self.oscillation_streak.is_empty() {
+            // staying put breaks the back and forth
+            self.track_oscillation(ant_id, current_colony, current_colony);
+        }
+        match self.options.dead_end_behavior {
+            DeadEndBehavior::StayForever => None,
+            DeadEndBehavior::DieAfterNMoves(limit) => {
+                self.failed_moves[ant_id] += 1;
+                if self.failed_moves[ant_id] >= limit.max(1) {
+                    self.remove_ant_from_colony(current_colony, ant_id);
+                    perf_count!(self, list_removals);
+                    self.ant_count[current_colony] -= 1;
+                    self.kill_ant(ant_id, DeathCause::DeadEnd);
+                    self.components_stale = true;
+                }
+                None
+            }
+            DeadEndBehavior::TeleportToRandom => {
+                // a live colony other than this one, drawn from the live colony list
+                // with this colony's entry skipped over
+                let here = (!self.destroyed[current_colony]).then(|| self.alive_colony_index[current_colony] as usize);
+                let choices = self.alive_colony_ids.len() - here.is_some() as usize;
+                if choices == 0 {
+                    return None;
+                }
+                let mut index = self.pick_move(ant_id, choices);
+                perf_count!(self, rng_calls);
+                if here.is_some_and(|here| index >= here) {
+                    index += 1;
+                }
+                let target = self.alive_colony_ids[index];
+                self.components_stale = true;
+                Some(self.relocate_ant(ant_id, current_colony, target))
+            }
+        }
+    }
+    
+    /// Move an ant from one colony to another, counting it as one of its moves
+    #[inline]
+    fn relocate_ant(&mut self, ant_id: AntId, current_colony: ColonyId, next_colony: ColonyId) -> (ColonyId, ColonyId) {
+        perf_count!(self, moves);
+        
+        self.ant_position[ant_id] = next_colony as ColonyIndex;
+        self.move_count[ant_id] += 1;
+        self.total_moves_made += 1;
+        if !self.oscillation_streak.is_empty() {
+            self.track_oscillation(ant_id, current_colony, next_colony);
+        }
+        if !self.trace_slot.is_empty() {
+            self.trace_move(ant_id, next_colony);
+        }
+        if let Some(events) = &mut self.events {
+            events.push(SimEvent::Move { ant: ant_id, from: current_colony, to: next_colony });
+        }
+        if let Some(visits) = &mut self.visits {
+            visits[next_colony] += 1;
+        }
+        
+        if self.move_count[ant_id] == self.max_moves {
+            self.active_ants_under_max_moves -= 1; // stop scanning in should_continue
+        }
+        
+        self.ant_count[current_colony] -= 1;
+        self.ant_count[next_colony] += 1;
+        let count = self.ant_count[next_colony] as u32;
+        if count > self.colony_peak_ant_count[next_colony] {
+            self.colony_peak_ant_count[next_colony] = count;
+        }
+        
+        self.remove_ant_from_colony(current_colony, ant_id);
+        perf_count!(self, list_removals);
+        self.add_ant_to_colony(next_colony, ant_id);
+        self.debug_check_colony(current_colony);
+        self.debug_check_colony(next_colony);
+        
+        (current_colony, next_colony)
+    }
+    
+    /// Add `to` to the ant's path if it's traced
+    #[cold]
+    fn trace_move(&mut self, ant_id: AntId, to: ColonyId) {
+        let slot = self.trace_slot[ant_id];
+        if slot != NO_SLOT {
+            self.traces[slot as usize].push(to);
+        }
+    }
+    
+    /// Lengthen the ant's streak if going from `from` to `to` takes it back
+    /// where it was before, or end it
+    #[cold]
+    fn track_oscillation(&mut self, ant_id: AntId, from: ColonyId, to: ColonyId) {
+        let Some(after) = self.options.oscillation_after else { return };
+        let was_oscillating = self.oscillation_streak[ant_id] > after;
+        let streak = if from != to && self.previous_position[ant_id] == to as ColonyIndex {
+            self.oscillation_streak[ant_id].saturating_add(1)
+        } else {
+            0
+        };
+        self.oscillation_streak[ant_id] = streak;
+        self.previous_position[ant_id] = from as ColonyIndex;
+        match (was_oscillating, streak > after) {
+            (false, true) => self.oscillating_ants += 1,
+            (true, false) => self.oscillating_ants -= 1,
+            _ => {}
+        }
+    }
+    
+    /// Fight it out if `collision_threshold` ants are at a live colony,
+    /// exactly that many or more as `FightTrigger` says, returning the ants
+    /// that died
+    #[inline]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
+    pub fn check_collision(&mut self, colony_id: ColonyId) -> Option<&[AntId]> {
+        let count = self.ant_count[colony_id];
+        let crowded = match self.fight_trigger {
+            FightTrigger::Exactly => count == self.collision_threshold,
+            FightTrigger::AtLeast => count >= self.collision_threshold,
+        };
+        if self.destroyed[colony_id] || !crowded {
+            return None;
+        }
+        
+        // ants of the same faction share the colony peacefully
+        let ants = &self.ants_at_colony[colony_id];
+        if self.faction_mode() {
+            let faction = self.ant_faction[ants[0]];
+            if ants.iter().all(|&ant| self.ant_faction[ant] == faction) {
+                return None;
+            }
+        }
+        
+        self.killed_ants.clear();
+        self.killed_ants.extend_from_slice(ants);
+        if !self.fights_by_ant.is_empty() {
+            self.count_fight();
+        }
+        if let Some(events) = &mut self.events {
+            let (ant_a, ant_b) = (self.killed_ants[0], self.killed_ants[1]);
+            events.push(SimEvent::Fight { colony: colony_id, ant_a, ant_b });
+        }
+        let count = match self.collision_policy {
+            CollisionPolicy::KillAll => {
+                self.destroy_colony(colony_id);
+                for i in 0..self.killed_ants.len() {
+                    self.kill_ant(self.killed_ants[i], DeathCause::Fight);
+                }
+                return Some(&self.killed_ants);
+            }
+            CollisionPolicy::KillFirst { count } => count,
+            CollisionPolicy::KillRandom { count } => {
+                // partial Fisher-Yates, the first `count` entries are the victims
+                for i in 0..count.min(self.killed_ants.len()) {
+                    let j = self.rng.usize(i..self.killed_ants.len());
+                    perf_count!(self, rng_calls);
+                    self.killed_ants.swap(i, j);
+                }
+                count
+            }
+            CollisionPolicy::KillOldest { count } => {
+                let move_count = &self.move_count;
+                self.killed_ants.sort_unstable_by_key(|&ant| (std::cmp::Reverse(move_count[ant]), ant));
+                count
+            }
+        };
+        self.killed_ants.truncate(count);
+        
+        // survivors stay listed at the ruins until they move on
+        self.mark_destroyed(colony_id);
+        for i in 0..self.killed_ants.len() {
+            let ant = self.killed_ants[i];
+            self.remove_ant_from_colony(colony_id, ant);
+            perf_count!(self, list_removals);
+            self.ant_count[colony_id] -= 1;
+            self.kill_ant(ant, DeathCause::Fight);
+        }
+        self.debug_check_colony(colony_id);
+        
+        Some(&self.killed_ants)
+    }
+    
+    /// `check_collision`, followed by the cascade when `cascade_on_destroy`
+    /// is on, counted into `result`
+    #[inline]
+    fn settle_colony(&mut self, colony_id: ColonyId, cascaded: &mut Vec<ColonyId>, result: &mut IterationResult) {
+        let Some(killed) = self.check_collision(colony_id) else {
+            return;
+        };
+        result.colonies_destroyed += 1;
+        result.ants_killed += killed.len();
+        if self.watch_first_fight && self.first_fight.is_none() {
+            self.first_fight = Some((colony_id, self.killed_ants.clone()));
+        }
+        if let Some(fights) = &mut self.fights {
+            fights.push((colony_id, self.killed_ants.clone()));
+        }
+        if self.options.cascade_on_destroy {
+            cascaded.clear();
+            result.ants_killed += self.cascade_from(colony_id, cascaded);
+            result.colonies_destroyed += cascaded.len();
+        }
+    }
+    
+    /// Destroy `colony_id`, killing the ants there, then every neighbor left
+    /// with no live tunnel out, then theirs and so on. Returns the colonies
+    /// destroyed, `colony_id` first unless it already was. Only tunnels out
+    /// of destroyed colonies are followed, so on maps with one-way tunnels a
+    /// colony whose only way out was a one-way tunnel in is left standing.
+    pub fn destroy_cascade(&mut self, colony_id: ColonyId) -> Vec<ColonyId> {
+        let mut destroyed = Vec::new();
+        if !self.destroyed[colony_id] {
+            self.destroy_with_ants(colony_id);
+            destroyed.push(colony_id);
+        }
+        self.cascade_from(colony_id, &mut destroyed);
+        if self.components.is_some() && !destroyed.is_empty() {
+            self.components = Some(self.build_components());
+        }
+        destroyed
+    }
+    
+    /// Destroy the dead ends that the destruction of `colony_id` leaves,
+    /// outward from it, adding them to `destroyed`. Returns the ants killed.
+    fn cascade_from(&mut self, colony_id: ColonyId, destroyed: &mut Vec<ColonyId>) -> usize {
+        let graph = Arc::clone(&self.graph);
+        let mut ants_killed = 0;
+        let mut next = destroyed.len();
+        let mut from = colony_id;
+        loop {
+            for &neighbor in graph.neighbors(from) {
+                let neighbor = neighbor as ColonyId;
+                if !self.destroyed[neighbor] && self.colony_out_degree(neighbor) == 0 {
+                    ants_killed += self.destroy_with_ants(neighbor);
+                    destroyed.push(neighbor);
+                }
+            }
+            let Some(&colony) = destroyed.get(next) else {
+                return ants_killed;
+            };
+            from = colony;
+            next += 1;
+        }
+    }
+    
+    /// Destroy a live colony and kill every ant in it, returning how many died
+    fn destroy_with_ants(&mut self, colony_id: ColonyId) -> usize {
+        let ants = self.ants_at_colony[colony_id].len();
+        for i in 0..ants {
+            self.kill_ant(self.ants_at_colony[colony_id][i], DeathCause::Cascade);
+        }
+        self.destroy_colony(colony_id);
+        ants
+    }
+    
+    #[inline]
+    fn destroy_colony(&mut self, colony_id: ColonyId) {
+        self.mark_destroyed(colony_id);
+        self.ant_count[colony_id] = 0;
+        for &ant in &self.ants_at_colony[colony_id] {
+            self.ant_slot[ant] = NO_SLOT;
+        }
+        self.ants_at_colony[colony_id].clear();
+        debug_assert!(self.destroyed[colony_id], "simulation invariant broken: colony {} isn't flagged", colony_id);
+    }
+    
+    /// Debug builds: a colony's ant count matches its list of ants. Free in
+    /// release builds.
+    #[inline]
+    fn debug_check_colony(&self, colony_id: ColonyId) {
+        debug_assert_eq!(
+            self.ant_count[colony_id] as usize,
+            self.ants_at_colony[colony_id].len(),
+            "simulation invariant broken: colony {} counts ants it doesn't list",
+            colony_id
+        );
+    }
+    
+    /// Flag a colony destroyed, taking its tunnels out of its neighbors' in-degrees
+    #[inline]
+    fn mark_destroyed(&mut self, colony_id: ColonyId) {
+        if self.destroyed[colony_id] {
+            return;
+        }
+        self.destroyed[colony_id] = true;
+        self.colonies_destroyed += 1;
+        if let Some(events) = &mut self.events {
+            events.push(SimEvent::Destroyed { colony: colony_id });
+        }
+        if self.recent_destructions.len() == RECENT_DESTRUCTIONS {
+            self.recent_destructions.pop_front();
+        }
+        self.recent_destructions.push_back((self.iteration, colony_id));
+        if let Some(log) = &mut self.destruction_log {
+            log.push((self.iteration, colony_id));
+        }
+        if let Some(regions) = &self.regions {
+            self.region_destructions[regions.region_of(colony_id)].push(self.iteration);
+        }
+        let index = self.alive_colony_index[colony_id] as usize;
+        self.alive_colony_ids.swap_remove(index);
+        if let Some(&moved) = self.alive_colony_ids.get(index) {
+            self.alive_colony_index[moved] = index as u32;
+        }
+        self.alive_colony_index[colony_id] = NO_SLOT;
+        for &neighbor in self.graph.neighbors(colony_id) {
+            self.colony_in_degree[neighbor as ColonyId] -= 1;
+        }
+    }
+    
+    #[inline]
+    fn ant_is_alive_bit(&self, ant: AntId) -> bool {
+        is_alive_in(&self.ant_alive_bits, ant)
+    }
+    
+    #[inline]
+    fn set_ant_alive_bit(&mut self, ant: AntId, alive: bool) {
+        let mask = 1 << (ant % 64);
+        if alive {
+            self.ant_alive_bits[ant / 64] |= mask;
+        } else {
+            self.ant_alive_bits[ant / 64] &= !mask;
+        }
+    }
+    
+    #[inline]
+    fn kill_ant(&mut self, ant_id: AntId, cause: DeathCause) {
+        if self.ant_is_alive_bit(ant_id) {
+            if self.deaths.is_some() {
+                self.record_death(ant_id, cause);
+            }
+            if let Some(events) = &mut self.events {
+                events.push(SimEvent::Death { ant: ant_id, colony: self.ant_position[ant_id] as ColonyId, cause });
+            }
+            self.set_ant_alive_bit(ant_id, false);
+            self.dead_in_alive_order = true;
+            self.alive_ants -= 1;
+            if self.move_count[ant_id] < self.max_moves {
+                self.active_ants_under_max_moves -= 1;
+            }
+            if self.faction_mode() {
+                self.faction_alive_counts[self.ant_faction[ant_id] as usize] -= 1;
+            }
+            if let Some(after) = self.options.oscillation_after {
+                if self.oscillation_streak[ant_id] > after {
+                    self.oscillating_ants -= 1;
+                }
+            }
+        }
+    }
+    
+    /// Credit every ant of the fight in `killed_ants` with the colony
+    #[cold]
+    fn count_fight(&mut self) {
+        for &ant in &self.killed_ants {
+            self.fights_by_ant[ant] += 1;
+        }
+    }
+    
+    /// Count a death this iteration; causes can take turns within one, so
+    /// the iteration's entries are searched, at most one per cause
+    #[cold]
+    fn record_death(&mut self, ant_id: AntId, cause: DeathCause) {
+        let iteration = self.iteration;
+        self.died_in[ant_id] = iteration;
+        let Some(timeline) = &mut self.deaths else { return };
+        let mut this_iteration = timeline.iter_mut().rev().take_while(|deaths| deaths.iteration == iteration);
+        match this_iteration.find(|deaths| deaths.cause == cause) {
+            Some(deaths) => deaths.ants += 1,
+            None => timeline.push(Deaths { iteration, cause, ants: 1 }),
+        }
+    }
+    
+    /// check if simulation should continue
+    #[inline]
+    pub fn should_continue(&self) -> bool {
+        if self.faction_mode() {
+            // a lone faction has nobody left to fight
+            let factions_left = self.faction_alive_counts.iter().filter(|&&count| count > 0).count();
+            if factions_left <= 1 {
+                return false;
+            }
+        }
+        if let Some(components) = &self.components {
+            if components.crowded() == 0 {
+                return false;
+            }
+        }
+        self.alive_ants > 0 && self.active_ants_under_max_moves > 0
+    }
+    
+    /// Why the run is over, none while `should_continue` and iterations are
+    /// left. The first reason that applies, in the order `Termination` lists
+    /// them from the last up.
+    pub fn termination(&self) -> Option<Termination> {
+        if self.faction_mode() && self.faction_alive_counts.iter().filter(|&&count| count > 0).count() <= 1 {
+            return Some(Termination::FactionWon);
+        }
+        if self.components.as_ref().is_some_and(|components| components.crowded() == 0) {
+            return Some(Termination::NoFightsLeft);
+        }
+        if self.alive_ants == 0 {
+            Some(Termination::AllAntsDead)
+        } else if self.active_ants_under_max_moves == 0 {
+            Some(Termination::MovesUsedUp)
+        } else if self.iteration >= self.max_moves {
+            Some(Termination::IterationLimit)
+        } else {
+            None
+        }
+    }
+    
+    /// Run one iteration of the simulation
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), fields(iteration = self.iteration))
+    )]
+    pub fn run_iteration(&mut self) -> IterationResult {
+        // taken out of self for the duration of the loop, handed back at the end
+        let mut buffer = std::mem::take(&mut self.move_buffer);
+        let mut colonies_to_check = std::mem::take(&mut self.collision_check_buffer);
+        let mut cascaded = std::mem::take(&mut self.cascade_buffer);
+        colonies_to_check.clear();
+        if let Some(fights) = &mut self.fights {
+            fights.clear();
+        }
+        if let Some(events) = &mut self.events {
+            events.clear();
+        }
+        
+        // the whole order is reordered and shuffled, dead ants included, so
+        // the random draws don't depend on how many have died
+        let mut reordered = false;
+        if let Some(every) = self.options.reorder_every {
+            if every > 0 && self.iteration.is_multiple_of(every) {
+                self.reorder_ants();
+                reordered = true;
+            }
+        }
+        if self.options.shuffle_order && self.options.movement == MovementMode::Sequential {
+            self.rng.shuffle(&mut self.ant_order);
+            perf_count!(self, rng_calls, self.ant_order.len());
+            reordered = true;
+        }
+        if reordered {
+            let bits = &self.ant_alive_bits;
+            self.alive_ant_order.clear();
+            self.alive_ant_order.extend(self.ant_order.iter().filter(|&&ant| is_alive_in(bits, ant)));
+            self.dead_in_alive_order = false;
+        } else if self.dead_in_alive_order {
+            let bits = &self.ant_alive_bits;
+            self.alive_ant_order.retain(|&ant| is_alive_in(bits, ant));
+            self.dead_in_alive_order = false;
+        }
+        
+        let simultaneous = self.options.movement != MovementMode::Sequential;
+        let immediate = !simultaneous && self.options.destruction == DestructionMode::Immediate;
+        let threshold = self.collision_threshold;
+        let mut result = IterationResult { iteration: self.iteration, ..IterationResult::default() };
+        
+        // a single-colony start is one crowd, it fights before anyone gets away
+        if let Some(colony_id) = self.start_crowd.take() {
+            perf_count!(self, collision_checks);
+            self.settle_colony(colony_id, &mut cascaded, &mut result);
+        }
+        
+        #[cfg(feature = "parallel")]
+        let parallel = self.options.movement == MovementMode::Parallel;
+        #[cfg(feature = "parallel")]
+        if parallel {
+            self.plan_moves();
+        }
+        
+        // ants killed during this iteration are still listed, move_ant skips them
+        for i in 0..self.alive_ant_order.len() {
+            let ant_id = self.alive_ant_order[i];
+            #[cfg(feature = "parallel")]
+            let moved = if parallel {
+                self.move_ant_as_planned(ant_id, self.planned_moves[i])
+            } else {
+                self.move_ant(ant_id, &mut buffer)
+            };
+            #[cfg(not(feature = "parallel"))]
+            let moved = self.move_ant(ant_id, &mut buffer);
+            if let Some((_, next_colony)) = moved {
+                result.moves += 1;
+                let count = self.ant_count[next_colony];
+                if immediate {
+                    perf_count!(self, collision_checks);
+                    self.settle_colony(next_colony, &mut cascaded, &mut result);
+                } else if count >= threshold {
+                    // avoid pushing duplicates, check_collision skips colonies already destroyed
+                    if colonies_to_check.last() != Some(&next_colony) {
+                        colonies_to_check.push(next_colony);
+                    }
+                }
+            }
+        }
+        
+        for &colony_id in &colonies_to_check {
+            self.settle_colony(colony_id, &mut cascaded, &mut result);
+        }
+        perf_count!(self, collision_checks, colonies_to_check.len());
+        
+        if self.components.is_some() && (result.colonies_destroyed > 0 || self.components_stale) {
+            self.components = Some(self.build_components());
+        }
+        self.components_stale = false;
+        
+        self.move_buffer = buffer;
+        self.collision_check_buffer = colonies_to_check;
+        self.cascade_buffer = cascaded;
+        self.iteration += 1;
+        
+        #[cfg(debug_assertions)]
+        self.assert_invariants();
+        
+        result
+    }
+    
+    /// Run until every ant is dead or out of moves, returning the iterations run
+    pub fn run_to_completion(&mut self) -> u32 {
+        self.run_interruptible(&AtomicBool::new(false), |_| {}).iterations
+    }
+    
+    /// Run to completion, but stop at the next iteration boundary once
+    /// `interrupt` is set (e.g. by a Ctrl-C handler). `on_iteration` sees
+    /// every iteration's result.
+    pub fn run_interruptible(
+        &mut self,
+        interrupt: &AtomicBool,
+        mut on_iteration: impl FnMut(&IterationResult),
+    ) -> RunStatus {
+        self.run_until(interrupt, None, |_, result| {
+            on_iteration(result);
+            ControlFlow::Continue(())
+        })
+    }
+    
+    /// `run_interruptible` with a wall-clock budget: no iteration starts
+    /// after `deadline`. `on_iteration` also gets the simulation as it stands
+    /// at the iteration boundary, e.g. to take a snapshot, and can end the
+    /// run there by returning `Break`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", skip_all, fields(ants = self.total_ants(), max_moves = self.max_moves))
+    )]
+    pub fn run_until(
+        &mut self,
+        interrupt: &AtomicBool,
+        deadline: Option<Instant>,
+        mut on_iteration: impl FnMut(&AntSimulation, &IterationResult) -> ControlFlow<()>,
+    ) -> RunStatus {
+        let mut status = RunStatus::default();
+        while self.should_continue() && self.iteration < self.max_moves {
+            if interrupt.load(Ordering::Relaxed) {
+                status.interrupted = true;
+                break;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                status.timed_out = true;
+                break;
+            }
+            let result = self.run_iteration();
+            status.iterations += 1;
+            if on_iteration(self, &result).is_break() {
+                status.stopped = true;
+                break;
+            }
+        }
+        status
+    }
+    
+    /// Keep the fights of each iteration for `last_fights` from the next one
+    /// on, or stop keeping them. Off by default, as it allocates per fight.
+    pub fn record_fights(&mut self, record: bool) {
+        self.fights = record.then(Vec::new);
+    }
+    
+    /// Where each fight of the last iteration was and the ants it killed, in
+    /// the order they happened; cascades aren't fights. Empty unless
+    /// `record_fights` is on.
+    pub fn last_fights(&self) -> &[(ColonyId, Vec<AntId>)] {
+        self.fights.as_deref().unwrap_or_default()
+    }
+    
+    /// Keep everything that happens in each iteration for `last_events` from
+    /// the next one on, or stop keeping it. Off by default, as it allocates
+    /// per move until the buffer has grown to an iteration's worth.
+    pub fn record_events(&mut self, record: bool) {
+        self.events = record.then(Vec::new);
+    }
+    
+    /// Moves, fights, deaths and destructions of the last iteration, in the
+    /// order they happened; a `destroy_cascade` between iterations adds to
+    /// them. Empty unless `record_events` is on.
+    pub fn last_events(&self) -> &[SimEvent] {
+        self.events.as_deref().unwrap_or_default()
+    }
+    
+    /// Run until the first fight, returning where it was, the ants it
+    /// killed and the (0-based) iteration it happened in, or `None` if the
+    /// run ends without one. Ants killed by a cascade don't count as a fight.
+    /// The simulation is left at the end of that iteration, so it can carry on.
+    pub fn simulate_until_first_collision(&mut self) -> Option<(ColonyId, Vec<AntId>, u32)> {
+        self.first_fight = None;
+        self.watch_first_fight = true;
+        let mut found = None;
+        while self.should_continue() && self.iteration < self.max_moves {
+            let result = self.run_iteration();
+            if let Some((colony, killed)) = self.first_fight.take() {
+                found = Some((colony, killed, result.iteration));
+                break;
+            }
+        }
+        self.watch_first_fight = false;
+        found
+    }
+    
+    /// Run up to `batch_size` iterations in one call, stopping early once the
+    /// simulation is over
+    pub fn run_iteration_batch(&mut self, batch_size: usize) -> Vec<IterationResult> {
+        let mut results = Vec::with_capacity(batch_size);
+        while results.len() < batch_size && self.should_continue() {
+            results.push(self.run_iteration());
+        }
+        results
+    }
+    
+    /// Debug builds check the whole of `check_invariants` after every
+    /// iteration, so drifting bookkeeping fails the iteration that caused it
+    #[cfg(debug_assertions)]
+    fn assert_invariants(&self) {
+        if let Err(problem) = self.check_invariants() {
+            panic!("simulation invariant broken after iteration {}: {}", self.iteration, problem);
+        }
+    }
+    
+    /// Break the bookkeeping on purpose, counting an ant at `colony_id` that
+    /// isn't there, to check the debug assertions fire. Debug builds only.
+    #[doc(hidden)]
+    #[cfg(debug_assertions)]
+    pub fn corrupt_ant_count(&mut self, colony_id: ColonyId) {
+        self.ant_count[colony_id] += 1;
+    }
+    
+    /// Check that the simulation's bookkeeping agrees with itself: the
+    /// counters with the flags they summarize, the colony lists with the ant
+    /// positions, the live colony list and in-degrees with the destroyed
+    /// flags (the in-degrees in total). O(ants + tunnels) and allocation-free
+    /// unless something is wrong, so debug builds run it after every
+    /// iteration. Returns the first thing found out of line.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let alive = || (0..self.total_ants).filter(|&ant| self.ant_is_alive_bit(ant));
+        let alive_count = alive().count();
+        let stray_bits = self.ant_alive_bits.iter().map(|word| word.count_ones() as usize).sum::<usize>() - alive_count;
+        if alive_count != self.alive_ants || stray_bits != 0 {
+            return Err(format!("alive_ants is {} but {} ants are flagged alive", self.alive_ants, alive_count));
+        }
+        let under_max_moves = alive().filter(|&ant| self.move_count[ant] < self.max_moves).count();
+        if under_max_moves != self.active_ants_under_max_moves {
+            return Err(format!(
+                "active_ants_under_max_moves is {} but {} alive ants have moves left",
+                self.active_ants_under_max_moves, under_max_moves
+            ));
+        }
+        let moves: u64 = self.move_count.iter().map(|&moves| moves as u64).sum();
+        if moves != self.total_moves_made {
+            return Err(format!("total_moves_made is {} but the ants made {} moves", self.total_moves_made, moves));
+        }
+        
+        let mut listed = 0;
+        for (colony, ants) in self.ants_at_colony.iter().enumerate() {
+            if self.ant_count[colony] as usize != ants.len() {
+                let counted = self.ant_count[colony];
+                return Err(format!("colony {} counts {} ants but lists {}", colony, counted, ants.len()));
+            }
+            // only a fight that kills everyone empties the colony it destroys
+            if self.destroyed[colony] && self.collision_policy == CollisionPolicy::KillAll && !ants.is_empty() {
+                return Err(format!("destroyed colony {} still lists {} ants", colony, ants.len()));
+            }
+            for (slot, &ant) in ants.iter().enumerate() {
+                if !self.ant_is_alive_bit(ant) {
+                    return Err(format!("dead ant {} is listed at colony {}", ant, colony));
+                }
+                if self.ant_position[ant] as usize != colony || self.ant_slot[ant] as usize != slot {
+                    return Err(format!("ant {} is listed at colony {} but isn't there", ant, colony));
+                }
+            }
+            listed += ants.len();
+        }
+        if listed != self.alive_ants {
+            return Err(format!("{} ants are listed at colonies but {} are alive", listed, self.alive_ants));
+        }
+        let unlisted = self.ant_slot.iter().filter(|&&slot| slot == NO_SLOT).count();
+        if listed + unlisted != self.total_ants {
+            return Err(format!("{} ants are listed and {} have no slot, out of {}", listed, unlisted, self.total_ants));
+        }
+        
+        let destroyed = self.destroyed.iter().filter(|&&destroyed| destroyed).count();
+        if destroyed != self.colonies_destroyed || self.alive_colony_ids.len() != self.total_colonies - destroyed {
+            return Err(format!(
+                "colonies_destroyed is {} and {} colonies are listed live, but {} are flagged destroyed",
+                self.colonies_destroyed,
+                self.alive_colony_ids.len(),
+                destroyed
+            ));
+        }
+        for (index, &colony) in self.alive_colony_ids.iter().enumerate() {
+            if self.destroyed[colony] || self.alive_colony_index[colony] as usize != index {
+                return Err(format!("colony {} is out of place in the live colony list", colony));
+            }
+        }
+        // in total; checking each colony's would take a second in-degree table
+        let in_degrees: usize = self.colony_in_degree.iter().map(|&in_degree| in_degree as usize).sum();
+        let live_tunnels: usize = self.alive_colony_ids.iter().map(|&colony| self.colony_degree(colony)).sum();
+        if in_degrees != live_tunnels {
+            return Err(format!("in-degrees add up to {} but {} tunnels leave live colonies", in_degrees, live_tunnels));
+        }
+        
+        if self.faction_mode() {
+            for (faction, &kept) in self.faction_alive_counts.iter().enumerate() {
+                let counted = alive().filter(|&ant| self.ant_faction[ant] as usize == faction).count();
+                if counted != kept {
+                    return Err(format!("faction {} counts {} alive ants but has {}", faction, kept, counted));
+                }
+            }
+        }
+        if let Some(after) = self.options.oscillation_after {
+            let oscillating = alive().filter(|&ant| self.oscillation_streak[ant] > after).count();
+            if oscillating != self.oscillating_ants {
+                let kept = self.oscillating_ants;
+                return Err(format!("oscillating_ants is {} but {} live ants oscillate", kept, oscillating));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Sort the processing order by current colony for cache locality
+    fn reorder_ants(&mut self) {
+        let positions = &self.ant_position;
+        self.ant_order.sort_unstable_by_key(|&ant| positions[ant]);
+    }
+    
+    /// Number of connections in the map
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+    
+    /// Heap bytes currently held by each part of the simulation, from actual capacities
+    pub fn memory_usage(&self) -> MemoryBreakdown {
+        let colony_lists = self.ants_at_colony.capacity() * size_of::<Vec<AntId>>()
+            + self.ants_at_colony.iter().map(|ants| ants.capacity() * size_of::<AntId>()).sum::<usize>();
+        
+        MemoryBreakdown {
+            adjacency: vec_bytes(&self.graph.adjacency_list)
+                + vec_bytes(&self.graph.start_index)
+                + vec_bytes(&self.graph.connection_count),
+            edge_labels: self.graph.edge_labels.capacity() * size_of::<String>()
+                + self.graph.edge_labels.iter().map(String::capacity).sum::<usize>(),
+            names: self.graph.colony_names.heap_bytes()
+                + self.graph.aliases.capacity() * size_of::<(String, ColonyId)>()
+                + self.graph.aliases.iter().map(|(alias, _)| alias.capacity()).sum::<usize>(),
+            colony_state: vec_bytes(&self.ant_count)
+                + vec_bytes(&self.colony_peak_ant_count)
+                + vec_bytes(&self.colony_in_degree)
+                + vec_bytes(&self.destroyed)
+                + vec_bytes(&self.alive_colony_ids)
+                + vec_bytes(&self.alive_colony_index)
+                + self.components.as_ref().map_or(0, Components::heap_bytes),
+            colony_lists,
+            ant_state: vec_bytes(&self.ant_position)
+                + vec_bytes(&self.move_count)
+                + vec_bytes(&self.ant_alive_bits)
+                + vec_bytes(&self.ant_slot)
+                + vec_bytes(&self.ant_order)
+                + vec_bytes(&self.alive_ant_order)
+                + vec_bytes(&self.failed_moves)
+                + vec_bytes(&self.previous_position)
+                + vec_bytes(&self.oscillation_streak),
+            factions: vec_bytes(&self.ant_faction) + vec_bytes(&self.faction_alive_counts),
+            scratch: vec_bytes(&self.move_buffer)
+                + vec_bytes(&self.collision_check_buffer)
+                + vec_bytes(&self.cascade_buffer)
+                + vec_bytes(&self.killed_ants),
+        }
+    }
+    
+    /// Hot-loop counters so far, `None` when they're compiled out
+    pub fn perf_counters(&self) -> Option<PerfCounters> {
+        #[cfg(any(feature = "perf-counters", debug_assertions))]
+        return Some(self.perf);
+        #[cfg(not(any(feature = "perf-counters", debug_assertions)))]
+        return None;
+    }
+    
+    /// Direction label of a colony's `neighbor_index`-th connection
+    pub fn edge_label(&self, from: ColonyId, neighbor_index: usize) -> &str {
+        self.graph.edge_label(from, neighbor_index)
+    }
+    
+    /// Write the surviving colonies and tunnels in the map file format
+    pub fn write_map<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        self.write_map_ordered(out, MapOrder::Declared)
+    }
+    
+    /// `write_map` in `order`, see `MapGraph::write_map_ordered`
+    pub fn write_map_ordered<W: Write + ?Sized>(&self, out: &mut W, order: MapOrder) -> io::Result<()> {
+        self.graph.write_map_ordered(out, |colony| !self.destroyed[colony], order)
+    }
+    
+    /// Write the remaining map with its header and the alive ant count
+    pub fn write_remaining_world<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        self.write_remaining_world_ordered(out, MapOrder::Declared)
+    }
+    
+    /// `write_remaining_world` with the map in `order`
+    pub fn write_remaining_world_ordered<W: Write + ?Sized>(&self, out: &mut W, order: MapOrder) -> io::Result<()> {
+        writeln!(out, "\n=== Remaining World ===")?;
+        self.write_map_ordered(out, order)?;
+        writeln!(out, "\nAlive ants: {}/{}", self.alive_ants, self.total_ants)
+    }
+    
+    /// Print the remaining map
+    pub fn print_remaining_world(&self) {
+        let stdout = std::io::stdout();
+        self.write_remaining_world(&mut stdout.lock()).expect("failed to write to stdout");
+    }
+    
+    /// Shape of the live map, see `GraphStats`. Searches from every colony
+    /// for the diameter on small maps.
+    pub fn graph_stats(&self) -> GraphStats {
+        GraphStats::of(&self.graph, &self.destroyed)
+    }
+    
+    /// Print `graph_stats`, for a look at a map before running it
+    pub fn print_graph_stats(&self) {
+        println!("{}", self.graph_stats());
+    }
+    
+    /// Moves made per ant, over every ant
+    pub fn ant_move_statistics(&self) -> MoveStats {
+        self.move_statistics(|_| true)
+    }
+    
+    /// Moves made per ant, over the ants still alive
+    pub fn alive_ant_move_statistics(&self) -> MoveStats {
+        self.move_statistics(|alive| alive)
+    }
+    
+    /// Moves made per ant, over the ants that died (how long they lasted)
+    pub fn dead_ant_move_statistics(&self) -> MoveStats {
+        self.move_statistics(|alive| !alive)
+    }
+    
+    fn move_statistics(&self, include: impl Fn(bool) -> bool) -> MoveStats {
+        let mut moves: Vec<u32> = (0..self.total_ants)
+            .filter(|&ant| include(self.ant_is_alive_bit(ant)))
+            .map(|ant| self.move_count[ant])
+            .collect();
+        MoveStats::from_samples(&mut moves)
+    }
+    
+    /// Get statistics
+    pub fn stats(&self) -> (usize, usize, usize) {
+        (self.alive_ants, self.total_colonies - self.colonies_destroyed, self.total_colonies)
+    }
+    
+    /// Colonies destroyed so far, O(1)
+    pub fn colonies_destroyed(&self) -> usize {
+        self.colonies_destroyed
+    }
+    
+    /// The current turn number, 0 before the first iteration: iterations
+    /// run since the start, a resumed run's included. Goes up by one at the
+    /// end of every `run_iteration`, so during a run it's one past the last
+    /// `IterationResult::iteration`; also in `SimulationStats::iterations`.
+    pub fn current_iteration(&self) -> u32 {
+        self.iteration
+    }
+    
+    /// Same as `current_iteration`
+    pub fn iteration(&self) -> u32 {
+        self.current_iteration()
+    }
+    
+    /// Most ants that were ever at a colony at once, placement included. A
+    /// destroyed colony keeps the count it had when it fell.
+    pub fn peak_ant_count_at(&self, colony: ColonyId) -> u32 {
+        self.colony_peak_ant_count[colony]
+    }
+    
+    /// Ants at `colony` as a fraction of the `collision_threshold` that
+    /// starts a fight there: with the default threshold of 2, 0.5 is one ant
+    /// and 1.0 a fight due
+    pub fn density_at(&self, colony: ColonyId) -> f64 {
+        self.ant_count[colony] as f64 / self.collision_threshold as f64
+    }
+    
+    /// Mean `density_at` over the live colonies, 0 with none left
+    pub fn average_density(&self) -> f64 {
+        if self.alive_colony_ids.is_empty() {
+            return 0.0;
+        }
+        let ants: usize = self.alive_colony_ids.iter().map(|&colony| self.ant_count[colony] as usize).sum();
+        ants as f64 / self.alive_colony_ids.len() as f64 / self.collision_threshold as f64
+    }
+    
+    /// The live colony with the highest `density_at`, the lowest ID on ties;
+    /// `None` once every colony is destroyed
+    pub fn max_density_colony(&self) -> Option<(ColonyId, f64)> {
+        let colony = (0..self.total_colonies)
+            .filter(|&colony| !self.destroyed[colony])
+            .max_by_key(|&colony| (self.ant_count[colony], std::cmp::Reverse(colony)))?;
+        Some((colony, self.density_at(colony)))
+    }
+    
+    /// Tunnels out of `colony` on the map, whether or not they lead anywhere live
+    pub fn colony_degree(&self, colony: ColonyId) -> usize {
+        self.graph.neighbors(colony).len()
+    }
+    
+    /// Whether each colony is destroyed, by colony ID
+    #[cfg(feature = "arrow")]
+    pub(crate) fn destroyed_flags(&self) -> &[bool] {
+        &self.destroyed
+    }
+    
+    /// Ants at each colony, by colony ID
+    #[cfg(feature = "arrow")]
+    pub(crate) fn ant_counts(&self) -> &[u16] {
+        &self.ant_count
+    }
+    
+    /// Tunnels out of `colony` into live colonies, the moves an ant there
+    /// can make. Scans the colony's tunnels.
+    pub fn colony_out_degree(&self, colony: ColonyId) -> usize {
+        let neighbors = self.graph.neighbors(colony);
+        neighbors.iter().filter(|&&neighbor| !self.destroyed[neighbor as ColonyId]).count()
+    }
+    
+    /// Tunnels into `colony` from live colonies, O(1). Matches
+    /// `colony_out_degree` on maps where every tunnel goes both ways.
+    pub fn colony_in_degree(&self, colony: ColonyId) -> usize {
+        self.colony_in_degree[colony] as usize
+    }
+    
+    /// Rough bounds on how long the run can take from here, see
+    /// `ComplexityEstimate`. Scans the map once.
+    pub fn simulation_complexity_estimate(&self) -> ComplexityEstimate {
+        let live_degrees: Vec<u32> = (0..self.total_colonies)
+            .filter(|&colony| !self.destroyed[colony])
+            .map(|colony| self.colony_out_degree(colony) as u32)
+            .collect();
+        ComplexityEstimate::new(self.alive_ants, &live_degrees, self.max_moves, self.collision_threshold)
+    }
+    
+    /// Moves made by all ants since the start, without scanning the ants
+    pub fn total_moves_made(&self) -> u64 {
+        self.total_moves_made
+    }
+    
+    /// Fingerprint of the state that decides how the run goes on: which
+    /// colonies are destroyed, which ants are alive, where every ant is, its
+    /// moves so far and the iteration, hashed in that order. Two simulations
+    /// in the same state hash the same on any machine and across program
+    /// runs, so long runs can be compared a hash at a time; the value may
+    /// change between crate versions. O(ants + colonies).
+    pub fn state_hash(&self) -> u64 {
+        let mut hash = 0;
+        let mut mix = |value: u64| hash = splitmix64(hash ^ value);
+        for word in self.destroyed.chunks(64) {
+            mix(word.iter().enumerate().fold(0, |bits, (i, &destroyed)| bits | (destroyed as u64) << i));
+        }
+        self.ant_alive_bits.iter().for_each(|&word| mix(word));
+        self.ant_position.iter().for_each(|&colony| mix(colony as u64));
+        self.move_count.iter().for_each(|&moves| mix(moves as u64));
+        mix(self.iteration as u64);
+        hash
+    }
+    
+    /// `stats` plus the move counters
+    pub fn simulation_stats(&self) -> SimulationStats {
+        let (alive_ants, active_colonies, total_colonies) = self.stats();
+        SimulationStats {
+            alive_ants,
+            active_colonies,
+            total_colonies,
+            iterations: self.iteration,
+            total_moves_made: self.total_moves_made,
+            moves_per_iteration: self.moves_per_iteration(),
+            peak_ant_counts: self.colony_peak_ant_count.clone(),
+        }
+    }
+    
+    /// Average moves per iteration run so far, 0 before the first one
+    fn moves_per_iteration(&self) -> f64 {
+        if self.iteration > 0 {
+            self.total_moves_made as f64 / self.iteration as f64
+        } else {
+            0.0
+        }
+    }
+    
+    /// `simulation_stats` without the per-colony vector, plus the busiest
+    /// colonies, the latest destructions and the termination reason, for a
+    /// dashboard or a notebook cell. O(colonies) for the busiest colonies.
+    pub fn summary(&self) -> Summary {
+        let (alive_ants, active_colonies, total_colonies) = self.stats();
+        let mut busiest: Vec<ColonyId> =
+            (0..self.total_colonies).filter(|&colony| self.colony_peak_ant_count[colony] > 0).collect();
+        let by_peak = |&a: &ColonyId, &b: &ColonyId| {
+            self.colony_peak_ant_count[b].cmp(&self.colony_peak_ant_count[a]).then(a.cmp(&b))
+        };
+        if busiest.len() > BUSIEST_COLONIES {
+            busiest.select_nth_unstable_by(BUSIEST_COLONIES, by_peak);
+            busiest.truncate(BUSIEST_COLONIES);
+        }
+        busiest.sort_unstable_by(by_peak);
+        
+        Summary {
+            seed: self.seed,
+            iteration: self.iteration,
+            max_moves: self.max_moves,
+            total_ants: self.total_ants,
+            alive_ants,
+            total_colonies,
+            active_colonies,
+            colonies_destroyed: self.colonies_destroyed,
+            total_moves_made: self.total_moves_made,
+            moves_per_iteration: self.moves_per_iteration(),
+            busiest_colonies: busiest
+                .into_iter()
+                .map(|id| ColonySummary {
+                    id,
+                    name: self.graph.colony_name(id).to_string(),
+                    peak_ant_count: self.colony_peak_ant_count[id],
+                    destroyed: self.destroyed[id],
+                })
+                .collect(),
+            recent_destructions: self
+                .recent_destructions
+                .iter()
+                .map(|&(iteration, colony)| Destruction {
+                    iteration,
+                    colony,
+                    name: self.graph.colony_name(colony).to_string(),
+                })
+                .collect(),
+            termination: self.termination(),
+        }
+    }
+}
+
+/// Counters and the first few entries of the per-ant and per-colony state,
+/// cheap however big the simulation
+impl fmt::Debug for AntSimulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AntSimulation")
+            .field("seed", &self.seed)
+            .field("iteration", &self.iteration)
+            .field("max_moves", &self.max_moves)
+            .field("total_ants", &self.total_ants)
+            .field("alive_ants", &self.alive_ants)
+            .field("total_colonies", &self.total_colonies)
+            .field("colonies_destroyed", &self.colonies_destroyed)
+            .field("collision_policy", &self.collision_policy)
+            .field("fight_trigger", &self.fight_trigger)
+            .field("options", &self.options)
+            .field("graph", &self.graph)
+            .field("ant_position", &Preview(&self.ant_position))
+            .field("move_count", &Preview(&self.move_count))
+            .field("ant_count", &Preview(&self.ant_count))
+            .field("destroyed", &Preview(&self.destroyed))
+            .finish_non_exhaustive()
+    }
+}
+
+#[inline]
+fn is_alive_in(bits: &[u64], ant: AntId) -> bool {
+    bits[ant / 64] & (1 << (ant % 64)) != 0
+}
+
+/// Tunnels into each colony from the colonies not yet `destroyed`
+fn live_in_degrees(graph: &MapGraph, destroyed: &[bool]) -> Vec<u32> {
+    let mut in_degree = vec![0; graph.colony_count()];
+    for colony in (0..graph.colony_count()).filter(|&colony| !destroyed[colony]) {
+        for &neighbor in graph.neighbors(colony) {
+            in_degree[neighbor as ColonyId] += 1;
+        }
+    }
+    in_degree
+}
+
+/// The live colonies of `destroyed` in ID order, and each one's index in
+/// that list
+fn alive_colony_list(destroyed: &[bool]) -> (Vec<ColonyId>, Vec<u32>) {
+    let alive: Vec<ColonyId> = (0..destroyed.len()).filter(|&colony| !destroyed[colony]).collect();
+    let mut index = vec![NO_SLOT; destroyed.len()];
+    for (slot, &colony) in alive.iter().enumerate() {
+        index[colony] = slot as u32;
+    }
+    (alive, index)
+}
+
+/// Alive bitset with the first `num_ants` bits set
+fn alive_bits(num_ants: usize) -> Vec<u64> {
+    let mut bits = vec![u64::MAX; num_ants / 64];
+    let partial = num_ants % 64;
+    if partial > 0 {
+        bits.push((1 << partial) - 1);
+    }
+    bits
+}
+
+/// Catch placements that can't work before anything is allocated
+fn check_placement(strategy: &PlacementStrategy, num_ants: usize, total_colonies: usize) -> Result<(), SimError> {
+    if let PlacementStrategy::Explicit(positions) = strategy {
+        return check_positions(positions, num_ants, total_colonies);
+    }
+    if num_ants == 0 {
+        return Ok(());
+    }
+    let problem = match strategy {
+        PlacementStrategy::Uniform => None,
+        PlacementStrategy::Distinct if num_ants > total_colonies => {
+            Some(format!("can't give {} ants a colony each on a map of {} colonies", num_ants, total_colonies))
+        }
+        PlacementStrategy::Distinct => None,
+        &PlacementStrategy::Clustered { clusters, .. } if clusters == 0 || clusters > total_colonies => {
+            Some(format!("need between 1 and {} clusters, got {}", total_colonies, clusters))
+        }
+        PlacementStrategy::Clustered { .. } => None,
+        &PlacementStrategy::Single(colony_id) if colony_id >= total_colonies => {
+            Some(format!("colony {} doesn't exist", colony_id))
+        }
+        PlacementStrategy::Single(_) if num_ants > u16::MAX as usize => {
+            Some(format!("at most {} ants fit on a single colony", u16::MAX))
+        }
+        PlacementStrategy::Single(_) => None,
+        PlacementStrategy::Explicit(_) => unreachable!("checked above"),
+    };
+    match problem {
+        Some(reason) => Err(SimError::InvalidConfig(reason)),
+        None => Ok(()),
+    }
+}
+
+/// An explicit placement needs a position per ant, on colonies that exist and
+/// that don't overflow a colony's ant count
+fn check_positions(positions: &[Option<ColonyId>], num_ants: usize, total_colonies: usize) -> Result<(), SimError> {
+    if positions.len() != num_ants {
+        return Err(SimError::InvalidConfig(format!(
+            "explicit placement has {} positions for {} ants",
+            positions.len(),
+            num_ants
+        )));
+    }
+    let mut ant_count = vec![0usize; total_colonies];
+    for (ant_id, &colony_id) in positions.iter().enumerate() {
+        let Some(colony_id) = colony_id else {
+            continue;
+        };
+        if colony_id >= total_colonies {
+            let reason = format!("ant {} is placed on colony {}, which doesn't exist", ant_id, colony_id);
+            return Err(SimError::InvalidConfig(reason));
+        }
+        ant_count[colony_id] += 1;
+        if ant_count[colony_id] > u16::MAX as usize {
+            return Err(SimError::InvalidConfig(format!("at most {} ants fit on a single colony", u16::MAX)));
+        }
+    }
+    Ok(())
+}
+
+/// `run_iteration`'s move and collision buffers at their working size: room
+/// for every tunnel out of the busiest colony, and for every colony that can
+/// hold a fight at once
+fn scratch_buffers(graph: &MapGraph, num_ants: usize, threshold: u16) -> (Vec<ColonyId>, Vec<ColonyId>) {
+    let max_degree = graph.connection_counts().iter().max().map_or(0, |&count| count as usize);
+    let fights = (num_ants / threshold.max(1) as usize).min(graph.colony_count());
+    (Vec::with_capacity(max_degree), Vec::with_capacity(fights))
+}
+
+/// Heap bytes reserved by a Vec
+fn vec_bytes<T>(v: &Vec<T>) -> usize {
+    v.capacity() * size_of::<T>()
+}
+
+/// Counter-based mixer for the per-ant move streams
+#[inline]
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+impl TryFrom<(MapGraph, AntConfig)> for AntSimulation {
+    type Error = SimError;
+    
+    fn try_from((graph, config): (MapGraph, AntConfig)) -> Result<Self, SimError> {
+        AntSimulation::new(Arc::new(graph), config)
+    }
+}
+
+/// Simulations are equal when they're in the same state as `state_hash`
+/// sees it: the same colonies destroyed, the same ants alive, every ant in
+/// the same place with the same moves, at the same iteration. The map, the
+/// rules and the RNG aren't compared, so equal simulations on different maps
+/// or with different seeds can still go different ways from here.
+impl PartialEq for AntSimulation {
+    fn eq(&self, other: &Self) -> bool {
+        self.iteration == other.iteration
+            && self.destroyed == other.destroyed
+            && self.ant_alive_bits == other.ant_alive_bits
+            && self.ant_position == other.ant_position
+            && self.move_count == other.move_count
+    }
+}
+
+impl Eq for AntSimulation {}
+
+/// Hashes the state `PartialEq` compares, by feeding in `state_hash`
+impl Hash for AntSimulation {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.state_hash());
+    }
+}
+
+/// A placeholder simulation: one colony called `default`, no tunnels and no
+/// ants, with seed 0. It's over before it starts, `should_continue()` is
+/// false.
+impl Default for AntSimulation {
+    fn default() -> Self {
+        let mut colony_names = ColonyNames::new();
+        colony_names.get_or_insert("default");
+        colony_names.finish();
+        let graph = MapGraph::from_parts(colony_names, Vec::new(), vec![0], vec![0], Vec::new(), Vec::new());
+        let config = AntConfig { seed: Some(0), ..AntConfig::new(0) };
+        AntSimulation::new(Arc::new(graph), config).expect("the default simulation is valid")
+    }
+}
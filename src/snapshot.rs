@@ -0,0 +1,234 @@
+use std::fmt;
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{CollisionPolicy, FightTrigger};
+use crate::error::SimError;
+use crate::graph::{Attributes, ColonyIndex, MapGraph};
+use crate::names::ColonyNames;
+use crate::options::SimOptions;
+use crate::simulation::{AntId, ColonyId};
+use crate::summary::Preview;
+
+/// Snapshot layout version, bumped whenever a field changes
+pub const SNAPSHOT_VERSION: u32 = 7;
+
+/// First bytes of every bincode snapshot file, ahead of the version
+const SNAPSHOT_MAGIC: &[u8; 8] = b"antsnap\0";
+
+/// First bytes of every MessagePack snapshot file, ahead of the version
+const MSGPACK_MAGIC: &[u8; 8] = b"antsnapM";
+
+/// How a snapshot's state is encoded. Both start with a magic number and
+/// `SNAPSHOT_VERSION` as 4 little-endian bytes, and `Snapshot::read_from`
+/// goes by the magic number, whatever the file is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SnapshotFormat {
+    /// The fastest to write and read, but only Rust's bincode reads it
+    #[default]
+    Bincode,
+    /// MessagePack with named fields, for inspecting snapshots with other
+    /// tools; around half the size, but 2-3x slower (see the `snapshot`
+    /// bench). Needs the `msgpack` feature
+    MessagePack,
+}
+
+impl SnapshotFormat {
+    /// File extension of the format, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            SnapshotFormat::Bincode => "bin",
+            SnapshotFormat::MessagePack => "msgpack",
+        }
+    }
+
+    fn magic(self) -> &'static [u8; 8] {
+        match self {
+            SnapshotFormat::Bincode => SNAPSHOT_MAGIC,
+            SnapshotFormat::MessagePack => MSGPACK_MAGIC,
+        }
+    }
+}
+
+/// Everything needed to carry a simulation on exactly where it stopped:
+/// the map plus every piece of state that survives an iteration boundary.
+/// Taken between iterations with `AntSimulation::snapshot`, turned back into
+/// a simulation with `AntSimulation::from_snapshot`. Perf counters start
+/// over, and tracked components are rebuilt.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub(crate) map: MapSnapshot,
+
+    pub(crate) ant_count: Vec<u16>,
+    pub(crate) colony_peak_ant_count: Vec<u32>,
+    pub(crate) destroyed: Vec<bool>,
+
+    pub(crate) ant_position: Vec<ColonyIndex>,
+    pub(crate) move_count: Vec<u32>,
+    pub(crate) ant_alive_bits: Vec<u64>,
+    pub(crate) ants_at_colony: Vec<Vec<AntId>>,
+    pub(crate) ant_slot: Vec<u32>,
+    pub(crate) ant_order: Vec<AntId>,
+    pub(crate) ant_faction: Vec<u8>,
+    pub(crate) faction_alive_counts: Vec<usize>,
+    pub(crate) failed_moves: Vec<u32>,
+    /// Set until the first iteration after a `PlacementStrategy::Single` start
+    pub(crate) start_crowd: Option<ColonyId>,
+
+    pub(crate) options: SimOptions,
+    pub(crate) max_moves: u32,
+    pub(crate) collision_threshold: u16,
+    pub(crate) collision_policy: CollisionPolicy,
+    pub(crate) fight_trigger: FightTrigger,
+
+    pub(crate) seed: u64,
+    /// Where the shared RNG's stream had got to
+    pub(crate) rng_state: u64,
+    pub(crate) stream_seed: u64,
+    pub(crate) iteration: u32,
+    pub(crate) total_moves_made: u64,
+
+    pub(crate) total_ants: usize,
+    pub(crate) alive_ants: usize,
+    pub(crate) active_ants_under_max_moves: usize,
+}
+
+/// Counters and the first few ant positions, cheap however big the snapshot
+impl fmt::Debug for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snapshot")
+            .field("seed", &self.seed)
+            .field("iteration", &self.iteration)
+            .field("total_ants", &self.total_ants)
+            .field("alive_ants", &self.alive_ants)
+            .field("colonies", &self.map.colony_names.len())
+            .field("tunnels", &self.map.adjacency_list.len())
+            .field("ant_position", &Preview(&self.ant_position))
+            .finish_non_exhaustive()
+    }
+}
+
+/// The map as stored in a snapshot, so a snapshot doesn't depend on the map
+/// file still being around
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MapSnapshot {
+    colony_names: Vec<String>,
+    adjacency_list: Vec<ColonyIndex>,
+    start_index: Vec<u32>,
+    connection_count: Vec<u8>,
+    edge_labels: Vec<String>,
+    aliases: Vec<(String, u32)>,
+    attributes: Vec<Attributes>,
+}
+
+impl Snapshot {
+    /// Iterations the simulation had run when the snapshot was taken
+    pub fn iteration(&self) -> u32 {
+        self.iteration
+    }
+
+    /// Ants placed at the start of the run, dead or alive
+    pub fn total_ants(&self) -> usize {
+        self.total_ants
+    }
+
+    /// Was the snapshot taken on a simulation of `graph`
+    pub fn matches_map(&self, graph: &MapGraph) -> bool {
+        self.map == MapSnapshot::of(graph)
+    }
+
+    /// Write the snapshot in its binary format: a magic number and the
+    /// version, then the state
+    pub fn write_to<W: Write>(&self, out: W) -> Result<(), SimError> {
+        self.write_as(out, SnapshotFormat::Bincode)
+    }
+
+    /// `write_to` in either format
+    pub fn write_as<W: Write>(&self, mut out: W, format: SnapshotFormat) -> Result<(), SimError> {
+        out.write_all(format.magic()).map_err(|err| SimError::Snapshot(err.to_string()))?;
+        out.write_all(&SNAPSHOT_VERSION.to_le_bytes()).map_err(|err| SimError::Snapshot(err.to_string()))?;
+        match format {
+            SnapshotFormat::Bincode => bincode::serialize_into(out, self).map_err(snapshot_error),
+            #[cfg(feature = "msgpack")]
+            SnapshotFormat::MessagePack => {
+                rmp_serde::encode::write_named(&mut out, self).map_err(|err| SimError::Snapshot(err.to_string()))
+            }
+            #[cfg(not(feature = "msgpack"))]
+            SnapshotFormat::MessagePack => Err(msgpack_compiled_out()),
+        }
+    }
+
+    /// Read a snapshot written by `write_to` or `write_as`, in the format its
+    /// magic number says, refusing other versions before trying to make
+    /// sense of the state
+    pub fn read_from<R: Read>(mut input: R) -> Result<Self, SimError> {
+        let mut header = [0; SNAPSHOT_MAGIC.len() + 4];
+        let format = match input.read_exact(&mut header) {
+            Ok(()) if header[..8] == *SNAPSHOT_MAGIC => SnapshotFormat::Bincode,
+            Ok(()) if header[..8] == *MSGPACK_MAGIC => SnapshotFormat::MessagePack,
+            _ => return Err(SimError::Snapshot("not an ant_mania snapshot".to_string())),
+        };
+        let version = u32::from_le_bytes(header[8..].try_into().expect("4 bytes"));
+        if version != SNAPSHOT_VERSION {
+            return Err(SimError::Snapshot(format!(
+                "snapshot version {} isn't supported, this build reads version {}",
+                version, SNAPSHOT_VERSION
+            )));
+        }
+        match format {
+            SnapshotFormat::Bincode => bincode::deserialize_from(input).map_err(snapshot_error),
+            #[cfg(feature = "msgpack")]
+            SnapshotFormat::MessagePack => {
+                rmp_serde::decode::from_read(input).map_err(|err| SimError::Snapshot(err.to_string()))
+            }
+            #[cfg(not(feature = "msgpack"))]
+            SnapshotFormat::MessagePack => Err(msgpack_compiled_out()),
+        }
+    }
+}
+
+impl MapSnapshot {
+    pub(crate) fn of(graph: &MapGraph) -> Self {
+        MapSnapshot {
+            colony_names: (0..graph.colony_count()).map(|colony| graph.colony_name(colony).to_string()).collect(),
+            adjacency_list: graph.adjacency_list.clone(),
+            start_index: graph.start_index.clone(),
+            connection_count: graph.connection_count.clone(),
+            edge_labels: graph.edge_labels.clone(),
+            aliases: (graph.aliases()).map(|(alias, colony)| (alias.to_string(), colony as u32)).collect(),
+            attributes: graph.attributes.clone(),
+        }
+    }
+
+    pub(crate) fn into_graph(self) -> Result<MapGraph, SimError> {
+        let mut colony_names = ColonyNames::new();
+        for (colony, name) in self.colony_names.iter().enumerate() {
+            if colony_names.get_or_insert(name) != colony {
+                return Err(SimError::Snapshot(format!("colony name '{}' appears twice in snapshot", name)));
+            }
+        }
+        colony_names.finish();
+        let mut graph = MapGraph::from_parts(
+            colony_names,
+            self.adjacency_list,
+            self.start_index,
+            self.connection_count,
+            self.edge_labels,
+            self.aliases.into_iter().map(|(alias, colony)| (alias, colony as ColonyId)).collect(),
+        );
+        graph.attributes = self.attributes;
+        let inconsistent = |problem| SimError::Snapshot(format!("map in snapshot is inconsistent: {}", problem));
+        graph.check_structure().map_err(inconsistent)?;
+        Ok(graph)
+    }
+}
+
+fn snapshot_error(err: bincode::Error) -> SimError {
+    SimError::Snapshot(err.to_string())
+}
+
+#[cfg(not(feature = "msgpack"))]
+fn msgpack_compiled_out() -> SimError {
+    SimError::Snapshot("MessagePack snapshots are compiled out, rebuild with --features msgpack".to_string())
+}
@@ -0,0 +1,60 @@
+use std::fmt;
+
+/// Summary of a set of counts (moves per ant, survivors per run, ...)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SampleStats {
+    /// Number of samples, every other field is 0 when this is
+    pub count: usize,
+    pub min: u32,
+    pub max: u32,
+    pub mean: f64,
+    /// Population standard deviation
+    pub stddev: f64,
+    pub median: f64,
+}
+
+/// Stats over how many moves each ant in a group made
+pub type MoveStats = SampleStats;
+
+impl SampleStats {
+    /// Stats over `samples`, which gets reordered to find the median
+    pub fn from_samples(samples: &mut [u32]) -> Self {
+        if samples.is_empty() {
+            return SampleStats::default();
+        }
+
+        let count = samples.len();
+        let mut min = u32::MAX;
+        let mut max = 0;
+        let mut sum = 0u64;
+        for &sample in samples.iter() {
+            min = min.min(sample);
+            max = max.max(sample);
+            sum += sample as u64;
+        }
+        let mean = sum as f64 / count as f64;
+        let variance = samples.iter().map(|&sample| (sample as f64 - mean).powi(2)).sum::<f64>() / count as f64;
+
+        let mid = count / 2;
+        let (_, &mut upper, _) = samples.select_nth_unstable(mid);
+        let median = if count % 2 == 1 {
+            upper as f64
+        } else {
+            // everything left of `mid` is <= upper, so the lower middle is their max
+            let lower = samples[..mid].iter().copied().max().unwrap_or(upper);
+            (lower as f64 + upper as f64) / 2.0
+        };
+
+        SampleStats { count, min, max, mean, stddev: variance.sqrt(), median }
+    }
+}
+
+impl fmt::Display for SampleStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "count {}, min {}, max {}, mean {:.1}, stddev {:.1}, median {:.1}",
+            self.count, self.min, self.max, self.mean, self.stddev, self.median
+        )
+    }
+}
@@ -0,0 +1,221 @@
+//! Conditions that end a run early, like `alive_ants<=2` or
+//! `colonies_destroyed>=100`: a metric, a comparison and a number or a
+//! second metric (`oscillating_ants==alive_ants`). The metrics also rank
+//! the runs of a batch.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::batch::RunSummary;
+use crate::error::SimError;
+use crate::simulation::AntSimulation;
+
+/// Something about a running simulation a condition can test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Iterations run since the start
+    Iterations,
+    AliveAnts,
+    DeadAnts,
+    /// Colonies not destroyed
+    ActiveColonies,
+    ColoniesDestroyed,
+    /// Moves made by all ants since the start
+    TotalMoves,
+    /// Live ants going back and forth between two colonies, 0 unless
+    /// oscillations are detected, see `AntSimulation::oscillating_ants`
+    OscillatingAnts,
+}
+
+impl Metric {
+    /// Every metric, in the order error messages list them
+    pub const ALL: [Metric; 7] = [
+        Metric::Iterations,
+        Metric::AliveAnts,
+        Metric::DeadAnts,
+        Metric::ActiveColonies,
+        Metric::ColoniesDestroyed,
+        Metric::TotalMoves,
+        Metric::OscillatingAnts,
+    ];
+
+    /// Name used in condition expressions
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Iterations => "iterations",
+            Metric::AliveAnts => "alive_ants",
+            Metric::DeadAnts => "dead_ants",
+            Metric::ActiveColonies => "active_colonies",
+            Metric::ColoniesDestroyed => "colonies_destroyed",
+            Metric::TotalMoves => "total_moves",
+            Metric::OscillatingAnts => "oscillating_ants",
+        }
+    }
+
+    /// The metric called `name`, with an error listing the valid names
+    pub fn from_name(name: &str) -> Result<Metric, SimError> {
+        Metric::named(name).ok_or_else(|| SimError::InvalidConfig(unknown_metric(name)))
+    }
+
+    fn named(name: &str) -> Option<Metric> {
+        Metric::ALL.into_iter().find(|metric| metric.name() == name)
+    }
+
+    /// Value at the end of a batch run
+    pub fn of_run(self, run: &RunSummary) -> u64 {
+        match self {
+            Metric::Iterations => run.iterations as u64,
+            Metric::AliveAnts => run.alive_ants as u64,
+            Metric::DeadAnts => run.dead_ants as u64,
+            Metric::ActiveColonies => run.active_colonies as u64,
+            Metric::ColoniesDestroyed => run.colonies_destroyed as u64,
+            Metric::TotalMoves => run.total_moves,
+            Metric::OscillatingAnts => run.oscillating_ants as u64,
+        }
+    }
+
+    /// Current value in `sim`, O(1)
+    pub fn value(self, sim: &AntSimulation) -> u64 {
+        let (alive_ants, active_colonies, _) = sim.stats();
+        match self {
+            Metric::Iterations => sim.iteration() as u64,
+            Metric::AliveAnts => alive_ants as u64,
+            Metric::DeadAnts => (sim.total_ants() - alive_ants) as u64,
+            Metric::ActiveColonies => active_colonies as u64,
+            Metric::ColoniesDestroyed => sim.colonies_destroyed() as u64,
+            Metric::TotalMoves => sim.total_moves_made(),
+            Metric::OscillatingAnts => sim.oscillating_ants() as u64,
+        }
+    }
+}
+
+/// How a metric is compared with the condition's right-hand side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    /// Two-character operators first, so `>=` isn't read as `>`
+    const OPERATORS: [(&'static str, Comparison); 6] = [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Greater => ">",
+        }
+    }
+
+    pub fn holds(self, left: u64, right: u64) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Greater => left > right,
+        }
+    }
+}
+
+/// What a metric is compared with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Threshold {
+    Number(u64),
+    /// Another metric's current value
+    Metric(Metric),
+}
+
+impl Threshold {
+    pub fn value(self, sim: &AntSimulation) -> u64 {
+        match self {
+            Threshold::Number(number) => number,
+            Threshold::Metric(metric) => metric.value(sim),
+        }
+    }
+}
+
+impl fmt::Display for Threshold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Threshold::Number(number) => write!(f, "{}", number),
+            Threshold::Metric(metric) => f.write_str(metric.name()),
+        }
+    }
+}
+
+/// `metric <op> value`, met when the comparison holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StopCondition {
+    pub metric: Metric,
+    pub comparison: Comparison,
+    pub value: Threshold,
+}
+
+impl StopCondition {
+    /// Does `sim` meet the condition right now
+    pub fn is_met(&self, sim: &AntSimulation) -> bool {
+        self.comparison.holds(self.metric.value(sim), self.value.value(sim))
+    }
+}
+
+/// Parse a condition like `alive_ants<=2` or `oscillating_ants==alive_ants`;
+/// spaces around the operator are fine
+pub fn parse(spec: &str) -> Result<StopCondition, SimError> {
+    let invalid = |reason: String| SimError::InvalidConfig(format!("stop condition '{}': {}", spec, reason));
+
+    let operator_start = spec.find(['<', '>', '=', '!']).ok_or_else(|| {
+        invalid("expected METRIC OP NUMBER or METRIC OP METRIC, with OP one of <, <=, ==, !=, >=, >".to_string())
+    })?;
+    let (metric, rest) = spec.split_at(operator_start);
+    let metric = metric.trim();
+    let metric = Metric::named(metric).ok_or_else(|| invalid(unknown_metric(metric)))?;
+
+    let (symbol, comparison) = Comparison::OPERATORS
+        .into_iter()
+        .find(|(symbol, _)| rest.starts_with(symbol))
+        .ok_or_else(|| invalid("expected one of <, <=, ==, !=, >=, > after the metric".to_string()))?;
+    let right = rest[symbol.len()..].trim();
+    let value = match (right.parse(), Metric::named(right)) {
+        (Ok(number), _) => Threshold::Number(number),
+        (_, Some(metric)) => Threshold::Metric(metric),
+        _ => return Err(invalid(format!("'{}' isn't a whole number or a metric", right))),
+    };
+
+    Ok(StopCondition { metric, comparison, value })
+}
+
+fn unknown_metric(name: &str) -> String {
+    let names: Vec<_> = Metric::ALL.iter().map(|metric| metric.name()).collect();
+    format!("unknown metric '{}', expected one of {}", name, names.join(", "))
+}
+
+impl FromStr for StopCondition {
+    type Err = SimError;
+
+    fn from_str(spec: &str) -> Result<Self, SimError> {
+        parse(spec)
+    }
+}
+
+impl fmt::Display for StopCondition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}{}", self.metric.name(), self.comparison.symbol(), self.value)
+    }
+}
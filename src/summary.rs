@@ -0,0 +1,200 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::simulation::{AntId, ColonyId};
+
+/// Colonies listed in `Summary::busiest_colonies`
+pub const BUSIEST_COLONIES: usize = 10;
+
+/// Destructions kept for `Summary::recent_destructions`
+pub const RECENT_DESTRUCTIONS: usize = 10;
+
+/// Entries `Debug` shows of a long list before eliding the rest
+pub(crate) const DEBUG_PREVIEW: usize = 4;
+
+/// Where a simulation stands, in one serializable value, see
+/// `AntSimulation::summary`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Summary {
+    pub seed: u64,
+    pub iteration: u32,
+    pub max_moves: u32,
+    pub total_ants: usize,
+    pub alive_ants: usize,
+    pub total_colonies: usize,
+    pub active_colonies: usize,
+    pub colonies_destroyed: usize,
+    pub total_moves_made: u64,
+    /// Average moves per iteration run so far, 0 before the first one
+    pub moves_per_iteration: f64,
+    /// The `BUSIEST_COLONIES` colonies that held the most ants at once, most
+    /// first, ties by ID; colonies no ant ever reached are left out
+    pub busiest_colonies: Vec<ColonySummary>,
+    /// The last `RECENT_DESTRUCTIONS` colonies destroyed since the simulation
+    /// was built or resumed, oldest first
+    pub recent_destructions: Vec<Destruction>,
+    /// Why the run is over, none while it can go on
+    pub termination: Option<Termination>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ColonySummary {
+    pub id: ColonyId,
+    pub name: String,
+    pub peak_ant_count: u32,
+    pub destroyed: bool,
+}
+
+/// A colony destroyed, by a fight or in the cascade after one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Destruction {
+    /// 0-based iteration it fell in
+    pub iteration: u32,
+    pub colony: ColonyId,
+    pub name: String,
+}
+
+/// What killed an ant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeathCause {
+    /// Killed in a fight, as its colony was destroyed
+    Fight,
+    /// In a colony `cascade_on_destroy` destroyed, or `destroy_cascade`
+    Cascade,
+    /// Ran out of tries at a dead end, see `DeadEndBehavior::DieAfterNMoves`
+    DeadEnd,
+}
+
+impl DeathCause {
+    /// Name in reports, as in JSON
+    pub fn name(self) -> &'static str {
+        match self {
+            DeathCause::Fight => "fight",
+            DeathCause::Cascade => "cascade",
+            DeathCause::DeadEnd => "dead_end",
+        }
+    }
+}
+
+/// One thing that happened in an iteration, see `AntSimulation::last_events`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SimEvent {
+    /// An ant went down a tunnel, or teleported at a dead end
+    Move { ant: AntId, from: ColonyId, to: ColonyId },
+    /// Ants met at a colony, the first two of them there
+    Fight { colony: ColonyId, ant_a: AntId, ant_b: AntId },
+    /// An ant died where it stood
+    Death { ant: AntId, colony: ColonyId, cause: DeathCause },
+    /// A colony was destroyed, by a fight or in a cascade
+    Destroyed { colony: ColonyId },
+}
+
+impl SimEvent {
+    /// Name in reports, as in JSON
+    pub fn name(self) -> &'static str {
+        match self {
+            SimEvent::Move { .. } => "move",
+            SimEvent::Fight { .. } => "fight",
+            SimEvent::Death { .. } => "death",
+            SimEvent::Destroyed { .. } => "destroyed",
+        }
+    }
+}
+
+/// Ants one cause killed in one iteration, see `AntSimulation::deaths_timeline`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Deaths {
+    /// 0-based iteration they died in
+    pub iteration: u32,
+    pub cause: DeathCause,
+    pub ants: u32,
+}
+
+/// The ants that went furthest, see `AntSimulation::marathon_ants`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MarathonAnts {
+    /// Most moves made, none if no ant moved
+    pub most_moves: Option<AntRecord>,
+    /// Most distinct colonies on its `ant_path`, the one it started on
+    /// included; none unless ants are traced
+    pub most_distinct: Option<AntRecord>,
+    /// The ant that died last, its value the iteration it died in (the
+    /// iterations it lived through); none if nobody died while deaths were kept
+    pub longest_lived: Option<AntRecord>,
+}
+
+/// An ant and the count it holds a record for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AntRecord {
+    pub ant: AntId,
+    pub value: u32,
+}
+
+/// The last ant standing, see `AntSimulation::winner`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Winner {
+    pub ant: AntId,
+    /// Where it stands, ruins of a fight it survived included
+    pub colony: ColonyId,
+    pub moves: u32,
+    /// Colonies destroyed in fights it was in, see
+    /// `AntSimulation::record_fight_counts`
+    pub colonies_destroyed: u32,
+}
+
+/// Everything about one ant, see `AntSimulation::ant_view`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AntView {
+    pub ant: AntId,
+    pub alive: bool,
+    /// Alive, but every tunnel out of its colony leads to a destroyed one
+    pub trapped: bool,
+    /// Where it is, or where it was when it died
+    pub colony: ColonyId,
+    pub colony_name: String,
+    pub moves: u32,
+    /// What's left of the `max_moves` budget
+    pub moves_left: u32,
+    /// None with factions off
+    pub faction: Option<u8>,
+    /// Iteration it died in, if it died while `record_deaths` was on
+    pub died_in: Option<u32>,
+    /// Colonies it has been at, see `AntSimulation::ant_path`; None unless
+    /// it's traced
+    pub path: Option<Vec<ColonyId>>,
+}
+
+/// Why a run stopped by itself, see `AntSimulation::termination`. Being
+/// interrupted or timing out is up to whoever drives the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Termination {
+    /// Every ant was killed
+    AllAntsDead,
+    /// Every ant left made `max_moves` moves
+    MovesUsedUp,
+    /// `max_moves` iterations ran
+    IterationLimit,
+    /// Factions are on and one (or none) has ants left
+    FactionWon,
+    /// Components are tracked and none holds two ants, so no fight can happen
+    NoFightsLeft,
+}
+
+/// A long list in `Debug` output: its length and first few entries
+pub(crate) struct Preview<'a, T>(pub(crate) &'a [T]);
+
+impl<T: fmt::Debug> fmt::Debug for Preview<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.len() <= DEBUG_PREVIEW {
+            return f.debug_list().entries(self.0).finish();
+        }
+        write!(f, "{} entries ", self.0.len())?;
+        let mut list = f.debug_list();
+        list.entries(&self.0[..DEBUG_PREVIEW]);
+        list.finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,63 @@
+use ant_mania::{MapGraph, SimError};
+
+const MAP: &str = "\
+ant_mania_map v1
+New_York_City east=Boston south=DC
+Boston west=NYC
+DC north=NYC
+
+[aliases]
+NYC = New_York_City
+Beantown=Boston
+";
+
+#[test]
+fn aliases_name_existing_colonies() {
+    let graph = MapGraph::from_reader(MAP.as_bytes()).unwrap();
+    assert_eq!(graph.colony_count(), 3);
+    assert_eq!(graph.edge_count(), 4);
+    assert_eq!(graph.find_colony("NYC"), graph.find_colony("New_York_City"));
+    assert_eq!(graph.find_colony("Beantown"), Some(1));
+    assert_eq!(graph.aliases().collect::<Vec<_>>(), [("NYC", 0), ("Beantown", 1)]);
+
+    // written back under the canonical names, aliases listed after the colonies
+    let mut written = Vec::new();
+    graph.write_map(&mut written, |_| true).unwrap();
+    let written = String::from_utf8(written).unwrap();
+    assert!(written.contains("Boston west=New_York_City\n"), "{}", written);
+    assert!(written.ends_with("[aliases]\nNYC=New_York_City\nBeantown=Boston\n"), "{}", written);
+    let reread = MapGraph::from_reader(written.as_bytes()).unwrap();
+    assert_eq!(reread.aliases().collect::<Vec<_>>(), graph.aliases().collect::<Vec<_>>());
+
+    // aliases of left out colonies go with them
+    let mut without_boston = Vec::new();
+    graph.write_map(&mut without_boston, |colony| colony != 1).unwrap();
+    assert!(!String::from_utf8(without_boston).unwrap().contains("Beantown"));
+}
+
+#[test]
+fn bad_aliases_are_rejected() {
+    for (aliases, reason) in [
+        ("Gotham=Metropolis", "no colony named 'Metropolis'"),
+        ("Boston=New_York_City", "already taken"),
+        ("NYC=New_York_City\nNYC=Boston", "already taken"),
+        ("Big Apple=New_York_City", "single word"),
+        ("NYC", "expected 'alias=colony'"),
+    ] {
+        let map = format!("New_York_City east=Boston\nBoston west=New_York_City\n[aliases]\n{}\n", aliases);
+        let err = MapGraph::from_reader(map.as_bytes()).expect_err("bad alias accepted").to_string();
+        assert!(err.contains(reason), "{}: {}", aliases, err);
+    }
+}
+
+#[test]
+fn add_alias_after_loading() {
+    let mut graph = MapGraph::from_adjacency_list_raw(&[(0, 1), (1, 0)], 2).unwrap();
+    graph.add_alias(1, "Home").unwrap();
+    assert_eq!(graph.find_colony("Home"), Some(1));
+
+    assert!(matches!(graph.add_alias(0, "Home"), Err(SimError::InvalidAlias { .. })));
+    assert!(matches!(graph.add_alias(0, "Colony1"), Err(SimError::InvalidAlias { .. })));
+    assert!(matches!(graph.add_alias(2, "Away"), Err(SimError::InvalidAlias { .. })));
+    assert!(matches!(graph.add_alias(0, "a=b"), Err(SimError::InvalidAlias { .. })));
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, AntView, MapGraph, PlacementStrategy, SimOptions};
+
+/// 0 - 1 - 5 and 3 - 4 are two-way tunnels, 2 has none. Ants 0 and 1 both
+/// head for 1 and meet there, ant 2 is stuck on 2, ant 3 goes over to 4.
+fn micro_run(options: SimOptions, traced: &[usize]) -> AntSimulation {
+    let edges = [(0, 1), (1, 0), (5, 1), (1, 5), (3, 4), (4, 3)];
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 6).unwrap());
+    let config = AntConfig {
+        placement_strategy: PlacementStrategy::Explicit(vec![Some(0), Some(5), Some(2), Some(3)]),
+        seed: Some(4),
+        max_moves: 10,
+        ..AntConfig::new(4)
+    };
+    let mut sim = AntSimulation::new(graph, config).unwrap().with_options(options);
+    sim.record_deaths(true);
+    sim.trace_ants(traced);
+    sim.run_iteration();
+    sim
+}
+
+fn view(ant: usize, colony: usize, alive: bool, trapped: bool, moves: u32, died_in: Option<u32>) -> AntView {
+    AntView {
+        ant,
+        alive,
+        trapped,
+        colony,
+        colony_name: format!("Colony{}", colony),
+        moves,
+        moves_left: 10 - moves,
+        faction: None,
+        died_in,
+        path: None,
+    }
+}
+
+#[test]
+fn views_tell_the_living_the_trapped_and_the_dead() {
+    let sim = micro_run(SimOptions::default(), &[]);
+    // both fighters are shown where they fell
+    assert_eq!(sim.ant_view(0), Some(view(0, 1, false, false, 1, Some(0))));
+    assert_eq!(sim.ant_view(1), Some(view(1, 1, false, false, 1, Some(0))));
+    assert_eq!(sim.ant_view(2), Some(view(2, 2, true, true, 0, None)));
+    assert_eq!(sim.ant_view(3), Some(view(3, 4, true, false, 1, None)));
+    assert_eq!(sim.ant_view(4), None);
+}
+
+#[test]
+fn views_carry_the_faction_when_there_are_factions() {
+    // ants 0 and 1 are rivals, so the fight on 1 still happens
+    let sim = micro_run(SimOptions { factions: 2, ..SimOptions::default() }, &[]);
+    let factions: Vec<_> = (0..4).map(|ant| sim.ant_view(ant).unwrap().faction).collect();
+    assert_eq!(factions, [Some(0), Some(1), Some(0), Some(1)]);
+    assert!(!sim.ant_view(1).unwrap().alive);
+
+    let json = serde_json::to_value(sim.ant_view(2).unwrap()).unwrap();
+    assert_eq!(json["colony_name"], "Colony2");
+    assert_eq!(json["trapped"], true);
+    assert_eq!(json["died_in"], serde_json::Value::Null);
+}
+
+#[test]
+fn views_carry_the_path_of_traced_ants() {
+    let sim = micro_run(SimOptions::default(), &[0, 3]);
+    assert_eq!(sim.ant_view(0), Some(AntView { path: Some(vec![0, 1]), ..view(0, 1, false, false, 1, Some(0)) }));
+    assert_eq!(sim.ant_view(3), Some(AntView { path: Some(vec![3, 4]), ..view(3, 4, true, false, 1, None) }));
+    // untraced ants have no path, not an empty one
+    assert_eq!(sim.ant_view(2), Some(view(2, 2, true, true, 0, None)));
+    assert_eq!(serde_json::to_value(sim.ant_view(1).unwrap()).unwrap()["path"], serde_json::Value::Null);
+}
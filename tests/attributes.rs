@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, ParseWarningKind, Regions, Snapshot};
+
+fn written(graph: &MapGraph) -> String {
+    let mut out = Vec::new();
+    graph.write_map(&mut out, |_| true).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn attributes_are_read_and_written_back() {
+    let map = "\
+ant_mania_map v1
+Foo[cap=2,defense=1,protected] north=Bar
+Bar[] south=Foo
+Baz[ note = \"a, b] \\\"c\\\" \\\\ d\" , region=North ] west=Foo
+";
+    let graph = MapGraph::from_reader(map.as_bytes()).unwrap();
+    assert_eq!(graph.colony_attr(0, "cap"), Some("2"));
+    assert_eq!(graph.colony_attr(0, "protected"), Some(""));
+    assert_eq!(graph.colony_attr(0, "region"), None);
+    assert_eq!(graph.colony_attrs(1).count(), 0);
+    assert_eq!(graph.colony_attr(2, "note"), Some("a, b] \"c\" \\ d"));
+    assert_eq!(graph.colony_attrs(2).collect::<Vec<_>>(), [("note", "a, b] \"c\" \\ d"), ("region", "North")]);
+    assert_eq!((graph.edge_count(), graph.edge_label(2, 0)), (3, "west"));
+
+    let text = written(&graph);
+    assert_eq!(
+        text,
+        "ant_mania_map v1\nFoo[cap=2,defense=1,protected] north=Bar\nBar south=Foo\n\
+         Baz[note=\"a, b] \\\"c\\\" \\\\ d\",region=North] west=Foo\n"
+    );
+    let again = MapGraph::from_reader(text.as_bytes()).unwrap();
+    assert_eq!(written(&again), text);
+    assert_eq!(again.colony_attr(2, "note"), graph.colony_attr(2, "note"));
+}
+
+#[test]
+fn unknown_keys_are_kept_with_a_warning() {
+    let graph = MapGraph::from_reader("A[region=West,colour=red] east=B\nB[region=East] west=A\n".as_bytes()).unwrap();
+    let unknown: Vec<_> = graph.parse_warnings().iter().map(|warning| warning.kind.clone()).collect();
+    assert_eq!(unknown, [ParseWarningKind::UnknownAttribute { colony: "A".to_string(), key: "colour".to_string() }]);
+    assert_eq!(graph.colony_attr(0, "colour"), Some("red"));
+
+    let regions = Regions::from_attributes(&graph).unwrap();
+    assert_eq!(regions.names(), ["unassigned", "West", "East"]);
+    assert_eq!((regions.region_of(0), regions.region_of(1)), (1, 2));
+    let plain = MapGraph::from_reader("A east=B\nB west=A\n".as_bytes()).unwrap();
+    assert!(Regions::from_attributes(&plain).is_none());
+}
+
+#[test]
+fn malformed_attributes_are_rejected() {
+    for line in [
+        "Foo[cap=2 north=Bar",
+        "Foo[cap=\"2] north=Bar",
+        "Foo[cap=2]north=Bar",
+        "Foo[,cap=2] north=Bar",
+        "Foo[cap=2,] north=Bar",
+        "Foo[=2] north=Bar",
+        "Foo[cap=1,cap=2] north=Bar",
+        "Foo[ca p=2] north=Bar",
+        "Foo[note=a\"b] north=Bar",
+        "Foo[note=\"a\\b\"] north=Bar",
+        "[cap=2] north=Bar",
+    ] {
+        let map = format!("{}\nBar south=Foo\n", line);
+        let err = MapGraph::from_reader(map.as_bytes()).expect_err(line).to_string();
+        assert!(err.contains("line 1"), "{}: {}", line, err);
+    }
+}
+
+#[test]
+fn snapshots_keep_the_attributes() {
+    let graph = MapGraph::from_reader("A[cap=3] east=B\nB west=A\n".as_bytes()).unwrap();
+    let sim = AntSimulation::new(Arc::new(graph), AntConfig::new(2)).unwrap();
+    let mut bytes = Vec::new();
+    sim.snapshot().write_to(&mut bytes).unwrap();
+    let resumed = AntSimulation::from_snapshot(Snapshot::read_from(&bytes[..]).unwrap()).unwrap();
+    assert_eq!(resumed.graph().colony_attr(0, "cap"), Some("3"));
+    assert_eq!(resumed.graph().colony_attr(1, "cap"), None);
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use ant_mania::{run_batch, run_batch_survival, AntConfig, BatchStats, MapGraph, SimOptions};
+
+#[test]
+fn batch_results_dont_depend_on_thread_count() {
+    let graph = Arc::new(MapGraph::from_file("hiveum_map_small.txt").unwrap());
+    let config = AntConfig { max_moves: 500, ..AntConfig::new(12) };
+    let options = SimOptions::default();
+
+    let serial = run_batch(&graph, &config, &options, 25, 1000, 1).unwrap();
+    let parallel = run_batch(&graph, &config, &options, 25, 1000, 4).unwrap();
+    assert_eq!(serial, parallel);
+    assert_eq!(serial.iter().map(|run| run.seed).collect::<Vec<_>>(), (1000..1025).collect::<Vec<_>>());
+
+    let stats = BatchStats::from_runs(&serial).unwrap();
+    assert_eq!(stats.runs, 25);
+    assert_eq!(stats, BatchStats::from_runs(&parallel).unwrap());
+}
+
+#[test]
+fn a_hub_survives_less_often_than_the_map() {
+    // colony 0 is a hub every leaf goes through, the leaves are also in a ring
+    let mut edges = Vec::new();
+    for leaf in 1..=8 {
+        let next = leaf % 8 + 1;
+        edges.extend([(0, leaf), (leaf, 0), (leaf, next), (next, leaf)]);
+    }
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 9).unwrap());
+    let config = AntConfig { max_moves: 200, ..AntConfig::new(4) };
+    let options = SimOptions::default();
+
+    let (results, survival) = run_batch_survival(&graph, &config, &options, 40, 7, 1, None).unwrap();
+    assert_eq!((results.len(), survival.runs()), (40, 40));
+    assert!(survival.rate(0) < survival.mean_rate(), "{} vs a mean of {}", survival.rate(0), survival.mean_rate());
+    assert!((1..=8).all(|leaf| survival.rate(leaf) > survival.rate(0)), "{:?}", survival.rates());
+    assert_eq!(survival.percentile(0.0), survival.rate(0));
+
+    // every run counts once whatever worker ran it
+    let (_, parallel) = run_batch_survival(&graph, &config, &options, 40, 7, 3, None).unwrap();
+    assert_eq!(survival, parallel);
+}
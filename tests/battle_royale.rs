@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, CollisionPolicy, MapGraph, PlacementStrategy, Winner};
+
+#[test]
+fn the_last_ant_standing_wins() {
+    // ants 0 and 1 share colony 0, ant 2 is alone on 2
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[(0, 1), (1, 0)], 3).unwrap());
+    let config = AntConfig {
+        placement_strategy: PlacementStrategy::Explicit(vec![Some(0), Some(0), Some(2)]),
+        collision_policy: CollisionPolicy::KillFirst { count: 1 },
+        seed: Some(1),
+        ..AntConfig::new(3)
+    };
+    let mut sim = AntSimulation::new(graph, config).unwrap();
+    sim.record_fight_counts(true);
+    assert_eq!(sim.winner(), None);
+
+    assert_eq!(sim.check_collision(0), Some(&[0][..]));
+    assert_eq!((sim.colonies_destroyed_by(0), sim.colonies_destroyed_by(1)), (1, 1));
+    assert_eq!(sim.winner(), None, "ant 2 is still alive");
+
+    sim.destroy_cascade(2);
+    assert_eq!(sim.winner(), Some(Winner { ant: 1, colony: 0, moves: 0, colonies_destroyed: 1 }));
+
+    sim.record_fight_counts(false);
+    assert_eq!(sim.winner().unwrap().colonies_destroyed, 0);
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, PlacementStrategy, SimOptions};
+
+/// Both ways for every `(a, b)`
+fn two_way(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    pairs.iter().flat_map(|&(a, b)| [(a, b), (b, a)]).collect()
+}
+
+#[test]
+fn cascade_takes_out_colonies_left_without_a_way_out() {
+    // a path 0-1-2 into the triangle 2-3-4
+    let edges = two_way(&[(0, 1), (1, 2), (2, 3), (3, 4), (4, 2)]);
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 5).unwrap());
+    let positions = vec![Some(0), Some(0), Some(1), Some(3)];
+    let config = AntConfig { placement_strategy: PlacementStrategy::Explicit(positions), ..AntConfig::new(4) };
+    let mut sim = AntSimulation::new(graph, config).unwrap();
+
+    assert_eq!(sim.destroy_cascade(1), [1, 0]);
+    assert_eq!(sim.colonies_destroyed(), 2);
+    assert_eq!(sim.stats().0, 1, "ants on 0 and 1 should be dead");
+
+    // the triangle still has a way out of every colony
+    assert_eq!(sim.destroy_cascade(2), [2]);
+    assert_eq!(sim.destroy_cascade(2), Vec::<usize>::new());
+    assert_eq!(sim.destroy_cascade(4), [4, 3]);
+    assert_eq!(sim.stats(), (0, 0, 5));
+}
+
+#[test]
+fn cascade_on_destroy_kills_ants_stuck_by_a_fight() {
+    // a star around 1: the ants on 0 and 2 can only meet there, while the
+    // one on 1 walks out to a leaf and is cut off by the fight
+    let edges = two_way(&[(1, 0), (1, 2), (1, 3)]);
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 4).unwrap());
+    let run = |cascade_on_destroy| {
+        let positions = vec![Some(0), Some(2), Some(1)];
+        let config = AntConfig {
+            placement_strategy: PlacementStrategy::Explicit(positions),
+            seed: Some(8),
+            max_moves: 50,
+            ..AntConfig::new(3)
+        };
+        let options = SimOptions { cascade_on_destroy, ..SimOptions::default() };
+        let mut sim = AntSimulation::new(Arc::clone(&graph), config).unwrap().with_options(options);
+        let first = sim.run_iteration();
+        (first, sim.run_to_completion() + 1, sim.stats().0)
+    };
+
+    let (first, iterations, alive) = run(true);
+    assert_eq!((first.colonies_destroyed, first.ants_killed), (4, 3));
+    assert_eq!((iterations, alive), (1, 0));
+
+    let (first, iterations, alive) = run(false);
+    assert_eq!((first.colonies_destroyed, first.ants_killed), (1, 2));
+    assert_eq!((iterations, alive), (50, 1));
+}
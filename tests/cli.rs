@@ -0,0 +1,682 @@
+use std::process::{Command, Output};
+
+const SMALL_MAP: &str = "hiveum_map_small.txt";
+
+fn ant_mania(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ant-mania"))
+        .args(args)
+        .output()
+        .expect("failed to run ant-mania")
+}
+
+#[test]
+fn positional_args_still_work() {
+    let output = ant_mania(&[SMALL_MAP, "10", "--seed", "7"]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Starting simulation: 10 ants"));
+    assert!(stdout.contains("=== Remaining World ==="));
+}
+
+#[test]
+fn help_and_version_exit_cleanly() {
+    for flag in ["--help", "--version"] {
+        let output = ant_mania(&[flag]);
+        assert!(output.status.success(), "{} failed", flag);
+        assert!(!output.stdout.is_empty());
+    }
+}
+
+#[test]
+fn bad_arguments_exit_with_1() {
+    let cases: &[&[&str]] = &[
+        &[],
+        &[SMALL_MAP],
+        &[SMALL_MAP, "many"],
+        &[SMALL_MAP, "10", "--seed", "-3"],
+        &[SMALL_MAP, "10", "--format", "xml"],
+        &[SMALL_MAP, "10", "--max-moves", "0"],
+        &[SMALL_MAP, "10", "--ants-per-colony", "0.5"],
+        &[SMALL_MAP, "--ants-per-colony=-1"],
+        &[SMALL_MAP, "--ants-per-colony", "NaN"],
+        &[SMALL_MAP, "--ants-per-colony", "1e12"],
+        &[SMALL_MAP, "10", "--max-memory", "lots"],
+        &[SMALL_MAP, "10", "--quiet", "--verbose"],
+        &[SMALL_MAP, "10", "--no-such-flag"],
+        &["does_not_exist.txt", "10"],
+    ];
+    for args in cases {
+        let output = ant_mania(args);
+        assert_eq!(output.status.code(), Some(1), "args {:?}", args);
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("error"), "args {:?}: {}", args, stderr);
+        assert!(!stderr.contains("panicked"), "args {:?}: {}", args, stderr);
+    }
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn quiet_json_output_to_file() {
+    let path = std::env::temp_dir().join(format!("ant_mania_cli_{}.json", std::process::id()));
+    let output = ant_mania(&[SMALL_MAP, "10", "--quiet", "--format", "json", "--output", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(report["total_ants"], 10);
+    assert_eq!(report["total_colonies"], 28);
+}
+
+#[test]
+fn same_seed_gives_identical_output() {
+    let args = [SMALL_MAP, "20", "--seed", "12345", "--no-timing"];
+    let first = ant_mania(&args);
+    let second = ant_mania(&args);
+    assert!(first.status.success());
+    assert!(String::from_utf8_lossy(&first.stdout).contains("seed: 12345"));
+    assert_eq!(first.stdout, second.stdout);
+}
+
+#[test]
+fn generated_seed_is_reported_and_reusable() {
+    let first = ant_mania(&[SMALL_MAP, "20", "--no-timing"]);
+    let stdout = String::from_utf8(first.stdout).unwrap();
+    let seed = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("seed: "))
+        .expect("no seed line in banner");
+
+    let second = ant_mania(&[SMALL_MAP, "20", "--no-timing", "--seed", seed]);
+    assert_eq!(stdout.as_bytes(), second.stdout);
+}
+
+#[test]
+fn summary_echoes_move_budget() {
+    let output = ant_mania(&[SMALL_MAP, "10", "--max-moves", "500", "--seed", "1"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Move budget: 500 moves per ant"));
+}
+
+#[test]
+fn ants_per_colony_resolves_against_the_map() {
+    // 28 colonies * 0.5
+    let output = ant_mania(&[SMALL_MAP, "--ants-per-colony", "0.5", "--max-moves", "10"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Starting simulation: 14 ants"));
+}
+
+#[test]
+fn config_file_matches_equivalent_flags() {
+    let from_config = ant_mania(&["--config", "examples/experiment.toml"]);
+    let from_flags = ant_mania(&[
+        SMALL_MAP,
+        "--ants-per-colony", "0.5",
+        "--seed", "12345",
+        "--max-moves", "2000",
+        "--movement", "simultaneous",
+        "--destruction", "lazy",
+        "--reorder-every", "10",
+        "--factions", "0",
+        "--format", "text",
+        "--no-timing",
+    ]);
+    assert!(from_config.status.success(), "{}", String::from_utf8_lossy(&from_config.stderr));
+    assert_eq!(from_config.stdout, from_flags.stdout);
+}
+
+#[test]
+fn command_line_overrides_config_file() {
+    let output = ant_mania(&["--config", "examples/experiment.toml", "--max-moves", "5", "--quiet"]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Move budget: 5 moves per ant"));
+    assert!(!stdout.contains("Starting simulation"));
+}
+
+#[test]
+fn unknown_config_key_is_an_error() {
+    let path = std::env::temp_dir().join(format!("ant_mania_typo_{}.toml", std::process::id()));
+    std::fs::write(&path, "max-move = 500\n").unwrap();
+    let output = ant_mania(&[SMALL_MAP, "10", "--config", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("max-move"));
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn runs_aggregate_is_deterministic_for_a_base_seed() {
+    let args = [SMALL_MAP, "10", "--runs", "30", "--seed", "99", "--max-moves", "300", "--no-timing"];
+    let first = ant_mania(&args);
+    let second = ant_mania(&args);
+    assert!(first.status.success());
+    assert_eq!(first.stdout, second.stdout);
+    assert!(String::from_utf8_lossy(&first.stdout).contains("=== 30 Runs, seeds 99 to 128 ==="));
+
+    let json = ant_mania(&[SMALL_MAP, "10", "--runs", "30", "--seed", "99", "--max-moves", "300", "--format", "json"]);
+    let report: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(report["results"].as_array().unwrap().len(), 30);
+}
+
+#[test]
+fn map_header_version_is_checked() {
+    let path = std::env::temp_dir().join(format!("ant_mania_v2_{}.txt", std::process::id()));
+    std::fs::write(&path, "ant_mania_map v2\nA east=B\nB west=A\n").unwrap();
+    let unsupported = ant_mania(&[path.to_str().unwrap(), "2"]);
+    std::fs::write(&path, "A east=B\nB west=A\n").unwrap();
+    let legacy = ant_mania(&[path.to_str().unwrap(), "2", "--seed", "1"]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(unsupported.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&unsupported.stderr).contains("map format v2 isn't supported"));
+    assert!(legacy.status.success());
+    assert!(String::from_utf8_lossy(&legacy.stderr).contains("deprecated"));
+    assert!(String::from_utf8_lossy(&legacy.stdout).contains("ant_mania_map v1"));
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn time_limit_stops_the_run_with_its_own_exit_code() {
+    // a lone ant never fights, so only the limit ends this run
+    let endless = [SMALL_MAP, "1", "--max-moves", "4000000000", "--time-limit", "100ms"];
+    let output = ant_mania(&endless);
+    assert_eq!(output.status.code(), Some(124));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("time limit at iteration"));
+
+    let batch = ant_mania(&[&endless[..], &["--runs", "3", "--format", "json"]].concat());
+    assert_eq!(batch.status.code(), Some(124));
+
+    let finished = ant_mania(&[SMALL_MAP, "10", "--time-limit", "1h", "--max-moves", "300"]);
+    assert!(finished.status.success());
+
+    for (limit, message) in [("90", "needs a unit"), ("-5s", "negative"), ("0s", "above zero"), ("5 parsecs", "invalid")] {
+        let output = ant_mania(&[SMALL_MAP, "10", "--time-limit", limit]);
+        assert_eq!(output.status.code(), Some(1), "--time-limit {}", limit);
+        assert!(String::from_utf8_lossy(&output.stderr).contains(message), "--time-limit {}", limit);
+    }
+}
+
+#[test]
+fn thread_count_doesnt_change_batch_results() {
+    let batch = |threads: &str| ant_mania(&[SMALL_MAP, "10", "--runs", "12", "--seed", "5", "--threads", threads, "--no-timing"]);
+    let serial = batch("1");
+    assert!(serial.status.success());
+    assert_eq!(serial.stdout, batch("4").stdout);
+    assert_eq!(batch("0").status.code(), Some(1));
+}
+
+#[test]
+fn stats_describes_the_map_without_running() {
+    let output = ant_mania(&[SMALL_MAP, "10", "--stats"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for line in ["colonies:            28/28 live", "components:          1", "diameter:"] {
+        assert!(stdout.contains(line), "missing '{}' in:\n{}", line, stdout);
+    }
+    assert!(!stdout.contains("Remaining World"), "{}", stdout);
+
+    let output = ant_mania(&[SMALL_MAP, "10", "--stats", "--runs", "2"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn dry_run_reports_without_allocating_ants() {
+    // 4 billion ants would need ~100 GiB, a dry run never allocates them
+    let start = std::time::Instant::now();
+    let output = ant_mania(&["hiveum_map_medium.txt", "4000000000", "--dry-run", "--seed", "8"]);
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for line in ["colonies:   6763", "components: 1", "seed:        8", "ants:        4000000000", "total:"] {
+        assert!(stdout.contains(line), "missing '{}' in:\n{}", line, stdout);
+    }
+
+    // validation still runs
+    let output = ant_mania(&[SMALL_MAP, "100", "--distribution", "distinct", "--dry-run"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn log_file_takes_diagnostics_off_the_terminal() {
+    let log = std::env::temp_dir().join(format!("ant_mania_log_{}.log", std::process::id()));
+    let log_path = log.to_str().unwrap();
+    let output = ant_mania(&[SMALL_MAP, "10", "-v", "--seed", "4", "--log-file", log_path]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("Starting simulation"));
+    assert!(stdout.contains("=== Remaining World ==="));
+
+    let first = std::fs::read_to_string(&log).unwrap();
+    assert!(first.lines().all(|line| line.starts_with('[')), "{}", first);
+    assert!(first.contains("] Starting simulation: 10 ants"));
+
+    // truncated by default, added to with --log-append
+    ant_mania(&[SMALL_MAP, "10", "-v", "--seed", "4", "--log-file", log_path]);
+    assert_eq!(std::fs::read_to_string(&log).unwrap().lines().count(), first.lines().count());
+    ant_mania(&[SMALL_MAP, "10", "-v", "--seed", "4", "--log-file", log_path, "--log-append"]);
+    assert_eq!(std::fs::read_to_string(&log).unwrap().lines().count(), 2 * first.lines().count());
+    std::fs::remove_file(&log).unwrap();
+}
+
+#[test]
+fn stop_when_reports_the_condition_that_fired() {
+    let output = ant_mania(&[
+        SMALL_MAP, "200", "--seed", "2", "--stop-when", "iterations>=5000", "--stop-when", "colonies_destroyed>=1",
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(": colonies_destroyed>=1 holds"), "{}", stdout);
+
+    let output = ant_mania(&[SMALL_MAP, "10", "--stop-when", "ants<3"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("expected one of iterations, alive_ants"));
+}
+
+#[test]
+fn seed_sweep_is_deterministic() {
+    let csv = std::env::temp_dir().join(format!("ant_mania_sweep_{}.csv", std::process::id()));
+    let csv_path = csv.to_str().unwrap();
+    let sweep = |threads: &str| {
+        let output = ant_mania(&[
+            SMALL_MAP, "30", "--sweep-seeds", "10..40", "--rank-by", "total_moves", "--max-moves", "200",
+            "--threads", threads, "--sweep-csv", csv_path, "--no-timing", "--quiet",
+        ]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        (String::from_utf8(output.stdout).unwrap(), std::fs::read_to_string(&csv).unwrap())
+    };
+
+    let (report, rows) = sweep("1");
+    assert!(report.contains("=== Seed Sweep: seeds 10..40, ranked by total_moves ==="), "{}", report);
+    assert!(report.contains("Top 10:") && report.contains("Bottom 10:"));
+    let header = "seed,iterations,alive_ants,dead_ants,active_colonies,colonies_destroyed,total_moves,oscillating_ants";
+    assert_eq!(rows.lines().next(), Some(header));
+    let seeds: Vec<_> = rows.lines().skip(1).map(|row| row.split(',').next().unwrap().to_string()).collect();
+    assert_eq!(seeds, (10..40).map(|seed| seed.to_string()).collect::<Vec<_>>());
+
+    assert_eq!(sweep("1"), (report.clone(), rows.clone()));
+    assert_eq!(sweep("3"), (report, rows));
+    std::fs::remove_file(&csv).unwrap();
+}
+
+#[test]
+fn placement_file_sets_the_starting_colonies() {
+    let dir = std::env::temp_dir().join(format!("ant_mania_placement_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let map = dir.join("line.txt");
+    std::fs::write(&map, "ant_mania_map v1\nA east=B\nB west=A east=C\nC west=B\n").unwrap();
+    let positions = dir.join("positions.txt");
+    let run = |placement: &str, extra: &[&str]| {
+        std::fs::write(&positions, placement).unwrap();
+        let args = [map.to_str().unwrap(), "2", "--placement", positions.to_str().unwrap(), "--seed", "4"];
+        ant_mania(&[&args[..], extra].concat())
+    };
+
+    // both ends of the line can only move to B, where they fight at once
+    let output = run("A\n1 C\n", &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Simulation ended after 1 iterations"), "{}", stdout);
+    assert!(stdout.contains("A\nC\n\nAlive ants: 0/2"), "{}", stdout);
+
+    let output = run("A\n7 B\nNowhere\n0 C\n", &[]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    for problem in [
+        "line 2: ant 7 doesn't exist",
+        "line 3: no colony named 'Nowhere'",
+        "line 4: ant 0 is already placed",
+    ] {
+        assert!(stderr.contains(problem), "{}", stderr);
+    }
+
+    let output = run("C\n", &[]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("places 1 of 2 ants"));
+    assert!(run("C\n", &["--placement-partial"]).status.success());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn batch_file_runs_past_a_bad_line() {
+    let dir = std::env::temp_dir().join(format!("ant_mania_batch_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let jobs = dir.join("jobs.txt");
+    let lines = [
+        "# a good line, a bad one and another good one".to_string(),
+        format!("{} 10 --seed 1 --no-timing", SMALL_MAP),
+        String::new(),
+        "does_not_exist.txt 10".to_string(),
+        format!("{} 5 --seed 2 --format json --quiet", SMALL_MAP),
+    ];
+    std::fs::write(&jobs, lines.join("\n")).unwrap();
+    let batch = |extra: &[&str]| {
+        let output = ant_mania(&[&["batch", jobs.to_str().unwrap()], extra].concat());
+        assert_eq!(output.status.code(), Some(1), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let summary = batch(&[]);
+    for row in ["     2     0  finished", "     4     1  failed", "     5     0  finished"] {
+        assert!(summary.contains(row), "{}", summary);
+    }
+    assert!(summary.contains("line 4: does_not_exist.txt"), "{}", summary);
+    assert!(summary.contains("2 of 3 lines succeeded"), "{}", summary);
+
+    let out_dir = dir.join("jobs.out");
+    let text = std::fs::read_to_string(out_dir.join("line_2.out")).unwrap();
+    assert_eq!(text, String::from_utf8(ant_mania(&[SMALL_MAP, "10", "--seed", "1", "--no-timing"]).stdout).unwrap());
+    let json: serde_json::Value = serde_json::from_slice(&std::fs::read(out_dir.join("line_5.out")).unwrap()).unwrap();
+    assert_eq!(json["seed"], 2);
+
+    assert_eq!(batch(&["--threads", "3"]), summary);
+    let fail_fast = batch(&["--fail-fast"]);
+    assert!(fail_fast.contains("     5     -  skipped"), "{}", fail_fast);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn fail_on_warning_rejects_a_map_that_otherwise_loads() {
+    let path = std::env::temp_dir().join(format!("ant_mania_warnings_{}.txt", std::process::id()));
+    std::fs::write(&path, "ant_mania_map v1\nA east=B\nB west=A north=C\nC\n").unwrap();
+    let map = path.to_str().unwrap();
+    let lenient = ant_mania(&[map, "2", "--seed", "1"]);
+    let strict = ant_mania(&[map, "2", "--seed", "1", "--fail-on-warning"]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(lenient.status.success());
+    let warning = "warning: ".to_string() + map + " line 3: B north=C has no tunnel back from C";
+    assert!(String::from_utf8_lossy(&lenient.stderr).contains(&warning));
+
+    assert_eq!(strict.status.code(), Some(65));
+    let stderr = String::from_utf8(strict.stderr).unwrap();
+    assert!(stderr.contains(&warning), "{}", stderr);
+    assert!(stderr.contains("1 parse warnings with --fail-on-warning"), "{}", stderr);
+    assert!(strict.stdout.is_empty());
+}
+
+#[test]
+fn hash_every_prints_the_state_hash() {
+    let hashes = || {
+        let output = ant_mania(&[SMALL_MAP, "10", "--seed", "4", "-v", "--max-moves", "40", "--hash-every", "10"]);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        stdout.lines().filter(|line| line.starts_with("state hash after ")).map(str::to_string).collect::<Vec<_>>()
+    };
+    let first = hashes();
+    assert_eq!(first.len(), 4, "{:?}", first);
+    assert!(first[0].starts_with("state hash after 10 iterations: "), "{}", first[0]);
+    assert_eq!(hashes(), first, "same seed, same hashes");
+
+    let output = ant_mania(&[SMALL_MAP, "10", "--hash-every", "10"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn metrics_file_holds_the_final_counters() {
+    let path = std::env::temp_dir().join(format!("ant_mania_metrics_{}.prom", std::process::id()));
+    let output = ant_mania(&[SMALL_MAP, "100", "--seed", "7", "--metrics-file", path.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let labels = format!("{{seed=\"7\",map=\"{}\"}}", SMALL_MAP);
+    for sample in ["alive_ants", "surviving_colonies", "destructions_total", "iterations_total"] {
+        assert_eq!(text.matches(&format!("# TYPE ant_mania_{} ", sample)).count(), 1, "{}", text);
+    }
+    assert!(text.contains(&format!("ant_mania_alive_ants{} 60\n", labels)), "{}", text);
+    assert!(text.contains(&format!("ant_mania_iterations_total{} 10000\n", labels)), "{}", text);
+    assert!(text.contains(&format!("ant_mania_run_duration_seconds{} ", labels)), "{}", text);
+    let mut partial = path.into_os_string();
+    partial.push(".partial");
+    assert!(!std::path::Path::new(&partial).exists());
+
+    let output = ant_mania(&[SMALL_MAP, "10", "--metrics-every", "5"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+#[cfg(feature = "geojson")]
+fn geojson_maps_in_and_out() {
+    let dir = std::env::temp_dir();
+    let map = dir.join(format!("ant_mania_map_{}.geojson", std::process::id()));
+    let world = dir.join(format!("ant_mania_world_{}.geojson", std::process::id()));
+    std::fs::write(
+        &map,
+        r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "geometry": {"type": "Point", "coordinates": [1, 2]},
+             "properties": {"name": "A", "tunnels": ["east=B"]}},
+            {"type": "Feature", "geometry": {"type": "Point", "coordinates": [3, 4]},
+             "properties": {"name": "B", "tunnels": ["west=A"]}}
+        ]}"#,
+    )
+    .unwrap();
+    let output = ant_mania(&[map.to_str().unwrap(), "1", "--geojson-file", world.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("A east=B"));
+    let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&world).unwrap()).unwrap();
+    std::fs::remove_file(&map).unwrap();
+    std::fs::remove_file(&world).unwrap();
+    assert_eq!(written["features"][1]["geometry"]["coordinates"], serde_json::json!([3.0, 4.0]));
+    assert!(written["features"][1]["properties"]["ants"].is_u64());
+
+    let output = ant_mania(&[SMALL_MAP, "10", "--runs", "2", "--geojson-file", world.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn symmetrize_adds_the_missing_tunnels_back() {
+    let map = std::env::temp_dir().join(format!("ant_mania_one_way_cli_{}.txt", std::process::id()));
+    std::fs::write(&map, "ant_mania_map v1\nA north=B\nB\nC west=B\n").unwrap();
+    let map_path = map.to_str().unwrap();
+    let output = ant_mania(&[map_path, "0", "--symmetrize", "--no-timing"]);
+    let plain = ant_mania(&[map_path, "0", "--no-timing"]);
+    std::fs::remove_file(&map).unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("added 2 reverse tunnels"), "{}", stdout);
+    assert!(stdout.contains("A north=B") && stdout.contains("B south=A east=C"), "{}", stdout);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("no tunnel back"));
+    assert!(String::from_utf8_lossy(&plain.stderr).contains("no tunnel back"));
+}
+
+#[test]
+fn fights_are_told_with_the_ants_names() {
+    let dir = std::env::temp_dir();
+    let file = |name: &str, text: &str| {
+        let path = dir.join(format!("ant_mania_{}_{}", name, std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        path.to_str().unwrap().to_string()
+    };
+    let map = file("fight_map", "ant_mania_map v1\nLeft east=FizzBuzz\nRight west=FizzBuzz\nFizzBuzz\n");
+    let placement = file("fight_placement", "Left\nRight\n");
+    let roster = file("fight_roster", "Alice\nBob\n");
+    let repeated = file("fight_roster_repeated", "Alice\nAlice\n");
+
+    let output = ant_mania(&[&map, "2", "--seed", "1", "--placement", &placement, "--ant-names", &roster]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\nFizzBuzz has been destroyed by ant Alice and ant Bob!\n"), "{}", stdout);
+
+    // a third ant past the end of the roster goes by its ID; three make a crowd
+    let args = [&map, "3", "--seed", "1", "--placement", &placement, "--placement-partial", "--ant-names", &repeated];
+    let output = ant_mania(&[&args[..], &["--fight-at", "at-least", "--collision-policy", "all"]].concat());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("warning: --ant-names gives 'Alice' to more than one ant\n"), "{}", stderr);
+    assert!(stdout.contains("has been destroyed by ant Alice, ant Alice and ant 2!\n"), "{}", stdout);
+
+    let output = ant_mania(&[&map, "2", "--placement", &placement, "--quiet", "--ant-names", &roster]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("destroyed by"));
+    for path in [map, placement, roster, repeated] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn json_report_breaks_down_by_region() {
+    let path = std::env::temp_dir().join(format!("ant_mania_regions_{}.txt", std::process::id()));
+    std::fs::write(&path, "Kara Downtown\nNowhere Uptown\n").unwrap();
+    let output = ant_mania(&[SMALL_MAP, "20", "--seed", "2", "--format", "json", "--regions", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("line 2: no colony called Nowhere"));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let regions = report["regions"].as_array().unwrap();
+    let names: Vec<_> = regions.iter().map(|region| region["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["unassigned", "Downtown"]);
+    assert_eq!(regions[1]["colonies"], 1);
+    let colonies: u64 = regions.iter().map(|region| region["colonies"].as_u64().unwrap()).sum();
+    assert_eq!(colonies, report["total_colonies"].as_u64().unwrap());
+    let ants: u64 = regions.iter().map(|region| region["ants"].as_u64().unwrap()).sum();
+    assert_eq!(ants, report["alive_ants"].as_u64().unwrap());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn deaths_are_reported_by_iteration() {
+    let output = ant_mania(&[SMALL_MAP, "60", "--seed", "3", "--format", "json"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let deaths = report["deaths"].as_array().unwrap();
+    assert!(deaths.iter().all(|deaths| deaths["cause"] == "fight"));
+    let killed: u64 = deaths.iter().map(|deaths| deaths["ants"].as_u64().unwrap()).sum();
+    assert_eq!(killed, 60 - report["alive_ants"].as_u64().unwrap());
+
+    let output = ant_mania(&[SMALL_MAP, "60", "--seed", "3", "--verbose", "--no-timing"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("=== Deaths ===\nfight: {}\nMost in one iteration: ", killed)), "{}", stdout);
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn sort_output_lists_the_remaining_world_by_name() {
+    let map = std::env::temp_dir().join(format!("ant_mania_unsorted_cli_{}.txt", std::process::id()));
+    std::fs::write(&map, "ant_mania_map v1\nZeta west=Alpha\nAlpha east=Zeta north=Mid\nMid south=Alpha\n").unwrap();
+    let map_path = map.to_str().unwrap();
+    let output = ant_mania(&[map_path, "0", "--sort-output", "--no-timing"]);
+    let json = ant_mania(&[map_path, "0", "--sort-output", "--format", "json"]);
+    std::fs::remove_file(&map).unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let sorted = "Alpha east=Zeta north=Mid\nMid south=Alpha\nZeta west=Alpha\n";
+    assert!(String::from_utf8_lossy(&output.stdout).contains(sorted));
+    let report: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    let world = report["remaining_world"].as_array().unwrap();
+    let lines: Vec<&str> = world.iter().map(|line| line.as_str().unwrap()).collect();
+    assert!(lines.join("\n").contains(sorted.trim_end()), "{}", report);
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn marathon_ants_are_named_in_the_report() {
+    let roster = std::env::temp_dir().join(format!("ant_mania_marathon_names_{}.txt", std::process::id()));
+    std::fs::write(&roster, (0..30).map(|ant| format!("Runner{}\n", ant)).collect::<String>()).unwrap();
+    let roster = roster.to_str().unwrap();
+    let args = [SMALL_MAP, "30", "--seed", "4", "--ant-names", roster, "--trace-ant", "3", "--trace-ant", "7"];
+    let json = ant_mania(&[&args[..], &["--format", "json"]].concat());
+    let text = ant_mania(&[&args[..], &["--verbose", "--no-timing"]].concat());
+    std::fs::remove_file(roster).unwrap();
+
+    assert!(json.status.success(), "{}", String::from_utf8_lossy(&json.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    let most_moves = &report["marathon_ants"]["most_moves"];
+    assert_eq!(most_moves["name"], format!("Runner{}", most_moves["ant"]));
+    let stdout = String::from_utf8_lossy(&text.stdout);
+    let name = most_moves["name"].as_str().unwrap();
+    let line = format!("=== Marathon Ants ===\nMost moves: ant {}, {} moves\n", name, most_moves["value"]);
+    assert!(stdout.contains(&line), "{}", stdout);
+    let most_distinct = &report["marathon_ants"]["most_distinct"];
+    assert!(most_distinct["ant"] == 3 || most_distinct["ant"] == 7, "{}", most_distinct);
+    let name = most_distinct["name"].as_str().unwrap();
+    let line = format!("Most distinct colonies: ant {}, {} colonies\n", name, most_distinct["value"]);
+    assert!(stdout.contains(&line), "{}", stdout);
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn battle_royale_announces_the_winner() {
+    // fights kill two ants at a time, so it takes an odd number to end with one
+    let output = ant_mania(&[SMALL_MAP, "7", "--seed", "1", "--battle-royale", "--no-timing"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let winner = "=== Battle Royale ===\nWinner: ant 1, at Dovmars after 9 moves\n\
+                  Colonies destroyed in its fights: 0\n";
+    assert!(stdout.ends_with(winner), "{}", stdout);
+
+    let output = ant_mania(&[SMALL_MAP, "7", "--seed", "1", "--battle-royale", "--format", "json"]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["battle_royale"]["winner"]["colony"], "Dovmars");
+    assert_eq!(report["battle_royale"]["survivors"], 1);
+
+    // with seed 2 the last ants die together
+    let output = ant_mania(&[SMALL_MAP, "6", "--seed", "2", "--battle-royale", "--no-timing"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No winner: every ant died"));
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn trace_ant_reports_the_path_it_took() {
+    let args = [SMALL_MAP, "10", "--seed", "3", "--trace-ant", "4", "--trace-ant", "0", "--no-timing"];
+    let json = ant_mania(&[&args[..], &["--format", "json"]].concat());
+    assert!(json.status.success(), "{}", String::from_utf8_lossy(&json.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    let traced = report["traced_ants"].as_array().unwrap();
+    assert_eq!(traced.iter().map(|ant| ant["ant"].as_u64().unwrap()).collect::<Vec<_>>(), [0, 4]);
+    let path = traced[0]["path"].as_array().unwrap();
+    assert_eq!(traced[0]["moves"].as_u64().unwrap() as usize, path.len() - 1);
+    // the plain text map has no coordinates to measure by
+    assert!(traced[0]["distance_km"].is_null());
+
+    let text = ant_mania(&args);
+    let stdout = String::from_utf8_lossy(&text.stdout);
+    let at = if traced[0]["alive"].as_bool().unwrap() { "now at" } else { "died at" };
+    let line = format!("=== Traced Ants ===\nant 0: {} moves, {} {}\n", traced[0]["moves"], at, path.last().unwrap());
+    assert!(stdout.contains(&line.replace('"', "")), "{}", stdout);
+
+    let output = ant_mania(&[SMALL_MAP, "10", "--trace-ant", "10"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--trace-ant 10: there are only 10 ants"));
+}
+
+#[test]
+fn survival_csv_has_a_rate_per_colony() {
+    let csv = std::env::temp_dir().join(format!("ant_mania_survival_{}.csv", std::process::id()));
+    let csv_path = csv.to_str().unwrap();
+    let args = [SMALL_MAP, "30", "--runs", "10", "--seed", "1", "--no-timing"];
+    let output = ant_mania(&[&args[..], &["--survival-csv", csv_path]].concat());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("colony survival:  mean "));
+    let written = std::fs::read_to_string(&csv).unwrap();
+    std::fs::remove_file(&csv).unwrap();
+
+    let mut lines = written.lines();
+    assert_eq!(lines.next(), Some("colony,survival_rate"));
+    let rates: Vec<f64> = lines.map(|line| line.split_once(',').unwrap().1.parse().unwrap()).collect();
+    assert_eq!(rates.len(), 28);
+    // ten runs, so every rate is a whole number of tenths
+    assert!(rates.iter().all(|rate| (rate * 10.0 - (rate * 10.0).round()).abs() < 1e-9), "{:?}", rates);
+
+    let single = ant_mania(&[SMALL_MAP, "30", "--survival-csv", csv_path]);
+    assert!(String::from_utf8_lossy(&single.stderr).contains("--survival-csv needs --runs"));
+}
+
+#[test]
+fn single_distribution_fights_as_a_crowd_unless_told_otherwise() {
+    let args = [SMALL_MAP, "5", "--distribution", "single:Kara", "--seed", "1", "--verbose", "--no-timing"];
+    let output = ant_mania(&args);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fight = "iteration 0: 1 colonies destroyed, 2 ants killed\nKara has been destroyed by";
+    assert!(stdout.contains(fight), "{}", stdout);
+
+    let output = ant_mania(&[&args[..], &["--fight-at", "exactly"]].concat());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("Kara has been destroyed"), "{}", stdout);
+}
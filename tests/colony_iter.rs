@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, PlacementStrategy};
+
+#[test]
+fn iterating_a_simulation_visits_every_colony() {
+    // both ants walk from 0 into 1 and destroy it
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[(0, 1), (1, 2), (2, 1)], 3).unwrap());
+    let config = AntConfig { placement_strategy: PlacementStrategy::Explicit(vec![Some(0); 2]), ..AntConfig::new(2) };
+    let mut sim = AntSimulation::new(graph, config).unwrap();
+    sim.run_iteration();
+
+    let names: Vec<_> = sim.into_iter().map(|colony| colony.name).collect();
+    assert_eq!(names, ["Colony0", "Colony1", "Colony2"]);
+    assert_eq!(sim.iter().len(), 3);
+    assert_eq!(sim.iter().filter(|colony| !colony.destroyed).count(), 2);
+
+    let mut peaks = Vec::new();
+    for colony in &sim {
+        assert_eq!(colony.ant_count, 0);
+        peaks.push(colony.peak_ant_count);
+    }
+    assert_eq!(peaks, [2, 2, 0]);
+}
+
+#[test]
+fn alive_colony_list_drops_colonies_as_they_fall() {
+    // a ring of six, so taking out two colonies apart leaves no dead end
+    let edges: Vec<_> = (0..6).flat_map(|colony| [(colony, (colony + 1) % 6), ((colony + 1) % 6, colony)]).collect();
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 6).unwrap());
+    let mut sim = AntSimulation::new(graph, AntConfig::new(0)).unwrap();
+    assert_eq!(sim.alive_colony_list(), [0, 1, 2, 3, 4, 5]);
+
+    sim.destroy_cascade(1);
+    sim.destroy_cascade(4);
+    let mut alive = sim.alive_colony_list().to_vec();
+    alive.sort_unstable();
+    assert_eq!(alive, [0, 2, 3, 5]);
+    let expected: Vec<_> = sim.iter().filter(|colony| !colony.destroyed).map(|colony| colony.id).collect();
+    assert_eq!(alive, expected);
+}
+
+#[test]
+fn default_simulation_is_already_over() {
+    let mut sim = AntSimulation::default();
+    assert!(!sim.should_continue());
+    assert_eq!(sim.stats(), (0, 1, 1));
+    assert_eq!(sim.iter().map(|colony| colony.name).collect::<Vec<_>>(), ["default"]);
+    assert_eq!(sim.run_to_completion(), 0);
+}
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, CollisionPolicy, FightTrigger, MapGraph};
+
+const ITERATIONS: u32 = 100;
+
+/// ~15 ants per colony on the medium map; a fight takes 30 or more, so the
+/// crowd thins out over many iterations instead of all dying in the first
+fn dense_crowd() -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_file("hiveum_map_medium.txt").unwrap());
+    let config = AntConfig {
+        seed: Some(1),
+        collision_threshold: 30,
+        collision_policy: CollisionPolicy::KillAll,
+        fight_trigger: FightTrigger::AtLeast,
+        ..AntConfig::new(100_000)
+    };
+    AntSimulation::new(graph, config).unwrap()
+}
+
+// run_iteration re-checks the whole slot table in debug builds, so this
+// only has to keep a dense crowd moving and fighting for a while
+#[test]
+fn slot_table_matches_colony_lists_at_high_density() {
+    let mut sim = dense_crowd();
+    let mut alive = sim.simulation_stats().alive_ants;
+    let mut iterations_with_deaths = 0;
+    let mut removals = 0;
+    for _ in 0..ITERATIONS {
+        assert!(sim.should_continue());
+        sim.run_iteration();
+        sim.check_invariants().unwrap();
+
+        let now_alive = sim.simulation_stats().alive_ants;
+        if now_alive < alive {
+            iterations_with_deaths += 1;
+        }
+        alive = now_alive;
+        // the counters are there in debug builds or with perf-counters
+        if let Some(perf) = sim.perf_counters() {
+            assert!(perf.list_removals > removals, "no ant left a colony list in an iteration");
+            removals = perf.list_removals;
+        }
+    }
+    assert_eq!(sim.iteration(), ITERATIONS);
+    assert!(iterations_with_deaths > ITERATIONS / 2, "fights in only {} iterations", iterations_with_deaths);
+    assert!(alive > 90_000, "{} ants left", alive);
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "arrow")]
+
+use std::fs::File;
+use std::process::Command;
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph};
+use arrow_array::cast::AsArray;
+use arrow_array::types::UInt32Type;
+use arrow_array::{Array, RecordBatch};
+use arrow_ipc::reader::FileReader;
+
+const SMALL_MAP: &str = "hiveum_map_small.txt";
+
+/// A finished run of 40 ants from seed 9 with destructions and visits kept
+fn finished_run() -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_file(SMALL_MAP).unwrap());
+    let mut sim = AntSimulation::new(graph, AntConfig { seed: Some(9), ..AntConfig::new(40) }).unwrap();
+    sim.record_destructions(true);
+    sim.record_visits(true);
+    sim.run_to_completion();
+    sim
+}
+
+#[test]
+fn batch_has_a_row_per_colony() {
+    let sim = finished_run();
+    let batch = sim.colony_stats_to_arrow();
+    assert_eq!(batch.num_rows(), sim.graph().colony_count());
+
+    let names = batch.column_by_name("name").unwrap().as_string::<i32>();
+    let ids = batch.column_by_name("id").unwrap().as_primitive::<UInt32Type>();
+    let destroyed = batch.column_by_name("destroyed").unwrap().as_boolean();
+    let destroyed_at = batch.column_by_name("destroyed_at_iteration").unwrap().as_primitive::<UInt32Type>();
+    let ant_counts = batch.column_by_name("ant_count").unwrap().as_primitive::<UInt32Type>();
+    let degrees = batch.column_by_name("degree").unwrap().as_primitive::<UInt32Type>();
+    let live_degrees = batch.column_by_name("live_degree").unwrap().as_primitive::<UInt32Type>();
+    let visits = batch.column_by_name("visits").unwrap().as_primitive::<UInt32Type>();
+
+    let destructions = sim.destructions();
+    assert!(!destructions.is_empty(), "nothing was destroyed");
+    for destruction in &destructions {
+        let row = destruction.colony;
+        assert_eq!(names.value(row), destruction.name);
+        assert!(destroyed.value(row));
+        assert_eq!(destroyed_at.value(row), destruction.iteration);
+    }
+    assert_eq!(destroyed_at.null_count(), sim.graph().colony_count() - destructions.len());
+
+    for state in &sim {
+        let row = state.id;
+        assert_eq!(ids.value(row) as usize, state.id);
+        assert_eq!(names.value(row), state.name);
+        assert_eq!(destroyed.value(row), state.destroyed);
+        assert_eq!(destroyed_at.is_null(row), !state.destroyed);
+        assert_eq!(ant_counts.value(row) as usize, state.ant_count);
+        assert_eq!(degrees.value(row) as usize, sim.colony_degree(row));
+        assert_eq!(live_degrees.value(row) as usize, sim.colony_out_degree(row));
+    }
+    let total_visits: u64 = visits.values().iter().map(|&count| count as u64).sum();
+    assert_eq!(total_visits, sim.simulation_stats().total_moves_made);
+}
+
+#[test]
+fn visits_are_left_out_unless_kept() {
+    let sim = AntSimulation::from_file(SMALL_MAP, 10).unwrap();
+    let batch = sim.colony_stats_to_arrow();
+    assert!(batch.column_by_name("visits").is_none());
+    assert_eq!(batch.num_columns(), 7);
+    // destructions weren't kept either
+    let destroyed_at = batch.column_by_name("destroyed_at_iteration").unwrap();
+    assert_eq!(destroyed_at.null_count(), batch.num_rows());
+}
+
+fn read_feather(path: &std::path::Path) -> RecordBatch {
+    let reader = FileReader::try_new(File::open(path).unwrap(), None).unwrap();
+    let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+    assert_eq!(batches.len(), 1);
+    batches.into_iter().next().unwrap()
+}
+
+fn run_cli(path: &std::path::Path) {
+    let output = Command::new(env!("CARGO_BIN_EXE_ant-mania"))
+        .args([SMALL_MAP, "40", "--seed", "9", "--quiet", "--colony-stats", path.to_str().unwrap()])
+        .output()
+        .expect("failed to run ant-mania");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn cli_writes_the_runs_colony_stats_as_feather() {
+    let path = std::env::temp_dir().join(format!("ant_mania_colonies_{}.feather", std::process::id()));
+    run_cli(&path);
+    let written = read_feather(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(written, finished_run().colony_stats_to_arrow());
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn cli_writes_the_runs_colony_stats_as_parquet() {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let path = std::env::temp_dir().join(format!("ant_mania_colonies_{}.parquet", std::process::id()));
+    run_cli(&path);
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap().build().unwrap();
+    let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(batches, vec![finished_run().colony_stats_to_arrow()]);
+}
+
+#[test]
+fn cli_refuses_other_extensions() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ant-mania"))
+        .args([SMALL_MAP, "10", "--colony-stats", "colonies.csv"])
+        .output()
+        .expect("failed to run ant-mania");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("expected a .feather, .arrow or .parquet file"));
+}
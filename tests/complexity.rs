@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph};
+
+#[test]
+fn complexity_estimate_bounds_and_extremes() {
+    let graph = Arc::new(MapGraph::from_file("hiveum_map_small.txt").unwrap());
+    let config = AntConfig { max_moves: 500, ..AntConfig::new(12) };
+    let estimate = AntSimulation::new(Arc::clone(&graph), config).unwrap().simulation_complexity_estimate();
+    assert_eq!(estimate.max_iterations, 500);
+    assert_eq!(estimate.max_moves_per_iteration, 12);
+    assert_eq!(estimate.total_ops_upper_bound, 6000);
+    let first = estimate.expected_first_collision_iteration.unwrap();
+    assert!(first >= 1.0 && first <= estimate.estimated_termination_iteration, "{:?}", estimate);
+
+    let lone = AntSimulation::new(Arc::clone(&graph), AntConfig::new(1)).unwrap();
+    assert_eq!(lone.simulation_complexity_estimate().expected_first_collision_iteration, None);
+
+    // thousands of ants on two colonies wipe each other out right away
+    let loop_graph = Arc::new(MapGraph::from_adjacency_list_raw(&[(0, 1), (1, 0)], 2).unwrap());
+    let crowded = AntSimulation::new(loop_graph, AntConfig::new(5000)).unwrap().simulation_complexity_estimate();
+    assert_eq!(crowded.expected_first_collision_iteration, Some(1.0));
+    assert!(crowded.estimated_termination_iteration <= 2.0, "{:?}", crowded);
+}
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, PlacementStrategy, SimOptions};
+
+#[test]
+fn component_sizes_follow_destroyed_colonies() {
+    // a triangle 0-1-2, a pair 3-4 and colony 5 on its own
+    let edges = [(0, 1), (1, 2), (2, 0), (3, 4)];
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 6).unwrap());
+    let positions = vec![Some(3), Some(4), Some(0)];
+    let config = AntConfig { placement_strategy: PlacementStrategy::Explicit(positions), ..AntConfig::new(3) };
+    let options = SimOptions { track_components: true, ..SimOptions::default() };
+    let mut sim = AntSimulation::new(graph, config).unwrap().with_options(options);
+
+    assert_eq!(sim.max_component_size(), 3);
+    assert_eq!(sim.average_component_size(), 2.0);
+    assert_eq!(sim.component_ant_count(4), 2);
+    assert_eq!(sim.component_ant_count(2), 1);
+
+    // 0 and 2 still reach each other round the gap
+    sim.destroy_cascade(1);
+    assert_eq!(sim.max_component_size(), 2);
+    assert_eq!(sim.average_component_size(), 5.0 / 3.0);
+    for colony in [0, 2, 3, 5] {
+        sim.destroy_cascade(colony);
+    }
+    assert_eq!((sim.max_component_size(), sim.average_component_size()), (0, 0.0));
+}
+
+#[test]
+fn isolate_component_keeps_only_that_part_of_the_map() {
+    // a triangle 0-1-2, a pair 3-4 and colony 5 on its own
+    let edges = [(0, 1), (1, 2), (2, 0), (3, 4)];
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 6).unwrap());
+    let positions = vec![Some(3), Some(4), Some(0)];
+    let config = AntConfig { placement_strategy: PlacementStrategy::Explicit(positions), ..AntConfig::new(3) };
+    let sim = AntSimulation::new(Arc::clone(&graph), config).unwrap();
+
+    let pair = sim.isolate_component(4);
+    pair.check_invariants().unwrap();
+    assert_eq!(pair.graph().colony_count(), 2);
+    assert_eq!(pair.graph().colony_name(0), graph.colony_name(3));
+    assert_eq!(pair.graph().colony_name(1), graph.colony_name(4));
+    assert_eq!(pair.graph().connection_counts(), [1, 0]);
+    assert_eq!((pair.total_ants(), pair.stats()), (3, (2, 2, 2)));
+    assert_eq!((pair.ants_at_slice(0), pair.ants_at_slice(1)), (&[0][..], &[1][..]));
+
+    let alone = sim.isolate_component(5);
+    assert_eq!((alone.graph().colony_count(), alone.stats().0), (1, 0));
+}
+
+#[test]
+fn isolated_components_keep_ruins_and_run_on() {
+    let edges = [(0, 1), (1, 2), (2, 0), (3, 4)];
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 6).unwrap());
+    let positions = vec![Some(2), Some(0), Some(3)];
+    let config = AntConfig { placement_strategy: PlacementStrategy::Explicit(positions), ..AntConfig::new(3) };
+    let mut sim = AntSimulation::new(graph, config).unwrap();
+    sim.destroy_cascade(1);
+
+    // 0 and 2 still reach each other round the gap; the tunnel into the ruin goes
+    let mut triangle = sim.isolate_component(0);
+    triangle.check_invariants().unwrap();
+    assert_eq!(triangle.graph().colony_count(), 2);
+    assert_eq!(triangle.graph().connection_counts(), [0, 1]);
+    assert_eq!(triangle.stats(), (2, 2, 2));
+    triangle.run_to_completion();
+    triangle.check_invariants().unwrap();
+}
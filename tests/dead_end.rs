@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, DeadEndBehavior, MapGraph, PlacementStrategy, SimOptions};
+
+/// One ant on a map of two colonies without tunnels, stuck from the start
+fn stranded_ant(dead_end_behavior: DeadEndBehavior) -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[], 2).unwrap());
+    let config = AntConfig { placement_strategy: PlacementStrategy::Single(0), max_moves: 10, ..AntConfig::new(1) };
+    let options = SimOptions { dead_end_behavior, ..SimOptions::default() };
+    AntSimulation::new(graph, config).unwrap().with_options(options)
+}
+
+#[test]
+fn dead_end_behaviors() {
+    let mut stay = stranded_ant(DeadEndBehavior::StayForever);
+    assert_eq!(stay.dead_end_colonies(), vec![0, 1]);
+    for _ in 0..5 {
+        stay.run_iteration();
+    }
+    assert_eq!(stay.stats().0, 1);
+    assert_eq!(stay.total_moves_made(), 0);
+
+    let mut die = stranded_ant(DeadEndBehavior::DieAfterNMoves(3));
+    die.run_iteration();
+    die.run_iteration();
+    assert_eq!(die.stats().0, 1);
+    die.run_iteration();
+    assert_eq!(die.stats().0, 0);
+    assert!(!die.has_ants_at(0));
+    assert!(!die.should_continue());
+
+    // the only other live colony is the one across the map
+    let mut teleport = stranded_ant(DeadEndBehavior::TeleportToRandom);
+    teleport.run_iteration();
+    assert!(teleport.has_ants_at(1));
+    teleport.run_iteration();
+    assert!(teleport.has_ants_at(0));
+    assert_eq!(teleport.total_moves_made(), 2);
+}
+
+#[test]
+fn teleports_land_on_every_other_live_colony() {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[], 5).unwrap());
+    let config = AntConfig { placement_strategy: PlacementStrategy::Single(0), max_moves: 1000, ..AntConfig::new(1) };
+    let options = SimOptions { dead_end_behavior: DeadEndBehavior::TeleportToRandom, ..SimOptions::default() };
+    let mut sim = AntSimulation::new(graph, config).unwrap().with_options(options);
+    sim.destroy_cascade(3);
+
+    let mut landed = [0; 5];
+    let mut buffer = Vec::new();
+    for _ in 0..400 {
+        let (from, to) = sim.move_ant(0, &mut buffer).unwrap();
+        assert_ne!(from, to);
+        landed[to] += 1;
+    }
+    assert_eq!(landed[3], 0);
+    assert!([0, 1, 2, 4].iter().all(|&colony| landed[colony] > 50), "{:?}", landed);
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use ant_mania::{
+    AntConfig, AntSimulation, DeadEndBehavior, DeathCause, Deaths, MapGraph, PlacementStrategy, SimOptions,
+};
+
+fn sim(edges: &[(usize, usize)], colonies: usize, positions: Vec<Option<usize>>, options: SimOptions) -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(edges, colonies).unwrap());
+    let config = AntConfig {
+        placement_strategy: PlacementStrategy::Explicit(positions.clone()),
+        seed: Some(8),
+        max_moves: 50,
+        ..AntConfig::new(positions.len())
+    };
+    let mut sim = AntSimulation::new(graph, config).unwrap().with_options(options);
+    sim.record_deaths(true);
+    sim
+}
+
+#[test]
+fn fights_and_the_cascade_after_them_are_told_apart() {
+    // the star test from cascade.rs: two ants meet on 1, the third is cut off on a leaf
+    let edges = [(1, 0), (0, 1), (1, 2), (2, 1), (1, 3), (3, 1)];
+    let options = SimOptions { cascade_on_destroy: true, ..SimOptions::default() };
+    let mut sim = sim(&edges, 4, vec![Some(0), Some(2), Some(1)], options);
+    assert!(sim.deaths_timeline().is_empty());
+    sim.run_iteration();
+    assert_eq!(
+        sim.deaths_timeline(),
+        [
+            Deaths { iteration: 0, cause: DeathCause::Fight, ants: 2 },
+            Deaths { iteration: 0, cause: DeathCause::Cascade, ants: 1 },
+        ]
+    );
+    assert_eq!(sim.deaths_per_iteration(), [3]);
+}
+
+#[test]
+fn deaths_are_dated_by_iteration() {
+    // one ant stranded on 0 dies on its third try, two others shuttle
+    // between 1 and 2 and never meet
+    let options = SimOptions { dead_end_behavior: DeadEndBehavior::DieAfterNMoves(3), ..SimOptions::default() };
+    let mut sim = sim(&[(1, 2), (2, 1)], 3, vec![Some(0), Some(1), Some(2)], options);
+    for _ in 0..5 {
+        sim.run_iteration();
+    }
+    assert_eq!(sim.deaths_timeline(), [Deaths { iteration: 2, cause: DeathCause::DeadEnd, ants: 1 }]);
+    assert_eq!(sim.deaths_per_iteration(), [0, 0, 1, 0, 0]);
+
+    // a cascade between iterations counts towards the next one; 2 goes
+    // down with 1, so both shuttling ants die
+    sim.destroy_cascade(1);
+    assert_eq!(sim.deaths_timeline()[1], Deaths { iteration: 5, cause: DeathCause::Cascade, ants: 2 });
+    assert_eq!(sim.deaths_per_iteration(), [0, 0, 1, 0, 0, 2]);
+
+    sim.record_deaths(false);
+    assert!(sim.deaths_timeline().is_empty());
+    assert_eq!(sim.deaths_per_iteration(), [0; 5]);
+}
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, PlacementStrategy};
+
+#[test]
+fn degrees_follow_destroyed_colonies() {
+    // 0 only leads to 1, so both ants meet there on the first move
+    let edges = [(0, 1), (1, 2), (1, 3), (2, 1), (3, 1), (3, 2)];
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 4).unwrap());
+    let config = AntConfig { placement_strategy: PlacementStrategy::Explicit(vec![Some(0); 2]), ..AntConfig::new(2) };
+    let mut sim = AntSimulation::new(graph, config).unwrap();
+
+    let in_degrees: Vec<_> = (0..4).map(|colony| sim.colony_in_degree(colony)).collect();
+    let out_degrees: Vec<_> = (0..4).map(|colony| sim.colony_out_degree(colony)).collect();
+    assert_eq!(in_degrees, [0, 3, 2, 1]);
+    assert_eq!(out_degrees, [1, 2, 1, 2]);
+
+    let result = sim.run_iteration();
+    assert_eq!(result.colonies_destroyed, 1);
+    let in_degrees: Vec<_> = (0..4).map(|colony| sim.colony_in_degree(colony)).collect();
+    let out_degrees: Vec<_> = (0..4).map(|colony| sim.colony_out_degree(colony)).collect();
+    assert_eq!(in_degrees, [0, 3, 1, 0]);
+    assert_eq!(out_degrees, [0, 2, 0, 1]);
+    assert_eq!(sim.colony_degree(0), 1);
+}
+
+#[test]
+fn two_way_tunnels_have_equal_degrees() {
+    let sim = AntSimulation::from_adjacency_list_raw(&[(0, 1), (1, 0), (1, 2), (2, 1)], 3, 0).unwrap();
+    for colony in 0..3 {
+        assert_eq!(sim.colony_in_degree(colony), sim.colony_out_degree(colony));
+    }
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, PlacementStrategy};
+
+/// A line of four colonies with the given ants on each
+fn line(ants: &[usize], collision_threshold: u16) -> AntSimulation {
+    let edges = [(0, 1), (1, 0), (1, 2), (2, 1), (2, 3), (3, 2)];
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 4).unwrap());
+    let positions = (ants.iter().enumerate()).flat_map(|(colony, &count)| vec![Some(colony); count]).collect();
+    let config = AntConfig {
+        seed: Some(1),
+        collision_threshold,
+        placement_strategy: PlacementStrategy::Explicit(positions),
+        ..AntConfig::new(ants.iter().sum())
+    };
+    AntSimulation::new(graph, config).unwrap()
+}
+
+#[test]
+fn density_is_ants_over_the_threshold() {
+    let sim = line(&[0, 1, 2, 3], 2);
+    let densities: Vec<f64> = (0..4).map(|colony| sim.density_at(colony)).collect();
+    assert_eq!(densities, [0.0, 0.5, 1.0, 1.5]);
+    assert_eq!(sim.average_density(), 0.75);
+    assert_eq!(sim.max_density_colony(), Some((3, 1.5)));
+
+    let sim = line(&[2, 0, 2, 0], 4);
+    assert_eq!(sim.max_density_colony(), Some((0, 0.5)));
+}
+
+#[test]
+fn destroyed_colonies_are_left_out() {
+    let mut sim = line(&[1, 0, 0, 3], 2);
+    sim.destroy_cascade(3);
+    assert_eq!(sim.max_density_colony(), Some((0, 0.5)));
+    assert_eq!(sim.average_density(), 1.0 / 3.0 / 2.0);
+
+    for colony in 0..3 {
+        sim.destroy_cascade(colony);
+    }
+    assert_eq!((sim.average_density(), sim.max_density_colony()), (0.0, None));
+}
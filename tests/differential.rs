@@ -0,0 +1,145 @@
+//! The engine against the plain simulator in tests/reference, on random small
+//! worlds and on the generator's topologies, compared after every iteration.
+//! A failure names the first iteration the two part ways and what differs.
+
+mod reference;
+
+use std::sync::Arc;
+
+use ant_mania::{
+    AntConfig, AntSimulation, CollisionPolicy, DeadEndBehavior, DestructionMode, FightTrigger, MapGraph,
+    MovementMode, ProceduralMapGenerator, SimOptions, Topology,
+};
+use reference::{Reference, WorldState};
+
+const WORLDS: u64 = 200;
+const ITERATIONS: u32 = 300;
+
+/// The engine's state in the reference's terms
+fn engine_state(sim: &AntSimulation) -> WorldState {
+    let mut state = WorldState { positions: Default::default(), destroyed: Default::default() };
+    for colony in sim.iter() {
+        if colony.destroyed {
+            state.destroyed.insert(colony.id);
+        }
+        state.positions.extend(sim.ants_at_slice(colony.id).iter().map(|&ant| (ant, colony.id)));
+    }
+    state
+}
+
+/// How `engine` and `reference` differ, the first few ants and colonies of it
+fn describe_difference(engine: &WorldState, reference: &WorldState) -> String {
+    let mut differences = Vec::new();
+    let ants: std::collections::BTreeSet<_> = engine.positions.keys().chain(reference.positions.keys()).collect();
+    for ant in ants {
+        let (in_engine, in_reference) = (engine.positions.get(ant), reference.positions.get(ant));
+        if in_engine != in_reference {
+            let (in_engine, in_reference) = (format!("{:?}", in_engine), format!("{:?}", in_reference));
+            differences.push(format!("ant {} at {} in the engine, {} in the reference", ant, in_engine, in_reference));
+        }
+    }
+    for colony in engine.destroyed.symmetric_difference(&reference.destroyed) {
+        let destroyed_in = if engine.destroyed.contains(colony) { "engine" } else { "reference" };
+        differences.push(format!("colony {} destroyed only in the {}", colony, destroyed_in));
+    }
+    differences.truncate(5);
+    differences.join("; ")
+}
+
+/// Run both side by side, the first iteration they disagree after
+fn compare(graph: MapGraph, config: AntConfig, options: SimOptions) -> Result<(), String> {
+    let mut reference = Reference::new(&graph, config.clone(), options.clone());
+    let mut sim = AntSimulation::new(Arc::new(graph), config).unwrap().with_options(options);
+    loop {
+        let (engine, expected) = (engine_state(&sim), reference.state());
+        if engine != expected {
+            let difference = describe_difference(&engine, &expected);
+            return Err(format!("after iteration {}: {}", reference.iteration(), difference));
+        }
+        let running = sim.should_continue() && sim.iteration() < sim.max_moves();
+        if running != reference.should_continue() {
+            return Err(format!("after iteration {}: only one of them carries on", reference.iteration()));
+        }
+        if !running {
+            return Ok(());
+        }
+        sim.run_iteration();
+        reference.run_iteration();
+    }
+}
+
+/// A random map of up to 10 colonies, or one of the generator's shapes
+fn random_map(rng: &mut fastrand::Rng) -> MapGraph {
+    let shape = match rng.u8(..6) {
+        0 => Topology::Grid { width: rng.usize(1..5), height: rng.usize(1..5) },
+        1 => Topology::Ring { colonies: rng.usize(3..9) },
+        2 => Topology::Torus { width: rng.usize(3..5), height: rng.usize(3..5) },
+        3 => Topology::Regular { colonies: 2 * rng.usize(3..6), degree: rng.usize(1..=4) },
+        _ => {
+            let colonies = rng.usize(1..=10);
+            let mut edges = Vec::new();
+            for from in 0..colonies {
+                for to in from + 1..colonies {
+                    match rng.u8(..10) {
+                        0 => edges.push((from, to)),
+                        1 => edges.push((to, from)),
+                        2..=4 => edges.extend([(from, to), (to, from)]),
+                        _ => {}
+                    }
+                }
+            }
+            return MapGraph::from_adjacency_list_raw(&edges, colonies).unwrap();
+        }
+    };
+    ProceduralMapGenerator::topology(shape, rng.u64(..)).unwrap()
+}
+
+fn random_rules(rng: &mut fastrand::Rng, colonies: usize) -> (AntConfig, SimOptions) {
+    let config = AntConfig {
+        seed: Some(rng.u64(..)),
+        max_moves: rng.u32(1..=ITERATIONS),
+        collision_threshold: rng.u16(2..4),
+        collision_policy: if rng.bool() {
+            CollisionPolicy::KillAll
+        } else {
+            CollisionPolicy::KillOldest { count: rng.usize(1..3) }
+        },
+        fight_trigger: if rng.bool() { FightTrigger::Exactly } else { FightTrigger::AtLeast },
+        ..AntConfig::new(rng.usize(..=2 * colonies))
+    };
+    let movement = if rng.bool() { MovementMode::Sequential } else { MovementMode::Simultaneous };
+    let options = SimOptions {
+        movement,
+        destruction: if rng.bool() { DestructionMode::Immediate } else { DestructionMode::Lazy },
+        dead_end_behavior: match rng.u8(..3) {
+            0 => DeadEndBehavior::StayForever,
+            1 => DeadEndBehavior::DieAfterNMoves(rng.u32(..3)),
+            _ => DeadEndBehavior::TeleportToRandom,
+        },
+        shuffle_order: rng.bool(),
+        factions: if rng.u8(..4) == 0 { rng.u8(2..4) } else { 0 },
+        cascade_on_destroy: rng.bool(),
+        ..SimOptions::default()
+    };
+    (config, options)
+}
+
+#[test]
+fn engine_matches_the_reference() {
+    for world in 0..WORLDS {
+        let mut rng = fastrand::Rng::with_seed(world);
+        let graph = random_map(&mut rng);
+        let (config, options) = random_rules(&mut rng, graph.colony_count());
+        let description = format!("world {}: {} colonies, {:?}, {:?}", world, graph.colony_count(), config, options);
+        if let Err(problem) = compare(graph, config, options) {
+            panic!("{}\n{}", problem, description);
+        }
+    }
+}
+
+#[test]
+fn engine_matches_the_reference_on_the_default_rules() {
+    let graph = ProceduralMapGenerator::topology(Topology::Grid { width: 6, height: 6 }, 0).unwrap();
+    let config = AntConfig { seed: Some(9), max_moves: ITERATIONS, ..AntConfig::new(30) };
+    compare(graph, config, SimOptions::default()).unwrap();
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "json")]
+
+use std::process::{Command, Output};
+
+fn ant_mania(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ant-mania"))
+        .args(args)
+        .output()
+        .expect("failed to run ant-mania")
+}
+
+fn json_report(output: &Output) -> serde_json::Value {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+// one test on purpose: the variables are process-wide and every child inherits them
+#[test]
+fn env_vars_sit_between_flags_and_the_config_file() {
+    let config = std::env::temp_dir().join(format!("ant_mania_env_{}.toml", std::process::id()));
+    std::fs::write(&config, "seed = 1\nmax-moves = 100\nformat = \"text\"\n").unwrap();
+    let config = config.to_str().unwrap();
+
+    std::env::set_var("ANT_MANIA_MAP", "hiveum_map_small.txt");
+    std::env::set_var("ANT_MANIA_NUM_ANTS", "6");
+    std::env::set_var("ANT_MANIA_MAX_MOVES", "250");
+    std::env::set_var("ANT_MANIA_FORMAT", "json");
+    std::env::set_var("ANT_MANIA_QUIET", "true");
+
+    // env beats the file for max-moves and format, the file still sets the seed
+    let report = json_report(&ant_mania(&["--config", config]));
+    assert_eq!(report["seed"], 1);
+    assert_eq!(report["max_moves"], 250);
+    assert_eq!(report["total_ants"], 6);
+
+    // flags beat both
+    let report = json_report(&ant_mania(&["--config", config, "--seed", "2", "--max-moves", "300"]));
+    assert_eq!(report["seed"], 2);
+    assert_eq!(report["max_moves"], 300);
+
+    std::env::set_var("ANT_MANIA_MAX_MOVES", "lots");
+    let output = ant_mania(&[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'lots' in ANT_MANIA_MAX_MOVES"));
+    // a good flag hides a bad variable
+    assert!(ant_mania(&["--max-moves", "50"]).status.success());
+
+    std::fs::remove_file(config).unwrap();
+}
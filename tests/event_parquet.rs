@@ -0,0 +1,96 @@
+#![cfg(feature = "parquet")]
+
+use std::fs::File;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ant_mania::{AntConfig, AntSimulation, EventParquetWriter, MapGraph, SimEvent};
+use arrow_array::cast::AsArray;
+use arrow_array::types::UInt32Type;
+use arrow_array::{Array, RecordBatch};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("ant_mania_{}_{}.parquet", name, std::process::id()))
+}
+
+/// The row groups and the batches in a file
+fn read_back(path: &std::path::Path) -> (usize, Vec<RecordBatch>) {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap()).unwrap();
+    assert_eq!(reader.schema().as_ref(), EventParquetWriter::<File>::schema().as_ref());
+    let row_groups = reader.metadata().num_row_groups();
+    (row_groups, reader.build().unwrap().map(Result::unwrap).collect())
+}
+
+#[test]
+fn reads_back_every_event_of_a_run() {
+    let graph = Arc::new(MapGraph::from_file("hiveum_map_small.txt").unwrap());
+    let mut sim = AntSimulation::new(graph, AntConfig { seed: Some(5), ..AntConfig::new(20) }).unwrap();
+    sim.record_events(true);
+    let path = temp_path("events");
+    let mut writer = EventParquetWriter::new(File::create(&path).unwrap(), 64).unwrap();
+    let mut events = Vec::new();
+    while sim.should_continue() && sim.iteration() < sim.max_moves() {
+        sim.run_iteration();
+        writer.write(sim.iteration() - 1, sim.last_events()).unwrap();
+        events.extend(sim.last_events().iter().map(|&event| (sim.iteration() - 1, event)));
+    }
+    assert_eq!(writer.events_written(), events.len() as u64);
+    writer.finish().unwrap();
+
+    let (row_groups, batches) = read_back(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(row_groups, events.len().div_ceil(64));
+    assert_eq!(batches.iter().map(RecordBatch::num_rows).sum::<usize>(), events.len());
+
+    let mut rows = events.iter();
+    for batch in &batches {
+        let iterations = batch.column_by_name("iteration").unwrap().as_primitive::<UInt32Type>();
+        let types = batch.column_by_name("event_type").unwrap().as_string::<i32>();
+        let colonies = batch.column_by_name("colony").unwrap().as_primitive::<UInt32Type>();
+        let to = batch.column_by_name("to").unwrap().as_primitive::<UInt32Type>();
+        for row in 0..batch.num_rows() {
+            let &(iteration, event) = rows.next().unwrap();
+            assert_eq!(iterations.value(row), iteration);
+            assert_eq!(types.value(row), event.name());
+            match event {
+                SimEvent::Move { to: colony, .. } => {
+                    assert!(colonies.is_null(row));
+                    assert_eq!(to.value(row) as usize, colony);
+                }
+                SimEvent::Fight { colony, .. } | SimEvent::Death { colony, .. } | SimEvent::Destroyed { colony } => {
+                    assert!(to.is_null(row));
+                    assert_eq!(colonies.value(row) as usize, colony);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn the_cli_finishes_the_file_when_interrupted() {
+    // two ants on a two-colony loop swap places until Ctrl-C stops them
+    let map = std::env::temp_dir().join(format!("ant_mania_loop_{}.txt", std::process::id()));
+    std::fs::write(&map, "A east=B\nB west=A\n").unwrap();
+    let path = temp_path("interrupted");
+    let child = Command::new(env!("CARGO_BIN_EXE_ant-mania"))
+        .args([map.to_str().unwrap(), "2", "--distribution", "distinct", "--max-moves", "4000000000"])
+        .args(["--format", "json", "--quiet", "--events-parquet", path.to_str().unwrap()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(500));
+    let killed = Command::new("kill").args(["-INT", &child.id().to_string()]).status().unwrap();
+    assert!(killed.success());
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_file(&map).unwrap();
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["interrupted"], true);
+    let (_, batches) = read_back(&path);
+    std::fs::remove_file(&path).unwrap();
+    let rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+    assert_eq!(rows as u64, 2 * report["iterations"].as_u64().unwrap());
+}
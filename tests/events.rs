@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, DeathCause, MapGraph, PlacementStrategy, SimEvent};
+
+/// A star around 1: the ants on 0 and 2 can only go to 1, where they fight
+fn star() -> AntSimulation {
+    let edges = [(0, 1), (1, 0), (2, 1), (1, 2)];
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 3).unwrap());
+    let placement_strategy = PlacementStrategy::Explicit(vec![Some(0), Some(2)]);
+    AntSimulation::new(graph, AntConfig { placement_strategy, ..AntConfig::new(2) }).unwrap()
+}
+
+#[test]
+fn events_are_only_kept_when_asked_for() {
+    let mut sim = star();
+    sim.run_iteration();
+    assert!(sim.last_events().is_empty());
+}
+
+#[test]
+fn events_tell_what_happened_in_the_last_iteration() {
+    let mut sim = star();
+    sim.record_events(true);
+    sim.run_iteration();
+    assert_eq!(
+        sim.last_events(),
+        [
+            SimEvent::Move { ant: 0, from: 0, to: 1 },
+            SimEvent::Move { ant: 1, from: 2, to: 1 },
+            SimEvent::Fight { colony: 1, ant_a: 0, ant_b: 1 },
+            SimEvent::Destroyed { colony: 1 },
+            SimEvent::Death { ant: 0, colony: 1, cause: DeathCause::Fight },
+            SimEvent::Death { ant: 1, colony: 1, cause: DeathCause::Fight },
+        ]
+    );
+
+    // each iteration starts over
+    sim.run_iteration();
+    assert!(sim.last_events().is_empty());
+}
+
+#[test]
+fn every_move_is_an_event() {
+    let graph = Arc::new(MapGraph::from_file("hiveum_map_small.txt").unwrap());
+    let mut sim = AntSimulation::new(graph, AntConfig { seed: Some(5), ..AntConfig::new(20) }).unwrap();
+    sim.record_events(true);
+    let mut moves = 0;
+    let mut destroyed = 0;
+    while sim.should_continue() && sim.iteration() < sim.max_moves() {
+        sim.run_iteration();
+        moves += sim.last_events().iter().filter(|event| matches!(event, SimEvent::Move { .. })).count() as u64;
+        destroyed += sim.last_events().iter().filter(|event| matches!(event, SimEvent::Destroyed { .. })).count();
+    }
+    assert_eq!(moves, sim.simulation_stats().total_moves_made);
+    assert_eq!(destroyed, sim.simulation_stats().total_colonies - sim.simulation_stats().active_colonies);
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, CollisionPolicy, FightTrigger, MapGraph, PlacementStrategy};
+
+fn sim(edges: &[(usize, usize)], colonies: usize, positions: Vec<Option<usize>>, config: AntConfig) -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(edges, colonies).unwrap());
+    let config = AntConfig {
+        placement_strategy: PlacementStrategy::Explicit(positions),
+        seed: Some(1),
+        ..config
+    };
+    AntSimulation::new(graph, config).unwrap()
+}
+
+#[test]
+fn reports_where_and_when_the_first_fight_was() {
+    // one-way paths 0 -> 3 -> 1 and 2 -> 4 -> 1, the ants meet on their second move
+    let edges = [(0, 3), (3, 1), (2, 4), (4, 1)];
+    let mut sim = sim(&edges, 5, vec![Some(0), Some(2)], AntConfig::new(2));
+    let (colony, mut killed, iteration) = sim.simulate_until_first_collision().unwrap();
+    killed.sort_unstable();
+    assert_eq!((colony, killed, iteration), (1, vec![0, 1], 1));
+    assert_eq!(sim.iteration(), 2);
+}
+
+#[test]
+fn only_the_victims_are_reported() {
+    let edges = [(0, 2), (1, 2), (3, 2)];
+    // three ants arrive together, a crowd that only fights with AtLeast
+    let config = AntConfig {
+        collision_policy: CollisionPolicy::KillOldest { count: 1 },
+        fight_trigger: FightTrigger::AtLeast,
+        ..AntConfig::new(3)
+    };
+    let mut sim = sim(&edges, 4, vec![Some(0), Some(1), Some(2)], config);
+    let (colony, killed, iteration) = sim.simulate_until_first_collision().unwrap();
+    assert_eq!((colony, killed.len(), iteration), (2, 1, 0));
+    assert_eq!(sim.stats().0, 2);
+}
+
+#[test]
+fn none_when_the_run_ends_without_a_fight() {
+    // two ants swapping places along a single tunnel never share a colony
+    let edges = [(0, 1), (1, 0)];
+    let config = AntConfig { max_moves: 10, ..AntConfig::new(2) };
+    let mut sim = sim(&edges, 2, vec![Some(0), Some(1)], config);
+    assert_eq!(sim.simulate_until_first_collision(), None);
+    assert_eq!(sim.iteration(), 10);
+    assert_eq!(sim.simulate_until_first_collision(), None);
+}
+
+#[test]
+fn agrees_with_stepping_by_hand() {
+    let graph = Arc::new(MapGraph::from_file("hiveum_map_small.txt").unwrap());
+    for seed in 0..20 {
+        let config = AntConfig { seed: Some(seed), ..AntConfig::new(30) };
+        let mut stepped = AntSimulation::new(Arc::clone(&graph), config.clone()).unwrap();
+        let first = loop {
+            if !stepped.should_continue() || stepped.iteration() >= stepped.max_moves() {
+                break None;
+            }
+            let result = stepped.run_iteration();
+            if result.colonies_destroyed > 0 {
+                break Some(result);
+            }
+        };
+
+        let mut sim = AntSimulation::new(Arc::clone(&graph), config).unwrap();
+        match (sim.simulate_until_first_collision(), first) {
+            (Some((colony, killed, iteration)), Some(result)) => {
+                assert_eq!(iteration, result.iteration, "seed {}", seed);
+                assert!(sim.iter().nth(colony).unwrap().destroyed, "seed {}", seed);
+                assert!(!killed.is_empty() && killed.len() <= result.ants_killed, "seed {}", seed);
+            }
+            (None, None) => {}
+            (found, first) => panic!("seed {}: {:?} but stepping found {:?}", seed, found, first),
+        }
+        assert_eq!(sim.iteration(), stepped.iteration(), "seed {}", seed);
+    }
+}
+
+#[test]
+fn recorded_fights_last_one_iteration() {
+    let edges = [(0, 3), (3, 1), (2, 4), (4, 1)];
+    let mut sim = sim(&edges, 5, vec![Some(0), Some(2)], AntConfig::new(2));
+    sim.run_iteration();
+    assert!(sim.last_fights().is_empty());
+    sim.record_fights(true);
+    sim.run_iteration();
+    let fights = sim.last_fights();
+    assert_eq!(fights.len(), 1);
+    assert_eq!((fights[0].0, fights[0].1.len()), (1, 2));
+    sim.run_iteration();
+    assert!(sim.last_fights().is_empty());
+}
+
+#[test]
+fn by_default_only_two_ants_fight() {
+    // three ants walk into colony 2 at once, as they can with lazy destruction
+    let edges = [(0, 2), (1, 2), (3, 2)];
+    let positions = || vec![Some(0), Some(1), Some(3)];
+    let mut crowd = sim(&edges, 4, positions(), AntConfig { max_moves: 5, ..AntConfig::new(3) });
+    assert_eq!(crowd.simulate_until_first_collision(), None);
+    assert_eq!(crowd.stats().0, 3);
+
+    let config = AntConfig { fight_trigger: FightTrigger::AtLeast, ..AntConfig::new(3) };
+    let mut crowd = sim(&edges, 4, positions(), config);
+    let (colony, killed, _) = crowd.simulate_until_first_collision().unwrap();
+    // KillFirst { count: 2 } leaves the third ant at the ruins
+    assert_eq!((colony, killed.len()), (2, 2));
+    assert_eq!(crowd.stats().0, 1);
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use ant_mania::{AntSimulation, Direction, MapGraph, SimError};
+
+/// A colony and its `(neighbor, direction)` tunnels
+type Colony<'a> = (&'a str, &'a [(&'a str, &'a str)]);
+
+/// `[("A", [("B", "north")]), ...]` as the map `from_dict` takes
+fn dict(colonies: &[Colony]) -> HashMap<String, Vec<(String, Direction)>> {
+    (colonies.iter())
+        .map(|&(name, tunnels)| {
+            let tunnels = tunnels.iter().map(|&(to, direction)| (to.to_string(), direction.into())).collect();
+            (name.to_string(), tunnels)
+        })
+        .collect()
+}
+
+#[test]
+fn builds_the_map_described() {
+    let map = dict(&[
+        ("Foo", &[("Bar", "north"), ("Baz", "west")]),
+        ("Bar", &[("Foo", "south")]),
+        ("Baz", &[("Foo", "east"), ("Bar", "ferry")]),
+        ("Qux", &[]),
+    ]);
+    let graph = MapGraph::from_dict(map.clone()).unwrap();
+    graph.check_structure().unwrap();
+
+    // numbered by name, tunnels in the order given
+    let names: Vec<_> = (0..graph.colony_count()).map(|colony| graph.colony_name(colony)).collect();
+    assert_eq!(names, ["Bar", "Baz", "Foo", "Qux"]);
+    let foo = graph.find_colony("Foo").unwrap();
+    assert_eq!((graph.edge_label(foo, 0), graph.edge_label(foo, 1)), ("north", "west"));
+    assert_eq!(graph.edge_label(1, 1), "ferry");
+    assert_eq!(graph.adjacency_slice(), [2, 2, 0, 0, 1]);
+
+    let mut written = Vec::new();
+    graph.write_map(&mut written, |_| true).unwrap();
+    let written = String::from_utf8(written).unwrap();
+    assert!(written.contains("Foo north=Bar west=Baz"), "{}", written);
+
+    let sim = AntSimulation::from_dict(map, 3).unwrap();
+    assert_eq!(sim.total_ants(), 3);
+}
+
+#[test]
+fn directions_read_as_their_labels() {
+    assert_eq!(Direction::from("east"), Direction::East);
+    assert_eq!(Direction::from("ferry"), Direction::Other("ferry".to_string()));
+    assert_eq!(Direction::South.to_string(), "south");
+}
+
+#[test]
+fn unknown_neighbors_are_rejected() {
+    let err = MapGraph::from_dict(dict(&[("Foo", &[("Nowhere", "north")])])).unwrap_err();
+    assert!(matches!(&err, SimError::UnknownColony(name) if name == "Nowhere"), "{}", err);
+}
+
+#[test]
+fn bad_tunnels_are_rejected() {
+    let cases: [&[Colony]; 5] = [
+        &[("Foo", &[("Foo", "north")])],
+        &[("Foo", &[("Bar", "north"), ("Bar", "north")]), ("Bar", &[])],
+        &[("Foo", &[("Bar", "north"), ("Bar", "south")]), ("Bar", &[])],
+        &[("Foo", &[("Bar", "north"), ("Baz", "north")]), ("Bar", &[]), ("Baz", &[])],
+        &[("Foo", &[("Bar", "up=down")]), ("Bar", &[])],
+    ];
+    for case in cases {
+        let err = MapGraph::from_dict(dict(case)).unwrap_err();
+        assert!(matches!(&err, SimError::InvalidTunnel { colony, .. } if colony == "Foo"), "{:?}: {}", case, err);
+    }
+
+    let err = MapGraph::from_dict(dict(&[("New York", &[])])).unwrap_err();
+    assert!(matches!(err, SimError::InvalidConfig(_)), "{}", err);
+}
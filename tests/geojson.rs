@@ -0,0 +1,110 @@
+#![cfg(feature = "geojson")]
+
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, PlacementStrategy, SimError};
+
+const FRANCE: &str = r#"{"type": "FeatureCollection", "features": [
+    {"type": "Feature", "geometry": {"type": "Point", "coordinates": [2.3522, 48.8566]},
+     "properties": {"name": "Paris", "tunnels": ["south=Lyon", "west=Nantes"], "aliases": ["Lutetia"]}},
+    {"type": "Feature", "geometry": {"type": "Point", "coordinates": [4.8357, 45.764]},
+     "properties": {"name": "Lyon", "tunnels": ["north=Paris"]}},
+    {"type": "Feature", "geometry": null, "properties": {"name": "Nantes", "tunnels": ["east=Paris"]}}
+]}"#;
+
+fn map_text(graph: &MapGraph) -> String {
+    let mut out = Vec::new();
+    graph.write_map(&mut out, |_| true).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn reads_colonies_tunnels_and_coordinates() {
+    let graph = MapGraph::from_geojson(FRANCE).unwrap();
+    assert!(!graph.is_legacy_format());
+    assert!(graph.parse_warnings().is_empty());
+    assert_eq!(
+        map_text(&graph),
+        "ant_mania_map v1\nParis south=Lyon west=Nantes\nLyon north=Paris\nNantes east=Paris\n\
+         [aliases]\nLutetia=Paris\n"
+    );
+    assert_eq!(graph.coordinates(0), Some([2.3522, 48.8566]));
+    assert_eq!(graph.coordinates(2), None);
+
+    let km = graph.distance_km(0, 1).unwrap();
+    assert!((km - 392.0).abs() < 1.0, "{}", km);
+    assert_eq!(graph.distance_km(0, 2), None);
+    assert_eq!(graph.path_km(&[0, 1, 0]), Some(2.0 * km));
+    assert_eq!(graph.path_km(&[1, 0, 2]), None);
+}
+
+#[test]
+fn round_trips_through_write_geojson() {
+    let graph = MapGraph::from_geojson(FRANCE).unwrap();
+    let mut out = Vec::new();
+    graph.write_geojson(&mut out, |_| true).unwrap();
+    let again = MapGraph::from_geojson(std::str::from_utf8(&out).unwrap()).unwrap();
+    assert_eq!(map_text(&again), map_text(&graph));
+    for colony in 0..graph.colony_count() {
+        assert_eq!(again.coordinates(colony), graph.coordinates(colony));
+    }
+}
+
+#[test]
+fn writes_the_surviving_world_with_its_ants() {
+    let graph = Arc::new(MapGraph::from_geojson(FRANCE).unwrap());
+    let config = AntConfig {
+        // both ants in Lyon can only go to Paris, and the one there leaves it
+        placement_strategy: PlacementStrategy::Explicit(vec![Some(1), Some(1), Some(0)]),
+        ..AntConfig::new(3)
+    };
+    let mut sim = AntSimulation::new(graph, config).unwrap();
+    sim.run_iteration();
+    let mut out = Vec::new();
+    sim.write_geojson(&mut out).unwrap();
+    let world: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let features = world["features"].as_array().unwrap();
+    let names: Vec<_> = features.iter().map(|feature| feature["properties"]["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["Lyon", "Nantes"]);
+    assert_eq!(features[0]["properties"]["tunnels"], serde_json::json!([]));
+    assert_eq!(features[0]["geometry"]["coordinates"], serde_json::json!([4.8357, 45.764]));
+    assert!(features[1]["geometry"].is_null());
+    let ants: u64 = features.iter().map(|feature| feature["properties"]["ants"].as_u64().unwrap()).sum();
+    assert_eq!(ants, sim.stats().0 as u64);
+}
+
+#[test]
+fn refuses_what_isnt_a_map() {
+    for text in [
+        "[]",
+        r#"{"type": "Feature"}"#,
+        r#"{"type": "FeatureCollection", "features": [{"properties": {"name": "two words"}}]}"#,
+        r#"{"type": "FeatureCollection", "features": [{"properties": {"name": "A", "tunnels": "north=B"}}]}"#,
+        r#"{"type": "FeatureCollection", "features": [
+            {"geometry": {"type": "LineString", "coordinates": [[0, 0], [1, 1]]}, "properties": {"name": "A"}}
+        ]}"#,
+    ] {
+        assert!(matches!(MapGraph::from_geojson(text), Err(SimError::GeoJson(_))), "{}", text);
+    }
+}
+
+#[test]
+fn measures_how_far_a_traced_ant_went() {
+    let graph = Arc::new(MapGraph::from_geojson(FRANCE).unwrap());
+    let config = AntConfig {
+        placement_strategy: PlacementStrategy::Explicit(vec![Some(1), Some(2)]),
+        ..AntConfig::new(2)
+    };
+    let mut sim = AntSimulation::new(graph.clone(), config).unwrap();
+    sim.trace_ants(&[0]);
+    assert_eq!(sim.distance_traveled_km(0), Some(0.0));
+    // Lyon and Nantes only lead to Paris, where the two meet and fight
+    sim.run_iteration();
+    assert_eq!(sim.ant_path(0), Some(&[1, 0][..]));
+    assert_eq!(sim.distance_traveled_km(0), graph.distance_km(1, 0));
+    assert_eq!(sim.ant_path(1), None);
+    assert_eq!(sim.distance_traveled_km(1), None);
+
+    sim.trace_ants(&[]);
+    assert_eq!(sim.ant_path(0), None);
+}
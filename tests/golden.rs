@@ -0,0 +1,131 @@
+//! Whole runs of the small maps in tests/maps with fixed seeds, checked line
+//! for line against the transcripts in tests/golden. A change that moves any
+//! ant differently shows up here as a diff. When the change is meant, run
+//! `UPDATE_GOLDENS=1 cargo test --test golden` and review the new files.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, CollisionPolicy, FightTrigger, MapGraph, PlacementStrategy};
+
+fn map_path(map: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/maps").join(map)
+}
+
+/// Compare `actual` with tests/golden/`name`, or overwrite the file with it
+/// when UPDATE_GOLDENS is set
+fn check_golden(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(name);
+    if std::env::var_os("UPDATE_GOLDENS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("{}: {}, run with UPDATE_GOLDENS=1 to create it", path.display(), err));
+    if actual != expected {
+        let first_difference = actual.lines().zip(expected.lines()).position(|(a, e)| a != e);
+        let line = first_difference.unwrap_or(actual.lines().count().min(expected.lines().count()));
+        panic!(
+            "{} differs from this run from line {}, rerun with UPDATE_GOLDENS=1 if that's intended\n\
+             --- expected\n{}\n--- actual\n{}",
+            path.display(),
+            line + 1,
+            expected,
+            actual
+        );
+    }
+}
+
+/// Run `map` to the end and write down what happened: where the ants
+/// started, every fight by the colonies it destroyed, then the final world
+/// and the counters
+fn transcript(map: &str, config: AntConfig, max_moves: u32) -> String {
+    let graph = Arc::new(MapGraph::from_file(map_path(map).to_str().unwrap()).unwrap());
+    let mut sim = AntSimulation::new(graph, config).unwrap().with_max_moves(max_moves);
+    let mut out = String::new();
+
+    writeln!(out, "map: {}, {} ants, seed {}", map, sim.total_ants(), sim.seed()).unwrap();
+    let start: Vec<_> = (sim.iter())
+        .filter(|colony| colony.ant_count > 0)
+        .map(|colony| format!("{} {}", colony.name, colony.ant_count))
+        .collect();
+    writeln!(out, "start: {}", start.join(", ")).unwrap();
+
+    let mut destroyed: Vec<bool> = sim.iter().map(|colony| colony.destroyed).collect();
+    let status = sim.run_until(&AtomicBool::new(false), None, |sim, result| {
+        if result.colonies_destroyed > 0 {
+            let names: Vec<_> = (sim.iter())
+                .filter(|colony| colony.destroyed && !destroyed[colony.id])
+                .map(|colony| colony.name)
+                .collect();
+            let (iteration, killed) = (result.iteration, result.ants_killed);
+            writeln!(out, "iteration {}: {} destroyed, {} ants killed", iteration, names.join(", "), killed).unwrap();
+            for colony in sim.iter() {
+                destroyed[colony.id] = colony.destroyed;
+            }
+        }
+        std::ops::ControlFlow::Continue(())
+    });
+    writeln!(out, "ended after {} iterations", status.iterations).unwrap();
+
+    let trapped: Vec<_> = (sim.dead_end_colonies().into_iter())
+        .filter(|&colony| sim.has_ants_at(colony))
+        .map(|colony| format!("{} {}", sim.graph().colony_name(colony), sim.ants_at_slice(colony).len()))
+        .collect();
+    writeln!(out, "trapped: {}", if trapped.is_empty() { "none".to_string() } else { trapped.join(", ") }).unwrap();
+
+    let mut world = Vec::new();
+    sim.write_remaining_world(&mut world).unwrap();
+    out.push_str(&String::from_utf8(world).unwrap());
+
+    let stats = sim.simulation_stats();
+    writeln!(out, "\nactive colonies: {}/{}", stats.active_colonies, stats.total_colonies).unwrap();
+    writeln!(out, "total moves: {} ({:.2} per iteration)", stats.total_moves_made, stats.moves_per_iteration).unwrap();
+    let peaks: Vec<_> = (sim.iter()).map(|colony| format!("{} {}", colony.name, colony.peak_ant_count)).collect();
+    writeln!(out, "peak ants: {}", peaks.join(", ")).unwrap();
+    out
+}
+
+fn seeded(num_ants: usize, seed: u64) -> AntConfig {
+    AntConfig { seed: Some(seed), ..AntConfig::new(num_ants) }
+}
+
+#[test]
+fn golden_trapping() {
+    // the hub goes first, leaving the survivors stuck on the spokes; for
+    // that the crowds at the hub have to fight, and to the last ant
+    let config = AntConfig {
+        collision_policy: CollisionPolicy::KillAll,
+        fight_trigger: FightTrigger::AtLeast,
+        ..seeded(6, 11)
+    };
+    check_golden("trapping.txt", &transcript("trapping.txt", config, 50));
+}
+
+#[test]
+fn golden_spawn_collision() {
+    // the ants on the ends can only walk into the middle, so they meet
+    // there on the very first move
+    let positions = vec![Some(1), Some(1), Some(0), Some(2)];
+    let config = AntConfig { placement_strategy: PlacementStrategy::Explicit(positions), ..seeded(4, 2) };
+    check_golden("spawn_collision.txt", &transcript("spawn_collision.txt", config, 50));
+}
+
+#[test]
+fn golden_extinction() {
+    check_golden("extinction.txt", &transcript("extinction.txt", seeded(4, 3), 50));
+}
+
+#[test]
+fn golden_binary_output() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ant-mania"))
+        .arg(map_path("extinction.txt"))
+        .args(["4", "--seed", "3", "--no-timing"])
+        .output()
+        .expect("failed to run ant-mania");
+    assert!(output.status.success());
+    check_golden("extinction.cli.txt", &String::from_utf8(output.stdout).unwrap());
+}
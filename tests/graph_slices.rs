@@ -0,0 +1,26 @@
+use ant_mania::MapGraph;
+
+#[test]
+fn csr_slices_describe_the_map() {
+    let graph = MapGraph::from_file("hiveum_map_small.txt").unwrap();
+    let (adjacency, offsets, counts) = (graph.adjacency_slice(), graph.start_offsets(), graph.connection_counts());
+    assert_eq!(offsets.len(), graph.colony_count());
+    assert_eq!(counts.len(), graph.colony_count());
+    assert_eq!(adjacency.len(), graph.edge_count());
+
+    let mut next = 0;
+    for colony in 0..graph.colony_count() {
+        assert_eq!(offsets[colony], next, "colony {} doesn't start where the last one ended", colony);
+        next += counts[colony] as u32;
+    }
+    assert_eq!(next as usize, graph.edge_count());
+
+    // matches what the map file says about the first colony
+    let first = &adjacency[offsets[0] as usize..][..counts[0] as usize];
+    let mut written = Vec::new();
+    graph.write_map(&mut written, |_| true).unwrap();
+    let first_line = String::from_utf8(written).unwrap().lines().nth(1).unwrap().to_string();
+    let named: Vec<_> = first.iter().map(|&target| graph.colony_name(target as usize)).collect();
+    let expected: Vec<_> = first_line.split_whitespace().skip(1).map(|part| part.split_once('=').unwrap().1).collect();
+    assert_eq!(named, expected);
+}
@@ -0,0 +1,50 @@
+use ant_mania::{AntSimulation, ProceduralMapGenerator, Topology};
+
+fn on(shape: &str) -> AntSimulation {
+    ProceduralMapGenerator::from_topology(shape.parse().unwrap(), 0, 0).unwrap()
+}
+
+#[test]
+fn a_ring_has_no_weak_spot() {
+    let stats = on("ring:6").graph_stats();
+    assert_eq!((stats.live_colonies, stats.tunnels, stats.components), (6, 12, 1));
+    assert_eq!((stats.degree.min, stats.degree.max), (2, 2));
+    assert_eq!((stats.dead_ends, stats.articulation_points, stats.diameter), (0, 0, Some(3)));
+    assert_eq!(stats.clustering_coefficient, 0.0);
+}
+
+#[test]
+fn every_inner_colony_of_a_line_holds_it_together() {
+    let stats = on("grid:5x1").graph_stats();
+    assert_eq!((stats.articulation_points, stats.diameter), (3, Some(4)));
+}
+
+#[test]
+fn a_triangle_is_fully_clustered() {
+    let edges = [(0, 1), (1, 0), (1, 2), (2, 1), (2, 0), (0, 2)];
+    let stats = AntSimulation::from_adjacency_list_raw(&edges, 3, 0).unwrap().graph_stats();
+    assert_eq!(stats.clustering_coefficient, 1.0);
+    assert_eq!(stats.diameter, Some(1));
+}
+
+#[test]
+fn destroyed_colonies_leave_the_stats() {
+    // a one-way tunnel out of 0 and a lone colony 3
+    let edges = [(0, 1), (1, 2), (2, 1)];
+    let mut sim = AntSimulation::from_adjacency_list_raw(&edges, 4, 0).unwrap();
+    let stats = sim.graph_stats();
+    assert_eq!((stats.tunnels, stats.dead_ends, stats.components, stats.articulation_points), (3, 1, 2, 1));
+
+    // 2 goes with 1, 0 only had a way in
+    sim.destroy_cascade(1);
+    let stats = sim.graph_stats();
+    assert_eq!((stats.live_colonies, stats.total_colonies), (2, 4));
+    assert_eq!((stats.tunnels, stats.dead_ends, stats.components), (0, 2, 2));
+}
+
+#[test]
+fn a_torus_has_no_edge() {
+    let sim = ProceduralMapGenerator::from_topology(Topology::Torus { width: 4, height: 4 }, 0, 0).unwrap();
+    let stats = sim.graph_stats();
+    assert_eq!((stats.degree.min, stats.articulation_points, stats.diameter), (4, 0, Some(4)));
+}
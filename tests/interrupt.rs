@@ -0,0 +1,41 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, PlacementStrategy};
+
+#[test]
+fn interrupt_flag_stops_at_the_iteration_boundary() {
+    // two ants apart on a two-colony loop swap places forever, so only the flag stops them
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[(0, 1), (1, 0)], 2).unwrap());
+    let config = AntConfig { placement_strategy: PlacementStrategy::Distinct, ..AntConfig::new(2) };
+    let mut sim = AntSimulation::new(graph, config).unwrap();
+    let interrupt = AtomicBool::new(false);
+
+    // stands in for the Ctrl-C handler firing during iteration 5
+    let status = sim.run_interruptible(&interrupt, |result| {
+        if result.iteration == 4 {
+            interrupt.store(true, Ordering::Relaxed);
+        }
+    });
+    assert!(status.interrupted);
+    assert_eq!(status.iterations, 5);
+    assert!(sim.should_continue());
+
+    // an already raised flag doesn't run anything
+    let status = sim.run_interruptible(&interrupt, |_| panic!("ran an iteration after the interrupt"));
+    assert!(status.interrupted);
+    assert_eq!(status.iterations, 0);
+}
+
+#[test]
+fn passed_deadline_stops_before_the_next_iteration() {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[(0, 1), (1, 0)], 2).unwrap());
+    let config = AntConfig { placement_strategy: PlacementStrategy::Distinct, ..AntConfig::new(2) };
+    let mut sim = AntSimulation::new(graph, config).unwrap();
+
+    let status = sim.run_until(&AtomicBool::new(false), Some(Instant::now()), |_, _| panic!("ran past the deadline"));
+    assert!(status.timed_out);
+    assert!(!status.interrupted);
+    assert_eq!(status.iterations, 0);
+}
@@ -0,0 +1,165 @@
+//! Random small worlds run with random rules, checking the simulation's
+//! bookkeeping after every iteration. The worlds come from proptest, which
+//! shrinks a failing one down to as few colonies, tunnels and ants as still
+//! fail before reporting it.
+
+use std::ops::ControlFlow;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use ant_mania::{
+    AntConfig, AntSimulation, CollisionPolicy, DeadEndBehavior, DestructionMode, FightTrigger, MapGraph, MovementMode,
+    SimOptions,
+};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+const MAX_COLONIES: usize = 8;
+const ITERATIONS: u32 = 40;
+
+/// One generated world and the rules it runs under
+#[derive(Debug, Clone)]
+struct World {
+    colonies: usize,
+    edges: Vec<(usize, usize)>,
+    config: AntConfig,
+    options: SimOptions,
+}
+
+/// What joins a pair of colonies; shrinks towards no tunnel at all
+#[derive(Debug, Clone, Copy)]
+enum Tunnel {
+    None,
+    Both,
+    Forward,
+    Back,
+}
+
+fn tunnel() -> impl Strategy<Value = Tunnel> {
+    // mostly two-way tunnels, with the odd one-way one
+    prop_oneof![6 => Just(Tunnel::None), 3 => Just(Tunnel::Both), 1 => Just(Tunnel::Forward), 1 => Just(Tunnel::Back)]
+}
+
+fn collision_policy() -> impl Strategy<Value = CollisionPolicy> {
+    prop_oneof![
+        Just(CollisionPolicy::KillAll),
+        (1..3usize).prop_map(|count| CollisionPolicy::KillFirst { count }),
+        (1..3usize).prop_map(|count| CollisionPolicy::KillRandom { count }),
+        (1..3usize).prop_map(|count| CollisionPolicy::KillOldest { count }),
+    ]
+}
+
+fn config(colonies: usize) -> impl Strategy<Value = AntConfig> {
+    let trigger = prop_oneof![Just(FightTrigger::Exactly), Just(FightTrigger::AtLeast)];
+    (0..=3 * colonies, any::<u64>(), 1..ITERATIONS, 2..4u16, collision_policy(), trigger).prop_map(
+        |(num_ants, seed, max_moves, collision_threshold, collision_policy, fight_trigger)| AntConfig {
+            seed: Some(seed),
+            max_moves,
+            collision_threshold,
+            collision_policy,
+            fight_trigger,
+            ..AntConfig::new(num_ants)
+        },
+    )
+}
+
+fn options() -> impl Strategy<Value = SimOptions> {
+    let movement = prop_oneof![Just(MovementMode::Sequential), Just(MovementMode::Simultaneous)];
+    let dead_end = prop_oneof![
+        Just(DeadEndBehavior::StayForever),
+        (0..3u32).prop_map(DeadEndBehavior::DieAfterNMoves),
+        Just(DeadEndBehavior::TeleportToRandom),
+    ];
+    let factions = prop_oneof![3 => Just(0u8), 1 => 2..4u8];
+    let rules = (movement, any::<bool>(), dead_end, factions);
+    let extras = (any::<[bool; 3]>(), proptest::option::of(1..4u32), proptest::option::of(1..4u16));
+    (rules, extras).prop_map(|(rules, extras)| {
+        let (movement, immediate, dead_end_behavior, factions) = rules;
+        let ([shuffle_order, track_components, cascade_on_destroy], reorder_every, oscillation_after) = extras;
+        SimOptions {
+            movement,
+            // simultaneous movement always settles lazily
+            destruction: if immediate && movement == MovementMode::Sequential {
+                DestructionMode::Immediate
+            } else {
+                DestructionMode::Lazy
+            },
+            dead_end_behavior,
+            shuffle_order,
+            reorder_every,
+            track_components,
+            factions,
+            cascade_on_destroy,
+            oscillation_after,
+        }
+    })
+}
+
+fn world() -> impl Strategy<Value = World> {
+    (1..=MAX_COLONIES)
+        .prop_flat_map(|colonies| {
+            let pairs = colonies * (colonies - 1) / 2;
+            (Just(colonies), vec(tunnel(), pairs), config(colonies), options())
+        })
+        .prop_map(|(colonies, tunnels, config, options)| {
+            let pairs = (0..colonies).flat_map(|from| (from + 1..colonies).map(move |to| (from, to)));
+            let mut edges = Vec::new();
+            for ((from, to), tunnel) in pairs.zip(tunnels) {
+                match tunnel {
+                    Tunnel::None => {}
+                    Tunnel::Both => edges.extend([(from, to), (to, from)]),
+                    Tunnel::Forward => edges.push((from, to)),
+                    Tunnel::Back => edges.push((to, from)),
+                }
+            }
+            World { colonies, edges, config, options }
+        })
+}
+
+fn build(world: &World) -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&world.edges, world.colonies).unwrap());
+    AntSimulation::new(graph, world.config.clone()).unwrap().with_options(world.options.clone())
+}
+
+/// Run `world`, checking after setup and after every iteration; the first
+/// problem and when it turned up
+fn check(world: &World) -> Result<(), String> {
+    let mut sim = build(world);
+    sim.check_invariants().map_err(|problem| format!("after setup: {}", problem))?;
+
+    let mut failure = None;
+    sim.run_until(&AtomicBool::new(false), None, |sim, result| match sim.check_invariants() {
+        Ok(()) => ControlFlow::Continue(()),
+        Err(problem) => {
+            failure = Some(format!("after iteration {}: {}", result.iteration, problem));
+            ControlFlow::Break(())
+        }
+    });
+    failure.map_or(Ok(()), Err)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(500))]
+
+    #[test]
+    fn bookkeeping_holds_in_random_worlds(world in world()) {
+        check(&world).map_err(TestCaseError::fail)?;
+    }
+
+    #[test]
+    fn bookkeeping_holds_after_destroy_cascade(world in world(), colony in any::<prop::sample::Index>()) {
+        let mut sim = build(&world);
+        sim.destroy_cascade(colony.index(world.colonies));
+        sim.check_invariants().map_err(|problem| TestCaseError::fail(format!("after destroy_cascade: {}", problem)))?;
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "simulation invariant broken")]
+fn debug_assertions_catch_a_corrupted_count() {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[(0, 1), (1, 0)], 2).unwrap());
+    let mut sim = AntSimulation::new(graph, AntConfig { seed: Some(1), ..AntConfig::new(0) }).unwrap();
+    sim.corrupt_ant_count(0);
+    sim.run_iteration();
+}
@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use ant_mania::{
+    AntConfig, AntRecord, AntSimulation, DeadEndBehavior, MapGraph, MarathonAnts, PlacementStrategy, SimOptions,
+};
+
+/// One ant stranded on 0 dies on its third try, two others shuttle between
+/// 1 and 2 and never meet
+fn stranded_and_shuttling() -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[(1, 2), (2, 1)], 3).unwrap());
+    let config = AntConfig {
+        placement_strategy: PlacementStrategy::Explicit(vec![Some(0), Some(1), Some(2)]),
+        seed: Some(8),
+        max_moves: 50,
+        ..AntConfig::new(3)
+    };
+    let options = SimOptions { dead_end_behavior: DeadEndBehavior::DieAfterNMoves(3), ..SimOptions::default() };
+    AntSimulation::new(graph, config).unwrap().with_options(options)
+}
+
+#[test]
+fn records_go_to_the_lowest_id_on_a_tie() {
+    let mut sim = stranded_and_shuttling();
+    sim.record_deaths(true);
+    assert_eq!(sim.marathon_ants(), MarathonAnts { most_moves: None, most_distinct: None, longest_lived: None });
+    for _ in 0..5 {
+        sim.run_iteration();
+    }
+    // both shuttling ants made 5 moves
+    assert_eq!(
+        sim.marathon_ants(),
+        MarathonAnts {
+            most_moves: Some(AntRecord { ant: 1, value: 5 }),
+            most_distinct: None,
+            longest_lived: Some(AntRecord { ant: 0, value: 2 }),
+        }
+    );
+    sim.destroy_cascade(1);
+    assert_eq!(sim.marathon_ants().longest_lived, Some(AntRecord { ant: 1, value: 5 }));
+}
+
+#[test]
+fn the_longest_lived_needs_deaths_recorded() {
+    let mut sim = stranded_and_shuttling();
+    for _ in 0..5 {
+        sim.run_iteration();
+    }
+    assert_eq!(sim.marathon_ants().longest_lived, None);
+    assert_eq!(sim.marathon_ants().most_moves, Some(AntRecord { ant: 1, value: 5 }));
+}
+
+#[test]
+fn most_distinct_colonies_come_from_the_traced_paths() {
+    let mut sim = stranded_and_shuttling();
+    sim.trace_ants(&[2, 0, 1]);
+    for _ in 0..5 {
+        sim.run_iteration();
+    }
+    // both shuttling ants saw 1 and 2, ant 0 only ever saw 0
+    assert_eq!(sim.marathon_ants().most_distinct, Some(AntRecord { ant: 1, value: 2 }));
+    sim.trace_ants(&[0]);
+    assert_eq!(sim.marathon_ants().most_distinct, Some(AntRecord { ant: 0, value: 1 }));
+    sim.trace_ants(&[]);
+    assert_eq!(sim.marathon_ants().most_distinct, None);
+}
+
+#[test]
+fn most_distinct_colonies_on_a_seeded_run() {
+    let graph = Arc::new(MapGraph::from_file("hiveum_map_small.txt").unwrap());
+    let mut sim = AntSimulation::new(graph, AntConfig { seed: Some(12), ..AntConfig::new(40) }).unwrap();
+    let traced: Vec<usize> = (0..40).step_by(3).collect();
+    sim.trace_ants(&traced);
+    sim.run_to_completion();
+
+    let distinct = |ant: usize| sim.ant_path(ant).unwrap().iter().collect::<HashSet<_>>().len() as u32;
+    let most = traced.iter().map(|&ant| distinct(ant)).max().unwrap();
+    let ant = *traced.iter().find(|&&ant| distinct(ant) == most).unwrap();
+    assert_eq!(sim.marathon_ants().most_distinct, Some(AntRecord { ant, value: most }));
+    assert!(most > 1, "no traced ant got anywhere");
+}
@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph};
+
+#[test]
+fn two_ants_on_a_loop_stop_at_the_move_budget() {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[(0, 1), (1, 0)], 2).unwrap());
+
+    // find a seed that starts the ants apart, after that they swap places
+    // every iteration and never end up together
+    let sim = (0..100).find_map(|seed| {
+        let config = AntConfig { num_ants: 2, seed: Some(seed), max_moves: 37, ..AntConfig::default() };
+        let sim = AntSimulation::new(Arc::clone(&graph), config).unwrap();
+        (sim.has_ants_at(0) && sim.has_ants_at(1)).then_some(sim)
+    });
+    let mut sim = sim.expect("no seed placed the ants apart");
+
+    let mut iterations = 0;
+    while sim.should_continue() {
+        sim.run_iteration();
+        iterations += 1;
+    }
+    assert_eq!(iterations, 37);
+    assert_eq!(sim.stats().0, 2);
+
+    let stats = sim.simulation_stats();
+    assert_eq!(sim.total_moves_made(), 2 * 37);
+    assert_eq!(stats.total_moves_made, 2 * 37);
+    assert_eq!(stats.moves_per_iteration, 2.0);
+}
@@ -0,0 +1,45 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ant_mania::{write_prometheus, AntConfig, AntSimulation, MapGraph};
+
+fn metrics(sim: &AntSimulation, map: &str, elapsed: Duration) -> String {
+    let mut out = Vec::new();
+    write_prometheus(&mut out, sim, map, elapsed).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn seeded_small_run() -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_file("hiveum_map_small.txt").unwrap());
+    let mut sim = AntSimulation::new(graph, AntConfig { seed: Some(7), ..AntConfig::new(100) }).unwrap();
+    sim.run_to_completion();
+    sim
+}
+
+#[test]
+fn seeded_run_exposition() {
+    let expected = r#"# HELP ant_mania_alive_ants Ants still alive
+# TYPE ant_mania_alive_ants gauge
+ant_mania_alive_ants{seed="7",map="hiveum_map_small.txt"} 60
+# HELP ant_mania_surviving_colonies Colonies not destroyed
+# TYPE ant_mania_surviving_colonies gauge
+ant_mania_surviving_colonies{seed="7",map="hiveum_map_small.txt"} 8
+# HELP ant_mania_destructions_total Colonies destroyed so far
+# TYPE ant_mania_destructions_total counter
+ant_mania_destructions_total{seed="7",map="hiveum_map_small.txt"} 20
+# HELP ant_mania_iterations_total Iterations run so far
+# TYPE ant_mania_iterations_total counter
+ant_mania_iterations_total{seed="7",map="hiveum_map_small.txt"} 10000
+# HELP ant_mania_run_duration_seconds Wall-clock time of the run
+# TYPE ant_mania_run_duration_seconds gauge
+ant_mania_run_duration_seconds{seed="7",map="hiveum_map_small.txt"} 1.25
+"#;
+    assert_eq!(metrics(&seeded_small_run(), "hiveum_map_small.txt", Duration::from_millis(1250)), expected);
+}
+
+#[test]
+fn map_labels_are_escaped() {
+    let text = metrics(&seeded_small_run(), "maps\\a \"b\"\nc.txt", Duration::ZERO);
+    assert!(text.contains(r#"ant_mania_alive_ants{seed="7",map="maps\\a \"b\"\nc.txt"} 60"#), "{}", text);
+    assert!(text.contains("ant_mania_run_duration_seconds{seed=\"7\",map=\"maps\\\\a \\\"b\\\"\\nc.txt\"} 0\n"));
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph};
+
+/// Both ways for every `(a, b)`
+fn two_way(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    pairs.iter().flat_map(|&(a, b)| [(a, b), (b, a)]).collect()
+}
+
+fn sim(edges: &[(usize, usize)], colonies: usize) -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(edges, colonies).unwrap());
+    AntSimulation::new(graph, AntConfig::new(0)).unwrap()
+}
+
+#[test]
+fn min_cut_of_small_shapes() {
+    let ring: Vec<_> = (0..6).map(|colony| (colony, (colony + 1) % 6)).collect();
+    assert_eq!(sim(&two_way(&ring), 6).global_min_cut(), 2);
+    // one-way tunnels still hold the ring together
+    assert_eq!(sim(&ring, 6).global_min_cut(), 2);
+
+    let complete: Vec<_> = (0..5).flat_map(|a| (0..5).filter(move |&b| b != a).map(move |b| (a, b))).collect();
+    assert_eq!(sim(&complete, 5).global_min_cut(), 4);
+
+    // two triangles and the bridge 2-3 between them
+    let bridged = two_way(&[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 5), (5, 3)]);
+    assert_eq!(sim(&bridged, 6).global_min_cut(), 1);
+
+    assert_eq!(sim(&[], 1).global_min_cut(), 0);
+    assert_eq!(sim(&two_way(&[(0, 1)]), 3).global_min_cut(), 0, "colony 2 is on its own");
+}
+
+#[test]
+fn min_cut_follows_the_live_map() {
+    // a wheel: hub 0 joined to every colony of the ring 1..=5
+    let mut pairs: Vec<_> = (1..=5).map(|spoke| (0, spoke)).collect();
+    pairs.extend((1..=5).map(|colony| (colony, colony % 5 + 1)));
+    let mut sim = sim(&two_way(&pairs), 6);
+    assert_eq!(sim.global_min_cut(), 3);
+
+    // without the hub it's a ring, then a path
+    sim.destroy_cascade(0);
+    assert_eq!(sim.global_min_cut(), 2);
+    sim.destroy_cascade(1);
+    assert_eq!(sim.global_min_cut(), 1);
+}
+
+#[test]
+fn min_cut_matches_trying_every_split() {
+    for case in 0..200 {
+        let mut rng = fastrand::Rng::with_seed(case);
+        let colonies = rng.usize(2..8);
+        let pairs: Vec<_> = (0..colonies)
+            .flat_map(|a| (a + 1..colonies).map(move |b| (a, b)))
+            .filter(|_| rng.f32() < 0.5)
+            .collect();
+        // every split with colony 0 on one side, fewest pairs across
+        let brute_force = (0..1u32 << (colonies - 1))
+            .map(|side| side << 1 | 1)
+            .filter(|&side| side != (1 << colonies) - 1)
+            .map(|side| pairs.iter().filter(|&&(a, b)| (side >> a & 1) != (side >> b & 1)).count())
+            .min()
+            .unwrap();
+        assert_eq!(sim(&two_way(&pairs), colonies).global_min_cut(), brute_force, "{:?}", pairs);
+    }
+}
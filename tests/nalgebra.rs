@@ -0,0 +1,41 @@
+#![cfg(feature = "nalgebra")]
+
+use ant_mania::{AntSimulation, MapGraph, SimError};
+use nalgebra::DMatrix;
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn builds_the_map_in_the_matrix() {
+    // a path A <-> B <-> C, plus a one-way tunnel from C to A
+    let m = DMatrix::from_row_slice(3, 3, &[false, true, false, true, false, true, true, true, false]);
+    let graph = MapGraph::from_nalgebra_matrix(&m, names(&["A", "B", "C"])).unwrap();
+    assert_eq!(graph.colony_count(), 3);
+    assert_eq!(graph.adjacency_slice(), [1, 0, 2, 0, 1]);
+    assert_eq!(graph.edge_label(2, 0), "tunnel");
+    assert_eq!(graph.colony_name(2), "C");
+
+    let mut sim = AntSimulation::from_nalgebra_matrix(&m, names(&["A", "B", "C"]), 4).unwrap();
+    assert_eq!(sim.total_ants(), 4);
+    sim.run_to_completion();
+}
+
+#[test]
+fn the_matrix_has_to_fit_the_names() {
+    let wide = DMatrix::from_element(2, 3, false);
+    let err = AntSimulation::from_nalgebra_matrix(&wide, names(&["A", "B"]), 1).unwrap_err();
+    assert!(matches!(err, SimError::InvalidConfig(ref reason) if reason.contains("not square")), "{}", err);
+
+    let square = DMatrix::from_element(2, 2, false);
+    let err = AntSimulation::from_nalgebra_matrix(&square, names(&["A", "B", "C"]), 1).unwrap_err();
+    assert!(matches!(err, SimError::InvalidConfig(ref reason) if reason.contains("3 colony names")), "{}", err);
+
+    for bad in [names(&["A", "A"]), names(&["A", "two words"])] {
+        assert!(matches!(MapGraph::from_nalgebra_matrix(&square, bad), Err(SimError::InvalidConfig(_))));
+    }
+    let looped = DMatrix::from_fn(2, 2, |i, j| i == j);
+    let err = MapGraph::from_nalgebra_matrix(&looped, names(&["A", "B"])).unwrap_err();
+    assert!(matches!(err, SimError::InvalidTunnel { ref colony, .. } if colony == "A"), "{}", err);
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, MapOptions, PlacementStrategy, SimError};
+
+const MAP: &str = "\
+ant_mania_map v1
+Harlem south=Midtown
+Midtown north=Harlem south=harlem_river
+harlem_river north=Midtown
+[aliases]
+Uptown=Harlem
+";
+
+fn sim(graph: MapGraph) -> AntSimulation {
+    let config = AntConfig {
+        placement_strategy: PlacementStrategy::Explicit(vec![Some(1), Some(1), Some(2)]),
+        ..AntConfig::new(3)
+    };
+    AntSimulation::new(Arc::new(graph), config).unwrap()
+}
+
+#[test]
+fn colonies_are_found_by_name_and_alias() {
+    let mut sim = sim(MapGraph::from_reader(MAP.as_bytes()).unwrap());
+    assert!(sim.graph().has_name_index());
+    assert_eq!(sim.colony_by_name("Midtown"), Some(1));
+    assert_eq!(sim.colony_by_name("Uptown"), Some(0));
+    assert_eq!(sim.ants_at_named("Midtown").unwrap(), [0, 1]);
+    assert!(!sim.is_destroyed_named("Midtown").unwrap());
+
+    sim.check_collision(1);
+    assert!(sim.ants_at_named("Midtown").unwrap().is_empty());
+    assert!(sim.is_destroyed_named("Midtown").unwrap());
+}
+
+#[test]
+fn unknown_names_are_errors() {
+    let sim = sim(MapGraph::from_reader(MAP.as_bytes()).unwrap());
+    assert_eq!(sim.colony_by_name("Brooklyn"), None);
+    assert!(matches!(sim.ants_at_named("Brooklyn"), Err(SimError::UnknownColony(name)) if name == "Brooklyn"));
+    assert!(matches!(sim.is_destroyed_named(""), Err(SimError::UnknownColony(_))));
+}
+
+#[test]
+fn names_match_case_and_all() {
+    // the map is read case-sensitively, so Harlem and harlem_river are
+    // told apart and nothing folds HARLEM onto Harlem
+    let sim = sim(MapGraph::from_reader(MAP.as_bytes()).unwrap());
+    assert_eq!(sim.colony_by_name("Harlem"), Some(0));
+    assert_eq!(sim.colony_by_name("harlem_river"), Some(2));
+    assert_eq!(sim.colony_by_name("HARLEM"), None);
+    assert_eq!(sim.colony_by_name("uptown"), None);
+}
+
+#[test]
+fn lookups_still_work_without_the_index() {
+    let path = std::env::temp_dir().join(format!("ant_mania_name_lookup_{}.txt", std::process::id()));
+    std::fs::write(&path, MAP).unwrap();
+    let options = MapOptions { drop_name_index: true, ..MapOptions::default() };
+    let graph = MapGraph::from_file_with(path.to_str().unwrap(), &options);
+    std::fs::remove_file(&path).unwrap();
+
+    let graph = graph.unwrap();
+    assert_eq!(graph.has_name_index(), cfg!(feature = "intern"));
+    let sim = sim(graph);
+    assert_eq!(sim.colony_by_name("harlem_river"), Some(2));
+    assert_eq!(sim.colony_by_name("Uptown"), Some(0));
+    assert_eq!(sim.colony_by_name("HARLEM"), None);
+    assert_eq!(sim.ants_at_named("Midtown").unwrap(), [0, 1]);
+}
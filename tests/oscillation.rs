@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use ant_mania::{stop_condition, AntConfig, AntSimulation, MapGraph, PlacementStrategy, SimOptions};
+
+/// Two ants swapping places between two colonies: they pass each other
+/// every iteration and never end up in the same colony
+fn swapping_pair(oscillation_after: Option<u16>) -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[(0, 1), (1, 0)], 2).unwrap());
+    let config = AntConfig {
+        placement_strategy: PlacementStrategy::Explicit(vec![Some(0), Some(1)]),
+        seed: Some(7),
+        max_moves: 100,
+        ..AntConfig::new(2)
+    };
+    let options = SimOptions { oscillation_after, ..SimOptions::default() };
+    AntSimulation::new(graph, config).unwrap().with_options(options)
+}
+
+#[test]
+fn ants_bouncing_between_two_colonies_are_flagged() {
+    let mut sim = swapping_pair(Some(3));
+    // the first move has nothing to go back to, the next three are the streak
+    for _ in 0..4 {
+        sim.run_iteration();
+        assert_eq!(sim.oscillating_ants(), 0, "iteration {}", sim.iteration());
+    }
+    sim.run_iteration();
+    assert_eq!(sim.oscillating_ants(), 2);
+    assert_eq!(sim.stats().0, 2);
+}
+
+#[test]
+fn stop_condition_compares_two_metrics() {
+    let condition = stop_condition::parse("oscillating_ants==alive_ants").unwrap();
+    assert_eq!(condition.to_string(), "oscillating_ants==alive_ants");
+
+    let mut sim = swapping_pair(Some(3));
+    while !condition.is_met(&sim) {
+        sim.run_iteration();
+    }
+    assert_eq!(sim.iteration(), 5);
+
+    let mut sim = swapping_pair(None);
+    for _ in 0..20 {
+        sim.run_iteration();
+    }
+    assert!(!condition.is_met(&sim), "detection is off");
+    // and costs nothing then
+    assert!(sim.memory_usage().ant_state < swapping_pair(Some(3)).memory_usage().ant_state);
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "parallel")]
+
+use std::sync::Arc;
+
+use ant_mania::{
+    AntConfig, AntSimulation, DeadEndBehavior, MapGraph, MovementMode, ProceduralMapGenerator, SimOptions, Topology,
+};
+
+const TOPOLOGIES: [Topology; 4] = [
+    Topology::Grid { width: 40, height: 25 },
+    Topology::Ring { colonies: 600 },
+    Topology::Torus { width: 30, height: 30 },
+    Topology::Regular { colonies: 1000, degree: 3 },
+];
+
+fn engine(graph: &Arc<MapGraph>, ants: usize, seed: u64, options: SimOptions) -> AntSimulation {
+    let config = AntConfig { seed: Some(seed), max_moves: 400, ..AntConfig::new(ants) };
+    AntSimulation::new(Arc::clone(graph), config).unwrap().with_options(options)
+}
+
+/// The simultaneous and parallel engines on the same world, hash for hash
+fn assert_engines_agree(shape: Topology, ants_per_colony: f64, options: SimOptions) {
+    let graph = Arc::new(ProceduralMapGenerator::topology(shape, 7).unwrap());
+    let ants = (graph.colony_count() as f64 * ants_per_colony) as usize;
+    let label = format!("{} with {} ants and {:?}", shape, ants, options);
+    let simultaneous = SimOptions { movement: MovementMode::Simultaneous, ..options.clone() };
+    let mut reference = engine(&graph, ants, 11, simultaneous);
+    let mut parallel = engine(&graph, ants, 11, SimOptions { movement: MovementMode::Parallel, ..options });
+
+    assert_eq!(parallel.state_hash(), reference.state_hash(), "{}: placed differently", label);
+    while reference.should_continue() && reference.iteration() < reference.max_moves() {
+        let expected = reference.run_iteration();
+        assert_eq!(parallel.run_iteration(), expected, "{}", label);
+        assert_eq!(
+            parallel.state_hash(),
+            reference.state_hash(),
+            "{}: diverged at iteration {}",
+            label,
+            expected.iteration
+        );
+    }
+    assert!(!parallel.should_continue() || parallel.iteration() == parallel.max_moves(), "{}", label);
+    assert_eq!(parallel.simulation_stats(), reference.simulation_stats(), "{}", label);
+}
+
+#[test]
+fn parallel_engine_matches_simultaneous_on_every_topology() {
+    for shape in TOPOLOGIES {
+        for ants_per_colony in [0.25, 1.0, 4.0] {
+            assert_engines_agree(shape, ants_per_colony, SimOptions::default());
+        }
+    }
+}
+
+#[test]
+fn parallel_engine_matches_with_dead_ends_and_cascades() {
+    let options = [
+        SimOptions { dead_end_behavior: DeadEndBehavior::DieAfterNMoves(3), ..SimOptions::default() },
+        SimOptions { dead_end_behavior: DeadEndBehavior::TeleportToRandom, ..SimOptions::default() },
+        SimOptions { cascade_on_destroy: true, factions: 3, ..SimOptions::default() },
+    ];
+    for shape in [TOPOLOGIES[0], TOPOLOGIES[3]] {
+        for options in &options {
+            assert_engines_agree(shape, 2.0, options.clone());
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use ant_mania::{MapGraph, ParseWarningKind};
+
+#[test]
+fn oddities_are_collected_in_line_order() {
+    let map = "\
+ant_mania_map v1
+A east=B west=Nowhere
+B west=A south
+C north=A
+A north=B north=B
+c
+";
+    let graph = MapGraph::from_reader(map.as_bytes()).unwrap();
+    let warnings: Vec<_> = graph.parse_warnings().iter().map(|warning| (warning.line, warning.kind.clone())).collect();
+    let name = str::to_string;
+    assert_eq!(
+        warnings,
+        [
+            (2, ParseWarningKind::DanglingTunnel { colony: name("A"), label: name("west"), target: name("Nowhere") }),
+            (3, ParseWarningKind::MalformedTunnel { colony: name("B"), text: name("south") }),
+            (4, ParseWarningKind::OneWayTunnel { colony: name("C"), label: name("north"), target: name("A") }),
+            (5, ParseWarningKind::RepeatedColony { colony: name("A"), first_line: 2 }),
+            (5, ParseWarningKind::DuplicateLabel { colony: name("A"), label: name("north") }),
+            (6, ParseWarningKind::CaseCollision { colony: name("c"), other: name("C") }),
+        ]
+    );
+    assert_eq!(graph.parse_warnings()[0].to_string(), "line 2: A west=Nowhere leads to no colony on the map, dropped");
+
+    let clean = MapGraph::from_file("hiveum_map_small.txt").unwrap();
+    assert!(clean.parse_warnings().is_empty());
+}
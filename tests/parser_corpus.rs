@@ -0,0 +1,63 @@
+//! The fuzz target's checks (fuzz/fuzz_targets/map_parser.rs), run over its
+//! seed corpus, so the inputs it starts from keep passing between fuzzing
+//! sessions
+
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph};
+
+fn check_map_bytes(data: &[u8]) -> Result<(), String> {
+    let Ok(graph) = MapGraph::from_reader(data) else {
+        return Ok(());
+    };
+    graph.check_structure()?;
+    let mut written = Vec::new();
+    graph.write_map(&mut written, |_| true).unwrap();
+    let again = MapGraph::from_reader(&written[..]).map_err(|err| format!("written map doesn't read back: {}", err))?;
+    again.check_structure()?;
+    if again.adjacency_slice() != graph.adjacency_slice() || again.connection_counts() != graph.connection_counts() {
+        return Err("written map reads back with different tunnels".to_string());
+    }
+
+    if graph.colony_count() > 0 {
+        let config = AntConfig { seed: Some(0), max_moves: 32, ..AntConfig::new(8) };
+        let mut sim = AntSimulation::new(Arc::new(graph), config).unwrap();
+        let mut problem = None;
+        sim.run_until(&AtomicBool::new(false), None, |sim, _| match sim.check_invariants() {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(found) => {
+                problem = Some(found);
+                ControlFlow::Break(())
+            }
+        });
+        problem.map_or(Ok(()), Err)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn fuzz_corpus_passes_the_fuzz_checks() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/map_parser");
+    let mut inputs = 0;
+    for entry in std::fs::read_dir(&corpus).unwrap() {
+        let path = entry.unwrap().path();
+        if let Err(problem) = check_map_bytes(&std::fs::read(&path).unwrap()) {
+            panic!("{}: {}", path.display(), problem);
+        }
+        inputs += 1;
+    }
+    assert!(inputs > 0, "no seed inputs in {}", corpus.display());
+}
+
+#[test]
+fn unreadable_lines_are_reported_by_number() {
+    let error = MapGraph::from_reader(&b"ant_mania_map v1\nA north=B\nB south=\xff\n"[..]).unwrap_err();
+    assert!(error.to_string().contains("line 3: isn't valid UTF-8"), "{}", error);
+
+    // a tunnel where the colony name should be
+    let error = MapGraph::from_reader(&b"ant_mania_map v1\nnorth=B\nB south=A\n"[..]).unwrap_err();
+    assert!(error.to_string().contains("line 2: expected a colony name"), "{}", error);
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "sqlite")]
+
+use std::process::Command;
+use std::sync::Arc;
+
+use ant_mania::persist::{self, RunReport};
+use ant_mania::{AntConfig, AntSimulation, MapGraph};
+
+fn db_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("ant_mania_{}_{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn count(conn: &rusqlite::Connection, table: &str) -> usize {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get::<_, i64>(0)).unwrap() as usize
+}
+
+/// A finished run on the small map, with its destructions and timeline
+fn report(seed: u64) -> RunReport {
+    let graph = Arc::new(MapGraph::from_file("hiveum_map_small.txt").unwrap());
+    let mut sim = AntSimulation::new(graph, AntConfig { seed: Some(seed), ..AntConfig::new(20) }).unwrap();
+    sim.record_destructions(true);
+    let mut timeline = Vec::new();
+    while sim.should_continue() && sim.iteration() < sim.max_moves() {
+        timeline.push(sim.run_iteration());
+    }
+    RunReport { timeline, ..RunReport::new(&sim, "hiveum_map_small.txt") }
+}
+
+#[test]
+fn two_runs_from_two_threads_both_land() {
+    let path = db_path("two_runs");
+    let reports = [report(1), report(2)];
+    std::thread::scope(|scope| {
+        for report in &reports {
+            let path = &path;
+            scope.spawn(move || persist::write_run(&mut persist::open(path).unwrap(), report).unwrap());
+        }
+    });
+
+    let conn = persist::open(&path).unwrap();
+    assert_eq!(count(&conn, "runs"), 2);
+    let destructions: usize = reports.iter().map(|report| report.destructions.len()).sum();
+    assert!(destructions > 0);
+    assert_eq!(count(&conn, "destructions"), destructions);
+    let iterations: usize = reports.iter().map(|report| report.timeline.len()).sum();
+    assert_eq!(count(&conn, "per_iteration_stats"), iterations);
+
+    let seeds: Vec<(i64, i64)> = conn
+        .prepare("SELECT seed, alive_ants FROM runs ORDER BY seed")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    let expected: Vec<_> = reports.iter().map(|report| (report.seed as i64, report.stats.alive_ants as i64)).collect();
+    assert_eq!(seeds, expected);
+    let hashes: i64 = conn.query_row("SELECT COUNT(DISTINCT map_hash) FROM runs", [], |row| row.get(0)).unwrap();
+    assert_eq!(hashes, 1);
+    drop(conn);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn the_cli_adds_a_run_each_time() {
+    let path = db_path("cli");
+    for (seed, timeline) in [("1", false), ("2", true)] {
+        let mut args =
+            vec!["hiveum_map_small.txt", "10", "--seed", seed, "--quiet", "--sqlite", path.to_str().unwrap()];
+        if timeline {
+            args.push("--sqlite-timeline");
+        }
+        let output = Command::new(env!("CARGO_BIN_EXE_ant-mania")).args(&args).output().unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    assert_eq!(count(&conn, "runs"), 2);
+    let (iterations, ants): (i64, i64) = conn
+        .query_row("SELECT iterations, ants FROM runs WHERE seed = 2", [], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap();
+    assert_eq!(ants, 10);
+    // only the second run kept its timeline
+    assert_eq!(count(&conn, "per_iteration_stats"), iterations as usize);
+    drop(conn);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "petgraph")]
+
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, Direction, MapGraph, SimError};
+use petgraph::algo::dijkstra;
+use petgraph::graph::{Graph, NodeIndex};
+
+fn map_text(graph: &MapGraph) -> String {
+    let mut out = Vec::new();
+    graph.write_map(&mut out, |_| true).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn round_trips_a_map() {
+    let graph = MapGraph::from_file("hiveum_map_small.txt").unwrap();
+    let petgraph = graph.to_petgraph();
+    assert_eq!((petgraph.node_count(), petgraph.edge_count()), (graph.colony_count(), graph.edge_count()));
+    assert_eq!(petgraph[NodeIndex::new(3)], graph.colony_name(3));
+
+    let again = MapGraph::from_petgraph(&petgraph).unwrap();
+    assert_eq!(map_text(&again), map_text(&graph));
+    assert_eq!(again.adjacency_slice(), graph.adjacency_slice());
+}
+
+#[test]
+fn simulates_on_a_graph_built_with_petgraph() {
+    let mut petgraph = Graph::new();
+    let [a, b, c] = ["A", "B", "C"].map(|name| petgraph.add_node(name.to_string()));
+    petgraph.add_edge(a, b, Direction::East);
+    petgraph.add_edge(b, a, Direction::West);
+    petgraph.add_edge(b, c, Direction::Other("ferry".to_string()));
+    petgraph.add_edge(c, b, Direction::Other("ferry".to_string()));
+    // petgraph's algorithms see the same map the ants do
+    assert_eq!(dijkstra(&petgraph, a, Some(c), |_| 1)[&c], 2);
+
+    let graph = MapGraph::from_petgraph(&petgraph).unwrap();
+    assert_eq!(map_text(&graph), "ant_mania_map v1\nA east=B\nB west=A ferry=C\nC ferry=B\n");
+    let mut sim = AntSimulation::new(Arc::new(graph), AntConfig::new(2)).unwrap();
+    sim.run_to_completion();
+}
+
+#[test]
+fn refuses_what_a_map_file_couldnt_say() {
+    let mut twins = Graph::new();
+    twins.add_node("A".to_string());
+    twins.add_node("A".to_string());
+    assert!(matches!(MapGraph::from_petgraph(&twins), Err(SimError::InvalidConfig(_))));
+
+    let mut graph = Graph::new();
+    let [a, b] = ["A", "B"].map(|name| graph.add_node(name.to_string()));
+    graph.add_edge(a, b, Direction::North);
+    let mut looped = graph.clone();
+    looped.add_edge(a, a, Direction::South);
+    let mut same_way = graph.clone();
+    same_way.add_edge(a, b, Direction::South);
+    for graph in [looped, same_way] {
+        assert!(matches!(MapGraph::from_petgraph(&graph), Err(SimError::InvalidTunnel { .. })));
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use ant_mania::{
+    AntConfig, AntSimulation, CollisionPolicy, ColonyId, FightTrigger, MapGraph, PlacementStrategy, SimError,
+};
+
+const MEDIUM_MAP: &str = "hiveum_map_medium.txt";
+
+fn place(graph: &Arc<MapGraph>, num_ants: usize, strategy: PlacementStrategy, seed: u64) -> AntSimulation {
+    let config = AntConfig { num_ants, placement_strategy: strategy, seed: Some(seed), ..AntConfig::default() };
+    AntSimulation::new(Arc::clone(graph), config).unwrap()
+}
+
+/// Ants per colony, in colony order
+fn occupancy(sim: &AntSimulation) -> Vec<usize> {
+    (0..sim.graph().colony_count()).map(|colony| sim.ants_at_slice(colony).len()).collect()
+}
+
+/// Tunnel hops from `from` to every colony, `usize::MAX` when unreachable.
+/// Nothing is destroyed before the first iteration, so valid moves are all tunnels.
+fn distances(sim: &AntSimulation, from: ColonyId) -> Vec<usize> {
+    let mut distance = vec![usize::MAX; sim.graph().colony_count()];
+    let mut queue = VecDeque::from([from]);
+    let mut moves = Vec::new();
+    distance[from] = 0;
+    while let Some(colony) = queue.pop_front() {
+        sim.get_valid_moves(colony, &mut moves);
+        for &next in &moves {
+            if distance[next] == usize::MAX {
+                distance[next] = distance[colony] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    distance
+}
+
+#[test]
+fn distinct_never_stacks_ants() {
+    let graph = Arc::new(MapGraph::from_file(MEDIUM_MAP).unwrap());
+    let sim = place(&graph, 5000, PlacementStrategy::Distinct, 1);
+    assert!(occupancy(&sim).iter().all(|&ants| ants <= 1));
+
+    let too_many = AntConfig {
+        num_ants: graph.colony_count() + 1,
+        placement_strategy: PlacementStrategy::Distinct,
+        ..AntConfig::default()
+    };
+    assert!(AntSimulation::new(Arc::clone(&graph), too_many).is_err());
+}
+
+#[test]
+fn single_puts_every_ant_on_one_colony() {
+    let graph = Arc::new(MapGraph::from_file(MEDIUM_MAP).unwrap());
+    let colony = graph.find_colony(graph.colony_name(42)).unwrap();
+    let mut sim = place(&graph, 300, PlacementStrategy::Single(colony), 1);
+    assert_eq!(sim.ants_at_slice(colony).len(), 300);
+
+    // the crowd disperses, its high water mark stays
+    sim.run_iteration();
+    assert!(sim.ants_at_slice(colony).len() < 300);
+    assert_eq!(sim.peak_ant_count_at(colony), 300);
+    assert_eq!(sim.simulation_stats().peak_ant_counts[colony], 300);
+}
+
+#[test]
+fn single_start_fights_before_anyone_moves() {
+    let graph = Arc::new(MapGraph::from_file("hiveum_map_small.txt").unwrap());
+    let colony = graph.find_colony("Kara").unwrap();
+    let config = AntConfig {
+        num_ants: 300,
+        placement_strategy: PlacementStrategy::Single(colony),
+        fight_trigger: FightTrigger::AtLeast,
+        collision_policy: CollisionPolicy::KillAll,
+        seed: Some(1),
+        ..AntConfig::default()
+    };
+    let mut sim = AntSimulation::new(Arc::clone(&graph), config).unwrap();
+    sim.record_destructions(true);
+
+    let result = sim.run_iteration();
+    assert_eq!((result.colonies_destroyed, result.ants_killed, result.moves), (1, 300, 0));
+    assert_eq!(
+        sim.destructions().iter().map(|destruction| (destruction.colony, destruction.iteration)).collect::<Vec<_>>(),
+        [(colony, 0)]
+    );
+    assert!(sim.is_destroyed_named("Kara").unwrap());
+
+    // a pair fights under the default trigger too, a bigger crowd doesn't
+    let mut pair = place(&graph, 2, PlacementStrategy::Single(colony), 1);
+    assert_eq!(pair.run_iteration().colonies_destroyed, 1);
+    assert!(pair.is_destroyed_named("Kara").unwrap());
+    let mut crowd = place(&graph, 3, PlacementStrategy::Single(colony), 1);
+    crowd.run_iteration();
+    assert!(!crowd.is_destroyed_named("Kara").unwrap());
+}
+
+#[test]
+fn clustered_ants_start_near_a_seed_colony() {
+    let graph = Arc::new(MapGraph::from_file("hiveum_map_small.txt").unwrap());
+    let radius = 1;
+    let sim = place(&graph, 50, PlacementStrategy::Clustered { clusters: 1, radius }, 9);
+    let occupied: Vec<ColonyId> = (0..graph.colony_count()).filter(|&colony| sim.has_ants_at(colony)).collect();
+
+    // with one cluster, some colony has every ant within `radius` tunnels
+    let centered = (0..graph.colony_count()).any(|seed| {
+        let distance = distances(&sim, seed);
+        occupied.iter().all(|&colony| distance[colony] <= radius as usize)
+    });
+    assert!(centered, "ants at {:?} aren't within {} of any colony", occupied, radius);
+}
+
+#[test]
+fn placement_is_deterministic_under_the_seed() {
+    let graph = Arc::new(MapGraph::from_file(MEDIUM_MAP).unwrap());
+    for strategy in [
+        PlacementStrategy::Uniform,
+        PlacementStrategy::Distinct,
+        PlacementStrategy::Clustered { clusters: 8, radius: 2 },
+    ] {
+        let first = place(&graph, 2000, strategy.clone(), 77);
+        let second = place(&graph, 2000, strategy.clone(), 77);
+        assert_eq!(occupancy(&first), occupancy(&second), "{:?}", strategy);
+    }
+}
+
+#[test]
+fn explicit_placement_fills_in_left_out_ants() {
+    let graph = Arc::new(MapGraph::from_file(MEDIUM_MAP).unwrap());
+    let positions = vec![Some(3), None, Some(3), None];
+    let sim = place(&graph, 4, PlacementStrategy::Explicit(positions.clone()), 5);
+    assert_eq!(sim.ants_at_slice(3), [0, 2]);
+    assert_eq!(occupancy(&sim).iter().sum::<usize>(), 4);
+
+    // one position per ant, on colonies that exist
+    let config = AntConfig { placement_strategy: PlacementStrategy::Explicit(positions), ..AntConfig::new(5) };
+    assert!(AntSimulation::new(Arc::clone(&graph), config).is_err());
+    let nowhere = PlacementStrategy::Explicit(vec![Some(graph.colony_count())]);
+    let config = AntConfig { placement_strategy: nowhere, ..AntConfig::new(1) };
+    assert!(AntSimulation::new(Arc::clone(&graph), config).is_err());
+}
+
+#[test]
+fn random_live_colony_skips_destroyed_colonies_and_fails_once_all_are() {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[(0, 1), (1, 2), (2, 3)], 4).unwrap());
+    let mut sim = place(&graph, 0, PlacementStrategy::Uniform, 1);
+    sim.destroy_cascade(0);
+    sim.destroy_cascade(1);
+    sim.destroy_cascade(3);
+    for _ in 0..20 {
+        assert_eq!(sim.random_live_colony().unwrap(), 2);
+    }
+    sim.destroy_cascade(2);
+    assert!(matches!(sim.random_live_colony(), Err(SimError::NoLiveColoniesForPlacement)));
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "python")]
+
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+const SMALL_MAP: &str = "hiveum_map_small.txt";
+
+/// Run `code` with the module imported as `ant_mania`, then hand its globals to `check`
+fn run_python<T>(code: &std::ffi::CStr, check: impl for<'py> FnOnce(&Bound<'py, PyDict>) -> T) -> T {
+    Python::initialize();
+    Python::attach(|py| {
+        let globals = PyDict::new(py);
+        globals.set_item("ant_mania", pyo3::wrap_pymodule!(ant_mania::python::ant_mania)(py)).unwrap();
+        if let Err(err) = py.run(code, Some(&globals), None) {
+            panic!("{}", err);
+        }
+        check(&globals)
+    })
+}
+
+fn finished_run(ants: usize, seed: u64) -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_file(SMALL_MAP).unwrap());
+    let mut sim = AntSimulation::new(graph, AntConfig { seed: Some(seed), ..AntConfig::new(ants) }).unwrap();
+    sim.run_to_completion();
+    sim
+}
+
+#[test]
+fn run_reports_like_the_library() {
+    let sim = finished_run(40, 9);
+    let (report, world, positions) = run_python(
+        c"
+sim = ant_mania.AntMania('hiveum_map_small.txt', 40, 9)
+report = sim.run()
+world = sim.remaining_world()
+positions = sim.positions()
+assert sim.step() is None
+",
+        |globals| {
+            let report = globals.get_item("report").unwrap().unwrap();
+            let report = report.cast::<PyDict>().unwrap();
+            let get = |key: &str| report.get_item(key).unwrap().unwrap();
+            let counts: [u64; 4] = ["iterations", "alive_ants", "active_colonies", "total_moves"]
+                .map(|key| get(key).extract().unwrap());
+            let destructions: Vec<(u32, String)> = get("destructions").extract().unwrap();
+            let world: String = globals.get_item("world").unwrap().unwrap().extract().unwrap();
+            let positions: Vec<i64> = globals.get_item("positions").unwrap().unwrap().extract().unwrap();
+            ((counts, destructions), world, positions)
+        },
+    );
+
+    let stats = sim.simulation_stats();
+    let counts: [u64; 4] =
+        [stats.iterations.into(), stats.alive_ants as u64, stats.active_colonies as u64, stats.total_moves_made];
+    assert_eq!(report.0, counts);
+    assert_eq!(report.1.len(), sim.colonies_destroyed());
+    let mut out = Vec::new();
+    sim.write_remaining_world(&mut out).unwrap();
+    assert_eq!(world, String::from_utf8(out).unwrap());
+
+    assert_eq!(positions.len(), 40);
+    assert_eq!(positions.iter().filter(|&&colony| colony >= 0).count(), stats.alive_ants);
+    for (ant, &colony) in positions.iter().enumerate() {
+        let view = sim.ant_view(ant).unwrap();
+        assert_eq!(colony, if view.alive { view.colony as i64 } else { -1 });
+    }
+}
+
+#[test]
+fn steps_hand_out_each_destruction_once() {
+    let destroyed: usize = run_python(
+        c"
+sim = ant_mania.AntMania('hiveum_map_small.txt', 40, 9)
+destroyed = []
+iterations = 0
+while (tick := sim.step()) is not None:
+    assert tick['iteration'] == iterations
+    iterations += 1
+    destroyed += tick['destroyed']
+assert len(set(destroyed)) == len(destroyed)
+assert sorted(destroyed) == sorted(name for _, name in sim.run()['destructions'])
+",
+        |globals| globals.get_item("destroyed").unwrap().unwrap().len().unwrap(),
+    );
+    assert_eq!(destroyed, finished_run(40, 9).colonies_destroyed());
+}
+
+#[test]
+fn ant_views_come_out_as_dicts() {
+    let sim = finished_run(40, 9);
+    let views: Vec<(bool, i64, String, u32, Option<u32>)> = run_python(
+        c"
+sim = ant_mania.AntMania('hiveum_map_small.txt', 40, 9)
+sim.run()
+assert sim.ant_view(40) is None
+views = []
+for ant in range(40):
+    view = sim.ant_view(ant)
+    assert view['ant'] == ant and view['path'] is None and view['faction'] is None
+    assert view['moves'] + view['moves_left'] == 10000
+    views.append((view['alive'], view['colony'], view['colony_name'], view['moves'], view['died_in']))
+",
+        |globals| globals.get_item("views").unwrap().unwrap().extract().unwrap(),
+    );
+
+    for (ant, (alive, colony, name, moves, died_in)) in views.into_iter().enumerate() {
+        let view = sim.ant_view(ant).unwrap();
+        assert_eq!(
+            (alive, colony, name, moves, died_in),
+            (view.alive, view.colony as i64, view.colony_name, view.moves, view.died_in)
+        );
+    }
+}
+
+#[test]
+fn takes_map_text_and_maps_errors_to_exceptions() {
+    run_python(
+        c"
+sim = ant_mania.AntMania('A east=B\\nB west=A\\n', 2, 1)
+assert len(sim.positions()) == 2
+
+try:
+    ant_mania.AntMania('ant_mania_map v99\\nA\\n', 2)
+    raise AssertionError('read a map from the future')
+except ant_mania.MapError as err:
+    assert isinstance(err, ValueError)
+
+try:
+    ant_mania.AntMania('\\n', 2)
+    raise AssertionError('placed ants on an empty map')
+except ant_mania.ConfigError as err:
+    assert 'no colonies' in str(err)
+
+try:
+    ant_mania.AntMania('no_such_map.txt', 2)
+    raise AssertionError('read a missing file')
+except OSError:
+    pass
+",
+        |_| (),
+    );
+}
@@ -0,0 +1,255 @@
+//! A deliberately plain simulator to check the engine against: hash maps
+//! and scans over every ant, no counters, slots or cached degrees. It follows
+//! the engine's rules and draws the same random numbers in the same order
+//! (a fastrand::Rng seeded the way `AntSimulation::new` seeds its own), so for
+//! a given seed the two should agree on every iteration.
+//!
+//! Only rules whose outcome doesn't hang on the engine's internal order of
+//! ants in a colony are covered: uniform placement, `KillAll`, `KillOldest`
+//! and a `KillFirst` that takes the whole crowd, no `reorder_every` and no
+//! `track_components`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use ant_mania::{
+    AntConfig, CollisionPolicy, DeadEndBehavior, DestructionMode, FightTrigger, MapGraph, MovementMode,
+    PlacementStrategy, SimOptions,
+};
+
+/// What the two simulators are compared on: where every live ant is, and
+/// which colonies are destroyed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldState {
+    pub positions: BTreeMap<usize, usize>,
+    pub destroyed: BTreeSet<usize>,
+}
+
+pub struct Reference {
+    /// Tunnels out of each colony, in the map's order
+    tunnels: Vec<Vec<usize>>,
+    config: AntConfig,
+    options: SimOptions,
+    rng: fastrand::Rng,
+    stream_seed: u64,
+    iteration: u32,
+
+    /// Where each live ant is; dead ants aren't in it
+    position: HashMap<usize, usize>,
+    moves: HashMap<usize, u32>,
+    failed_moves: HashMap<usize, u32>,
+    destroyed: HashSet<usize>,
+    /// Live colonies in the order the engine keeps them, which teleports
+    /// draw from: a destroyed colony's place goes to the last one
+    live: Vec<usize>,
+    order: Vec<usize>,
+}
+
+impl Reference {
+    pub fn new(graph: &MapGraph, config: AntConfig, options: SimOptions) -> Self {
+        assert_eq!(config.placement_strategy, PlacementStrategy::Uniform, "the reference places ants uniformly");
+        let whole_crowd = |count| {
+            config.fight_trigger == FightTrigger::Exactly && count >= config.collision_threshold as usize
+        };
+        assert!(
+            match config.collision_policy {
+                CollisionPolicy::KillAll | CollisionPolicy::KillOldest { .. } => true,
+                CollisionPolicy::KillFirst { count } => whole_crowd(count),
+                CollisionPolicy::KillRandom { .. } => false,
+            },
+            "the reference only knows KillAll, KillOldest and a KillFirst that kills everyone"
+        );
+        assert!(options.reorder_every.is_none() && !options.track_components);
+
+        let tunnels = (0..graph.colony_count())
+            .map(|colony| {
+                let start = graph.start_offsets()[colony] as usize;
+                let count = graph.connection_counts()[colony] as usize;
+                graph.adjacency_slice()[start..start + count].iter().map(|&to| to as usize).collect()
+            })
+            .collect::<Vec<Vec<usize>>>();
+        let mut rng = fastrand::Rng::with_seed(config.seed.expect("the reference needs a seed"));
+        let stream_seed = rng.u64(..);
+        let position = (0..config.num_ants).map(|ant| (ant, rng.usize(..tunnels.len()))).collect();
+        Reference {
+            tunnels,
+            rng,
+            stream_seed,
+            iteration: 0,
+            position,
+            moves: HashMap::new(),
+            failed_moves: HashMap::new(),
+            destroyed: HashSet::new(),
+            live: (0..graph.colony_count()).collect(),
+            order: (0..config.num_ants).collect(),
+            config,
+            options,
+        }
+    }
+
+    pub fn state(&self) -> WorldState {
+        WorldState {
+            positions: self.position.iter().map(|(&ant, &colony)| (ant, colony)).collect(),
+            destroyed: self.destroyed.iter().copied().collect(),
+        }
+    }
+
+    pub fn iteration(&self) -> u32 {
+        self.iteration
+    }
+
+    fn moves(&self, ant: usize) -> u32 {
+        self.moves.get(&ant).copied().unwrap_or(0)
+    }
+
+    fn faction(&self, ant: usize) -> Option<usize> {
+        let factions = self.options.factions as usize;
+        (factions >= 2).then(|| ant % factions)
+    }
+
+    fn ants_at(&self, colony: usize) -> Vec<usize> {
+        let mut ants: Vec<usize> =
+            (self.position.iter()).filter(|&(_, &at)| at == colony).map(|(&ant, _)| ant).collect();
+        ants.sort_unstable();
+        ants
+    }
+
+    fn live_tunnels(&self, colony: usize) -> Vec<usize> {
+        self.tunnels[colony].iter().copied().filter(|to| !self.destroyed.contains(to)).collect()
+    }
+
+    pub fn should_continue(&self) -> bool {
+        if self.iteration >= self.config.max_moves {
+            return false;
+        }
+        let factions: HashSet<_> = self.position.keys().map(|&ant| self.faction(ant)).collect();
+        if self.faction(0).is_some() && factions.len() <= 1 {
+            return false;
+        }
+        self.position.keys().any(|&ant| self.moves(ant) < self.config.max_moves)
+    }
+
+    fn pick(&mut self, ant: usize, count: usize) -> usize {
+        match self.options.movement {
+            MovementMode::Sequential => self.rng.usize(..count),
+            MovementMode::Simultaneous | MovementMode::Parallel => {
+                let key = ((self.iteration as u64) << 32) ^ ant as u64;
+                let random = splitmix64(self.stream_seed ^ splitmix64(key));
+                (((random >> 32) * count as u64) >> 32) as usize
+            }
+        }
+    }
+
+    /// Move one ant, returning where it went
+    fn step(&mut self, ant: usize) -> Option<usize> {
+        let here = *self.position.get(&ant)?;
+        let mut choices = self.live_tunnels(here);
+        if choices.is_empty() {
+            match self.options.dead_end_behavior {
+                DeadEndBehavior::StayForever => return None,
+                DeadEndBehavior::DieAfterNMoves(limit) => {
+                    let failed = self.failed_moves.entry(ant).or_insert(0);
+                    *failed += 1;
+                    if *failed >= limit.max(1) {
+                        self.position.remove(&ant);
+                    }
+                    return None;
+                }
+                DeadEndBehavior::TeleportToRandom => {
+                    choices = self.live.iter().copied().filter(|&c| c != here).collect();
+                    if choices.is_empty() {
+                        return None;
+                    }
+                }
+            }
+        }
+        let to = choices[self.pick(ant, choices.len())];
+        self.position.insert(ant, to);
+        *self.moves.entry(ant).or_insert(0) += 1;
+        Some(to)
+    }
+
+    /// Fight at `colony` if it's crowded enough
+    fn settle(&mut self, colony: usize) {
+        let ants = self.ants_at(colony);
+        let threshold = self.config.collision_threshold as usize;
+        let crowded = match self.config.fight_trigger {
+            FightTrigger::Exactly => ants.len() == threshold,
+            FightTrigger::AtLeast => ants.len() >= threshold,
+        };
+        if self.destroyed.contains(&colony) || !crowded {
+            return;
+        }
+        // same faction, no fight; everyone's in faction `None` with factions off
+        if self.faction(0).is_some() && ants.iter().all(|&ant| self.faction(ant) == self.faction(ants[0])) {
+            return;
+        }
+        let victims = match self.config.collision_policy {
+            CollisionPolicy::KillAll | CollisionPolicy::KillFirst { .. } => ants,
+            CollisionPolicy::KillOldest { count } => {
+                let mut ants = ants;
+                ants.sort_by_key(|&ant| (std::cmp::Reverse(self.moves(ant)), ant));
+                ants.truncate(count);
+                ants
+            }
+            _ => unreachable!(),
+        };
+        for ant in victims {
+            self.position.remove(&ant);
+        }
+        self.destroy(colony);
+
+        if self.options.cascade_on_destroy {
+            let mut fallen = vec![colony];
+            let mut next = 0;
+            while let Some(&from) = fallen.get(next) {
+                next += 1;
+                for to in self.tunnels[from].clone() {
+                    if !self.destroyed.contains(&to) && self.live_tunnels(to).is_empty() {
+                        for ant in self.ants_at(to) {
+                            self.position.remove(&ant);
+                        }
+                        self.destroy(to);
+                        fallen.push(to);
+                    }
+                }
+            }
+        }
+    }
+
+    fn destroy(&mut self, colony: usize) {
+        self.destroyed.insert(colony);
+        let index = self.live.iter().position(|&c| c == colony).unwrap();
+        self.live.swap_remove(index);
+    }
+
+    pub fn run_iteration(&mut self) {
+        let sequential = self.options.movement == MovementMode::Sequential;
+        if self.options.shuffle_order && sequential {
+            self.rng.shuffle(&mut self.order);
+        }
+        let immediate = sequential && self.options.destruction == DestructionMode::Immediate;
+
+        let mut crowded = Vec::new();
+        for ant in self.order.clone() {
+            let Some(to) = self.step(ant) else {
+                continue;
+            };
+            if immediate {
+                self.settle(to);
+            } else if self.ants_at(to).len() >= self.config.collision_threshold as usize && !crowded.contains(&to) {
+                crowded.push(to);
+            }
+        }
+        for colony in crowded {
+            self.settle(colony);
+        }
+        self.iteration += 1;
+    }
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use ant_mania::{
+    AntConfig, AntSimulation, MapGraph, ParseWarningKind, PlacementStrategy, RegionStats, Regions, UNASSIGNED_REGION,
+};
+
+const MAP: &str = "ant_mania_map v1\nA east=B\nB west=A east=C\nC west=B\nD\n";
+
+fn graph() -> MapGraph {
+    MapGraph::from_reader(MAP.as_bytes()).unwrap()
+}
+
+#[test]
+fn reads_regions_with_warnings_for_what_it_skips() {
+    let graph = graph();
+    let text = "A North\n\nB North\nC  South Side \nZ Nowhere\nA South Side\n";
+    let regions = Regions::from_reader(text.as_bytes(), &graph).unwrap();
+    assert_eq!(regions.names(), [UNASSIGNED_REGION, "North", "South Side"]);
+    let region_of: Vec<_> = (0..4).map(|colony| regions.region_of(colony)).collect();
+    assert_eq!(region_of, [1, 1, 2, 0]);
+
+    let warnings: Vec<_> = regions.warnings().iter().map(|warning| (warning.line, warning.kind.clone())).collect();
+    assert_eq!(
+        warnings,
+        [
+            (5, ParseWarningKind::UnknownColony { colony: "Z".to_string() }),
+            (6, ParseWarningKind::RepeatedRegion { colony: "A".to_string(), first_line: 1 }),
+        ]
+    );
+
+    assert!(Regions::from_reader("A\n".as_bytes(), &graph).is_err());
+}
+
+#[test]
+fn region_stats_count_colonies_ants_and_destructions() {
+    let graph = Arc::new(graph());
+    let regions = Regions::from_reader("A North\nB North\nC South\n".as_bytes(), &graph).unwrap();
+    // the ants in A and C both have to go to B
+    let config = AntConfig {
+        placement_strategy: PlacementStrategy::Explicit(vec![Some(0), Some(2), Some(3)]),
+        seed: Some(1),
+        ..AntConfig::new(3)
+    };
+    let mut sim = AntSimulation::new(graph, config).unwrap().with_regions(regions);
+    assert_eq!(sim.region_of(1), 1);
+    sim.run_iteration();
+
+    let stat = |name: &str, colonies, destroyed, ants, destructions: &[u32]| RegionStats {
+        name: name.to_string(),
+        colonies,
+        destroyed,
+        surviving: colonies - destroyed,
+        ants,
+        destructions: destructions.to_vec(),
+    };
+    assert_eq!(
+        sim.region_stats(),
+        [stat(UNASSIGNED_REGION, 1, 0, 1, &[]), stat("North", 2, 1, 0, &[0]), stat("South", 1, 0, 0, &[])]
+    );
+}
+
+#[test]
+fn without_regions_everything_is_unassigned() {
+    let sim = AntSimulation::new(Arc::new(graph()), AntConfig::new(3)).unwrap();
+    let stats = sim.region_stats();
+    assert_eq!(stats.len(), 1);
+    assert_eq!((stats[0].name.as_str(), stats[0].colonies, stats[0].ants), (UNASSIGNED_REGION, 4, 3));
+    assert_eq!(sim.region_of(2), 0);
+}
@@ -0,0 +1,239 @@
+#![cfg(feature = "serve")]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+const SMALL_MAP: &str = "hiveum_map_small.txt";
+
+/// A running `ant-mania serve`, killed if a test fails before stopping it
+struct Server {
+    child: Child,
+    addr: String,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// `ant-mania serve` on a free port
+fn serve(args: &[&str]) -> Server {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ant-mania"))
+        .arg("serve")
+        .args(args)
+        .args(["--addr", "127.0.0.1:0"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run ant-mania serve");
+    let mut line = String::new();
+    BufReader::new(child.stdout.as_mut().unwrap()).read_line(&mut line).unwrap();
+    let addr = line.trim().strip_prefix("listening on http://").expect("no address printed").to_string();
+    Server { child, addr }
+}
+
+/// Send a request, returning the status code and the JSON body
+fn request(addr: &str, method: &str, path: &str) -> (u16, serde_json::Value) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\nHost: {}\r\n\r\n", method, path, addr).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").expect("no end of headers");
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+fn get(addr: &str, path: &str) -> serde_json::Value {
+    let (status, body) = request(addr, "GET", path);
+    assert_eq!(status, 200, "GET {}: {}", path, body);
+    body
+}
+
+fn post(addr: &str, path: &str) -> serde_json::Value {
+    let (status, body) = request(addr, "POST", path);
+    assert_eq!(status, 200, "POST {}: {}", path, body);
+    body
+}
+
+/// Read one server frame, returning its opcode and payload
+fn read_frame(reader: &mut impl Read) -> (u8, Vec<u8>) {
+    let mut header = [0; 2];
+    reader.read_exact(&mut header).unwrap();
+    assert_eq!(header[1] & 0x80, 0, "server frames aren't masked");
+    let len = match header[1] & 0x7f {
+        126 => {
+            let mut len = [0; 2];
+            reader.read_exact(&mut len).unwrap();
+            u16::from_be_bytes(len) as usize
+        }
+        127 => {
+            let mut len = [0; 8];
+            reader.read_exact(&mut len).unwrap();
+            u64::from_be_bytes(len) as usize
+        }
+        len => len as usize,
+    };
+    let mut payload = vec![0; len];
+    reader.read_exact(&mut payload).unwrap();
+    (header[0] & 0x0f, payload)
+}
+
+fn read_json_frame(reader: &mut impl Read) -> serde_json::Value {
+    let (opcode, payload) = read_frame(reader);
+    assert_eq!(opcode, 1, "expected a text frame, got {:?}", String::from_utf8_lossy(&payload));
+    serde_json::from_slice(&payload).unwrap()
+}
+
+/// Open `/ws`, checking the handshake, with the response headers read
+fn open_websocket(addr: &str) -> BufReader<TcpStream> {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    // the key and accept value from RFC 6455's example
+    write!(
+        stream,
+        "GET /ws HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        addr
+    )
+    .unwrap();
+    let mut reader = BufReader::new(stream);
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        if line.trim().is_empty() {
+            break;
+        }
+        headers.push(line.trim().to_string());
+    }
+    assert!(headers[0].starts_with("HTTP/1.1 101"), "{:?}", headers);
+    assert!(headers.contains(&"Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=".to_string()), "{:?}", headers);
+    reader
+}
+
+fn stop(mut server: Server) {
+    post(&server.addr, "/control/stop");
+    assert!(server.child.wait().unwrap().success());
+}
+
+#[test]
+fn serves_stats_events_and_world() {
+    let server = serve(&[SMALL_MAP, "40", "--seed", "3", "--paused"]);
+
+    let stats = get(&server.addr, "/stats");
+    assert_eq!(stats["iteration"], 0);
+    assert_eq!(stats["alive_ants"], 40);
+    assert_eq!(stats["paused"], true);
+    assert_eq!(stats["finished"], false);
+    assert!(stats["total_colonies"].as_u64().unwrap() > 0);
+
+    post(&server.addr, "/control/resume");
+    let started = Instant::now();
+    let stats = loop {
+        let stats = get(&server.addr, "/stats");
+        if stats["finished"] == true {
+            break stats;
+        }
+        assert!(started.elapsed() < Duration::from_secs(30), "the run never finished: {}", stats);
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    assert!(stats["iteration"].as_u64().unwrap() > 0);
+
+    // every ant that died did so in a fight the journal recorded
+    let events = get(&server.addr, "/events?since=0");
+    let journal = events["events"].as_array().unwrap();
+    let killed: u64 = journal.iter().map(|event| event["ants_killed"].as_u64().unwrap()).sum();
+    assert_eq!(killed, 40 - stats["alive_ants"].as_u64().unwrap());
+    let next = events["next"].as_u64().unwrap();
+    assert_eq!(journal.len() as u64, next);
+    assert!(get(&server.addr, &format!("/events?since={}", next))["events"].as_array().unwrap().is_empty());
+
+    let mut stream = TcpStream::connect(&server.addr).unwrap();
+    write!(stream, "GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+    let mut metrics = String::new();
+    stream.read_to_string(&mut metrics).unwrap();
+    let alive = format!("ant_mania_alive_ants{{seed=\"3\",map=\"{}\"}} {}\n", SMALL_MAP, stats["alive_ants"]);
+    assert!(metrics.starts_with("HTTP/1.1 200") && metrics.contains(&alive), "{}", metrics);
+
+    let ant = get(&server.addr, "/ant/39");
+    assert_eq!(ant["ant"], 39);
+    assert_eq!(ant["moves"].as_u64().unwrap() + ant["moves_left"].as_u64().unwrap(), 10000);
+    assert!(ant["colony_name"].is_string());
+
+    let world = get(&server.addr, "/world");
+    assert_eq!(world["truncated"], false);
+    assert_eq!(world["alive_ants"], stats["alive_ants"]);
+    assert!(!world["map"].as_str().unwrap().is_empty());
+
+    stop(server);
+}
+
+#[test]
+fn world_is_cut_at_a_line_above_the_limit() {
+    let server = serve(&[SMALL_MAP, "1", "--paused", "--world-limit", "50"]);
+    let world = get(&server.addr, "/world");
+    assert_eq!(world["truncated"], true);
+    let map = world["map"].as_str().unwrap();
+    assert!(map.len() <= 50 && map.ends_with('\n'), "{:?}", map);
+    stop(server);
+}
+
+#[test]
+fn bad_requests_are_refused() {
+    let server = serve(&[SMALL_MAP, "1", "--paused"]);
+    assert_eq!(request(&server.addr, "GET", "/nowhere").0, 404);
+    assert_eq!(request(&server.addr, "GET", "/control/stop").0, 405);
+    assert_eq!(request(&server.addr, "POST", "/stats").0, 405);
+    assert_eq!(request(&server.addr, "GET", "/events?since=soon").0, 400);
+    assert_eq!(request(&server.addr, "GET", "/ant/1").0, 404);
+    assert_eq!(request(&server.addr, "GET", "/ant/first").0, 400);
+    assert_eq!(request(&server.addr, "POST", "/ant/0").0, 405);
+    assert_eq!(post(&server.addr, "/control/pause")["paused"], true);
+    stop(server);
+}
+
+#[test]
+fn websocket_streams_a_snapshot_then_events() {
+    let mut server = serve(&[SMALL_MAP, "100", "--seed", "5", "--paused"]);
+    let mut socket = open_websocket(&server.addr);
+
+    let snapshot = read_json_frame(&mut socket);
+    assert_eq!(snapshot["type"], "snapshot");
+    assert_eq!(snapshot["iteration"], 0);
+    assert_eq!(snapshot["ants"].as_array().unwrap().len(), 100);
+    assert_eq!(snapshot["ants_sampled"], false);
+    let colonies = snapshot["colonies"].as_array().unwrap();
+    assert_eq!(colonies.len() as u64, snapshot["total_colonies"].as_u64().unwrap());
+    assert!(colonies.iter().all(|colony| colony["name"].is_string()));
+
+    post(&server.addr, "/control/resume");
+    let (mut destroyed, mut killed) = (0, 0);
+    let finished = loop {
+        let frame = read_json_frame(&mut socket);
+        match frame["type"].as_str().unwrap() {
+            "destroyed" => {
+                destroyed += 1;
+                killed += frame["ants_killed"].as_u64().unwrap();
+                assert!(!frame["colonies"].as_array().unwrap().is_empty());
+            }
+            "finished" => break frame,
+            other => panic!("unexpected {} frame", other),
+        }
+    };
+    assert!(destroyed > 0, "no fights in a crowded run");
+    assert_eq!(killed, 100 - finished["alive_ants"].as_u64().unwrap());
+
+    post(&server.addr, "/control/stop");
+    let (opcode, payload) = read_frame(&mut socket);
+    assert_eq!((opcode, &payload[..2]), (8, &1001u16.to_be_bytes()[..]));
+    assert!(server.child.wait().unwrap().success());
+}
+
+#[test]
+fn websocket_needs_a_handshake() {
+    let server = serve(&[SMALL_MAP, "1", "--paused"]);
+    assert_eq!(request(&server.addr, "GET", "/ws").0, 400);
+    stop(server);
+}
@@ -0,0 +1,19 @@
+use std::io::Cursor;
+
+use ant_mania::{ProceduralMapGenerator, SimError};
+
+#[test]
+fn snap_edge_list_becomes_a_map_named_by_node_id() {
+    let dataset = "# Directed graph: toy.txt\n# FromNodeId\tToNodeId\n10\t20\n20\t10\n20\t30\n\n30 10\n";
+    let sim = ProceduralMapGenerator::from_stanford_snap_format(Cursor::new(dataset), 0).unwrap();
+    assert_eq!(sim.stats().2, 3);
+
+    let mut world = Vec::new();
+    sim.write_map(&mut world).unwrap();
+    let world = String::from_utf8(world).unwrap();
+    assert!(world.contains("\n20 tunnel=10 tunnel=30\n"), "{}", world);
+    assert!(world.contains("\n30 tunnel=10\n"), "{}", world);
+
+    let broken = ProceduralMapGenerator::from_stanford_snap_format(Cursor::new("1 2\n3\n"), 0);
+    assert!(matches!(broken, Err(SimError::Io(_))));
+}
@@ -0,0 +1,231 @@
+use std::fs::{self, File};
+use std::process::Command;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, MovementMode, SimOptions, Snapshot, SNAPSHOT_VERSION};
+
+const SMALL_MAP: &str = "hiveum_map_small.txt";
+
+fn remaining_world(sim: &AntSimulation) -> String {
+    let mut out = Vec::new();
+    sim.write_remaining_world(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn resumed_snapshot_ends_in_the_same_world() {
+    let graph = MapGraph::from_file(SMALL_MAP).unwrap().into();
+    let config = AntConfig { seed: Some(11), max_moves: 200, ..AntConfig::new(60) };
+    let options = SimOptions {
+        movement: MovementMode::Simultaneous,
+        reorder_every: Some(7),
+        track_components: true,
+        factions: 3,
+        ..SimOptions::default()
+    };
+    let mut sim = AntSimulation::new(graph, config).unwrap().with_options(options);
+
+    for _ in 0..30 {
+        sim.run_iteration();
+    }
+    assert!(sim.should_continue(), "run ended before the snapshot");
+    let mut bytes = Vec::new();
+    sim.snapshot().write_to(&mut bytes).unwrap();
+
+    let snapshot = Snapshot::read_from(bytes.as_slice()).unwrap();
+    assert_eq!(snapshot.iteration(), 30);
+    let mut resumed = AntSimulation::from_snapshot(snapshot).unwrap();
+    // in lockstep, so a divergence shows up at the iteration it happens
+    while sim.should_continue() && sim.iteration() < sim.max_moves() {
+        assert_eq!(resumed.state_hash(), sim.state_hash(), "diverged at iteration {}", sim.iteration());
+        sim.run_iteration();
+        resumed.run_iteration();
+    }
+    assert_eq!(resumed.state_hash(), sim.state_hash());
+
+    assert_eq!(remaining_world(&resumed), remaining_world(&sim));
+    assert_eq!(resumed.simulation_stats(), sim.simulation_stats());
+}
+
+#[test]
+fn cli_keeps_the_latest_snapshots() {
+    let dir = std::env::temp_dir().join(format!("ant_mania_snapshots_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ant-mania"))
+        .args([SMALL_MAP, "20", "--seed", "1", "--max-moves", "100", "--no-timing", "--quiet"])
+        .args(["--snapshot-every", "20", "--snapshot-keep", "2", "--snapshot-dir"])
+        .arg(&dir)
+        .output()
+        .expect("failed to run ant-mania");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    let mut snapshots: Vec<_> = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+    snapshots.sort_by_key(|path| fs::metadata(path).unwrap().modified().unwrap());
+    assert_eq!(snapshots.len(), 2, "expected the last two snapshots, found {:?}", snapshots);
+
+    let latest = Snapshot::read_from(File::open(snapshots.last().unwrap()).unwrap()).unwrap();
+    let latest_name = format!("snap_{}.bin", latest.iteration());
+    assert_eq!(snapshots.last().unwrap().file_name().unwrap().to_str(), Some(latest_name.as_str()));
+
+    let mut resumed = AntSimulation::from_snapshot(latest).unwrap();
+    resumed.run_to_completion();
+    assert!(stdout.ends_with(&remaining_world(&resumed)), "resumed world differs from the run's:\n{}", stdout);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn ant_mania(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ant-mania")).args(args).output().expect("failed to run ant-mania");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// The report from the end line on, which holds the iteration count and the world
+fn report_tail(stdout: &str) -> &str {
+    &stdout[stdout.find("Simulation ended").expect("no end of run in the report")..]
+}
+
+#[test]
+fn resumed_halves_match_an_uninterrupted_run() {
+    let dir = std::env::temp_dir().join(format!("ant_mania_resume_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let dir_arg = dir.to_str().unwrap();
+    let run = [SMALL_MAP, "40", "--seed", "3", "--movement", "simultaneous", "--no-timing", "--quiet"];
+
+    let whole = ant_mania(&[&run[..], &["--max-moves", "80"]].concat());
+
+    ant_mania(&[&run[..], &["--max-moves", "40", "--snapshot-every", "40", "--snapshot-dir", dir_arg]].concat());
+    let snapshot = dir.join("snap_40.bin");
+    let resumed = ant_mania(&["--resume", snapshot.to_str().unwrap(), "--max-moves", "80", "--no-timing", "--quiet"]);
+
+    assert!(resumed.contains("Resumed from iteration 40"), "{}", resumed);
+    let resumed_tail = report_tail(&resumed).replacen("\nResumed from iteration 40, ran 40 more", "", 1);
+    assert_eq!(resumed_tail, report_tail(&whole));
+
+    // the map and ants can be given as long as they match the snapshot
+    let output = Command::new(env!("CARGO_BIN_EXE_ant-mania"))
+        .args([SMALL_MAP, "41", "--resume", snapshot.to_str().unwrap()])
+        .output()
+        .expect("failed to run ant-mania");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("has 40 ants, not 41"));
+    ant_mania(&[SMALL_MAP, "40", "--resume", snapshot.to_str().unwrap(), "--quiet"]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn other_versions_are_refused() {
+    let sim = AntSimulation::from_file(SMALL_MAP, 10).unwrap();
+    let mut bytes = Vec::new();
+    sim.snapshot().write_to(&mut bytes).unwrap();
+
+    // the version follows the 8-byte magic
+    bytes[8] += 1;
+    let err = Snapshot::read_from(bytes.as_slice()).unwrap_err().to_string();
+    let expected = format!("snapshot version {} isn't supported", SNAPSHOT_VERSION + 1);
+    assert!(err.contains(&expected), "{}", err);
+
+    let err = Snapshot::read_from(&b"ant_mania_map v1\n"[..]).unwrap_err().to_string();
+    assert!(err.contains("not an ant_mania snapshot"), "{}", err);
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn messagepack_snapshot_resumes_like_bincode() {
+    use ant_mania::SnapshotFormat;
+
+    let mut sim = AntSimulation::from_file(SMALL_MAP, 60).unwrap();
+    for _ in 0..20 {
+        sim.run_iteration();
+    }
+    let snapshot = sim.snapshot();
+    let [mut bincode, mut msgpack] = [Vec::new(), Vec::new()];
+    snapshot.write_as(&mut bincode, SnapshotFormat::Bincode).unwrap();
+    snapshot.write_as(&mut msgpack, SnapshotFormat::MessagePack).unwrap();
+    assert_ne!(bincode[..8], msgpack[..8]);
+    assert_eq!(bincode[8..12], msgpack[8..12], "both carry the same version header");
+
+    // read_from tells them apart by the magic number alone
+    let mut from_bincode = AntSimulation::from_snapshot(Snapshot::read_from(bincode.as_slice()).unwrap()).unwrap();
+    let mut from_msgpack = AntSimulation::from_snapshot(Snapshot::read_from(msgpack.as_slice()).unwrap()).unwrap();
+    assert_eq!(from_msgpack.iteration(), 20);
+    while sim.should_continue() && sim.iteration() < sim.max_moves() {
+        assert_eq!(from_msgpack.state_hash(), sim.state_hash(), "diverged at iteration {}", sim.iteration());
+        sim.run_iteration();
+        from_bincode.run_iteration();
+        from_msgpack.run_iteration();
+    }
+    assert_eq!(from_msgpack.state_hash(), from_bincode.state_hash());
+    assert_eq!(remaining_world(&from_msgpack), remaining_world(&sim));
+
+    msgpack[8] += 1;
+    let err = Snapshot::read_from(msgpack.as_slice()).unwrap_err().to_string();
+    assert!(err.contains(&format!("snapshot version {} isn't supported", SNAPSHOT_VERSION + 1)), "{}", err);
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn cli_resumes_messagepack_snapshots_whatever_they_are_called() {
+    let dir = std::env::temp_dir().join(format!("ant_mania_msgpack_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let dir_arg = dir.to_str().unwrap();
+    let run = [SMALL_MAP, "40", "--seed", "5", "--no-timing", "--quiet"];
+
+    let whole = ant_mania(&[&run[..], &["--max-moves", "60"]].concat());
+
+    let snapshot_args = ["--snapshot-every", "30", "--snapshot-format", "msgpack", "--snapshot-dir", dir_arg];
+    ant_mania(&[&run[..], &["--max-moves", "30"], &snapshot_args[..]].concat());
+    let snapshot = dir.join("snap_30.msgpack");
+    assert!(snapshot.exists(), "no {}", snapshot.display());
+    // the magic number decides, not the name
+    let renamed = dir.join("snap_30.bin");
+    fs::rename(&snapshot, &renamed).unwrap();
+    let resumed = ant_mania(&["--resume", renamed.to_str().unwrap(), "--max-moves", "60", "--no-timing", "--quiet"]);
+
+    let resumed_tail = report_tail(&resumed).replacen("\nResumed from iteration 30, ran 30 more", "", 1);
+    assert_eq!(resumed_tail, report_tail(&whole));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(not(feature = "msgpack"))]
+#[test]
+fn cli_refuses_messagepack_snapshots_when_compiled_out() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ant-mania"))
+        .args([SMALL_MAP, "10", "--snapshot-every", "5", "--snapshot-format", "msgpack", "--snapshot-dir", "unused"])
+        .output()
+        .expect("failed to run ant-mania");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("MessagePack snapshots are compiled out, rebuild with --features msgpack"), "{}", stderr);
+    assert!(fs::metadata("unused").is_err(), "made the snapshot dir anyway");
+}
+
+#[test]
+fn corrupt_snapshot_is_an_error() {
+    let mut sim = AntSimulation::from_file(SMALL_MAP, 10).unwrap();
+    sim.run_iteration();
+    let mut bytes = Vec::new();
+    sim.snapshot().write_to(&mut bytes).unwrap();
+
+    bytes.truncate(bytes.len() / 2);
+    assert!(Snapshot::read_from(bytes.as_slice()).is_err());
+}
+
+#[test]
+fn snapshot_whose_counters_dont_add_up_is_refused() {
+    let mut sim = AntSimulation::from_file(SMALL_MAP, 10).unwrap();
+    sim.run_iteration();
+    let alive = sim.simulation_stats().alive_ants;
+    let mut bytes = Vec::new();
+    sim.snapshot().write_to(&mut bytes).unwrap();
+
+    // alive_ants is the second to last field, a little-endian u64
+    let at = bytes.len() - 16;
+    bytes[at..at + 8].copy_from_slice(&(alive as u64 - 1).to_le_bytes());
+    let snapshot = Snapshot::read_from(bytes.as_slice()).unwrap();
+    let err = AntSimulation::from_snapshot(snapshot).unwrap_err().to_string();
+    assert!(err.contains("inconsistent simulation state"), "{}", err);
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, MapOrder};
+
+const DECLARED: &str = "\
+ant_mania_map v1
+Lyon north=Paris east=Geneva
+Paris south=Lyon west=Brest
+Brest east=Paris
+Geneva west=Lyon
+[aliases]
+Lugdunum=Lyon
+Lutetia=Paris
+";
+
+/// The same map from another generator: other colony and tunnel order
+const SCRAMBLED: &str = "\
+ant_mania_map v1
+Geneva west=Lyon
+Paris west=Brest south=Lyon
+Brest east=Paris
+Lyon east=Geneva north=Paris
+[aliases]
+Lutetia=Paris
+Lugdunum=Lyon
+";
+
+const CANONICAL: &str = "\
+ant_mania_map v1
+Brest east=Paris
+Geneva west=Lyon
+Lyon east=Geneva north=Paris
+Paris south=Lyon west=Brest
+[aliases]
+Lugdunum=Lyon
+Lutetia=Paris
+";
+
+fn written(graph: &MapGraph, order: MapOrder) -> String {
+    let mut out = Vec::new();
+    graph.write_map_ordered(&mut out, |_| true, order).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn sorted_output_is_the_same_for_any_declaration_order() {
+    let declared = MapGraph::from_reader(DECLARED.as_bytes()).unwrap();
+    let scrambled = MapGraph::from_reader(SCRAMBLED.as_bytes()).unwrap();
+    assert_eq!(written(&declared, MapOrder::Sorted), CANONICAL);
+    assert_eq!(written(&scrambled, MapOrder::Sorted), CANONICAL);
+
+    // the map itself is left in declaration order
+    assert_eq!(written(&scrambled, MapOrder::Declared), SCRAMBLED);
+    assert_eq!((scrambled.colony_name(0), scrambled.edge_label(1, 0)), ("Geneva", "west"));
+}
+
+#[test]
+fn sorted_remaining_world_skips_destroyed_colonies() {
+    let graph = Arc::new(MapGraph::from_reader(SCRAMBLED.as_bytes()).unwrap());
+    let mut sim = AntSimulation::new(graph, AntConfig::new(0)).unwrap();
+    sim.destroy_cascade(sim.graph().find_colony("Brest").unwrap());
+    let mut out = Vec::new();
+    sim.write_remaining_world_ordered(&mut out, MapOrder::Sorted).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "\n=== Remaining World ===\nant_mania_map v1\nGeneva west=Lyon\nLyon east=Geneva north=Paris\n\
+         Paris south=Lyon\n[aliases]\nLugdunum=Lyon\nLutetia=Paris\n\nAlive ants: 0/0\n"
+    );
+    let mut declared = Vec::new();
+    sim.write_map(&mut declared).unwrap();
+    assert!(String::from_utf8(declared).unwrap().starts_with("ant_mania_map v1\nGeneva west=Lyon\nParis south=Lyon\n"));
+}
@@ -0,0 +1,44 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph};
+
+const SMALL_MAP: &str = "hiveum_map_small.txt";
+
+fn seeded(seed: u64) -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_file(SMALL_MAP).unwrap());
+    AntSimulation::new(graph, AntConfig { seed: Some(seed), max_moves: 300, ..AntConfig::new(40) }).unwrap()
+}
+
+#[test]
+fn same_seed_runs_hash_the_same_every_iteration() {
+    let (mut first, mut second) = (seeded(5), seeded(5));
+    assert_eq!(first.state_hash(), second.state_hash());
+    while first.should_continue() && first.iteration() < first.max_moves() {
+        first.run_iteration();
+        second.run_iteration();
+        assert_eq!(first.state_hash(), second.state_hash(), "diverged at iteration {}", first.iteration());
+    }
+    assert_ne!(seeded(6).state_hash(), seeded(5).state_hash());
+}
+
+#[test]
+fn moving_one_ant_changes_the_hash() {
+    let (mut moved, untouched) = (seeded(5), seeded(5));
+    let before = moved.state_hash();
+    assert!(moved.move_ant(0, &mut Vec::new()).is_some());
+    assert_ne!(moved.state_hash(), before);
+    assert_ne!(moved.state_hash(), untouched.state_hash());
+}
+
+#[test]
+fn a_hash_set_keeps_one_of_each_state() {
+    let mut states = HashSet::new();
+    let mut sim = seeded(5);
+    assert!(states.insert(seeded(5)));
+    assert!(!states.insert(seeded(5)), "same start twice");
+    sim.run_iteration();
+    assert!(sim != seeded(5));
+    assert!(states.insert(sim));
+    assert_eq!(states.len(), 2);
+}
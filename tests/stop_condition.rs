@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use ant_mania::stop_condition::{self, Comparison, Metric, Threshold};
+use ant_mania::{AntConfig, AntSimulation, MapGraph, PlacementStrategy, StopCondition};
+
+/// Two ants walk from colony 0 into 1 and destroy it on the first iteration
+fn after_one_fight() -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[(0, 1), (2, 3), (3, 2)], 4).unwrap());
+    let config = AntConfig { placement_strategy: PlacementStrategy::Explicit(vec![Some(0); 2]), ..AntConfig::new(2) };
+    let mut sim = AntSimulation::new(graph, config).unwrap();
+    sim.run_iteration();
+    sim
+}
+
+fn met(spec: &str, sim: &AntSimulation) -> bool {
+    stop_condition::parse(spec).unwrap().is_met(sim)
+}
+
+#[test]
+fn every_metric_reads_the_simulation() {
+    let sim = after_one_fight();
+    let expected = [
+        (Metric::Iterations, 1),
+        (Metric::AliveAnts, 0),
+        (Metric::DeadAnts, 2),
+        (Metric::ActiveColonies, 3),
+        (Metric::ColoniesDestroyed, 1),
+        (Metric::TotalMoves, 2),
+        (Metric::OscillatingAnts, 0),
+    ];
+    assert_eq!(expected.len(), Metric::ALL.len());
+    for (metric, value) in expected {
+        assert_eq!(metric.value(&sim), value, "{}", metric.name());
+        let spec = format!("{}=={}", metric.name(), value);
+        assert!(met(&spec, &sim), "{} should hold", spec);
+    }
+}
+
+#[test]
+fn every_operator_compares() {
+    let sim = after_one_fight();
+    // colonies_destroyed is 1
+    let cases = [
+        ("<", [false, false, true]),
+        ("<=", [false, true, true]),
+        ("==", [false, true, false]),
+        ("!=", [true, false, true]),
+        (">=", [true, true, false]),
+        (">", [true, false, false]),
+    ];
+    for (operator, expected) in cases {
+        for (value, expected) in [0, 1, 2].into_iter().zip(expected) {
+            let spec = format!("colonies_destroyed {} {}", operator, value);
+            assert_eq!(met(&spec, &sim), expected, "{}", spec);
+        }
+    }
+}
+
+#[test]
+fn parses_and_prints_conditions() {
+    let condition: StopCondition = " alive_ants <= 2 ".parse().unwrap();
+    assert_eq!(
+        condition,
+        StopCondition { metric: Metric::AliveAnts, comparison: Comparison::LessOrEqual, value: Threshold::Number(2) }
+    );
+    assert_eq!(condition.to_string(), "alive_ants<=2");
+    assert_eq!(stop_condition::parse("iterations>=5000").unwrap().to_string(), "iterations>=5000");
+    let condition = stop_condition::parse("dead_ants > alive_ants").unwrap();
+    assert_eq!(condition.value, Threshold::Metric(Metric::AliveAnts));
+    assert_eq!(condition.to_string(), "dead_ants>alive_ants");
+}
+
+#[test]
+fn malformed_conditions_are_rejected() {
+    let err = stop_condition::parse("ants<3").unwrap_err().to_string();
+    assert!(err.contains("unknown metric 'ants'"), "{}", err);
+    let names = "iterations, alive_ants, dead_ants, active_colonies, colonies_destroyed, total_moves, oscillating_ants";
+    assert!(err.contains(names), "{}", err);
+
+    let malformed = [
+        "alive_ants", "alive_ants=>3", "alive_ants=3", "alive_ants>=", "alive_ants>=-1", "alive_ants>=2.5",
+        "alive_ants>=ants",
+    ];
+    for spec in malformed {
+        assert!(stop_condition::parse(spec).is_err(), "{} parsed", spec);
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, PlacementStrategy, Termination, BUSIEST_COLONIES};
+
+fn sim(edges: &[(usize, usize)], colonies: usize, positions: Vec<Option<usize>>, max_moves: u32) -> AntSimulation {
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(edges, colonies).unwrap());
+    let config = AntConfig {
+        placement_strategy: PlacementStrategy::Explicit(positions.clone()),
+        seed: Some(1),
+        max_moves,
+        ..AntConfig::new(positions.len())
+    };
+    AntSimulation::new(graph, config).unwrap()
+}
+
+#[test]
+fn summary_agrees_with_the_counters() {
+    let mut sim = AntSimulation::from_file("hiveum_map_small.txt", 100).unwrap().with_max_moves(10_000);
+    let summary = sim.summary();
+    assert_eq!((summary.iteration, summary.alive_ants, summary.termination), (0, 100, None));
+    assert!(summary.recent_destructions.is_empty());
+
+    sim.run_to_completion();
+    let summary = sim.summary();
+    let stats = sim.simulation_stats();
+    assert_eq!(summary.alive_ants, stats.alive_ants);
+    assert_eq!(summary.active_colonies, stats.active_colonies);
+    assert_eq!(summary.total_moves_made, stats.total_moves_made);
+    assert_eq!(summary.colonies_destroyed, sim.colonies_destroyed());
+    assert_eq!(summary.termination, sim.termination());
+    assert!(summary.termination.is_some());
+
+    let busiest = &summary.busiest_colonies;
+    assert_eq!(busiest.len(), BUSIEST_COLONIES);
+    assert!(busiest.windows(2).all(|pair| pair[0].peak_ant_count >= pair[1].peak_ant_count));
+    let most = stats.peak_ant_counts.iter().max().unwrap();
+    assert_eq!(busiest[0].peak_ant_count, *most);
+    assert_eq!(busiest[0].name, sim.graph().colony_name(busiest[0].id));
+
+    let recent = &summary.recent_destructions;
+    assert_eq!(recent.len(), summary.colonies_destroyed.min(ant_mania::RECENT_DESTRUCTIONS));
+    assert!(recent.windows(2).all(|pair| pair[0].iteration <= pair[1].iteration));
+    assert!(recent.iter().all(|destruction| sim.iter().nth(destruction.colony).unwrap().destroyed));
+
+    let json = serde_json::to_value(&summary).unwrap();
+    assert_eq!(json["alive_ants"], summary.alive_ants);
+    assert_eq!(json["busiest_colonies"][0]["name"], busiest[0].name.as_str());
+}
+
+#[test]
+fn termination_says_why_the_run_stopped() {
+    // two ants walking into the same colony
+    let mut fight = sim(&[(0, 2), (1, 2)], 3, vec![Some(0), Some(1)], 5);
+    fight.run_to_completion();
+    assert_eq!(fight.termination(), Some(Termination::AllAntsDead));
+    let summary = fight.summary();
+    assert_eq!(summary.recent_destructions.len(), 1);
+    assert_eq!((summary.recent_destructions[0].iteration, summary.recent_destructions[0].colony), (0, 2));
+    assert_eq!(serde_json::to_value(summary).unwrap()["termination"], "all_ants_dead");
+
+    // a lone ant going round a loop
+    let mut walker = sim(&[(0, 1), (1, 0)], 2, vec![Some(0)], 5);
+    assert_eq!(walker.termination(), None);
+    walker.run_to_completion();
+    assert_eq!(walker.termination(), Some(Termination::MovesUsedUp));
+
+    // a lone ant with nowhere to go
+    let mut stuck = sim(&[(1, 0)], 2, vec![Some(0)], 5);
+    while stuck.iteration() < stuck.max_moves() {
+        assert_eq!(stuck.termination(), None);
+        stuck.run_iteration();
+    }
+    assert_eq!(stuck.termination(), Some(Termination::IterationLimit));
+}
+
+#[test]
+fn debug_output_stays_short_on_a_big_simulation() {
+    let edges: Vec<(usize, usize)> = (0..1000).map(|colony| (colony, (colony + 1) % 1000)).collect();
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, 1000).unwrap());
+    let sim = AntSimulation::new(Arc::clone(&graph), AntConfig::new(2_000_000)).unwrap();
+    for text in [format!("{:?}", sim), format!("{:?}", sim.snapshot()), format!("{:?}", graph)] {
+        assert!(text.len() < 1000, "{}", text);
+    }
+    let text = format!("{:?}", sim);
+    assert!(text.contains("total_ants: 2000000") && text.contains("ant_position: 2000000 entries ["), "{}", text);
+}
+
+#[test]
+fn current_iteration_counts_finished_iterations() {
+    let mut sim = AntSimulation::from_file("hiveum_map_small.txt", 10).unwrap();
+    assert_eq!(sim.current_iteration(), 0);
+    for turn in 1..=3 {
+        sim.run_iteration();
+        assert_eq!(sim.current_iteration(), turn);
+    }
+    assert_eq!(sim.current_iteration(), sim.simulation_stats().iterations);
+}
+
+#[test]
+fn destructions_keep_every_colony_destroyed() {
+    let mut sim = AntSimulation::from_file("hiveum_map_small.txt", 100).unwrap().with_max_moves(10_000);
+    sim.record_destructions(true);
+    sim.run_to_completion();
+    let destructions = sim.destructions();
+    assert_eq!(destructions.len(), sim.colonies_destroyed());
+    assert!(destructions.len() > ant_mania::RECENT_DESTRUCTIONS);
+    assert!(destructions.windows(2).all(|pair| pair[0].iteration <= pair[1].iteration));
+    assert!(destructions.ends_with(&sim.summary().recent_destructions));
+
+    sim.record_destructions(false);
+    assert!(sim.destructions().is_empty());
+}
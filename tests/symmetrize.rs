@@ -0,0 +1,59 @@
+use ant_mania::{Direction, MapGraph, MapOptions, ParseWarningKind, SimError};
+
+#[test]
+fn one_way_tunnels_get_a_way_back() {
+    let map = "\
+ant_mania_map v1
+Foo north=Bar east=Baz
+Bar
+Baz ferry=Bar west=Foo
+";
+    let mut graph = MapGraph::from_reader(map.as_bytes()).unwrap();
+    let one_way = |graph: &MapGraph| {
+        let warnings = graph.parse_warnings().iter();
+        warnings.filter(|warning| matches!(warning.kind, ParseWarningKind::OneWayTunnel { .. })).count()
+    };
+    assert_eq!(one_way(&graph), 2);
+
+    assert_eq!(graph.symmetrize().unwrap(), 2);
+    assert_eq!(graph.reverse_tunnels_added(), 2);
+    assert_eq!(one_way(&graph), 0);
+    graph.check_structure().unwrap();
+    let mut written = Vec::new();
+    graph.write_map(&mut written, |_| true).unwrap();
+    assert_eq!(
+        String::from_utf8(written).unwrap(),
+        "ant_mania_map v1\nFoo north=Bar east=Baz\nBar south=Foo ferry=Baz\nBaz ferry=Bar west=Foo\n"
+    );
+
+    // symmetric already, nothing more to add
+    assert_eq!(graph.symmetrize().unwrap(), 0);
+    assert_eq!(Direction::West.opposite(), Direction::East);
+    assert_eq!(Direction::from("up").opposite(), Direction::from("up"));
+}
+
+#[test]
+fn symmetrizing_past_the_tunnel_limit_fails() {
+    let spokes = MapGraph::max_connections_per_colony() + 1;
+    let mut map = String::from("Hub\n");
+    for spoke in 0..spokes {
+        map.push_str(&format!("Spoke{} tunnel=Hub\n", spoke));
+    }
+    let mut graph = MapGraph::from_reader(map.as_bytes()).unwrap();
+    let err = graph.symmetrize().unwrap_err();
+    assert!(matches!(&err, SimError::TooManyConnections { colony, .. } if colony == "Hub"), "{}", err);
+    assert_eq!(graph.edge_count(), spokes, "the map is left as it was");
+    assert_eq!(graph.reverse_tunnels_added(), 0);
+}
+
+#[test]
+fn map_options_symmetrize_on_load() {
+    let path = std::env::temp_dir().join(format!("ant_mania_one_way_{}.txt", std::process::id()));
+    std::fs::write(&path, "A east=B\nB\n").unwrap();
+    let path = path.to_str().unwrap();
+    let graph = MapGraph::from_file_with(path, &MapOptions { symmetrize: true, ..MapOptions::default() }).unwrap();
+    let plain = MapGraph::from_file_with(path, &MapOptions::default()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!((graph.edge_count(), graph.edge_label(1, 0)), (2, "west"));
+    assert_eq!((plain.edge_count(), plain.reverse_tunnels_added()), (1, 0));
+}
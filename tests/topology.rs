@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+
+use ant_mania::{MapGraph, ProceduralMapGenerator, Topology};
+
+/// Every tunnel out of `colony` as (label, target)
+fn tunnels(graph: &MapGraph, colony: usize) -> Vec<(String, usize)> {
+    let start = graph.start_offsets()[colony] as usize;
+    (0..graph.connection_counts()[colony] as usize)
+        .map(|i| (graph.edge_label(colony, i).to_string(), graph.adjacency_slice()[start + i] as usize))
+        .collect()
+}
+
+fn opposite(label: &str) -> &str {
+    match label {
+        "north" => "south",
+        "south" => "north",
+        "east" => "west",
+        "west" => "east",
+        other => panic!("unexpected label {}", other),
+    }
+}
+
+/// Every tunnel has a way back with the opposite label, and no colony has a
+/// tunnel to itself or two to the same colony
+fn assert_consistent(graph: &MapGraph) {
+    for colony in 0..graph.colony_count() {
+        let out = tunnels(graph, colony);
+        let targets: HashSet<_> = out.iter().map(|(_, target)| *target).collect();
+        assert_eq!(targets.len(), out.len(), "{} has two tunnels to one colony", graph.colony_name(colony));
+        for (label, target) in &out {
+            assert_ne!(*target, colony, "{} has a tunnel to itself", graph.colony_name(colony));
+            let back = tunnels(graph, *target);
+            assert!(
+                back.iter().any(|(back_label, to)| *to == colony && back_label == opposite(label)),
+                "{} {} {} has no way back",
+                graph.colony_name(colony),
+                label,
+                graph.colony_name(*target)
+            );
+        }
+    }
+}
+
+fn degrees(graph: &MapGraph) -> Vec<u8> {
+    graph.connection_counts().to_vec()
+}
+
+#[test]
+fn grid_interior_colonies_have_all_four_neighbors() {
+    let graph = ProceduralMapGenerator::topology("grid:5x4".parse().unwrap(), 0).unwrap();
+    assert_eq!(graph.colony_count(), 20);
+    assert_consistent(&graph);
+
+    let colony = |x: usize, y: usize| graph.find_colony(&format!("Colony{}_{}", x, y)).unwrap();
+    for y in 0..4 {
+        for x in 0..5 {
+            let expected = 4 - (x == 0) as u8 - (x == 4) as u8 - (y == 0) as u8 - (y == 3) as u8;
+            assert_eq!(graph.connection_counts()[colony(x, y)], expected, "Colony{}_{}", x, y);
+        }
+    }
+    let mut out = tunnels(&graph, colony(2, 1));
+    out.sort();
+    let expected = [("east", colony(3, 1)), ("north", colony(2, 0)), ("south", colony(2, 2)), ("west", colony(1, 1))];
+    assert_eq!(out, expected.map(|(label, target)| (label.to_string(), target)));
+}
+
+#[test]
+fn ring_is_a_single_cycle() {
+    let graph = ProceduralMapGenerator::topology(Topology::Ring { colonies: 7 }, 0).unwrap();
+    assert_consistent(&graph);
+    assert!(degrees(&graph).iter().all(|&degree| degree == 2));
+
+    // walking east visits every colony once before coming back
+    let mut seen = HashSet::new();
+    let mut colony = 0;
+    while seen.insert(colony) {
+        colony = tunnels(&graph, colony).into_iter().find(|(label, _)| label == "east").unwrap().1;
+    }
+    assert_eq!((colony, seen.len()), (0, 7));
+}
+
+#[test]
+fn torus_has_no_boundary() {
+    let graph = ProceduralMapGenerator::topology(Topology::Torus { width: 3, height: 5 }, 0).unwrap();
+    assert_eq!(graph.colony_count(), 15);
+    assert_consistent(&graph);
+    assert!(degrees(&graph).iter().all(|&degree| degree == 4));
+}
+
+#[test]
+fn regular_maps_have_the_degree_asked_for() {
+    for degree in 1..=4 {
+        for seed in 0..20 {
+            let shape = Topology::Regular { colonies: 10, degree };
+            let graph = ProceduralMapGenerator::topology(shape, seed).unwrap();
+            assert_consistent(&graph);
+            assert!(degrees(&graph).iter().all(|&d| d as usize == degree), "{} seed {}", shape, seed);
+        }
+    }
+}
+
+#[test]
+fn the_same_seed_gives_the_same_map() {
+    let write = |seed| {
+        let graph = ProceduralMapGenerator::topology("regular:30:3".parse().unwrap(), seed).unwrap();
+        let mut out = Vec::new();
+        graph.write_map(&mut out, |_| true).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+    assert_eq!(write(4), write(4));
+    assert_ne!(write(4), write(5));
+}
+
+#[test]
+fn written_maps_read_back_the_same() {
+    for spec in ["grid:4x3", "ring:5", "torus:4x4", "regular:12:4"] {
+        let graph = ProceduralMapGenerator::topology(spec.parse().unwrap(), 1).unwrap();
+        let mut out = Vec::new();
+        ProceduralMapGenerator::write_topology(spec.parse().unwrap(), 1, &mut out).unwrap();
+        let read = MapGraph::from_reader(out.as_slice()).unwrap();
+        assert_eq!(read.colony_count(), graph.colony_count(), "{}", spec);
+        // the parser numbers colonies as it meets them, so compare by name
+        let named = |graph: &MapGraph, colony| -> Vec<(String, String)> {
+            (tunnels(graph, colony).into_iter())
+                .map(|(label, target)| (label, graph.colony_name(target).to_string()))
+                .collect()
+        };
+        for colony in 0..graph.colony_count() {
+            let read_colony = read.find_colony(graph.colony_name(colony)).unwrap();
+            assert_eq!(named(&read, read_colony), named(&graph, colony), "{}", spec);
+        }
+    }
+}
+
+#[test]
+fn simulations_run_on_topologies() {
+    let mut sim = ProceduralMapGenerator::from_topology(Topology::Torus { width: 4, height: 4 }, 10, 3).unwrap();
+    assert_eq!(sim.total_ants(), 10);
+    sim.run_iteration();
+    sim.check_invariants().unwrap();
+}
+
+#[test]
+fn shapes_print_as_they_parse() {
+    for spec in ["grid:3x2", "ring:9", "torus:3x3", "regular:8:3"] {
+        assert_eq!(spec.parse::<Topology>().unwrap().to_string(), spec);
+    }
+}
+
+#[test]
+fn bad_shapes_are_rejected() {
+    for spec in ["grid", "grid:3", "grid:3xa", "ring:-1", "hex:3x3", "regular:8"] {
+        assert!(spec.parse::<Topology>().is_err(), "{}", spec);
+    }
+    for spec in ["grid:0x3", "ring:2", "torus:2x5", "regular:5:5", "regular:4:4", "regular:7:3"] {
+        assert!(ProceduralMapGenerator::topology(spec.parse().unwrap(), 0).is_err(), "{}", spec);
+    }
+}
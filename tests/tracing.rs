@@ -0,0 +1,48 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+
+use ant_mania::AntSimulation;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// Keeps the name and level of every span opened
+#[derive(Default, Clone)]
+struct SpanNames(Arc<Mutex<Vec<(&'static str, Level)>>>);
+
+impl Subscriber for SpanNames {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut spans = self.0.lock().unwrap();
+        spans.push((span.metadata().name(), *span.metadata().level()));
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn a_run_opens_spans_at_each_level() {
+    let mut sim = AntSimulation::from_file("hiveum_map_small.txt", 20).unwrap();
+    let subscriber = SpanNames::default();
+    let iterations = tracing::subscriber::with_default(subscriber.clone(), || sim.run_to_completion());
+
+    let spans = subscriber.0.lock().unwrap();
+    let count = |name: &str, level: Level| spans.iter().filter(|&&span| span == (name, level)).count();
+    assert_eq!(count("run_until", Level::INFO), 1);
+    assert_eq!(count("run_iteration", Level::DEBUG), iterations as usize);
+    assert!(count("move_ant", Level::TRACE) > 0);
+    assert!(count("get_valid_moves", Level::TRACE) > 0);
+    assert!(count("check_collision", Level::TRACE) > 0);
+}
@@ -0,0 +1,75 @@
+//! Colony names and labels are whole UTF-8 words, however many bytes each
+//! character takes
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, ParseWarningKind, PlacementStrategy};
+
+const NAMES: [&str; 4] = ["Zürich", "東京", "São_Paulo", "Αθήνα"];
+
+fn unicode_map() -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/maps/unicode.txt").to_str().unwrap().to_string()
+}
+
+#[test]
+fn multi_byte_names_parse_whole() {
+    let graph = MapGraph::from_file(&unicode_map()).unwrap();
+    assert!(graph.parse_warnings().is_empty(), "{:?}", graph.parse_warnings());
+    let names: Vec<_> = (0..graph.colony_count()).map(|colony| graph.colony_name(colony)).collect();
+    assert_eq!(names, NAMES);
+
+    let tokyo = graph.find_colony("東京").unwrap();
+    let south = (0..2).find(|&i| graph.edge_label(tokyo, i) == "south").unwrap();
+    let target = graph.adjacency_slice()[graph.start_offsets()[tokyo] as usize + south] as usize;
+    assert_eq!(graph.colony_name(target), "Αθήνα");
+}
+
+#[test]
+fn multi_byte_names_are_written_out_whole() {
+    let graph = Arc::new(MapGraph::from_file(&unicode_map()).unwrap());
+    let config = AntConfig { placement_strategy: PlacementStrategy::Single(3), ..AntConfig::new(1) };
+    let mut sim = AntSimulation::new(graph, config).unwrap();
+    sim.destroy_cascade(0);
+
+    let mut out = Vec::new();
+    sim.write_remaining_world(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("東京 south=Αθήνα\n"), "{}", out);
+    assert!(out.contains("São_Paulo east=Αθήνα\n"), "{}", out);
+    assert!(!out.contains("Zürich"), "{}", out);
+
+    // and read back the same
+    let mut map = Vec::new();
+    sim.write_map(&mut map).unwrap();
+    let read = MapGraph::from_reader(map.as_slice()).unwrap();
+    let names: Vec<_> = (0..read.colony_count()).map(|colony| read.colony_name(colony)).collect();
+    assert_eq!(names, &NAMES[1..]);
+}
+
+#[test]
+fn case_folding_looks_past_ascii() {
+    let graph = MapGraph::from_reader("Zürich\nZÜRICH\n".as_bytes()).unwrap();
+    let collision = graph.parse_warnings().iter().any(|warning| match &warning.kind {
+        ParseWarningKind::CaseCollision { colony, other } => colony == "ZÜRICH" && other == "Zürich",
+        _ => false,
+    });
+    assert!(collision, "{:?}", graph.parse_warnings());
+}
+
+#[test]
+fn the_binary_prints_multi_byte_names() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ant-mania"))
+        .arg(unicode_map())
+        .args(["4", "--seed", "1", "--no-timing"])
+        .output()
+        .expect("failed to run ant-mania");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let remaining = stdout.split("=== Remaining World ===").nth(1).unwrap();
+    for line in remaining.lines().filter(|line| line.contains('=')) {
+        let name = line.split_whitespace().next().unwrap();
+        assert!(NAMES.contains(&name), "'{}' in:\n{}", name, stdout);
+    }
+}
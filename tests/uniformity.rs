@@ -0,0 +1,103 @@
+//! Is the next colony picked fairly among the live ones? One ant on the hub
+//! of a star walks out along a tunnel and straight back, over and over; every
+//! walk out is one trial. The counts per tunnel are checked against the
+//! ratios they should come up in with a chi-squared test. The seed is fixed,
+//! so a pass is a pass every time; the critical value (p = 0.001) only
+//! decides how far off a broken picker has to be to fail.
+
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, MapGraph, MovementMode, PlacementStrategy, SimOptions};
+
+const TRIALS: usize = 200_000;
+const SEED: u64 = 0x5eed;
+
+/// Chi-squared values with p = 0.001, indexed by degrees of freedom
+const CRITICAL: [f64; 6] = [0.0, 10.83, 13.82, 16.27, 18.47, 20.52];
+
+/// A hub (colony 0) with two-way tunnels out to colonies `1..=spokes`, the
+/// listed spokes destroyed, and one ant on the hub
+fn star(spokes: usize, destroyed: &[usize], options: SimOptions) -> AntSimulation {
+    let edges: Vec<_> = (1..=spokes).flat_map(|spoke| [(0, spoke), (spoke, 0)]).collect();
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&edges, spokes + 1).unwrap());
+    let config = AntConfig { seed: Some(SEED), placement_strategy: PlacementStrategy::Single(0), ..AntConfig::new(1) };
+    let mut sim = AntSimulation::new(graph, config).unwrap().with_options(options);
+    for &spoke in destroyed {
+        sim.destroy_cascade(spoke);
+    }
+    sim
+}
+
+/// Where the ant is
+fn position(sim: &AntSimulation) -> usize {
+    sim.iter().find(|colony| colony.ant_count > 0).expect("the ant is alive").id
+}
+
+/// How many of `trials` walks out of the hub went to each colony
+fn spoke_counts(mut sim: AntSimulation, trials: usize) -> Vec<usize> {
+    let mut counts = vec![0; sim.graph().colony_count()];
+    let mut taken = 0;
+    while taken < trials {
+        let from_hub = position(&sim) == 0;
+        sim.run_iteration();
+        if from_hub {
+            counts[position(&sim)] += 1;
+            taken += 1;
+        }
+    }
+    counts
+}
+
+/// Pearson's statistic for `observed` against `expected` ratios (summing to
+/// 1). Colonies expected never to be picked must not be, and add nothing.
+fn chi_squared(observed: &[usize], expected: &[f64]) -> f64 {
+    let total: usize = observed.iter().sum();
+    let mut statistic = 0.0;
+    for (colony, (&seen, &ratio)) in observed.iter().zip(expected).enumerate() {
+        if ratio == 0.0 {
+            assert_eq!(seen, 0, "colony {} should never be picked", colony);
+            continue;
+        }
+        let expected = ratio * total as f64;
+        statistic += (seen as f64 - expected).powi(2) / expected;
+    }
+    statistic
+}
+
+/// `observed` is a plausible sample of `expected`, and no ratio is more
+/// than `tolerance` off
+fn assert_matches_ratios(observed: &[usize], expected: &[f64], tolerance: f64) {
+    let total: usize = observed.iter().sum();
+    for (colony, (&seen, &ratio)) in observed.iter().zip(expected).enumerate() {
+        let share = seen as f64 / total as f64;
+        let message = format!("colony {} picked {:.4} of the time, expected {}", colony, share, ratio);
+        assert!((share - ratio).abs() <= tolerance, "{}", message);
+    }
+    let free = expected.iter().filter(|&&ratio| ratio > 0.0).count() - 1;
+    let statistic = chi_squared(observed, expected);
+    assert!(statistic < CRITICAL[free], "chi-squared {:.2} over {} for {:?}", statistic, CRITICAL[free], observed);
+}
+
+/// Even odds over the live spokes, none for the hub and the rest
+fn uniform_over(colonies: usize, live: &[usize]) -> Vec<f64> {
+    (0..colonies).map(|colony| if live.contains(&colony) { 1.0 / live.len() as f64 } else { 0.0 }).collect()
+}
+
+#[test]
+fn sequential_moves_pick_neighbors_uniformly() {
+    let counts = spoke_counts(star(4, &[], SimOptions::default()), TRIALS);
+    assert_matches_ratios(&counts, &uniform_over(5, &[1, 2, 3, 4]), 0.005);
+}
+
+#[test]
+fn simultaneous_moves_pick_neighbors_uniformly() {
+    let options = SimOptions { movement: MovementMode::Simultaneous, ..SimOptions::default() };
+    let counts = spoke_counts(star(4, &[], options), TRIALS);
+    assert_matches_ratios(&counts, &uniform_over(5, &[1, 2, 3, 4]), 0.005);
+}
+
+#[test]
+fn destroyed_neighbors_are_left_out_evenly() {
+    let counts = spoke_counts(star(5, &[3], SimOptions::default()), TRIALS);
+    assert_matches_ratios(&counts, &uniform_over(6, &[1, 2, 4, 5]), 0.005);
+}
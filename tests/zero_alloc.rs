@@ -0,0 +1,88 @@
+//! Installs a counting allocator, so this lives in its own test binary.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use ant_mania::{AntConfig, AntSimulation, DeadEndBehavior, MapGraph, SimOptions};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // only count the test thread, the harness allocates on its own threads
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn count_allocation() {
+    if COUNTING.try_with(Cell::get).unwrap_or(false) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[test]
+fn run_iteration_does_not_allocate_in_steady_state() {
+    fastrand::seed(42);
+    let mut sim = AntSimulation::from_file("hiveum_map_medium.txt", 1000).unwrap();
+
+    // warm-up lets the scratch buffers reach their working size
+    for _ in 0..10 {
+        sim.run_iteration();
+    }
+
+    COUNTING.with(|counting| counting.set(true));
+    for _ in 0..100 {
+        sim.run_iteration();
+    }
+    COUNTING.with(|counting| counting.set(false));
+
+    assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), 0, "run_iteration allocated in steady state");
+}
+
+#[test]
+fn teleporting_out_of_dead_ends_does_not_allocate() {
+    // no tunnels, so every move is a teleport, and a threshold nothing reaches;
+    // few colonies, so their ant lists soon have room for any crowd
+    let graph = Arc::new(MapGraph::from_adjacency_list_raw(&[], 4).unwrap());
+    let config = AntConfig { seed: Some(7), max_moves: 1000, collision_threshold: 1000, ..AntConfig::new(100) };
+    let options = SimOptions { dead_end_behavior: DeadEndBehavior::TeleportToRandom, ..SimOptions::default() };
+    let mut sim = AntSimulation::new(graph, config).unwrap().with_options(options);
+    for _ in 0..10 {
+        sim.run_iteration();
+    }
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    COUNTING.with(|counting| counting.set(true));
+    for _ in 0..100 {
+        sim.run_iteration();
+    }
+    COUNTING.with(|counting| counting.set(false));
+
+    assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before, "teleports allocated");
+}